@@ -1,10 +1,14 @@
 //! `LeftWM` general configuration
 
 use leftwm::{
-    config::{Keybind, ScratchPad, Workspace},
+    config::{
+        include, validation, EdgeAction, ExecOptions, FloatingPlacement, Hook, Idle, Keybind,
+        MouseTarget, Mousebind, NamedLayout, ScratchPad, StartupCommand, Systray,
+        TagDisplayBehavior, TiledResizeRequest, WindowRule, Workspace,
+    },
     errors::Result,
     layouts::{Layout, LAYOUTS},
-    models::{FocusBehaviour, Size},
+    models::{FocusBehaviour, FocusOnClose, Size, WindowType},
     Command,
 };
 use serde::{Deserialize, Serialize};
@@ -20,26 +24,102 @@ use xdg::BaseDirectories;
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 #[serde(default)]
 pub struct Config {
+    //the version of the config schema the file follows, see `validation::CONFIG_SCHEMA_VERSION`
+    pub schema_version: u32,
     pub modkey: String,
     pub mousekey: String,
     pub workspaces: Option<Vec<Workspace>>,
     pub tags: Option<Vec<String>>,
     pub max_window_width: Option<Size>,
+    //where new floating windows go: center, under_cursor or smart
+    pub floating_placement: FloatingPlacement,
+    //pixels down and right a floating window moves when one of its class is already in its spot
+    pub floating_cascade_offset: i32,
+    //window types that float when they appear, instead of being tiled
+    pub floating_types: Vec<WindowType>,
+    //classes of windows that float when they appear, such as password prompts
+    pub floating_classes: Vec<String>,
+    //a tiled window asking to be moved or resized: deny, allow_temporarily or float
+    pub tiled_resize_request: TiledResizeRequest,
+    //how many of the latest display events and actions `DumpDiagnostics` writes out
+    pub diagnostics_history: usize,
+    //least milliseconds between two writes of the state socket, so bursts of changes such as a
+    //window being dragged are sent once they settle
+    pub state_write_interval: u64,
+    //milliseconds drawing waits for more windows after one appears, so a session restore opening
+    //many windows at once is laid out and focused once. 0 draws each window right away
+    pub window_burst_delay: u64,
     pub layouts: Vec<Layout>,
+    //layouts with preset parameters, for `SetLayout`
+    pub layout: Option<Vec<NamedLayout>>,
     pub scratchpad: Option<Vec<ScratchPad>>,
+    pub window_rule: Option<Vec<WindowRule>>,
     //of you are on tag "1" and you goto tag "1" this takes you to the previous tag
     pub disable_current_tag_swap: bool,
+    //`FocusNextTag` on the last tag goes to the first, `FocusPreviousTag` the other way around
+    pub focus_tag_wraparound: bool,
+    //`FocusNextTag` and `FocusPreviousTag` pass over tags without windows
+    pub focus_tag_skip_empty: bool,
+    //every monitor gets its own set of tags, `GotoTag` only switches between those of the focused one
+    pub independent_tags: bool,
+    pub tag_display_behavior: TagDisplayBehavior,
+    //redraws per second of a window being moved or resized, 0 for no limit
+    pub drag_refresh_rate: u32,
+    //pixels the pointer has to move with a mousebind held before the window moves or resizes
+    pub drag_threshold: i32,
+    //most milliseconds between the two clicks of a double click, see `double_click` on mousebinds
+    pub double_click_time: u64,
+    //letting go of a window being moved at the side of a workspace or the middle of a tile tiles it
+    pub drop_zones: bool,
+    //windows being moved or resized are only drawn once let go, an outline shows where they go
+    pub drag_outline: bool,
+    //close dialogs and other transient windows when the window they belong to goes away
+    pub close_transients_with_parent: bool,
+    //true lets GTK apps drawing their own title bar draw shadows around it
+    pub csd_shadows: bool,
+    //the tray icons of programs are docked in, needs leftwm built with the `systray` feature
+    pub systray: Option<Systray>,
     pub focus_behaviour: FocusBehaviour,
     pub focus_new_windows: bool,
+    //the window focused when the focused one closes: previous, top or none
+    pub focus_on_close: FocusOnClose,
+    //new windows launched longer than this many ms before the latest input don't take focus
+    pub focus_stealing_threshold: Option<u32>,
+    //classes of windows that always take focus when they appear
+    pub focus_stealing_allowed: Vec<String>,
+    //the mouse moving over a dock, or the space kept free for it, leaves the focused workspace be
+    pub mouse_focus_ignores_docks: bool,
     pub keybind: Vec<Keybind>,
+    pub mousebind: Vec<Mousebind>,
+    pub idle: Option<Idle>,
+    //run with the tag and the output whenever a workspace shows another tag
+    pub tag_hook: Option<String>,
+    //shell commands run on the events of the window manager
+    pub hook: Option<Vec<Hook>>,
+    pub startup: Option<Vec<StartupCommand>>,
+    pub edge_action: Option<Vec<EdgeAction>>,
+    //commands sent to commands.pipe have to start with this token
+    pub command_token: Option<String>,
+    //started by `LockScreen`, the screen counts as locked until it exits
+    pub lock_command: Option<String>,
 }
 
+/// Loads config.toml, or the default config along with why config.toml was rejected.
 #[must_use]
 #[allow(dead_code)]
-pub fn load() -> Config {
-    load_from_file()
-        .map_err(|err| eprintln!("ERROR LOADING CONFIG: {:?}", err))
-        .unwrap_or_default()
+pub fn load() -> (Config, Option<String>) {
+    match load_from_file() {
+        Ok(config) if check_workspace_ids(&config) => (config, None),
+        Ok(_) => {
+            let reason = "Invalid workspace ID configuration in config.toml.";
+            log::warn!("{} Falling back to default config.", reason);
+            (Config::default(), Some(reason.to_owned()))
+        }
+        Err(err) => {
+            eprintln!("ERROR LOADING CONFIG: {:?}", err);
+            (Config::default(), Some(err.to_string()))
+        }
+    }
 }
 
 /// # Panics
@@ -58,14 +138,9 @@ fn load_from_file() -> Result<Config> {
     let path = BaseDirectories::with_prefix("leftwm")?;
     let config_filename = path.place_config_file("config.toml")?;
     if Path::new(&config_filename).exists() {
-        let contents = fs::read_to_string(config_filename)?;
-        let config = toml::from_str(&contents)?;
-        if check_workspace_ids(&config) {
-            Ok(config)
-        } else {
-            log::warn!("Invalid workspace ID configuration in config.toml. Falling back to default config.");
-            Ok(Config::default())
-        }
+        let config = include::read_with_includes(&config_filename)?.try_into()?;
+        warn_deprecated(&config_filename)?;
+        Ok(config)
     } else {
         let config = Config::default();
         let toml = toml::to_string(&config).unwrap();
@@ -75,33 +150,30 @@ fn load_from_file() -> Result<Config> {
     }
 }
 
-#[must_use]
-pub fn check_workspace_ids(config: &Config) -> bool {
-    config.workspaces.clone().map_or(true, |wss| {
-        let ids = get_workspace_ids(&wss);
-        if ids.iter().any(Option::is_some) {
-            all_ids_some(&ids) && all_ids_unique(&ids)
-        } else {
-            true
+/// Logs the renamed keys and commands of the config file and those it includes.
+fn warn_deprecated(config_filename: &Path) -> Result<()> {
+    for file in include::files(config_filename)? {
+        for diagnostic in validation::check_deprecated(&fs::read_to_string(&file)?) {
+            if let validation::Subject::Line(line, text) = diagnostic.subject {
+                log::warn!(
+                    "{} in {} on line {}: {}",
+                    diagnostic.message,
+                    file.display(),
+                    line,
+                    text
+                );
+            }
         }
-    })
-}
-
-#[must_use]
-pub fn get_workspace_ids(wss: &[Workspace]) -> Vec<Option<i32>> {
-    wss.iter().map(|ws| ws.id).collect()
-}
-
-pub fn all_ids_some(ids: &[Option<i32>]) -> bool {
-    ids.iter().all(Option::is_some)
+    }
+    Ok(())
 }
 
 #[must_use]
-pub fn all_ids_unique(ids: &[Option<i32>]) -> bool {
-    let mut sorted = ids.to_vec();
-    sorted.sort();
-    sorted.dedup();
-    ids.len() == sorted.len()
+pub fn check_workspace_ids(config: &Config) -> bool {
+    config
+        .workspaces
+        .as_ref()
+        .map_or(true, |wss| validation::check_workspace_ids(wss).is_empty())
 }
 
 #[must_use]
@@ -163,6 +235,10 @@ fn exit_strategy<'s>() -> &'s str {
 
 impl leftwm::config::Config for Config {
     fn mapped_bindings(&self) -> Vec<Keybind> {
+        self.mapped_bindings_for(&self.modkey)
+    }
+
+    fn mapped_bindings_for(&self, modkey: &str) -> Vec<Keybind> {
         // copy keybinds substituting "modkey" modifier with a new "modkey".
         self.keybind
             .clone()
@@ -170,7 +246,7 @@ impl leftwm::config::Config for Config {
             .map(|mut keybind| {
                 for m in &mut keybind.modifier {
                     if m == "modkey" {
-                        *m = self.modkey.clone();
+                        *m = modkey.to_owned();
                     }
                 }
                 keybind
@@ -195,13 +271,97 @@ impl leftwm::config::Config for Config {
         self.focus_behaviour
     }
 
-    fn mousekey(&self) -> &str {
-        &self.mousekey
+    fn mapped_mousebinds(&self) -> Vec<Mousebind> {
+        self.mapped_mousebinds_for(None, None)
+    }
+
+    fn mapped_mousebinds_for(
+        &self,
+        modkey: Option<&str>,
+        mousekey: Option<&str>,
+    ) -> Vec<Mousebind> {
+        // copy mousebinds substituting the "mousekey" and "modkey" modifiers.
+        let modkey = modkey.unwrap_or(&self.modkey);
+        let mousekey = mousekey.unwrap_or(&self.mousekey);
+        self.mousebind
+            .clone()
+            .into_iter()
+            .map(|mut mousebind| {
+                for m in &mut mousebind.modifier {
+                    if m == "mousekey" {
+                        *m = mousekey.to_owned();
+                    } else if m == "modkey" {
+                        *m = modkey.to_owned();
+                    }
+                }
+                mousebind
+            })
+            .collect()
     }
 
     fn disable_current_tag_swap(&self) -> bool {
         self.disable_current_tag_swap
     }
+
+    fn focus_tag_wraparound(&self) -> bool {
+        self.focus_tag_wraparound
+    }
+
+    fn focus_tag_skip_empty(&self) -> bool {
+        self.focus_tag_skip_empty
+    }
+
+    fn independent_tags(&self) -> bool {
+        self.independent_tags
+    }
+
+    fn tag_display_behavior(&self) -> TagDisplayBehavior {
+        self.tag_display_behavior
+    }
+
+    fn drag_refresh_rate(&self) -> u32 {
+        self.drag_refresh_rate
+    }
+
+    fn drag_threshold(&self) -> i32 {
+        self.drag_threshold
+    }
+
+    fn double_click_time(&self) -> u64 {
+        self.double_click_time
+    }
+
+    fn drop_zones(&self) -> bool {
+        self.drop_zones
+    }
+
+    fn drag_outline(&self) -> bool {
+        self.drag_outline
+    }
+
+    fn close_transients_with_parent(&self) -> bool {
+        self.close_transients_with_parent
+    }
+
+    fn csd_shadows(&self) -> bool {
+        self.csd_shadows
+    }
+
+    fn systray(&self) -> Option<Systray> {
+        self.systray.clone()
+    }
+
+    fn named_layouts(&self) -> Vec<NamedLayout> {
+        self.layout.clone().unwrap_or_default()
+    }
+
+    fn lock_command(&self) -> Option<String> {
+        self.lock_command.clone()
+    }
+
+    fn edge_actions(&self) -> Vec<EdgeAction> {
+        self.edge_action.clone().unwrap_or_default()
+    }
 }
 
 impl Config {
@@ -211,6 +371,14 @@ impl Config {
         }
         return vec![];
     }
+
+    pub fn create_list_of_window_rules(&self) -> Vec<WindowRule> {
+        self.window_rule.clone().unwrap_or_default()
+    }
+
+    pub fn create_list_of_startup_commands(&self) -> Vec<StartupCommand> {
+        self.startup.clone().unwrap_or_default()
+    }
 }
 
 impl Default for Config {
@@ -227,6 +395,9 @@ impl Default for Config {
                 value: Some("dmenu_run".to_owned()),
                 modifier: vec!["modkey".to_owned()],
                 key: "p".to_owned(),
+                repeat: false,
+                essential: false,
+                exec: ExecOptions::default(),
             },
             // Mod + Shift + Enter => Open A Shell
             Keybind {
@@ -234,6 +405,9 @@ impl Default for Config {
                 value: Some(default_terminal().to_owned()),
                 modifier: vec!["modkey".to_owned(), "Shift".to_owned()],
                 key: "Return".to_owned(),
+                repeat: false,
+                essential: false,
+                exec: ExecOptions::default(),
             },
             // Mod + Shift + q => kill focused window
             Keybind {
@@ -241,6 +415,9 @@ impl Default for Config {
                 value: None,
                 modifier: vec!["modkey".to_owned(), "Shift".to_owned()],
                 key: "q".to_owned(),
+                repeat: false,
+                essential: false,
+                exec: ExecOptions::default(),
             },
             // Mod + Shift + r => soft reload leftwm
             Keybind {
@@ -248,6 +425,9 @@ impl Default for Config {
                 value: None,
                 modifier: vec!["modkey".to_owned(), "Shift".to_owned()],
                 key: "r".to_owned(),
+                repeat: false,
+                essential: false,
+                exec: ExecOptions::default(),
             },
             // Mod + Shift + x => exit leftwm
             Keybind {
@@ -255,6 +435,9 @@ impl Default for Config {
                 value: Some(exit_strategy().to_owned()),
                 modifier: vec!["modkey".to_owned(), "Shift".to_owned()],
                 key: "x".to_owned(),
+                repeat: false,
+                essential: false,
+                exec: ExecOptions::default(),
             },
             // Mod + Ctrl + l => lock the screen
             Keybind {
@@ -262,6 +445,9 @@ impl Default for Config {
                 value: Some("slock".to_owned()),
                 modifier: vec!["modkey".to_owned(), "Control".to_owned()],
                 key: "l".to_owned(),
+                repeat: false,
+                essential: false,
+                exec: ExecOptions::default(),
             },
             // Mod + Shift + w => swap the tags on the last to active workspaces
             Keybind {
@@ -269,6 +455,9 @@ impl Default for Config {
                 value: None,
                 modifier: vec!["modkey".to_owned(), "Shift".to_owned()],
                 key: "w".to_owned(),
+                repeat: false,
+                essential: false,
+                exec: ExecOptions::default(),
             },
             // Mod + w => move the active window to the previous workspace
             Keybind {
@@ -276,108 +465,162 @@ impl Default for Config {
                 value: None,
                 modifier: vec!["modkey".to_owned()],
                 key: "w".to_owned(),
+                repeat: false,
+                essential: false,
+                exec: ExecOptions::default(),
             },
             Keybind {
                 command: Command::MoveWindowUp,
                 value: None,
                 modifier: vec!["modkey".to_owned(), "Shift".to_owned()],
                 key: "k".to_owned(),
+                repeat: false,
+                essential: false,
+                exec: ExecOptions::default(),
             },
             Keybind {
                 command: Command::MoveWindowDown,
                 value: None,
                 modifier: vec!["modkey".to_owned(), "Shift".to_owned()],
                 key: "j".to_owned(),
+                repeat: false,
+                essential: false,
+                exec: ExecOptions::default(),
             },
             Keybind {
                 command: Command::MoveWindowTop,
                 value: None,
                 modifier: vec!["modkey".to_owned()],
                 key: "Return".to_owned(),
+                repeat: false,
+                essential: false,
+                exec: ExecOptions::default(),
             },
             Keybind {
                 command: Command::FocusWindowUp,
                 value: None,
                 modifier: vec!["modkey".to_owned()],
                 key: "k".to_owned(),
+                repeat: false,
+                essential: false,
+                exec: ExecOptions::default(),
             },
             Keybind {
                 command: Command::FocusWindowDown,
                 value: None,
                 modifier: vec!["modkey".to_owned()],
                 key: "j".to_owned(),
+                repeat: false,
+                essential: false,
+                exec: ExecOptions::default(),
             },
             Keybind {
                 command: Command::NextLayout,
                 value: None,
                 modifier: vec!["modkey".to_owned(), "Control".to_owned()],
                 key: "k".to_owned(),
+                repeat: false,
+                essential: false,
+                exec: ExecOptions::default(),
             },
             Keybind {
                 command: Command::PreviousLayout,
                 value: None,
                 modifier: vec!["modkey".to_owned(), "Control".to_owned()],
                 key: "j".to_owned(),
+                repeat: false,
+                essential: false,
+                exec: ExecOptions::default(),
             },
             Keybind {
                 command: Command::FocusWorkspaceNext,
                 value: None,
                 modifier: vec!["modkey".to_owned()],
                 key: "l".to_owned(),
+                repeat: false,
+                essential: false,
+                exec: ExecOptions::default(),
             },
             Keybind {
                 command: Command::FocusWorkspacePrevious,
                 value: None,
                 modifier: vec!["modkey".to_owned()],
                 key: "h".to_owned(),
+                repeat: false,
+                essential: false,
+                exec: ExecOptions::default(),
             },
             Keybind {
                 command: Command::MoveWindowUp,
                 value: None,
                 modifier: vec!["modkey".to_owned(), "Shift".to_owned()],
                 key: "Up".to_owned(),
+                repeat: false,
+                essential: false,
+                exec: ExecOptions::default(),
             },
             Keybind {
                 command: Command::MoveWindowDown,
                 value: None,
                 modifier: vec!["modkey".to_owned(), "Shift".to_owned()],
                 key: "Down".to_owned(),
+                repeat: false,
+                essential: false,
+                exec: ExecOptions::default(),
             },
             Keybind {
                 command: Command::FocusWindowUp,
                 value: None,
                 modifier: vec!["modkey".to_owned()],
                 key: "Up".to_owned(),
+                repeat: false,
+                essential: false,
+                exec: ExecOptions::default(),
             },
             Keybind {
                 command: Command::FocusWindowDown,
                 value: None,
                 modifier: vec!["modkey".to_owned()],
                 key: "Down".to_owned(),
+                repeat: false,
+                essential: false,
+                exec: ExecOptions::default(),
             },
             Keybind {
                 command: Command::NextLayout,
                 value: None,
                 modifier: vec!["modkey".to_owned(), "Control".to_owned()],
                 key: "Up".to_owned(),
+                repeat: false,
+                essential: false,
+                exec: ExecOptions::default(),
             },
             Keybind {
                 command: Command::PreviousLayout,
                 value: None,
                 modifier: vec!["modkey".to_owned(), "Control".to_owned()],
                 key: "Down".to_owned(),
+                repeat: false,
+                essential: false,
+                exec: ExecOptions::default(),
             },
             Keybind {
                 command: Command::FocusWorkspaceNext,
                 value: None,
                 modifier: vec!["modkey".to_owned()],
                 key: "Right".to_owned(),
+                repeat: false,
+                essential: false,
+                exec: ExecOptions::default(),
             },
             Keybind {
                 command: Command::FocusWorkspacePrevious,
                 value: None,
                 modifier: vec!["modkey".to_owned()],
                 key: "Left".to_owned(),
+                repeat: false,
+                essential: false,
+                exec: ExecOptions::default(),
             },
         ];
 
@@ -388,6 +631,9 @@ impl Default for Config {
                 value: Some(i.to_string()),
                 modifier: vec!["modkey".to_owned()],
                 key: i.to_string(),
+                repeat: false,
+                essential: false,
+                exec: ExecOptions::default(),
             });
         }
 
@@ -398,26 +644,100 @@ impl Default for Config {
                 value: Some(i.to_string()),
                 modifier: vec!["modkey".to_owned(), "Shift".to_owned()],
                 key: i.to_string(),
+                repeat: false,
+                essential: false,
+                exec: ExecOptions::default(),
             });
         }
 
+        let mousebinds = vec![
+            // MouseKey + Left drag => move the window under the cursor
+            Mousebind {
+                command: Command::MouseMoveWindow,
+                value: None,
+                modifier: vec!["mousekey".to_owned()],
+                button: "Button1".to_owned(),
+                target: MouseTarget::Window,
+                double_click: false,
+            },
+            // MouseKey + Right drag => resize the window under the cursor
+            Mousebind {
+                command: Command::MouseResizeWindow,
+                value: None,
+                modifier: vec!["mousekey".to_owned()],
+                button: "Button3".to_owned(),
+                target: MouseTarget::Window,
+                double_click: false,
+            },
+            // Left drag on a title bar => move the window
+            Mousebind {
+                command: Command::MouseMoveWindow,
+                value: None,
+                modifier: vec![],
+                button: "Button1".to_owned(),
+                target: MouseTarget::TitleBar,
+                double_click: false,
+            },
+        ];
+
         let tags = vec!["1", "2", "3", "4", "5", "6", "7", "8", "9"]
             .iter()
             .map(|s| (*s).to_string())
             .collect();
 
         Self {
+            schema_version: validation::CONFIG_SCHEMA_VERSION,
             workspaces: Some(vec![]),
             tags: Some(tags),
             layouts: LAYOUTS.to_vec(),
+            layout: None,
             scratchpad: Some(vec![]),
+            window_rule: None,
             disable_current_tag_swap: false,
+            focus_tag_wraparound: true,
+            focus_tag_skip_empty: false,
+            independent_tags: false,
+            tag_display_behavior: TagDisplayBehavior::Swap, // default behaviour: swap tags between workspaces
+            drag_refresh_rate: 60,
+            drag_threshold: 0,
+            double_click_time: 400,
+            drop_zones: false,
+            drag_outline: false,
+            close_transients_with_parent: false,
+            csd_shadows: false,
+            systray: None,
             focus_behaviour: FocusBehaviour::Sloppy, // default behaviour: mouse move auto-focuses window
             focus_new_windows: true, // default behaviour: focuses windows on creation
-            modkey: "Mod4".to_owned(), //win key
+            focus_on_close: FocusOnClose::Previous,
+            focus_stealing_threshold: None,
+            focus_stealing_allowed: vec![],
+            mouse_focus_ignores_docks: false,
+            modkey: "Mod4".to_owned(),   //win key
             mousekey: "Mod4".to_owned(), //win key
             keybind: commands,
+            mousebind: mousebinds,
+            idle: None,
+            tag_hook: None,
+            hook: None,
+            startup: None,
+            edge_action: None,
+            command_token: None,
+            lock_command: None,
             max_window_width: None,
+            floating_placement: FloatingPlacement::Center,
+            floating_cascade_offset: 30,
+            floating_types: vec![WindowType::Dialog, WindowType::Utility, WindowType::Splash],
+            floating_classes: vec![
+                "Pinentry".to_owned(),
+                "Gcr-prompter".to_owned(),
+                "Yad".to_owned(),
+                "Zenity".to_owned(),
+                "Xmessage".to_owned(),
+            ],
+            tiled_resize_request: TiledResizeRequest::Deny,
+            diagnostics_history: 200,
+            state_write_interval: 50,
+            window_burst_delay: 50,
         }
     }
 }
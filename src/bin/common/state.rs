@@ -52,6 +52,9 @@ fn restore_workspaces(manager: &mut Manager, old_manager: &Manager) {
         if let Some(old_workspace) = old_manager.workspaces.iter().find(|w| w.id == workspace.id) {
             workspace.layout = old_workspace.layout.clone();
             workspace.margin_multiplier = old_workspace.margin_multiplier;
+            workspace.reserved = old_workspace.reserved.clone();
+            workspace.docks_hidden = old_workspace.docks_hidden;
+            workspace.update_avoided_areas();
         }
     }
 }
@@ -100,6 +103,9 @@ fn restore_windows(manager: &mut Manager, old_manager: &Manager) {
             }
             window.strut = old.strut;
             window.set_states(old.states());
+            window.group = old.group;
+            window.group_hidden = old.group_hidden;
+            window.saved_xyhw = old.saved_xyhw;
             ordered.push(window.clone());
             manager.windows.remove(index);
         }
@@ -114,4 +120,11 @@ fn restore_scratchpads(manager: &mut Manager, old_manager: &Manager) {
     for (scratchpad, id) in &old_manager.active_scratchpads {
         manager.active_scratchpads.insert(scratchpad.clone(), *id);
     }
+    for (scratchpad, handle) in &old_manager.attached_scratchpads {
+        if manager.windows.iter().any(|w| w.handle == *handle) {
+            manager
+                .attached_scratchpads
+                .insert(scratchpad.clone(), *handle);
+        }
+    }
 }
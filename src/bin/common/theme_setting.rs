@@ -26,7 +26,15 @@ impl leftwm::config::ThemeLoader for ThemeLoader {
             default_border_color: "#000000".to_owned(),
             floating_border_color: "#000000".to_owned(),
             focused_border_color: "#FF0000".to_owned(),
+            title_bar_height: None,
+            title_bar_color: None,
+            title_bar_focused_color: None,
+            title_bar_text_color: None,
             on_new_window_cmd: None,
+            smart_borders: false,
+            smart_gaps: false,
+            reserve_space: vec![],
+            focus_indicator: None,
         }
     }
 }
@@ -1,11 +1,11 @@
 mod common;
 
 use clap::{App, Arg};
-use leftwm::config::{Keybind, ThemeSetting, Workspace};
+use leftwm::config::include;
+use leftwm::config::validation::{self, Diagnostic, Severity, Subject};
+use leftwm::config::{Keybind, Mousebind, StartupCommand, Workspace};
 use leftwm::errors::Result;
-use leftwm::utils;
-use leftwm::Command;
-use std::collections::HashMap;
+use leftwm::layouts::{plugin, Layout};
 use std::fs;
 use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
@@ -31,10 +31,16 @@ async fn main() -> Result<()> {
                 .long("verbose")
                 .help("Outputs received configuration file."),
         )
+        .arg(
+            Arg::with_name("dry-run-keybinds")
+                .long("dry-run-keybinds")
+                .help("Runs each keybind on a test window manager to find values that do nothing."),
+        )
         .get_matches();
 
     let config_file = matches.value_of("INPUT");
     let verbose = matches.occurrences_of("verbose") >= 1;
+    let dry_run = matches.occurrences_of("dry-run-keybinds") >= 1;
 
     println!(
         "\x1b[0;94m::\x1b[0m LeftWM version: {}",
@@ -54,8 +60,14 @@ async fn main() -> Result<()> {
             if verbose {
                 dbg!(&config);
             }
+            if dry_run {
+                dry_run_keybinds(&config, verbose);
+            }
             check_workspace_ids(config.workspaces, verbose);
+            check_layouts(&config.layouts, verbose);
             check_keybinds(config.keybind, verbose);
+            check_mousebinds(config.mousebind, verbose);
+            check_startup_commands(config.startup.unwrap_or_default(), verbose);
         }
         Err(e) => {
             println!("Configuration failed. Reason: {:?}", e);
@@ -88,11 +100,12 @@ pub fn load_from_file(fspath: Option<&str>, verbose: bool) -> Result<Config> {
         dbg!(&config_filename);
     }
     if Path::new(&config_filename).exists() {
-        let contents = fs::read_to_string(config_filename)?;
+        let contents = include::read_with_includes(&config_filename)?;
         if verbose {
             dbg!(&contents);
         }
-        Ok(toml::from_str(&contents)?)
+        check_deprecated(&config_filename)?;
+        Ok(contents.try_into()?)
     } else {
         Err(leftwm::errors::LeftError::from(std::io::Error::new(
             std::io::ErrorKind::Other,
@@ -101,118 +114,133 @@ pub fn load_from_file(fspath: Option<&str>, verbose: bool) -> Result<Config> {
     }
 }
 
+/// Checks the config file and those it includes for renamed keys and commands.
+fn check_deprecated(config_filename: &Path) -> Result<()> {
+    for file in include::files(config_filename)? {
+        let diagnostics = validation::check_deprecated(&fs::read_to_string(&file)?);
+        if !diagnostics.is_empty() {
+            println!("\x1b[1;35mIn {}:\x1b[0m", file.display());
+            report(&diagnostics);
+        }
+    }
+    Ok(())
+}
+
 /// Checks defined workspaces to ensure no ID collisions occur.
 fn check_workspace_ids(workspaces: Option<Vec<Workspace>>, verbose: bool) -> bool {
-    workspaces.map_or(true, |wss|
-    {
+    workspaces.map_or(true, |wss| {
         if verbose {
             println!("Checking config for valid workspace definitions.");
         }
-        let ids = common::config::get_workspace_ids(&wss);
-        if ids.iter().any(std::option::Option::is_some) {
-            if !common::config::all_ids_some(&ids)
-            {
-                println!("Your config.toml specifies an ID for some but not all workspaces. This can lead to ID collisions and is not allowed. The default config will be used instead.");
-                false
-            } else if common::config::all_ids_unique(&ids) {
-                true
-            } else {
-                println!("Your config.toml contains duplicate workspace IDs. Please assign unique IDs to workspaces. The default config will be used instead.");
-                false
-            }
-        } else {
-            true
-        }
+        report(&validation::check_workspace_ids(&wss))
+    })
+}
+
+/// Checks that the plugin layouts can be found.
+fn check_layouts(layouts: &[Layout], verbose: bool) -> bool {
+    if verbose {
+        println!("Checking config for missing layout plugins.");
     }
-    )
+    report(&validation::check_layouts(layouts, &plugin::available()))
 }
 
 /// Check all keybinds to ensure that required values are provided
 /// Checks to see if value is provided (if required)
 /// Checks to see if keys are valid against Xkeysym
-/// Ideally, we will pass this to the command handler with a dummy config
+/// See `dry_run_keybinds` for passing them to the command handler
 fn check_keybinds(keybinds: Vec<Keybind>, verbose: bool) -> bool {
-    let mut returns = Vec::new();
-    let value_required_commands = vec![
-        Command::ToggleScratchPad,
-        Command::MoveToTag,
-        Command::GotoTag,
-        Command::Execute,
-        Command::IncreaseMainWidth,
-        Command::DecreaseMainWidth,
-        Command::SetLayout,
-        Command::SetMarginMultiplier,
-    ];
     println!("\x1b[0;94m::\x1b[0m Checking keybinds . . .");
-    let mut bindings = HashMap::new();
-    for keybind in keybinds {
-        if verbose {
+    if verbose {
+        for keybind in &keybinds {
             println!("Keybind: {:?} {}", keybind, keybind.value.is_none());
         }
-        if value_required_commands
-            .iter()
-            .any(|i| i.clone() == keybind.command)
-            && keybind.value.is_none()
-        {
-            returns.push((
-                Some(keybind.clone()),
-                "This keybind requres a `string`".to_owned(),
-            ));
-        }
-        if utils::xkeysym_lookup::into_keysym(&keybind.key).is_none() {
-            returns.push((
-                Some(keybind.clone()),
-                format!("Key `{}` is not valid", keybind.key),
-            ));
-        }
+    }
+    let ok = report(&validation::check_keybinds(&keybinds));
+    if ok {
+        println!("\x1b[0;92m    -> All keybinds OK\x1b[0m");
+    }
+    ok
+}
 
-        for m in &keybind.modifier {
-            if m != "modkey" && m != "mousekey" && utils::xkeysym_lookup::into_mod(m) == 0 {
-                returns.push((
-                    Some(keybind.clone()),
-                    format!("Modifier `{}` is not valid", m),
-                ));
-            }
+/// Runs the keybinds through the command handler, without a display, to catch values that are
+/// only rejected at runtime
+fn dry_run_keybinds(config: &Config, verbose: bool) -> bool {
+    println!("\x1b[0;94m::\x1b[0m Dry running keybinds . . .");
+    if verbose {
+        println!("Tags: {:?}", config.tags);
+    }
+    let ok = report(&validation::dry_run_keybinds(config, &config.keybind));
+    if ok {
+        println!("\x1b[0;92m    -> All keybinds ran OK\x1b[0m");
+    }
+    ok
+}
+
+/// Check all mousebinds to ensure that buttons and modifiers are valid
+/// and that only mouse commands are bound
+fn check_mousebinds(mousebinds: Vec<Mousebind>, verbose: bool) -> bool {
+    println!("\x1b[0;94m::\x1b[0m Checking mousebinds . . .");
+    if verbose {
+        for mousebind in &mousebinds {
+            println!("Mousebind: {:?}", mousebind);
         }
-        let mut modkey = keybind.modifier.clone();
-        modkey.sort();
-        if let Some(conflict_key) = bindings.get(&(modkey.clone(), keybind.key.clone())) {
-            returns.push((
-                None,
-                format!(
-                    "\x1b[0m\x1b[1mMultiple commands bound to key combination {} + {}:\
-                    \n\x1b[1;91m    -> {:?}\
-                    \n    -> {:?}\
-                    \n\x1b[0mHelp: change one of the keybindings to something else.\n",
-                    keybind.modifier.join(" + "),
-                    keybind.key,
-                    conflict_key,
-                    keybind.command,
-                ),
-            ));
-        } else {
-            bindings.insert((modkey, keybind.key), keybind.command);
+    }
+    let ok = report(&validation::check_mousebinds(&mousebinds));
+    if ok {
+        println!("\x1b[0;92m    -> All mousebinds OK\x1b[0m");
+    }
+    ok
+}
+
+/// Check all startup commands to ensure that required values are provided
+fn check_startup_commands(startup: Vec<StartupCommand>, verbose: bool) -> bool {
+    println!("\x1b[0;94m::\x1b[0m Checking startup commands . . .");
+    if verbose {
+        for command in &startup {
+            println!("Startup: {:?}", command);
         }
     }
-    if returns.is_empty() {
-        println!("\x1b[0;92m    -> All keybinds OK\x1b[0m");
-        true
-    } else {
-        for error in returns {
-            match error.0 {
-                Some(binding) => {
-                    println!(
-                        "\x1b[1;91mERROR: {} for keybind {:?}\x1b[0m",
-                        error.1, binding
-                    );
-                }
-                None => {
-                    println!("\x1b[1;91mERROR: {} \x1b[0m", error.1);
-                }
+    let ok = report(&validation::check_startup_commands(&startup));
+    if ok {
+        println!("\x1b[0;92m    -> All startup commands OK\x1b[0m");
+    }
+    ok
+}
+
+/// Prints the diagnostics, returns true if there were no errors.
+fn report(diagnostics: &[Diagnostic]) -> bool {
+    for diagnostic in diagnostics {
+        let (color, label) = match diagnostic.severity {
+            Severity::Warning => ("\x1b[1;93m", "WARN"),
+            Severity::Error => ("\x1b[1;91m", "ERROR"),
+        };
+        match &diagnostic.subject {
+            Subject::Keybind(binding) => println!(
+                "{}{}: {} for keybind {:?}\x1b[0m",
+                color, label, diagnostic.message, binding
+            ),
+            Subject::Mousebind(binding) => println!(
+                "{}{}: {} for mousebind {:?}\x1b[0m",
+                color, label, diagnostic.message, binding
+            ),
+            Subject::Startup(command) => println!(
+                "{}{}: {} for startup command {:?}\x1b[0m",
+                color, label, diagnostic.message, command
+            ),
+            Subject::Layout(layout) => println!(
+                "{}{}: {} for layout {}\x1b[0m",
+                color, label, diagnostic.message, layout
+            ),
+            Subject::Line(line, text) => println!(
+                "{}{}: {} on line {}: {}\x1b[0m",
+                color, label, diagnostic.message, line, text
+            ),
+            Subject::Workspaces | Subject::Theme => {
+                println!("{}{}: {}\x1b[0m", color, label, diagnostic.message);
             }
         }
-        false
     }
+    !diagnostics.iter().any(|d| d.severity == Severity::Error)
 }
 
 fn check_elogind(verbose: bool) -> Result<()> {
@@ -383,15 +411,12 @@ fn check_theme_toml(filepath: PathBuf, verbose: bool) -> Result<PathBuf> {
         )));
     };
     let contents = fs::read_to_string(&filepath.as_path())?;
-    let theme_parsable = toml::from_str::<ThemeSetting>(&contents).is_ok();
+    let diagnostics = validation::check_theme(&contents);
     if verbose {
         if metadata.is_file() {
             println!("Found {:?}", &filepath);
-            // TODO better Errormessage for broken theme file
-            if theme_parsable {
+            if report(&diagnostics) {
                 println!("The theme file looks OK.");
-            } else {
-                println!("\x1b[1;91mERROR: The theme file is broken.\x1b[0m");
             }
             Ok(filepath)
         } else {
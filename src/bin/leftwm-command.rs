@@ -1,9 +1,17 @@
 use clap::{App, Arg};
+use leftwm::config::include;
 use leftwm::errors::Result;
+use serde::Deserialize;
 use std::fs::OpenOptions;
 use std::io::prelude::*;
 use xdg::BaseDirectories;
 
+//the only part of config.toml needed to send commands
+#[derive(Deserialize, Default)]
+struct TokenConfig {
+    command_token: Option<String>,
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let matches = App::new("LeftWM Command")
@@ -32,8 +40,13 @@ async fn main() -> Result<()> {
         .open(file_path)
         .expect("ERROR: Couldn't open commands.pipe");
     if let Some(commands) = matches.values_of("command") {
+        let token = command_token();
         for command in commands {
-            if let Err(e) = writeln!(file, "{}", command) {
+            let line = match &token {
+                Some(token) => format!("{} {}", token, command),
+                None => command.to_owned(),
+            };
+            if let Err(e) = writeln!(file, "{}", line) {
                 eprintln!(" ERROR: Couldn't write to commands.pipe: {}", e);
             }
         }
@@ -51,21 +64,32 @@ async fn main() -> Result<()> {
         UnloadTheme
         Reload
         ToggleFullScreen
+        ToggleFakeFullscreen
         SwapScreens
         MoveWindowToLastWorkspace
         FloatingToTile
+        GroupWindow
+        UngroupWindow
+        CycleGroup
         MoveWindowUp
         MoveWindowDown
         FocusWindowUp
         MoveWindowTop
+        ZoomWindow
         FocusWindowDown
         FocusNextTag
         FocusPreviousTag
-        FocusWorkspaceNext
-        FocusWorkspacePrevious
         NextLayout
         PreviousLayout
         RotateTag
+        ToggleTiling
+        UndoWindowOperation
+        LockScreen
+        HideDock
+        ShowDock
+        ToggleDock
+        ShowKeybindHelp
+        ReleaseScratchPad
         CloseWindow
 
         Commands with arguments:
@@ -74,10 +98,28 @@ async fn main() -> Result<()> {
 
         LoadTheme              Args: <Path_to/theme.toml> 
         ToggleScratchPad       Args: <ScratchpadName>
-        SendWorkspaceToTag     Args: <workspaxe_index> <tag_index> (int)
+        AttachToScratchPad     Args: <ScratchpadName>
+        SendWorkspaceToTag     Args: <workspace_index> <tag_index> (int)
+        SwapTags               Args: <workspace_index> <workspace_index> (int)
         SendWindowToTag        Args: <tag_index> (int)
+        ToggleWindowTag        Args: <tag_index> (int)
+        SetWindowTag           Args: <tag> <regex matching the class or title>
+        SaveSnapshot           Args: <name>
+        LoadSnapshot           Args: <name>
+        ToggleTagView          Args: <tag_index> (int)
         SetLayout              Args: <LayoutName>
         SetMarginMultiplier    Args: <multiplier-value> (float)
+        SetMainWidth           Args: <percent> (int) or <pixels>px
+        SetModKey              Args: <modifier> (e.g. Mod1, Mod4)
+        SetMouseKey            Args: <modifier> (e.g. Mod1, Mod4)
+        ReserveSpace           Args: <workspace_index> <Top|Bottom|Left|Right> <pixels>
+        ReleaseSpace           Args: <workspace_index> [Top|Bottom|Left|Right]
+        FocusWorkspaceNext     Args: [warp|nowarp] (optional)
+        FocusWorkspacePrevious Args: [warp|nowarp] (optional)
+        TogglePresentationMode Args: [padding] (optional, int)
+        SetLogLevel            Args: <off|error|warn|info|debug|trace>
+        DumpDiagnostics        Args: [Path_to/file] (optional)
+        FocusWindowByHandle    Args: <window_id> (int or 0x hex)
         
         For more information please visit:
         https://github.com/leftwm/leftwm/wiki/External-Commands
@@ -86,3 +128,15 @@ async fn main() -> Result<()> {
     }
     Ok(())
 }
+
+fn command_token() -> Option<String> {
+    let path = BaseDirectories::with_prefix("leftwm")
+        .ok()?
+        .find_config_file("config.toml")?;
+    //the token may be set in a file config.toml includes
+    include::read_with_includes(&path)
+        .ok()?
+        .try_into::<TokenConfig>()
+        .unwrap_or_default()
+        .command_token
+}
@@ -1,11 +1,13 @@
 use clap::{value_t, App, Arg};
 use leftwm::errors::Result;
-use leftwm::models::dto::{DisplayState, ManagerState};
+use leftwm::models::dto::{
+    DisplayState, ManagerState, StateMessage, StateQuery, STATE_SCHEMA_VERSION,
+};
 use std::ffi::OsStr;
 use std::path::Path;
 use std::str;
 use tokio::fs;
-use tokio::io::{AsyncBufReadExt, BufReader, Lines};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
 use tokio::net::UnixStream;
 use xdg::BaseDirectories;
 
@@ -13,7 +15,7 @@ type Partials = liquid::partials::EagerCompiler<liquid::partials::InMemorySource
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let matches = App::new("LeftWM State")
+    let app = App::new("LeftWM State")
         .author("Lex Childs <lex.childs@gmail.com>")
         .version(env!("CARGO_PKG_VERSION"))
         .about("prints out the current state of LeftWM")
@@ -53,7 +55,24 @@ async fn main() -> Result<()> {
                 .long("quit")
                 .help("Prints the state once and quits"),
         )
-        .get_matches();
+        .arg(
+            Arg::with_name("keybinds")
+                .short("k")
+                .long("keybinds")
+                .help("Prints the active keybinds and quits"),
+        )
+        .arg(
+            Arg::with_name("windows")
+                .long("windows")
+                .help("Prints the managed windows and quits"),
+        );
+    #[cfg(feature = "metrics")]
+    let app = app.arg(
+        Arg::with_name("metrics")
+            .long("metrics")
+            .help("Prints the metrics in the Prometheus text format and quits"),
+    );
+    let matches = app.get_matches();
 
     let template_file = matches.value_of("template");
 
@@ -65,6 +84,17 @@ async fn main() -> Result<()> {
     };
 
     let mut stream_reader = stream_reader().await?;
+    let mut pending = read_hello(&mut stream_reader).await?;
+    if matches.occurrences_of("keybinds") == 1 {
+        return print_answer(stream_reader, StateQuery::Keybinds).await;
+    }
+    if matches.occurrences_of("windows") == 1 {
+        return print_answer(stream_reader, StateQuery::ListWindows).await;
+    }
+    #[cfg(feature = "metrics")]
+    if matches.occurrences_of("metrics") == 1 {
+        return print_answer(stream_reader, StateQuery::Metrics).await;
+    }
     let once = matches.occurrences_of("quit") == 1;
     let newline = matches.occurrences_of("newline") == 1;
 
@@ -78,7 +108,7 @@ async fn main() -> Result<()> {
             .expect("Unable to build template")
             .parse(&template_str)
             .expect("Unable to parse template");
-        while let Some(line) = stream_reader.next_line().await? {
+        while let Some(line) = next_line(&mut stream_reader, &mut pending).await? {
             let _droppable = template_handler(&template, newline, ws_num, &line);
             if once {
                 break;
@@ -90,14 +120,14 @@ async fn main() -> Result<()> {
             .expect("Unable to build template")
             .parse(string_literal)
             .expect("Unable to parse template");
-        while let Some(line) = stream_reader.next_line().await? {
+        while let Some(line) = next_line(&mut stream_reader, &mut pending).await? {
             let _droppable = template_handler(&template, newline, ws_num, &line);
             if once {
                 break;
             }
         }
     } else {
-        while let Some(line) = stream_reader.next_line().await? {
+        while let Some(line) = next_line(&mut stream_reader, &mut pending).await? {
             let _droppable2 = raw_handler(&line);
             if once {
                 break;
@@ -209,6 +239,74 @@ async fn stream_reader() -> Result<Lines<BufReader<UnixStream>>> {
     Ok(BufReader::new(stream).lines())
 }
 
+/// Reads the greeting of the state socket, and quits if leftwm sends a state this build can't
+/// read. Returns the first line instead if it is state already, leftwm being older than greetings.
+async fn read_hello(reader: &mut Lines<BufReader<UnixStream>>) -> Result<Option<String>> {
+    let line = match reader.next_line().await? {
+        Some(line) => line,
+        None => return Ok(None),
+    };
+    match serde_json::from_str(&line) {
+        Ok(StateMessage::Hello {
+            schema_version,
+            wm_version,
+        }) => {
+            if schema_version != STATE_SCHEMA_VERSION {
+                eprintln!(
+                    "leftwm {} sends version {} of the state, leftwm-state {} reads version {}",
+                    wm_version,
+                    schema_version,
+                    env!("CARGO_PKG_VERSION"),
+                    STATE_SCHEMA_VERSION
+                );
+                std::process::exit(1);
+            }
+            Ok(None)
+        }
+        _ => Ok(Some(line)),
+    }
+}
+
+/// Sends leftwm the query and prints its answer, the active keybinds or the managed windows as
+/// JSON, or the metrics in the Prometheus text format.
+async fn print_answer(mut reader: Lines<BufReader<UnixStream>>, query: StateQuery) -> Result<()> {
+    let mut line = serde_json::to_string(&query)?;
+    line.push('\n');
+    reader
+        .get_mut()
+        .get_mut()
+        .write_all(line.as_bytes())
+        .await?;
+    while let Some(line) = reader.next_line().await? {
+        let json = match serde_json::from_str(&line) {
+            Ok(StateMessage::Keybinds(keybinds)) if query == StateQuery::Keybinds => {
+                serde_json::to_string(&keybinds)?
+            }
+            Ok(StateMessage::Windows(windows)) if query == StateQuery::ListWindows => {
+                serde_json::to_string(&windows)?
+            }
+            Ok(StateMessage::Metrics(metrics)) if query == StateQuery::Metrics => {
+                print!("{}", metrics.to_prometheus());
+                break;
+            }
+            _ => continue,
+        };
+        println!("{}", json);
+        break;
+    }
+    Ok(())
+}
+
+async fn next_line(
+    reader: &mut Lines<BufReader<UnixStream>>,
+    pending: &mut Option<String>,
+) -> Result<Option<String>> {
+    match pending.take() {
+        Some(line) => Ok(Some(line)),
+        None => Ok(reader.next_line().await?),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -4,19 +4,46 @@ use leftwm::{
     child_process::{self, Nanny},
     config::Config as _,
     config::ThemeLoader as _,
-    models::{FocusBehaviour, FocusManager, Tag},
+    config::{ExecOptions, HookEvent},
+    models::{FocusBehaviour, FocusManager, Tag, WindowHandle},
+    utils::action_channel::ActionChannel,
+    utils::diagnostics::Diagnostics,
+    utils::hooks::HookTracker,
+    utils::profiler::Profiler,
 };
 
 use leftwm::{
-    external_command_handler, CommandPipe, DisplayEvent, DisplayEventHandler, DisplayServer,
-    Manager, Mode, State, StateSocket, Window, Workspace, XlibDisplayServer,
+    external_command_handler, CommandPipe, DisplayAction, DisplayEvent, DisplayEventHandler,
+    DisplayServer, Manager, MockDisplayServer, Mode, State, StateSocket, Window, Workspace,
+    XlibDisplayServer,
 };
+use std::collections::VecDeque;
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io::{BufReader, Write};
 use std::panic;
 use std::path::{Path, PathBuf};
 use std::sync::{atomic::Ordering, Arc, Once};
+use tokio::time::{Duration, Instant};
 
 use common::config::Config;
 
+/// How long autostart waits for the 'up' scripts before starting anyway.
+const UP_SCRIPTS_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Replay the events in this file instead of talking to X.
+const REPLAY_ENV: &str = "LEFTWM_REPLAY";
+
+/// Write every event the display server reports to this file, in the format `REPLAY_ENV` reads.
+const RECORD_ENV: &str = "LEFTWM_RECORD";
+
+/// Time every branch of the event loop and log a summary every `PROFILE_INTERVAL`.
+const PROFILE_FLAG: &str = "--profile";
+const PROFILE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The longest a burst of new windows holds back drawing, so a steady stream of them still shows.
+const WINDOW_BURST_MAX: Duration = Duration::from_millis(500);
+
 fn get_events<T: DisplayServer<C>, C: leftwm::config::Config>(ds: &mut T) -> Vec<DisplayEvent> {
     ds.get_next_events()
 }
@@ -32,7 +59,7 @@ fn main() {
         let rt = tokio::runtime::Runtime::new().expect("ERROR: couldn't init Tokio runtime");
         let _rt_guard = rt.enter();
 
-        let config = common::config::load();
+        let (config, rejection) = common::config::load();
         let state = common::state::State;
         let theme_loader = common::theme_setting::ThemeLoader;
         let default_theme = Arc::new(theme_loader.default());
@@ -40,13 +67,17 @@ fn main() {
         let focus_manager = FocusManager {
             behaviour: config.focus_behaviour,
             focus_new_windows: config.focus_new_windows,
+            focus_on_close: config.focus_on_close,
+            focus_stealing_threshold: config.focus_stealing_threshold,
+            focus_stealing_allowed: config.focus_stealing_allowed.clone(),
+            mouse_focus_ignores_docks: config.mouse_focus_ignores_docks,
             ..FocusManager::default()
         };
 
         let mut tags: Vec<Tag> = config
             .create_list_of_tags()
             .iter()
-            .map(|s| Tag::new(s))
+            .map(|s| Tag::from_config(s))
             .collect();
         tags.push(Tag {
             id: "NSP".to_owned(),
@@ -65,38 +96,96 @@ fn main() {
             workspaces: std::vec::Vec::default(),
             mode: leftwm::Mode::default(),
             active_scratchpads: std::collections::HashMap::default(),
+            attached_scratchpads: std::collections::HashMap::default(),
+            window_rules: config.create_list_of_window_rules(),
             actions: std::collections::VecDeque::default(),
-            frame_rate_limitor: Default::default(),
+            frame_rate_limitors: Default::default(),
             children: leftwm::child_process::Children::default(),
             reap_requested: std::sync::Arc::default(),
             reload_requested: Default::default(),
             max_window_width: config.max_window_width,
+            floating_placement: config.floating_placement,
+            floating_cascade_offset: config.floating_cascade_offset,
+            floating_types: config.floating_types.clone(),
+            floating_classes: config.floating_classes.clone(),
+            tiled_resize_request: config.tiled_resize_request,
+            independent_tags: config.independent_tags,
+            modkey: None,
+            mousekey: None,
+            systray_strut: None,
+            undo_journal: Default::default(),
+            screen_lock: None,
+            edge_tracker: Default::default(),
+            click_tracker: Default::default(),
+            diagnostics: Diagnostics::new(config.diagnostics_history),
+            metrics: Default::default(),
+            peek_origin: None,
+            presentation: None,
+            state_dirty: true,
+            keys_passed: false,
         };
 
         child_process::register_child_hook(manager.reap_requested.clone());
-
         let config = Arc::new(config);
-        let mut display_server = XlibDisplayServer::new(config.clone(), default_theme);
         let handler = DisplayEventHandler {
             config: config.clone(),
         };
 
-        rt.block_on(event_loop(
-            &mut manager,
-            &mut display_server,
-            &handler,
-            config,
-            state,
-            theme_loader,
-        ));
+        if let Some(trace) = std::env::var_os(REPLAY_ENV) {
+            let mut display_server: MockDisplayServer =
+                DisplayServer::new(config.clone(), default_theme);
+            load_trace(&mut display_server, &trace);
+            rt.block_on(event_loop(
+                &mut manager,
+                &mut display_server,
+                &handler,
+                config,
+                state,
+                theme_loader,
+                rejection,
+            ));
+        } else {
+            let mut display_server: XlibDisplayServer<_> =
+                DisplayServer::new(config.clone(), default_theme);
+            rt.block_on(event_loop(
+                &mut manager,
+                &mut display_server,
+                &handler,
+                config,
+                state,
+                theme_loader,
+                rejection,
+            ));
+        }
+        manager.reload_requested
     });
 
     match completed {
-        Ok(_) => log::info!("Completed"),
+        Ok(true) => {
+            log::info!("Completed, reloading");
+            drop(_log_guard);
+            std::process::exit(child_process::RELOAD_EXIT_CODE);
+        }
+        Ok(false) => log::info!("Completed"),
         Err(err) => log::error!("Completed with error: {:?}", err),
     }
 }
 
+/// Tells the user config.toml was rejected and why, by a popup, and by a notification too if
+/// `notify-send` is there.
+fn notify_config_rejected(manager: &mut Manager, reason: &str) {
+    let summary = "LeftWM: config.toml was rejected, the default config is used";
+    if common::config::is_program_in_path("notify-send") {
+        let args = ["--urgency=critical", summary, reason];
+        child_process::exec_hook("notify-send", &args, manager);
+    }
+    let mut lines = vec![summary.to_owned(), String::new()];
+    lines.extend(reason.lines().map(str::to_owned));
+    lines.push(String::new());
+    lines.push("Press any key to close this message.".to_owned());
+    manager.actions.push_back(DisplayAction::ShowMessage(lines));
+}
+
 fn place_runtime_file<P>(path: P) -> std::io::Result<PathBuf>
 where
     P: AsRef<Path>,
@@ -104,18 +193,135 @@ where
     xdg::BaseDirectories::with_prefix("leftwm")?.place_runtime_file(path)
 }
 
+fn load_trace(display_server: &mut MockDisplayServer, path: &OsStr) {
+    let loaded = match File::open(path) {
+        Ok(file) => display_server.load_events(BufReader::new(file)),
+        Err(err) => Err(err.into()),
+    };
+    if let Err(err) = loaded {
+        log::error!("Couldn't load the event trace {:?}: {}", path, err);
+    }
+}
+
+fn record_events(recorder: &mut File, events: &[DisplayEvent]) {
+    for event in events {
+        let written = serde_json::to_string(event)
+            .map_err(Into::into)
+            .and_then(|line| writeln!(recorder, "{}", line));
+        if let Err(err) = written {
+            log::error!("Couldn't record event {:?}: {}", event, err);
+        }
+    }
+}
+
+/// The `[[startup]]` commands as events, leaving out the ones not meant to rerun on a reload.
+fn startup_events(config: &Config) -> Vec<DisplayEvent> {
+    let reloaded = std::env::var_os(child_process::LEFTWM_RELOAD_ENV).is_some();
+    config
+        .create_list_of_startup_commands()
+        .into_iter()
+        .filter(|startup| !reloaded || startup.on_reload)
+        .map(|startup| DisplayEvent::SendCommand(startup.command, startup.value))
+        .collect()
+}
+
+/// Adds the time since `start` to the branch, when profiling.
+fn record(profiler: &mut Option<Profiler>, branch: &'static str, start: Instant) {
+    if let Some(profiler) = profiler {
+        profiler.record(branch, start.elapsed());
+    }
+}
+
+/// The focused workspace, window and tag, the state output is written again when they change.
+fn focus_of(manager: &Manager) -> (Option<usize>, Option<WindowHandle>, Option<String>) {
+    let focus = &manager.focus_manager;
+    (
+        focus.workspace_history.front().copied(),
+        focus.window_history.front().copied().flatten(),
+        focus.tag(0),
+    )
+}
+
+// Runs the actions taken from the channel on the display server, the events they cause are handled
+// next.
+fn run_actions<D: DisplayServer<Arc<Config>>>(
+    manager: &mut Manager,
+    display_server: &mut D,
+    actions: Vec<DisplayAction>,
+    event_buffer: &mut Vec<DisplayEvent>,
+) {
+    for act in actions {
+        manager.diagnostics.record_action(&act);
+        event_buffer.extend(display_server.execute_action(act));
+    }
+}
+
+//a new window taking the focus waits in `deferred` while windows are still appearing, the last
+//one is focused once they stop. Focus moved otherwise since, like by a keybind, is newer and wins.
+//`from` is where the actions queued for the window or the other change start.
+fn route_focus(
+    actions: &mut VecDeque<DisplayAction>,
+    from: usize,
+    created: bool,
+    deferred: &mut Option<DisplayAction>,
+) {
+    let is_focus = |act: &DisplayAction| matches!(act, DisplayAction::WindowTakeFocus(..));
+    if !created {
+        if actions.iter().skip(from).any(is_focus) {
+            *deferred = None;
+        }
+        return;
+    }
+    for act in actions.drain(from..).collect::<Vec<_>>() {
+        if is_focus(&act) {
+            *deferred = Some(act);
+        } else {
+            actions.push_back(act);
+        }
+    }
+}
+
 async fn timeout(mills: u64) {
-    use tokio::time::{sleep, Duration};
-    sleep(Duration::from_millis(mills)).await;
+    tokio::time::sleep(Duration::from_millis(mills)).await;
+}
+
+fn run_idle_hook(manager: &mut Manager, command: Option<&String>) {
+    if let Some(command) = command {
+        if child_process::exec_shell(command, manager).is_none() {
+            log::error!("Idle hook failed: {}", command);
+        }
+    }
+}
+
+/// Runs the `[[hook]]` commands of the event with `LEFTWM_EVENT` and the variables of the event
+/// set, and the tag hook with the tag and output when a workspace shows another tag.
+fn run_hooks(manager: &mut Manager, config: &Config, event: HookEvent, mut options: ExecOptions) {
+    options
+        .env
+        .insert("LEFTWM_EVENT".to_owned(), event.name().to_owned());
+    let hooks = config.hook.iter().flatten().filter(|h| h.event == event);
+    for hook in hooks {
+        if child_process::exec_shell_with(&hook.command, &options, manager).is_none() {
+            log::error!("Hook failed: {}", hook.command);
+        }
+    }
+    if let (HookEvent::TagChanged, Some(command)) = (event, &config.tag_hook) {
+        let arg = |name: &str| options.env.get(name).map_or("", String::as_str);
+        let args = [arg("LEFTWM_TAG"), arg("LEFTWM_OUTPUT")];
+        if child_process::exec_hook(command, &args, manager).is_none() {
+            log::error!("Tag hook failed: {}", command);
+        }
+    }
 }
 
-async fn event_loop(
+async fn event_loop<D: DisplayServer<Arc<Config>>>(
     manager: &mut Manager,
-    display_server: &mut XlibDisplayServer<Arc<Config>>,
+    display_server: &mut D,
     handler: &DisplayEventHandler<Arc<Config>>,
     config: Arc<Config>,
     state: common::state::State,
     theme_loader: common::theme_setting::ThemeLoader,
+    rejection: Option<String>,
 ) {
     let socket_file = place_runtime_file("current_state.sock")
         .expect("ERROR: couldn't create current_state.sock");
@@ -127,25 +333,145 @@ async fn event_loop(
 
     let pipe_file =
         place_runtime_file("commands.pipe").expect("ERROR: couldn't create commands.pipe");
-    let mut command_pipe = CommandPipe::new(pipe_file)
+    let mut command_pipe = CommandPipe::with_token(pipe_file, config.command_token.clone())
         .await
         .expect("ERROR: couldn't connect to commands.pipe");
 
     //start the current theme
     let after_first_loop: Once = Once::new();
 
+    let idle = config.idle.clone();
+    let idle_timeout = Duration::from_secs(idle.as_ref().map_or(0, |idle| idle.timeout));
+    let mut last_activity = Instant::now();
+    let mut is_idle = false;
+    //with the input the display counts, the events seen here don't tell the user is there
+    let idle_counted = display_server.idle_time().is_some();
+
+    let state_write_interval = Duration::from_millis(config.state_write_interval);
+    let mut state_written = Instant::now();
+    let window_burst_delay = Duration::from_millis(config.window_burst_delay);
+    //while windows keep appearing, drawing and moving the focus wait until this is due
+    let mut burst_started = None;
+    let mut burst_due: Option<Instant> = None;
+    let mut update_deferred = false;
+    let mut focus_deferred = None;
+    //the modkey the keybinds on the state socket were mapped with
+    let mut keybinds_modkey = None;
+    //how things were when the hooks last ran
+    let mut hook_tracker = HookTracker::default();
+    let has_hooks =
+        matches!(&config.hook, Some(hooks) if !hooks.is_empty()) || config.tag_hook.is_some();
+
+    let mut recorder = std::env::var_os(RECORD_ENV).and_then(|path| {
+        File::create(&path)
+            .map_err(|err| log::error!("Couldn't record events to {:?}: {}", path, err))
+            .ok()
+    });
+
+    //SIGTERM and SIGINT end the worker like a reload, after the wm_exiting hooks
+    let exit_signal = child_process::register_exit_hook()
+        .and_then(tokio::net::UnixStream::from_std)
+        .map_err(|err| log::error!("Cannot register the exit signal handlers: {}", err))
+        .ok();
+    let mut exit_requested = false;
+    let has_edge_actions = handler.has_edge_actions();
+
+    let mut profiler = std::env::args()
+        .any(|arg| arg == PROFILE_FLAG)
+        .then(|| Profiler::new(PROFILE_INTERVAL));
+
+    //the display server runs the actions of the handlers from a branch of its own
+    let mut actions = ActionChannel::default();
+
+    //something was handled since the window list and metrics were last serialized for queries
+    let mut queries_stale = true;
+
     //main event loop
     let mut event_buffer = vec![];
     loop {
-        if manager.mode == Mode::Normal {
+        //nothing is serialized until something changed and the interval has passed
+        if manager.mode == Mode::Normal
+            && manager.state_dirty
+            && state_written.elapsed() >= state_write_interval
+        {
+            let start = Instant::now();
             state_socket.write_manager_state(manager).await.ok();
+            if keybinds_modkey.as_ref() != Some(&manager.modkey) {
+                let keybinds = handler.mapped_bindings(manager);
+                state_socket.set_keybinds(keybinds).await.ok();
+                keybinds_modkey = Some(manager.modkey.clone());
+            }
+            record(&mut profiler, "WriteState", start);
+            state_written = Instant::now();
+            manager.state_dirty = false;
         }
+        //the answers to queries follow every change, the written state is held back
+        if queries_stale {
+            state_socket.refresh_queries(manager).await.ok();
+            queries_stale = false;
+        }
+        //a change still waiting for the interval to pass
+        let state_pending = manager.mode == Mode::Normal && manager.state_dirty;
         display_server.flush();
 
         let mut needs_update = false;
+        let edge_due = handler.edge_action_due(manager).map(Instant::from_std);
+        let select_started = Instant::now();
         tokio::select! {
-            _ = display_server.wait_readable(), if event_buffer.is_empty() => {
-                event_buffer.append(&mut get_events(display_server));
+            _ = display_server.wait_readable(), if event_buffer.is_empty() && actions.pending() == 0 => {
+                let mut events = get_events(display_server);
+                if let Some(recorder) = &mut recorder {
+                    record_events(recorder, &events);
+                }
+                event_buffer.append(&mut events);
+                if !event_buffer.is_empty() && !idle_counted {
+                    last_activity = Instant::now();
+                    if is_idle {
+                        is_idle = false;
+                        run_idle_hook(manager, idle.as_ref().and_then(|idle| idle.on_resume.as_ref()));
+                    }
+                }
+                continue;
+            }
+            _ = tokio::time::sleep_until(state_written + state_write_interval), if state_pending && event_buffer.is_empty() => {
+                continue;
+            }
+            _ = tokio::time::sleep_until(last_activity + idle_timeout), if idle.is_some() && !is_idle && event_buffer.is_empty() => {
+                match display_server.idle_time() {
+                    Some(idle_for) if idle_for < idle_timeout => {
+                        let now = Instant::now();
+                        last_activity = now.checked_sub(idle_for).unwrap_or(now);
+                    }
+                    _ => {
+                        is_idle = true;
+                        run_idle_hook(manager, idle.as_ref().and_then(|idle| idle.on_idle.as_ref()));
+                    }
+                }
+                continue;
+            }
+            _ = timeout(1000), if idle_counted && is_idle && event_buffer.is_empty() => {
+                if let Some(idle_for) = display_server.idle_time().filter(|idle_for| *idle_for < idle_timeout) {
+                    let now = Instant::now();
+                    last_activity = now.checked_sub(idle_for).unwrap_or(now);
+                    is_idle = false;
+                    run_idle_hook(manager, idle.as_ref().and_then(|idle| idle.on_resume.as_ref()));
+                }
+                continue;
+            }
+            _ = tokio::time::sleep_until(edge_due.unwrap_or_else(Instant::now)), if edge_due.is_some() && event_buffer.is_empty() && actions.pending() == 0 => {
+                let start = Instant::now();
+                let focus = focus_of(manager);
+                let queued = manager.actions.len();
+                needs_update = handler.run_edge_action(manager, &state);
+                route_focus(&mut manager.actions, queued, false, &mut focus_deferred);
+                manager.state_dirty |= needs_update || focus_of(manager) != focus;
+                record(&mut profiler, "EdgeAction", start);
+            }
+            //Over windows the root sees no movement, so edges are followed by asking for the pointer
+            _ = timeout(100), if has_edge_actions && event_buffer.is_empty() => {
+                if let Some((x, y)) = display_server.pointer_position() {
+                    handler.track_pointer(manager, x, y);
+                }
                 continue;
             }
             //Once in a blue moon we miss the focus event,
@@ -155,17 +481,75 @@ async fn event_loop(
                 event_buffer.append(&mut focus_event);
                 continue;
             }
-            Some(cmd) = command_pipe.read_command(), if event_buffer.is_empty() => {
-                needs_update = external_command_handler::process(manager, &state, &config, &theme_loader, cmd) || needs_update;
+            _ = tokio::time::sleep_until(burst_due.unwrap_or_else(Instant::now)), if burst_due.is_some() && event_buffer.is_empty() => {
+                burst_started = None;
+                burst_due = None;
+            }
+            //off while events are waiting, the `else` branch only runs once every other one is
+            Some(Ok(())) = async { Some(exit_signal.as_ref()?.readable().await) }, if exit_signal.is_some() && event_buffer.is_empty() && actions.pending() == 0 => {
+                log::info!("Exit signal received");
+                exit_requested = true;
+            }
+            //the display server catches up before more is handled
+            batch = actions.recv_all(), if actions.pending() > 0 => {
+                run_actions(manager, display_server, batch, &mut event_buffer);
+                record(&mut profiler, "DisplayActions", select_started);
+            }
+            Some(cmd) = command_pipe.read_command(), if event_buffer.is_empty() && actions.pending() == 0 => {
+                let start = Instant::now();
+                let focus = focus_of(manager);
+                let queued = manager.actions.len();
+                let changed = external_command_handler::process(manager, &state, &config, &theme_loader, cmd);
+                route_focus(&mut manager.actions, queued, false, &mut focus_deferred);
+                manager.state_dirty |= changed || focus_of(manager) != focus;
+                needs_update = changed || needs_update;
                 display_server.update_theme_settings(manager.theme_setting.clone());
+                record(&mut profiler, "ExternalCommand", start);
             }
             else => {
-                event_buffer.drain(..).for_each(|event| needs_update = handler.process(manager, &state, event) || needs_update);
+                let mut windows_created = false;
+                for event in event_buffer.drain(..) {
+                    let created = matches!(event, DisplayEvent::WindowCreate(..));
+                    windows_created |= created;
+                    let queued = manager.actions.len();
+                    manager.diagnostics.record_event(&event);
+                    manager.metrics.events_processed += 1;
+                    let (name, start) = (event.name(), Instant::now());
+                    let focus = focus_of(manager);
+                    let changed = handler.process(manager, &state, event);
+                    manager.state_dirty |= changed || focus_of(manager) != focus;
+                    needs_update = changed || needs_update;
+                    record(&mut profiler, name, start);
+                    let created = created && config.window_burst_delay > 0;
+                    route_focus(&mut manager.actions, queued, created, &mut focus_deferred);
+                }
+                if windows_created && config.window_burst_delay > 0 {
+                    let now = Instant::now();
+                    let started = *burst_started.get_or_insert(now);
+                    burst_due = Some((now + window_burst_delay).min(started + WINDOW_BURST_MAX));
+                }
+            }
+        }
+
+        //the branches that `continue` above handled nothing
+        queries_stale = true;
+
+        //windows are still appearing, draw them all at once when they stop
+        let bursting = burst_due.is_some();
+        if bursting {
+            update_deferred |= needs_update;
+            needs_update = false;
+        } else {
+            needs_update |= std::mem::take(&mut update_deferred);
+            //the last new window takes the focus now, unless it was moved since
+            if let Some(act) = focus_deferred.take() {
+                manager.actions.push_front(act);
             }
         }
 
         //if we need to update the displayed state
         if needs_update {
+            let start = Instant::now();
             match &manager.mode {
                 Mode::Normal => {
                     let windows: Vec<&Window> = manager.windows.iter().collect();
@@ -185,41 +569,82 @@ async fn event_loop(
                     display_server.update_windows(windows, focused, manager);
                 }
             }
+            record(&mut profiler, "UpdateWindows", start);
         }
 
-        //preform any actions requested by the handler
-        while !manager.actions.is_empty() {
-            if let Some(act) = manager.actions.pop_front() {
-                if let Some(event) = display_server.execute_action(act) {
-                    event_buffer.push(event);
-                }
-            }
-        }
+        //focus may have moved to or away from a window the keys are passed to
+        handler.sync_key_grabs(manager);
+
+        //hand the actions requested by the handlers to the display server
+        DisplayAction::coalesce(&mut manager.actions, bursting);
+        actions.forward(&mut manager.actions);
 
         //after the very first loop run the 'up' scripts (global and theme). we need the unix
-        //socket to already exist.
+        //socket to already exist. autostart waits for them, so bars see the theme's struts.
+        //the [[startup]] commands are handled like any other command on the next loop.
         after_first_loop.call_once(|| {
+            let mut up_scripts = vec![];
             match Nanny::run_global_up_script() {
-                Ok(child) => {
-                    child.map(|child| manager.children.insert(child));
-                }
+                Ok(child) => up_scripts.extend(child),
                 Err(err) => log::error!("Global up script faild: {}", err),
             }
             match Nanny::boot_current_theme() {
-                Ok(child) => {
-                    child.map(|child| manager.children.insert(child));
-                }
+                Ok(child) => up_scripts.extend(child),
                 Err(err) => log::error!("Theme loading failed: {}", err),
             }
+            Nanny::notify_when_up(up_scripts, UP_SCRIPTS_TIMEOUT);
+            //after the up scripts, which may start the notification daemon
+            if let Some(reason) = &rejection {
+                notify_config_rejected(manager, reason);
+            }
 
             state.load(manager);
+            event_buffer.append(&mut startup_events(&config));
+
+            let mut options = ExecOptions::default();
+            if std::env::var_os(child_process::LEFTWM_RELOAD_ENV).is_some() {
+                options
+                    .env
+                    .insert("LEFTWM_RELOADED".to_owned(), "1".to_owned());
+            }
+            run_hooks(manager, &config, HookEvent::WmStarted, options);
         });
 
+        if has_hooks {
+            for (event, options) in hook_tracker.changes(manager) {
+                run_hooks(manager, &config, event, options);
+            }
+        }
+
         if manager.reap_requested.swap(false, Ordering::SeqCst) {
-            manager.children.reap();
+            manager.reap_children();
         }
 
-        if manager.reload_requested {
+        if let Some(profiler) = profiler.as_mut().filter(|p| p.is_due()) {
+            log::info!("{}", profiler.summary(manager.windows.len()));
+        }
+
+        if manager.reload_requested || exit_requested {
+            if let Some(profiler) = &mut profiler {
+                log::info!("{}", profiler.summary(manager.windows.len()));
+            }
+            run_hooks(
+                manager,
+                &config,
+                HookEvent::WmExiting,
+                ExecOptions::default(),
+            );
+            //presentation mode is not carried over a reload, show the notifications it held back
+            if manager.presentation.is_some() {
+                manager
+                    .actions
+                    .push_back(DisplayAction::SetPresenting(false));
+            }
+            while !actions.forward(&mut manager.actions) || actions.pending() > 0 {
+                let batch = actions.recv_all().await;
+                run_actions(manager, display_server, batch, &mut event_buffer);
+            }
+            display_server.flush();
             state_socket.shutdown().await;
             break;
         }
@@ -275,9 +700,11 @@ fn setup_logging() -> slog_scope::GlobalLoggerGuard {
     #[cfg(all(not(feature = "slog-journald"), feature = "slog-term"))]
     let drain = stdout;
 
-    // Set level filters from RUST_LOG. Defaults to `info`.
+    // Set level filters from RUST_LOG. Without it everything passes here and the level is
+    // `info` until changed with `SetLogLevel`.
+    let rust_log = std::env::var("RUST_LOG").ok();
     let envlogger = slog_envlogger::LogBuilder::new(drain)
-        .parse(&std::env::var("RUST_LOG").unwrap_or_else(|_| "info".into()))
+        .parse(rust_log.as_deref().unwrap_or("trace"))
         .build()
         .ignore_res();
 
@@ -286,6 +713,9 @@ fn setup_logging() -> slog_scope::GlobalLoggerGuard {
     slog_stdlog::init().unwrap_or_else(|err| {
         eprintln!("failed to setup logging: {}", err);
     });
+    if rust_log.is_none() {
+        log::set_max_level(log::LevelFilter::Info);
+    }
 
     slog_scope::set_global_logger(logger)
 }
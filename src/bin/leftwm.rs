@@ -1,13 +1,15 @@
 //! Starts leftwm programs.
 //!
-//! If no arguments are passed, starts `leftwm-worker`. If arguments are passed, starts
-//! `leftwm-{check, command, state, theme}` as specified, and passes along any extra arguments.
+//! If no arguments are passed, starts `leftwm-worker`, `--profile` is passed along to it. If
+//! arguments are passed, starts `leftwm-{check, command, state, theme}` as specified, and passes
+//! along any extra arguments.
 
 use clap::{crate_version, App, AppSettings, SubCommand};
-use leftwm::child_process::{self, Nanny};
+use leftwm::child_process::{self, Children, Nanny};
+use nix::sys::signal::{SigSet, Signal};
 use std::collections::BTreeMap;
 use std::env;
-use std::process::{exit, Command};
+use std::process::{self, exit, Command};
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc,
@@ -25,9 +27,10 @@ fn main() {
     let subcommand_names: Vec<&str> = subcommands.keys().copied().collect();
 
     let args: Vec<String> = env::args().collect();
+    let profile = args.len() == 2 && args[1] == "--profile";
 
     // If called with arguments, attempt to execute a subcommand.
-    if args.len() > 1 {
+    if args.len() > 1 && !profile {
         match execute_subcommand(&args, &subcommand_names) {
             // Subcommand executed. Exit success.
             Some(true) => exit(0),
@@ -44,21 +47,37 @@ fn main() {
 
     // If _not_ invoked with a subcommand, start leftwm.
     if let Ok(current_exe) = std::env::current_exe() {
-        // Boot everything in ~/.config/autostart
-        let mut children = Nanny::autostart();
+        let mut children = Children::new();
+        let mut autostarted = false;
+        let mut reloaded = false;
 
         let flag = Arc::new(AtomicBool::new(false));
         child_process::register_child_hook(flag.clone());
+        let up_flag = Arc::new(AtomicBool::new(false));
+        child_process::register_up_hook(up_flag.clone());
 
         // Fix for Java apps so they repaint correctly
         env::set_var("_JAVA_AWT_WM_NONREPARENTING", "1");
 
         let worker_path = current_exe.with_file_name("leftwm-worker");
 
+        // SIGCHLD and SIGUSR1 are blocked while their flags are checked, so one arriving in
+        // between still wakes `sigsuspend` instead of being missed.
+        let mut wake_signals = SigSet::empty();
+        wake_signals.add(Signal::SIGCHLD);
+        wake_signals.add(Signal::SIGUSR1);
+        let unblocked = SigSet::thread_get_mask().expect("failed to read signal mask");
+
         loop {
-            let mut worker = Command::new(&worker_path)
-                .spawn()
-                .expect("failed to start leftwm");
+            let mut worker = Command::new(&worker_path);
+            worker.env(child_process::LEFTWM_PID_ENV, process::id().to_string());
+            if reloaded {
+                worker.env(child_process::LEFTWM_RELOAD_ENV, "1");
+            }
+            if profile {
+                worker.arg("--profile");
+            }
+            let mut worker = worker.spawn().expect("failed to start leftwm");
 
             // Wait until worker exits.
             while worker
@@ -69,11 +88,35 @@ fn main() {
                 // Not worker, then it might be autostart programs.
                 children.reap();
                 // Wait for SIGCHLD signal flag to be set.
-                while !flag.swap(false, Ordering::SeqCst) {
-                    nix::unistd::pause();
+                loop {
+                    wake_signals
+                        .thread_block()
+                        .expect("failed to block signals");
+                    let up = up_flag.swap(false, Ordering::SeqCst);
+                    let exited = flag.swap(false, Ordering::SeqCst);
+                    if !up && !exited {
+                        // SAFETY: `unblocked` is a valid signal set, sigsuspend only reads it.
+                        unsafe { libc::sigsuspend(unblocked.as_ref()) };
+                    }
+                    wake_signals
+                        .thread_unblock()
+                        .expect("failed to unblock signals");
+                    // Boot everything in ~/.config/autostart once the worker's 'up' scripts are
+                    // done. Later workers are reloads, autostart keeps running through those.
+                    if up && !autostarted {
+                        children.merge(Nanny::autostart());
+                        autostarted = true;
+                    }
+                    if exited {
+                        break;
+                    }
                 }
                 // Either worker or autostart program exited.
             }
+            // Only a worker that ended for a reload tells the next one it is a reload, not one
+            // that crashed.
+            let status = worker.try_wait().expect("failed to wait on worker");
+            reloaded = status.and_then(|s| s.code()) == Some(child_process::RELOAD_EXIT_CODE);
 
             // TODO: either add more details or find a better workaround.
             //
@@ -169,7 +212,8 @@ fn handle_help_or_version_flags(args: &[String], subcommands: &BTreeMap<&str, &s
         .long_about(
             "Starts LeftWM if no arguments are supplied. If a subcommand is given, executes the \
              the corresponding leftwm program, e.g. 'leftwm theme' will execute 'leftwm-theme', if \
-             it is installed.",
+             it is installed. With --profile, the time spent handling each kind of event is logged \
+             every minute.",
         )
         .version(&*version)
         .settings(&[AppSettings::DisableHelpSubcommand, AppSettings::ColoredHelp]);
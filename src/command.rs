@@ -8,26 +8,58 @@ pub enum Command {
     SoftReload,
     HardReload,
     ToggleScratchPad,
+    AttachToScratchPad,
+    ReleaseScratchPad,
     ToggleFullScreen,
+    ToggleFakeFullscreen,
     GotoTag,
+    PeekTag,
+    ToggleTagView,
     FloatingToTile,
     MoveWindowUp,
     MoveWindowDown,
     MoveWindowTop,
+    ZoomWindow,
     FocusNextTag,
     FocusPreviousTag,
     FocusWindowUp,
     FocusWindowDown,
+    #[serde(alias = "FocusNextWorkspace")]
     FocusWorkspaceNext,
+    #[serde(alias = "FocusPreviousWorkspace")]
     FocusWorkspacePrevious,
     MoveToTag,
+    MoveToTagAndFollow,
+    MoveFloatingToNextTag,
+    ToggleWindowTag,
+    SetWindowTag,
+    GroupWindow,
+    UngroupWindow,
+    CycleGroup,
     MoveToLastWorkspace,
     MouseMoveWindow,
+    MouseResizeWindow,
     NextLayout,
     PreviousLayout,
     SetLayout,
     RotateTag,
+    ToggleTiling,
     IncreaseMainWidth,
     DecreaseMainWidth,
+    SetMainWidth,
     SetMarginMultiplier,
+    SetModKey,
+    SetMouseKey,
+    UndoWindowOperation,
+    LockScreen,
+    HideDock,
+    ShowDock,
+    ToggleDock,
+    ShowKeybindHelp,
+    TogglePresentationMode,
+    SaveSnapshot,
+    LoadSnapshot,
+    SplitHorizontal,
+    SplitVertical,
+    FocusParent,
 }
@@ -1,7 +1,5 @@
-use super::config::Config;
 use super::config::Keybind;
 use super::utils::xkeysym_lookup;
-use super::Command;
 use super::ModMask;
 use super::XKeysym;
 use std::collections::HashMap;
@@ -12,8 +10,7 @@ pub struct CommandBuilder {
 }
 
 impl CommandBuilder {
-    pub fn new(config: &impl Config) -> Self {
-        let binds = config.mapped_bindings();
+    pub fn new(binds: Vec<Keybind>) -> Self {
         let mut lookup = HashMap::new();
         for b in binds {
             if let Some(key) = xkeysym_lookup::into_keysym(&b.key) {
@@ -36,22 +33,4 @@ impl CommandBuilder {
         let id = (mask, key);
         self.keybinds.get(&id)
     }
-
-    //Command((Command, Option<String>)),
-    pub fn xkeyevent(
-        &self,
-        mask: ModMask,
-        key: XKeysym,
-        //event: XKeyEvent,
-    ) -> Option<(Command, Option<String>)> {
-        let keybind = self.find_keybind_for(mask, key);
-        match keybind {
-            Some(bind) => {
-                let cmd = bind.command.clone();
-                let val = bind.value.clone();
-                Some((cmd, val))
-            }
-            None => None,
-        }
-    }
 }
@@ -0,0 +1,18 @@
+use crate::models::Side;
+use crate::Command;
+use serde::{Deserialize, Serialize};
+
+/// A command run when the pointer rests on an edge of a screen, see `[[edge_action]]`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EdgeAction {
+    pub edge: Side,
+    /// How long the pointer has to stay on the edge, in milliseconds.
+    #[serde(default = "default_delay")]
+    pub delay: u64,
+    pub command: Command,
+    pub value: Option<String>,
+}
+
+const fn default_delay() -> u64 {
+    300
+}
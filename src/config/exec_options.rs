@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Environment variables and working directory for a program started by `Execute` or a
+/// scratchpad. Programs inherit the environment and directory of `LeftWM` otherwise.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ExecOptions {
+    /// A leading `~` is the home directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+}
+
+impl ExecOptions {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.working_dir.is_none() && self.env.is_empty()
+    }
+}
@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+/// Where new floating windows, such as dialogs, are put on their workspace.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FloatingPlacement {
+    /// In the middle of the workspace.
+    Center,
+    /// Centered on the mouse pointer, as far as it fits on the workspace.
+    UnderCursor,
+    /// Where it covers the least of the other floating windows.
+    Smart,
+}
+
+impl Default for FloatingPlacement {
+    fn default() -> Self {
+        Self::Center
+    }
+}
@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+/// A shell command run whenever an event of the window manager happens, see `[[hook]]`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Hook {
+    pub event: HookEvent,
+    pub command: String,
+}
+
+/// What a `[[hook]]` runs on. The details go in `LEFTWM_*` environment variables.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum HookEvent {
+    /// A window appeared, with `LEFTWM_WINDOW`, `LEFTWM_CLASS` and `LEFTWM_TITLE`.
+    WindowCreated,
+    /// A window went away, with the same variables as `WindowCreated`.
+    WindowDestroyed,
+    /// A workspace shows another tag, with `LEFTWM_TAG`, `LEFTWM_OUTPUT` and `LEFTWM_WORKSPACE`.
+    TagChanged,
+    /// A workspace got another layout, with `LEFTWM_LAYOUT`, `LEFTWM_TAG` and `LEFTWM_WORKSPACE`.
+    LayoutChanged,
+    /// The worker started, with `LEFTWM_RELOADED` set to `1` after a reload.
+    WmStarted,
+    /// The worker is about to stop for a reload.
+    WmExiting,
+}
+
+impl HookEvent {
+    /// The name used in the config, also passed to the hook as `LEFTWM_EVENT`.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::WindowCreated => "window_created",
+            Self::WindowDestroyed => "window_destroyed",
+            Self::TagChanged => "tag_changed",
+            Self::LayoutChanged => "layout_changed",
+            Self::WmStarted => "wm_started",
+            Self::WmExiting => "wm_exiting",
+        }
+    }
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+/// Shell commands to run once there has been no input for `timeout` seconds, and again once
+/// there is. Without the `XScreenSaver` extension, only the display events the window manager
+/// sees count as input.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Idle {
+    pub timeout: u64,
+    pub on_idle: Option<String>,
+    pub on_resume: Option<String>,
+}
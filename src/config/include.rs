@@ -0,0 +1,147 @@
+//! Splitting a config over several files with `include = ["keybinds.toml"]`.
+use crate::errors::{LeftError, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::Value;
+
+/// Reads a toml file and the files listed in its `include` key, which may include files
+/// themselves. Paths are relative to the file including them.
+///
+/// The included files are merged in order, then the including file on top of them. Tables are
+/// merged key by key, lists of tables such as `[[keybind]]` are appended to and any other value
+/// is replaced.
+///
+/// # Errors
+///
+/// Will error if a file can't be read or parsed, or includes itself.
+pub fn read_with_includes(path: &Path) -> Result<Value> {
+    read(path, &mut vec![], &mut vec![])
+}
+
+/// The file and the files it includes, in the order they were read.
+///
+/// # Errors
+///
+/// Will error if a file can't be read or parsed, or includes itself.
+pub fn files(path: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    read(path, &mut vec![], &mut files)?;
+    Ok(files)
+}
+
+fn read(path: &Path, including: &mut Vec<PathBuf>, files: &mut Vec<PathBuf>) -> Result<Value> {
+    let canonical = fs::canonicalize(path)?;
+    if including.contains(&canonical) {
+        return Err(LeftError::from(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            format!("{} includes itself", path.display()),
+        )));
+    }
+    let mut value: Value = toml::from_str(&fs::read_to_string(path)?)?;
+    files.push(path.to_path_buf());
+    let includes: Vec<PathBuf> = match value.as_table_mut().and_then(|t| t.remove("include")) {
+        Some(include) => include.try_into()?,
+        None => vec![],
+    };
+
+    including.push(canonical);
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let mut merged = Value::Table(toml::value::Table::new());
+    for include in includes {
+        merge(&mut merged, read(&dir.join(include), including, files)?);
+    }
+    including.pop();
+
+    merge(&mut merged, value);
+    Ok(merged)
+}
+
+fn merge(base: &mut Value, other: Value) {
+    match (base, other) {
+        (Value::Table(base), Value::Table(other)) => {
+            for (key, value) in other {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (Value::Array(base), Value::Array(mut other))
+            if base.iter().chain(&other).all(Value::is_table) =>
+        {
+            base.append(&mut other);
+        }
+        (base, other) => *base = other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn includes_should_be_merged_under_the_including_file() {
+        let dir = tempfile::tempdir_in("target").unwrap();
+        fs::create_dir(dir.path().join("parts")).unwrap();
+        fs::write(
+            dir.path().join("config.toml"),
+            r#"
+include = ["parts/keybinds.toml"]
+modkey = "Mod4"
+tags = ["1", "2"]
+
+[[keybind]]
+command = "CloseWindow"
+modifier = ["modkey"]
+key = "q"
+"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("parts/keybinds.toml"),
+            r#"
+include = ["tags.toml"]
+modkey = "Mod1"
+
+[[keybind]]
+command = "GotoTag"
+value = "1"
+modifier = ["modkey"]
+key = "1"
+"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("parts/tags.toml"),
+            r#"tags = ["a", "b", "c"]"#,
+        )
+        .unwrap();
+
+        let config = read_with_includes(&dir.path().join("config.toml")).unwrap();
+        assert!(config.get("include").is_none());
+        assert_eq!(config["modkey"].as_str(), Some("Mod4"));
+        assert_eq!(config["tags"].as_array().map(Vec::len), Some(2));
+        let commands: Vec<&str> = config["keybind"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|k| k["command"].as_str())
+            .collect();
+        assert_eq!(commands, ["GotoTag", "CloseWindow"]);
+
+        let files = files(&dir.path().join("config.toml")).unwrap();
+        let names: Vec<_> = files.iter().filter_map(|f| f.file_name()).collect();
+        assert_eq!(names, ["config.toml", "keybinds.toml", "tags.toml"]);
+    }
+
+    #[test]
+    fn include_cycles_should_be_an_error() {
+        let dir = tempfile::tempdir_in("target").unwrap();
+        fs::write(dir.path().join("a.toml"), r#"include = ["b.toml"]"#).unwrap();
+        fs::write(dir.path().join("b.toml"), r#"include = ["a.toml"]"#).unwrap();
+        assert!(read_with_includes(&dir.path().join("a.toml")).is_err());
+        assert!(read_with_includes(&dir.path().join("missing.toml")).is_err());
+    }
+}
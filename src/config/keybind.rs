@@ -1,3 +1,4 @@
+use super::ExecOptions;
 use crate::Command;
 use serde::{Deserialize, Serialize};
 
@@ -7,4 +8,14 @@ pub struct Keybind {
     pub value: Option<String>,
     pub modifier: Vec<String>,
     pub key: String,
+    /// Fire again while the key is held, as fast as X repeats it.
+    #[serde(default)]
+    pub repeat: bool,
+    /// Stays grabbed while a window with the `pass_keys` rule is focused, such as a key to get
+    /// out of a virtual machine.
+    #[serde(default)]
+    pub essential: bool,
+    /// Used by `Execute`.
+    #[serde(flatten)]
+    pub exec: ExecOptions,
 }
@@ -1,28 +1,117 @@
+mod edge_action;
+mod exec_options;
+mod floating_placement;
+mod hook;
+mod idle;
+pub mod include;
 mod keybind;
+mod mousebind;
+mod named_layout;
 mod scratchpad;
+mod startup;
+mod systray;
+mod tag_display_behavior;
 mod theme_setting;
+mod tiled_resize_request;
+pub mod validation;
+mod window_rule;
 mod workspace_config;
 
 pub use crate::models::FocusBehaviour;
+pub use crate::models::FocusOnClose;
+pub use edge_action::EdgeAction;
+pub use exec_options::ExecOptions;
+pub use floating_placement::FloatingPlacement;
+pub use hook::{Hook, HookEvent};
+pub use idle::Idle;
 pub use keybind::Keybind;
+pub use mousebind::{MouseTarget, Mousebind};
+pub use named_layout::NamedLayout;
 pub use scratchpad::ScratchPad;
-pub use theme_setting::{ThemeLoader, ThemeSetting};
+pub use startup::StartupCommand;
+pub use systray::Systray;
+pub use tag_display_behavior::TagDisplayBehavior;
+pub use theme_setting::{
+    FocusIndicator, FocusIndicatorStyle, ReservedSpace, ThemeLoader, ThemeSetting,
+};
+pub use tiled_resize_request::TiledResizeRequest;
+pub use window_rule::WindowRule;
 pub use workspace_config::Workspace;
 
 pub trait Config {
     /// Returns a collection of bindings with the mod key mapped.
     fn mapped_bindings(&self) -> Vec<Keybind>;
 
+    /// Returns a collection of bindings with the mod key mapped to `modkey`.
+    fn mapped_bindings_for(&self, modkey: &str) -> Vec<Keybind>;
+
     fn create_list_of_tags(&self) -> Vec<String>;
 
     fn workspaces(&self) -> Option<&[Workspace]>;
 
     fn focus_behaviour(&self) -> FocusBehaviour;
 
-    fn mousekey(&self) -> &str;
+    /// Returns a collection of mouse bindings with the mouse key mapped.
+    fn mapped_mousebinds(&self) -> Vec<Mousebind>;
+
+    /// Returns a collection of mouse bindings with the mod key and mouse key mapped to the ones
+    /// given, or to the configured ones for `None`.
+    fn mapped_mousebinds_for(&self, modkey: Option<&str>, mousekey: Option<&str>)
+        -> Vec<Mousebind>;
 
     //of you are on tag "1" and you goto tag "1" this takes you to the previous tag
     fn disable_current_tag_swap(&self) -> bool;
+
+    /// Returns whether `FocusNextTag` and `FocusPreviousTag` go around from the last tag to the
+    /// first and back.
+    fn focus_tag_wraparound(&self) -> bool;
+
+    /// Returns whether `FocusNextTag` and `FocusPreviousTag` skip the tags without windows.
+    fn focus_tag_skip_empty(&self) -> bool;
+
+    /// Returns whether every workspace has its own set of tags instead of sharing them.
+    fn independent_tags(&self) -> bool;
+
+    /// Returns what `GotoTag` does with a tag that is visible on another workspace.
+    fn tag_display_behavior(&self) -> TagDisplayBehavior;
+
+    /// Returns how many times per second a window being moved or resized is redrawn, 0 for no
+    /// limit.
+    fn drag_refresh_rate(&self) -> u32;
+
+    /// Returns how many pixels the pointer has to move with a button held before a window is
+    /// moved or resized.
+    fn drag_threshold(&self) -> i32;
+
+    /// Returns the most milliseconds between the two clicks of a double click.
+    fn double_click_time(&self) -> u64;
+
+    /// Returns whether letting go of a window being moved over a drop zone tiles it there.
+    fn drop_zones(&self) -> bool;
+
+    /// Returns whether a window being moved or resized stays put until it is let go, with an
+    /// outline showing where it goes meanwhile.
+    fn drag_outline(&self) -> bool;
+
+    /// Returns whether dialogs and other transient windows are closed along with their parent.
+    fn close_transients_with_parent(&self) -> bool;
+
+    /// Returns whether windows drawing their own title bar may draw shadows around it, outside
+    /// of their tile.
+    fn csd_shadows(&self) -> bool;
+
+    /// Returns the built-in system tray, if there is to be one.
+    fn systray(&self) -> Option<Systray>;
+
+    /// Returns the layouts with preset parameters that `SetLayout` knows by name.
+    fn named_layouts(&self) -> Vec<NamedLayout>;
+
+    /// Returns the command `LockScreen` runs, which has to keep running until the screen is
+    /// unlocked.
+    fn lock_command(&self) -> Option<String>;
+
+    /// Returns the commands run when the pointer rests on an edge of a screen.
+    fn edge_actions(&self) -> Vec<EdgeAction>;
 }
 
 use std::sync::Arc;
@@ -34,6 +123,10 @@ where
         C::mapped_bindings(self)
     }
 
+    fn mapped_bindings_for(&self, modkey: &str) -> Vec<Keybind> {
+        C::mapped_bindings_for(self, modkey)
+    }
+
     fn create_list_of_tags(&self) -> Vec<String> {
         C::create_list_of_tags(self)
     }
@@ -46,11 +139,64 @@ where
         C::focus_behaviour(self)
     }
 
-    fn mousekey(&self) -> &str {
-        C::mousekey(self)
+    fn mapped_mousebinds(&self) -> Vec<Mousebind> {
+        C::mapped_mousebinds(self)
+    }
+
+    fn mapped_mousebinds_for(
+        &self,
+        modkey: Option<&str>,
+        mousekey: Option<&str>,
+    ) -> Vec<Mousebind> {
+        C::mapped_mousebinds_for(self, modkey, mousekey)
     }
 
     fn disable_current_tag_swap(&self) -> bool {
         C::disable_current_tag_swap(self)
     }
+    fn focus_tag_wraparound(&self) -> bool {
+        C::focus_tag_wraparound(self)
+    }
+    fn focus_tag_skip_empty(&self) -> bool {
+        C::focus_tag_skip_empty(self)
+    }
+    fn independent_tags(&self) -> bool {
+        C::independent_tags(self)
+    }
+    fn tag_display_behavior(&self) -> TagDisplayBehavior {
+        C::tag_display_behavior(self)
+    }
+    fn drag_refresh_rate(&self) -> u32 {
+        C::drag_refresh_rate(self)
+    }
+    fn drag_threshold(&self) -> i32 {
+        C::drag_threshold(self)
+    }
+    fn double_click_time(&self) -> u64 {
+        C::double_click_time(self)
+    }
+    fn drop_zones(&self) -> bool {
+        C::drop_zones(self)
+    }
+    fn drag_outline(&self) -> bool {
+        C::drag_outline(self)
+    }
+    fn close_transients_with_parent(&self) -> bool {
+        C::close_transients_with_parent(self)
+    }
+    fn csd_shadows(&self) -> bool {
+        C::csd_shadows(self)
+    }
+    fn systray(&self) -> Option<Systray> {
+        C::systray(self)
+    }
+    fn named_layouts(&self) -> Vec<NamedLayout> {
+        C::named_layouts(self)
+    }
+    fn lock_command(&self) -> Option<String> {
+        C::lock_command(self)
+    }
+    fn edge_actions(&self) -> Vec<EdgeAction> {
+        C::edge_actions(self)
+    }
 }
@@ -0,0 +1,32 @@
+use crate::Command;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Mousebind {
+    pub command: Command,
+    pub value: Option<String>,
+    pub modifier: Vec<String>,
+    pub button: String,
+    #[serde(default)]
+    pub target: MouseTarget,
+    /// Only the second click of a double click triggers this bind, see `double_click_time`.
+    #[serde(default)]
+    pub double_click: bool,
+}
+
+/// Where the click has to happen for a mousebind to trigger.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum MouseTarget {
+    /// The root window, e.g. an empty spot on the desktop.
+    Root,
+    /// Any managed window. The window is focused before the command runs.
+    Window,
+    /// The title bar of a floating window, see `title_bar_height` in the theme.
+    TitleBar,
+}
+
+impl Default for MouseTarget {
+    fn default() -> Self {
+        Self::Window
+    }
+}
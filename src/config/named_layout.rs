@@ -0,0 +1,11 @@
+use crate::layouts::Layout;
+use serde::{Deserialize, Serialize};
+
+/// A layout with preset parameters, selectable by its name with `SetLayout`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NamedLayout {
+    pub name: String,
+    pub base: Layout,
+    /// Width of the main column in percent, the default of `base` otherwise.
+    pub main_width: Option<u8>,
+}
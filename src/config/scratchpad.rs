@@ -1,7 +1,10 @@
+use super::ExecOptions;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ScratchPad {
     pub name: String,
     pub value: String,
+    #[serde(flatten)]
+    pub exec: ExecOptions,
 }
@@ -0,0 +1,12 @@
+use crate::Command;
+use serde::{Deserialize, Serialize};
+
+/// A command run once after the window manager has started, see `[[startup]]`. Soft reloads only
+/// run it again if `on_reload` is set.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StartupCommand {
+    pub command: Command,
+    pub value: Option<String>,
+    #[serde(default)]
+    pub on_reload: bool,
+}
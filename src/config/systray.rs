@@ -0,0 +1,27 @@
+use crate::models::Side;
+use serde::{Deserialize, Serialize};
+
+/// The system tray built into leftwm, which docks the icons of programs at the edge of a screen
+/// and keeps the space they take free. Only there when built with the `systray` feature.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Systray {
+    /// The index of the screen the tray is on.
+    pub screen: usize,
+    /// The edge the tray is at, `Top` or `Bottom`. The icons line up from its right end.
+    pub side: Side,
+    /// The width and height of an icon, in pixels.
+    pub icon_size: u32,
+    pub background_color: String,
+}
+
+impl Default for Systray {
+    fn default() -> Self {
+        Self {
+            screen: 0,
+            side: Side::Top,
+            icon_size: 20,
+            background_color: "#000000".to_owned(),
+        }
+    }
+}
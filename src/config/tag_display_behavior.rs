@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+/// What `GotoTag` does when the tag is already displayed on another workspace.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TagDisplayBehavior {
+    /// Swap the tags of the two workspaces, the tag moves to the focused workspace.
+    Swap,
+    /// Leave the tags alone and move the focus to the workspace displaying the tag.
+    Steal,
+}
+
+impl Default for TagDisplayBehavior {
+    fn default() -> Self {
+        Self::Swap
+    }
+}
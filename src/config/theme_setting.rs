@@ -1,6 +1,7 @@
 use crate::models::Gutter;
 use crate::models::Margins;
-use serde::{Deserialize, Serialize};
+use crate::models::Side;
+use serde::{Deserialize, Deserializer, Serialize};
 use std::path::Path;
 
 pub trait ThemeLoader {
@@ -17,8 +18,133 @@ pub struct ThemeSetting {
     pub default_border_color: String,
     pub floating_border_color: String,
     pub focused_border_color: String,
+    pub title_bar_height: Option<u32>,
+    pub title_bar_color: Option<String>,
+    pub title_bar_focused_color: Option<String>,
+    pub title_bar_text_color: Option<String>,
     #[serde(rename = "on_new_window")]
     pub on_new_window_cmd: Option<String>,
+    /// No border on a tiled window while it is the only one shown on its workspace.
+    #[serde(default)]
+    pub smart_borders: bool,
+    /// No margins around a tiled window while it is the only one shown on its workspace.
+    #[serde(default)]
+    pub smart_gaps: bool,
+    /// Space kept free along the sides of workspaces, for bars that don't set struts.
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub reserve_space: Vec<ReservedSpace>,
+    /// Marks drawn on the focused window, for themes without borders.
+    #[serde(default)]
+    pub focus_indicator: Option<FocusIndicator>,
+}
+
+/// How the focused window is marked by `FocusIndicator`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum FocusIndicatorStyle {
+    /// A line along all the edges of the window.
+    Ring,
+    /// A line along the edges at each corner of the window.
+    Corners,
+}
+
+/// Marks leftwm draws over the edges of the focused window, so that it can be told apart when
+/// windows have no borders.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct FocusIndicator {
+    pub style: FocusIndicatorStyle,
+    /// `focused_border_color` if not set.
+    pub color: Option<String>,
+    /// The thickness of the lines.
+    pub width: i32,
+    /// How far the lines of `Corners` reach along the edges.
+    pub length: i32,
+}
+
+impl Default for FocusIndicator {
+    fn default() -> Self {
+        Self {
+            style: FocusIndicatorStyle::Ring,
+            color: None,
+            width: 2,
+            length: 24,
+        }
+    }
+}
+
+impl FocusIndicator {
+    /// The rectangles to draw on a window, as x, y, width and height.
+    #[must_use]
+    pub fn rects(&self, x: i32, y: i32, w: i32, h: i32) -> Vec<(i32, i32, i32, i32)> {
+        let width = self.width.max(1).min(w / 2).min(h / 2).max(1);
+        let (right, bottom) = (x + w - width, y + h - width);
+        match self.style {
+            FocusIndicatorStyle::Ring => vec![
+                (x, y, w, width),
+                (x, bottom, w, width),
+                (x, y, width, h),
+                (right, y, width, h),
+            ],
+            FocusIndicatorStyle::Corners => {
+                let across = self.length.max(width).min(w / 2);
+                let down = self.length.max(width).min(h / 2);
+                vec![
+                    (x, y, across, width),
+                    (x, y, width, down),
+                    (x + w - across, y, across, width),
+                    (right, y, width, down),
+                    (x, bottom, across, width),
+                    (x, y + h - down, width, down),
+                    (x + w - across, bottom, across, width),
+                    (right, y + h - down, width, down),
+                ]
+            }
+        }
+    }
+}
+
+/// Space a theme keeps free along the sides of the workspaces on an output, or of all of them.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct ReservedSpace {
+    pub top: i32,
+    pub bottom: i32,
+    pub left: i32,
+    pub right: i32,
+    pub output: Option<String>,
+}
+
+impl ReservedSpace {
+    /// The sides with space reserved on them.
+    #[must_use]
+    pub fn sides(&self) -> Vec<(Side, i32)> {
+        vec![
+            (Side::Top, self.top),
+            (Side::Bottom, self.bottom),
+            (Side::Left, self.left),
+            (Side::Right, self.right),
+        ]
+        .into_iter()
+        .filter(|(_, value)| *value > 0)
+        .collect()
+    }
+}
+
+// `reserve_space` is a single table or an array of them, one for each output.
+fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<ReservedSpace>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(ReservedSpace),
+        Many(Vec<ReservedSpace>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(space) => vec![space],
+        OneOrMany::Many(spaces) => spaces,
+    })
 }
 
 impl ThemeSetting {
@@ -45,6 +171,8 @@ workspace_margin = 5
 default_border_color = '#222222'
 floating_border_color = '#005500'
 focused_border_color = '#FFB53A'
+title_bar_height = 18
+title_bar_text_color = '#EEEEEE'
 on_new_window = 'echo Hello World'
 
 [[gutter]]
@@ -67,8 +195,73 @@ value = 0
                 default_border_color: "#222222".to_string(),
                 floating_border_color: "#005500".to_string(),
                 focused_border_color: "#FFB53A".to_string(),
+                title_bar_height: Some(18),
+                title_bar_color: None,
+                title_bar_focused_color: None,
+                title_bar_text_color: Some("#EEEEEE".to_string()),
                 on_new_window_cmd: Some("echo Hello World".to_string()),
+                smart_borders: false,
+                smart_gaps: false,
+                reserve_space: vec![],
+                focus_indicator: None,
             }
         );
     }
+
+    #[test]
+    fn focus_indicators_should_stay_inside_the_window() {
+        let config: ThemeSetting = toml::from_str(
+            r"
+border_width = 0
+margin = 0
+default_border_color = '#222222'
+floating_border_color = '#005500'
+focused_border_color = '#FFB53A'
+focus_indicator = { style = 'Corners', length = 10 }
+",
+        )
+        .unwrap();
+        let indicator = config.focus_indicator.unwrap();
+        assert_eq!(indicator.width, 2);
+        let rects = indicator.rects(100, 50, 40, 16);
+        assert_eq!(rects.len(), 8);
+        assert_eq!(rects[0], (100, 50, 10, 2));
+        //too short a window for the corners to reach 10 pixels down
+        assert_eq!(rects[7], (138, 58, 2, 8));
+        assert!(rects
+            .iter()
+            .all(|&(x, y, w, h)| x >= 100 && y >= 50 && x + w <= 140 && y + h <= 66));
+
+        let ring = FocusIndicator::default().rects(0, 0, 100, 100);
+        assert_eq!(ring[3], (98, 0, 2, 100));
+    }
+
+    #[test]
+    fn deserialize_reserve_space() {
+        let base = r"
+border_width = 0
+margin = 5
+default_border_color = '#222222'
+floating_border_color = '#005500'
+focused_border_color = '#FFB53A'
+";
+        let one = format!("{}reserve_space = {{ top = 30, output = \"eDP-1\" }}", base);
+        let config: ThemeSetting = toml::from_str(&one).unwrap();
+        assert_eq!(
+            config.reserve_space,
+            vec![ReservedSpace {
+                top: 30,
+                output: Some("eDP-1".to_owned()),
+                ..ReservedSpace::default()
+            }]
+        );
+        assert_eq!(config.reserve_space[0].sides(), vec![(Side::Top, 30)]);
+
+        let many = format!(
+            "{}[[reserve_space]]\nbottom = 20\n[[reserve_space]]\nleft = 5",
+            base
+        );
+        let config: ThemeSetting = toml::from_str(&many).unwrap();
+        assert_eq!(config.reserve_space.len(), 2);
+    }
 }
@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+/// What a tiled window gets when it asks to be moved or resized, which would break the layout.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TiledResizeRequest {
+    /// Put back where the layout wants it right away.
+    Deny,
+    /// Moved and resized as asked, until the layout is next drawn.
+    AllowTemporarily,
+    /// Taken out of the layout and floated with the geometry it asked for.
+    Float,
+}
+
+impl Default for TiledResizeRequest {
+    fn default() -> Self {
+        Self::Deny
+    }
+}
@@ -0,0 +1,559 @@
+//! Validation of a config, without printing or loading anything.
+//!
+//! `leftwm-check` is built on these, other tools such as a GUI config editor can use them to
+//! point out mistakes before the config is saved.
+use super::{Config, Keybind, MouseTarget, Mousebind, StartupCommand, ThemeSetting, Workspace};
+use crate::handlers::command_handler;
+use crate::layouts::Layout;
+use crate::utils::xkeysym_lookup;
+use crate::Command;
+use serde::de::value::StrDeserializer;
+use serde::de::IntoDeserializer;
+use serde::Deserialize;
+use std::collections::HashMap;
+use toml::{Spanned, Value};
+
+/// Version of the config schema, raised whenever a key or command is renamed. A config says which
+/// version it follows with `schema_version`, one without it is taken to follow this one.
+pub const CONFIG_SCHEMA_VERSION: u32 = 1;
+
+/// A key or command renamed in a version of the config schema. The old name keeps loading through
+/// a `#[serde(alias)]` on the new one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Renamed {
+    pub old: &'static str,
+    pub new: &'static str,
+    pub schema_version: u32,
+}
+
+/// Everything renamed since the first version of the config schema, nothing so far. Names that
+/// are only other spellings, such as `FocusNextWorkspace`, aren't renamed and stay.
+pub const RENAMED: &[Renamed] = &[];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    /// The config works but likely not as intended.
+    Warning,
+    /// The config will be rejected or the entry ignored.
+    Error,
+}
+
+/// The part of the config a `Diagnostic` is about.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Subject {
+    Workspaces,
+    Keybind(Keybind),
+    Mousebind(Mousebind),
+    Startup(StartupCommand),
+    Layout(Layout),
+    Theme,
+    /// A line of a config file, counted from 1, and its text.
+    Line(usize, String),
+}
+
+/// A single problem found in a config.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub subject: Subject,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn error(subject: Subject, message: String) -> Self {
+        Self {
+            severity: Severity::Error,
+            subject,
+            message,
+        }
+    }
+
+    fn warning(subject: Subject, message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            subject,
+            message,
+        }
+    }
+}
+
+/// Returns true if the command does nothing without a value.
+#[must_use]
+pub fn requires_value(command: &Command) -> bool {
+    matches!(
+        command,
+        Command::ToggleScratchPad
+            | Command::AttachToScratchPad
+            | Command::MoveToTag
+            | Command::MoveToTagAndFollow
+            | Command::ToggleWindowTag
+            | Command::SetWindowTag
+            | Command::GotoTag
+            | Command::PeekTag
+            | Command::ToggleTagView
+            | Command::Execute
+            | Command::IncreaseMainWidth
+            | Command::DecreaseMainWidth
+            | Command::SetMainWidth
+            | Command::SetLayout
+            | Command::SetMarginMultiplier
+            | Command::SetModKey
+            | Command::SetMouseKey
+            | Command::SaveSnapshot
+            | Command::LoadSnapshot
+    )
+}
+
+/// Checks that workspace IDs are either all unset or all set and unique.
+#[must_use]
+pub fn check_workspace_ids(workspaces: &[Workspace]) -> Vec<Diagnostic> {
+    let ids: Vec<Option<i32>> = workspaces.iter().map(|ws| ws.id).collect();
+    if !ids.iter().any(Option::is_some) {
+        return vec![];
+    }
+    let message = if ids.iter().any(Option::is_none) {
+        "An ID is set for some but not all workspaces. This can lead to ID collisions and is not allowed."
+    } else {
+        let mut sorted = ids.clone();
+        sorted.sort_unstable();
+        sorted.dedup();
+        if sorted.len() == ids.len() {
+            return vec![];
+        }
+        "Workspace IDs are not unique. Please assign unique IDs to workspaces."
+    };
+    vec![Diagnostic::error(Subject::Workspaces, message.to_owned())]
+}
+
+/// Checks that keybinds have their required values, valid keys and modifiers, and that no key
+/// combination is bound twice.
+#[must_use]
+pub fn check_keybinds(keybinds: &[Keybind]) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    let mut bindings = HashMap::new();
+    for keybind in keybinds {
+        let subject = || Subject::Keybind(keybind.clone());
+        if requires_value(&keybind.command) && keybind.value.is_none() {
+            let message = "This keybind requires a `string`".to_owned();
+            diagnostics.push(Diagnostic::error(subject(), message));
+        }
+        if xkeysym_lookup::into_keysym(&keybind.key).is_none() {
+            let message = format!("Key `{}` is not valid", keybind.key);
+            diagnostics.push(Diagnostic::error(subject(), message));
+        }
+        diagnostics
+            .extend(invalid_modifiers(&keybind.modifier).map(|m| Diagnostic::error(subject(), m)));
+        let mut modifier = keybind.modifier.clone();
+        modifier.sort();
+        if let Some(conflict) = bindings.get(&(modifier.clone(), keybind.key.clone())) {
+            let message = format!(
+                "Multiple commands bound to key combination {} + {}: {:?} and {:?}",
+                keybind.modifier.join(" + "),
+                keybind.key,
+                conflict,
+                keybind.command,
+            );
+            diagnostics.push(Diagnostic::error(subject(), message));
+        } else {
+            bindings.insert((modifier, keybind.key.clone()), keybind.command.clone());
+        }
+    }
+    diagnostics
+}
+
+/// Runs the keybinds that take a value through the command handler on a headless manager with
+/// the tags of the config, and reports the ones that change nothing, e.g. a tag that doesn't
+/// exist or a layout name that isn't known. Commands that start programs or touch files, such as
+/// `Execute` or `SaveSnapshot`, are skipped.
+#[must_use]
+pub fn dry_run_keybinds(config: &impl Config, keybinds: &[Keybind]) -> Vec<Diagnostic> {
+    keybinds
+        .iter()
+        .filter(|k| k.value.is_some())
+        .filter(|k| !command_handler::dry_run(config, &k.command, &k.value))
+        .map(|k| {
+            let message = format!(
+                "{:?} does nothing with the value {:?} when run",
+                k.command,
+                k.value.as_deref().unwrap_or_default()
+            );
+            Diagnostic::error(Subject::Keybind(k.clone()), message)
+        })
+        .collect()
+}
+
+/// Checks that mousebinds have their required values, valid buttons and modifiers, and that
+/// window dragging is not bound to the root window.
+#[must_use]
+pub fn check_mousebinds(mousebinds: &[Mousebind]) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    for mousebind in mousebinds {
+        let subject = || Subject::Mousebind(mousebind.clone());
+        let is_mouse_command = mousebind.command == Command::MouseMoveWindow
+            || mousebind.command == Command::MouseResizeWindow;
+        if is_mouse_command && mousebind.target == MouseTarget::Root {
+            let message = format!(
+                "Command `{:?}` can not be bound to the root window",
+                mousebind.command
+            );
+            diagnostics.push(Diagnostic::error(subject(), message));
+        }
+        if requires_value(&mousebind.command) && mousebind.value.is_none() {
+            let message = "This mousebind requires a `string`".to_owned();
+            diagnostics.push(Diagnostic::error(subject(), message));
+        }
+        if xkeysym_lookup::into_button(&mousebind.button).is_none() {
+            let message = format!("Button `{}` is not valid", mousebind.button);
+            diagnostics.push(Diagnostic::error(subject(), message));
+        }
+        diagnostics.extend(
+            invalid_modifiers(&mousebind.modifier).map(|m| Diagnostic::error(subject(), m)),
+        );
+    }
+    diagnostics
+}
+
+/// Checks that startup commands have their required values.
+#[must_use]
+pub fn check_startup_commands(startup: &[StartupCommand]) -> Vec<Diagnostic> {
+    startup
+        .iter()
+        .filter(|s| requires_value(&s.command) && s.value.is_none())
+        .map(|s| {
+            let message = "This startup command requires a `string`".to_owned();
+            Diagnostic::error(Subject::Startup(s.clone()), message)
+        })
+        .collect()
+}
+
+/// Checks that the plugin layouts are among the `plugins` found, see `layouts::plugin::available`.
+#[must_use]
+pub fn check_layouts(layouts: &[Layout], plugins: &[String]) -> Vec<Diagnostic> {
+    layouts
+        .iter()
+        .filter_map(|layout| match layout {
+            Layout::Plugin(name) if !plugins.contains(name) => {
+                let message = format!("No plugin layouts/{}.so, `Fibonacci` is used instead", name);
+                Some(Diagnostic::warning(
+                    Subject::Layout(layout.clone()),
+                    message,
+                ))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Checks that the contents of a `theme.toml` can be loaded.
+#[must_use]
+pub fn check_theme(contents: &str) -> Vec<Diagnostic> {
+    match toml::from_str::<ThemeSetting>(contents) {
+        Ok(_) => vec![],
+        Err(err) => vec![Diagnostic::error(
+            Subject::Theme,
+            format!("The theme file is broken: {}", err),
+        )],
+    }
+}
+
+/// Finds the renamed keys and commands in the contents of a config file, and a `schema_version`
+/// newer than this leftwm reads. The old names still work, but may be dropped in a later version.
+#[must_use]
+pub fn check_deprecated(contents: &str) -> Vec<Diagnostic> {
+    check_renamed(contents, RENAMED)
+}
+
+// The commands and the schema version of a config file, where they are written.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Written {
+    schema_version: Option<Spanned<u32>>,
+    keybind: Vec<WrittenCommand>,
+    mousebind: Vec<WrittenCommand>,
+    edge_action: Vec<WrittenCommand>,
+}
+
+#[derive(Deserialize)]
+struct WrittenCommand {
+    command: Spanned<String>,
+}
+
+fn check_renamed(contents: &str, renamed: &[Renamed]) -> Vec<Diagnostic> {
+    //a file that doesn't load is reported by the checks of the config itself
+    let (written, value) = match (
+        toml::from_str::<Written>(contents),
+        toml::from_str::<Value>(contents),
+    ) {
+        (Ok(written), Ok(value)) => (written, value),
+        _ => return vec![],
+    };
+    let mut diagnostics = vec![];
+    let line_at = |offset: usize| {
+        let index = contents[..offset].matches('\n').count();
+        let text = contents.lines().nth(index).unwrap_or_default();
+        Subject::Line(index + 1, text.trim().to_owned())
+    };
+    if let Some(version) = &written.schema_version {
+        if *version.get_ref() > CONFIG_SCHEMA_VERSION {
+            let message = format!(
+                "`schema_version` {} is newer than the {} this leftwm reads, some keys may be ignored",
+                version.get_ref(),
+                CONFIG_SCHEMA_VERSION
+            );
+            diagnostics.push(Diagnostic::warning(line_at(version.start()), message));
+        }
+    }
+    for key in keys(&value) {
+        if let Some(r) = renamed.iter().find(|r| r.old == key) {
+            if let Some(offset) = offset_of_key(contents, key) {
+                diagnostics.push(Diagnostic::warning(line_at(offset), deprecated(r)));
+            }
+        }
+    }
+    let commands = written
+        .keybind
+        .iter()
+        .chain(&written.mousebind)
+        .chain(&written.edge_action)
+        .map(|c| &c.command);
+    for command in commands {
+        //an old name loads through a serde alias, so the command it loads names itself otherwise
+        let name = command.get_ref().as_str();
+        let deserializer: StrDeserializer<serde::de::value::Error> = name.into_deserializer();
+        let loaded = Command::deserialize(deserializer)
+            .ok()
+            .and_then(|c| Value::try_from(c).ok());
+        let new = match loaded.as_ref().and_then(Value::as_str) {
+            Some(new) if new != name => new,
+            _ => continue,
+        };
+        if let Some(r) = renamed.iter().find(|r| r.old == name && r.new == new) {
+            diagnostics.push(Diagnostic::warning(line_at(command.start()), deprecated(r)));
+        }
+    }
+    diagnostics.sort_by_key(|d| match d.subject {
+        Subject::Line(line, _) => line,
+        _ => 0,
+    });
+    diagnostics
+}
+
+fn deprecated(renamed: &Renamed) -> String {
+    format!(
+        "`{}` is deprecated since schema version {}, use `{}`",
+        renamed.old, renamed.schema_version, renamed.new
+    )
+}
+
+// The keys of the file and of the tables in it, such as those of each `[[keybind]]`.
+fn keys(value: &Value) -> Vec<&str> {
+    let mut keys = vec![];
+    if let Some(table) = value.as_table() {
+        for (key, value) in table {
+            keys.push(key.as_str());
+            let tables = match value {
+                Value::Array(array) => array.iter().collect(),
+                value => vec![value],
+            };
+            for table in tables.into_iter().filter_map(Value::as_table) {
+                keys.extend(table.keys().map(String::as_str));
+            }
+        }
+    }
+    keys.sort_unstable();
+    keys.dedup();
+    keys
+}
+
+// Where the key is first set or opens a table, as toml keeps no place for keys.
+fn offset_of_key(contents: &str, key: &str) -> Option<usize> {
+    let mut offset = 0;
+    for line in contents.split_inclusive('\n') {
+        let text = line.trim_start();
+        let header = text
+            .trim_start_matches('[')
+            .trim_end()
+            .trim_end_matches(']');
+        let set =
+            matches!(text.strip_prefix(key), Some(rest) if rest.trim_start().starts_with('='));
+        if set || header == key {
+            return Some(offset);
+        }
+        offset += line.len();
+    }
+    None
+}
+
+fn invalid_modifiers(modifiers: &[String]) -> impl Iterator<Item = String> + '_ {
+    modifiers
+        .iter()
+        .filter(|m| *m != "modkey" && *m != "mousekey" && xkeysym_lookup::into_mod(m) == 0)
+        .map(|m| format!("Modifier `{}` is not valid", m))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ExecOptions;
+
+    fn keybind(command: Command, modifier: &[&str], key: &str) -> Keybind {
+        Keybind {
+            command,
+            value: None,
+            modifier: modifier.iter().map(|m| (*m).to_owned()).collect(),
+            key: key.to_owned(),
+            repeat: false,
+            essential: false,
+            exec: ExecOptions::default(),
+        }
+    }
+
+    fn workspace(id: Option<i32>) -> Workspace {
+        Workspace {
+            x: 0,
+            y: 0,
+            height: 0,
+            width: 0,
+            id,
+            max_window_width: None,
+            tags: vec![],
+        }
+    }
+
+    #[test]
+    fn workspace_ids_should_be_all_unset_or_all_unique() {
+        assert!(check_workspace_ids(&[workspace(None), workspace(None)]).is_empty());
+        assert!(check_workspace_ids(&[workspace(Some(0)), workspace(Some(1))]).is_empty());
+        assert_eq!(
+            check_workspace_ids(&[workspace(Some(0)), workspace(None)]).len(),
+            1
+        );
+        assert_eq!(
+            check_workspace_ids(&[workspace(Some(0)), workspace(Some(0))]).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn keybinds_should_report_each_problem_with_its_keybind() {
+        let missing_value = keybind(Command::Execute, &["modkey"], "Return");
+        let conflict = keybind(Command::CloseWindow, &["modkey"], "Return");
+        let invalid = keybind(Command::CloseWindow, &["Hyper"], "NotAKey");
+        let diagnostics =
+            check_keybinds(&[missing_value.clone(), conflict.clone(), invalid.clone()]);
+        let subjects: Vec<&Subject> = diagnostics.iter().map(|d| &d.subject).collect();
+        assert_eq!(
+            subjects,
+            [
+                &Subject::Keybind(missing_value),
+                &Subject::Keybind(conflict),
+                &Subject::Keybind(invalid.clone()),
+                &Subject::Keybind(invalid),
+            ]
+        );
+    }
+
+    #[test]
+    fn renamed_commands_and_keys_should_be_reported_with_their_line() {
+        let renamed = [
+            Renamed {
+                old: "FocusNextWorkspace",
+                new: "FocusWorkspaceNext",
+                schema_version: 2,
+            },
+            Renamed {
+                old: "focus_tag_wrap",
+                new: "focus_tag_wraparound",
+                schema_version: 2,
+            },
+        ];
+        let contents = r#"
+modkey = "Mod4" # FocusNextWorkspace
+focus_tag_wrap = true
+
+[[keybind]]
+command = "FocusNextWorkspace"
+modifier = ["modkey"]
+key = "w"
+"#;
+        let diagnostics = check_renamed(contents, &renamed);
+        let subjects: Vec<_> = diagnostics.iter().map(|d| &d.subject).collect();
+        assert_eq!(
+            subjects,
+            [
+                &Subject::Line(3, "focus_tag_wrap = true".to_owned()),
+                &Subject::Line(6, r#"command = "FocusNextWorkspace""#.to_owned()),
+            ]
+        );
+        assert_eq!(diagnostics[1].severity, Severity::Warning);
+        assert_eq!(
+            diagnostics[1].message,
+            "`FocusNextWorkspace` is deprecated since schema version 2, use `FocusWorkspaceNext`"
+        );
+        //without a serde alias the old name wouldn't load, so it isn't taken for the new one
+        let renamed = [Renamed {
+            old: "FocusNextWorkspace",
+            new: "FocusWindowDown",
+            schema_version: 2,
+        }];
+        assert!(check_renamed(contents, &renamed).is_empty());
+    }
+
+    #[test]
+    fn other_spellings_of_commands_should_not_be_deprecated() {
+        let contents = r#"
+[[keybind]]
+command = "FocusPreviousWorkspace"
+modifier = ["modkey"]
+key = "q"
+"#;
+        assert!(check_deprecated(contents).is_empty());
+    }
+
+    #[test]
+    fn a_newer_schema_version_should_be_reported() {
+        let current = format!("schema_version = {}", CONFIG_SCHEMA_VERSION);
+        assert!(check_deprecated(&current).is_empty());
+        let newer = format!("schema_version = {}", CONFIG_SCHEMA_VERSION + 1);
+        let diagnostics = check_deprecated(&newer);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].subject, Subject::Line(1, newer));
+    }
+
+    #[test]
+    fn missing_plugin_layouts_should_be_reported() {
+        let spiral = Layout::Plugin("spiral".to_owned());
+        let layouts = [Layout::Monocle, spiral.clone()];
+        assert!(check_layouts(&layouts, &["spiral".to_owned()]).is_empty());
+        let diagnostics = check_layouts(&layouts, &[]);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].subject, Subject::Layout(spiral));
+    }
+
+    #[test]
+    fn theme_errors_should_include_the_parse_error() {
+        let diagnostics = check_theme("border_width = 'thick'");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].subject, Subject::Theme);
+        assert!(diagnostics[0]
+            .message
+            .starts_with("The theme file is broken: "));
+    }
+
+    #[test]
+    fn startup_commands_should_have_their_values() {
+        let startup = |command, value: Option<&str>| StartupCommand {
+            command,
+            value: value.map(ToOwned::to_owned),
+            on_reload: false,
+        };
+        let missing_value = startup(Command::Execute, None);
+        let diagnostics = check_startup_commands(&[
+            startup(Command::Execute, Some("nm-applet")),
+            startup(Command::SoftReload, None),
+            missing_value.clone(),
+        ]);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].subject, Subject::Startup(missing_value));
+    }
+}
@@ -0,0 +1,39 @@
+use crate::models::Window;
+use serde::{Deserialize, Serialize};
+
+/// Sends new windows matching `window_class` and/or `window_title` to a tag.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WindowRule {
+    pub window_class: Option<String>,
+    pub window_title: Option<String>,
+    /// 1 based, like `GotoTag`. 0 leaves the window where it is.
+    #[serde(default)]
+    pub spawn_on_tag: usize,
+    /// Go to the tag when a window is sent to it, instead of marking the tag urgent.
+    #[serde(default)]
+    pub follow_window: bool,
+    /// Keep the mouse from moving focus away while the window is focused and fullscreen.
+    #[serde(default)]
+    pub focus_lock: bool,
+    /// Ungrab the keybinds that aren't `essential` while the window is focused, so that it gets
+    /// the keys, such as a virtual machine or a remote desktop.
+    #[serde(default)]
+    pub pass_keys: bool,
+    /// Leave the window alone, such as an overlay: it is shown where it asks to be and never
+    /// tiled or focused.
+    #[serde(default)]
+    pub unmanaged: bool,
+}
+
+impl WindowRule {
+    #[must_use]
+    pub fn matches(&self, window: &Window) -> bool {
+        if self.window_class.is_none() && self.window_title.is_none() {
+            return false;
+        }
+        let class_matches = self.window_class.is_none() || self.window_class == window.res_class;
+        let title_matches = self.window_title.is_none() || self.window_title == window.name;
+        class_matches && title_matches
+    }
+}
@@ -10,4 +10,7 @@ pub struct Workspace {
     pub width: i32,
     pub id: Option<i32>,
     pub max_window_width: Option<Size>,
+    //the tags shown at startup, by id or name, instead of the next one in order
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
@@ -1,7 +1,10 @@
-use crate::config::Keybind;
+use crate::config::{Keybind, Mousebind};
 use crate::models::Window;
 use crate::models::WindowHandle;
+use crate::models::Xyhw;
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::mem;
 
 /// These are responses from the Window manager.
 /// The display server should act on these actions.
@@ -11,10 +14,19 @@ pub enum DisplayAction {
     /// Nicely ask a window if it would please close at its convenience.
     KillWindow(WindowHandle),
 
+    /// Ask a window to close by `WM_DELETE_WINDOW`, leaving it open if it doesn't support that.
+    CloseWindow(WindowHandle),
+
     /// Get triggered after a new window is discovered and WE are
     /// managing it.
     AddedWindow(WindowHandle, bool),
 
+    /// Shows a window leftwm leaves alone, as it asks to be shown.
+    MapUnmanaged(WindowHandle),
+
+    /// Moves and resizes a window outside of the layout, until it is next drawn.
+    ConfigureWindow(WindowHandle, Xyhw),
+
     /// Makes sure the mouse is over a given window.
     MoveMouseOver(WindowHandle),
 
@@ -28,12 +40,17 @@ pub enum DisplayAction {
     /// first in the array is top most
     SetWindowOrder(Vec<WindowHandle>),
 
+    /// The full stacking order of the windows on the workspace with this id, first in the array
+    /// is top most. They are raised and restacked in one go, so they don't flicker.
+    RestackWorkspace(Option<i32>, Vec<WindowHandle>),
+
     /// Tell the DS we no longer care about the this window and other
     /// cleanup.
     DestroyedWindow(WindowHandle),
 
-    /// Tell a window that it is to become focused.
-    WindowTakeFocus(Window),
+    /// Tell a window that it is to become focused. The bool is the `never_focus` of the window,
+    /// such a window is told but does not get the input focus.
+    WindowTakeFocus(WindowHandle, bool),
 
     /// Remove focus on any visible window by focusing the root window
     Unfocus,
@@ -61,4 +78,145 @@ pub enum DisplayAction {
 
     /// Reload keygrabs, needed when keyboard changes
     ReloadKeyGrabs(Vec<Keybind>),
+
+    /// Grab these mousebinds instead, after the modkey or mousekey changed.
+    ReloadMouseGrabs(Vec<Mousebind>),
+
+    /// Show a popup with these lines describing the keybinds, until any key is pressed.
+    ShowKeybindHelp(Vec<String>),
+
+    /// Show a popup with these lines of a message, until any key is pressed.
+    ShowMessage(Vec<String>),
+
+    /// Outline where a window being dragged goes, or hide the outline.
+    DrawOutline(Option<Xyhw>),
+
+    /// Presentation mode went on or off, notifications drawing themselves over everything are
+    /// held back while it is on.
+    SetPresenting(bool),
+}
+
+impl DisplayAction {
+    /// Removes the actions made redundant by a later action in the queue, so that bursts of
+    /// changes such as fast tag switching cost the display server less work. While windows appear
+    /// in a burst only the last window told to take the focus is, otherwise each one is.
+    pub fn coalesce(actions: &mut VecDeque<Self>, in_burst: bool) {
+        let mut kept = VecDeque::with_capacity(actions.len());
+        let mut current_tags_set = false;
+        let mut window_order_set = false;
+        let mut outline_set = false;
+        let mut window_tags_set = vec![];
+        let mut workspaces_restacked = vec![];
+        for action in actions.drain(..).rev() {
+            let redundant = match &action {
+                Self::SetCurrentTags(_) => mem::replace(&mut current_tags_set, true),
+                Self::SetWindowOrder(_) => mem::replace(&mut window_order_set, true),
+                Self::DrawOutline(_) => mem::replace(&mut outline_set, true),
+                Self::SetWindowTags(handle, _) => {
+                    let set = window_tags_set.contains(handle);
+                    window_tags_set.push(*handle);
+                    set
+                }
+                Self::RestackWorkspace(id, _) => {
+                    let set = workspaces_restacked.contains(id);
+                    workspaces_restacked.push(*id);
+                    set
+                }
+                //only the last window told to take the focus keeps it
+                Self::WindowTakeFocus(..) if in_burst => {
+                    kept.iter().any(|a| matches!(a, Self::WindowTakeFocus(..)))
+                }
+                _ => false,
+            };
+            if !redundant {
+                kept.push_front(action);
+            }
+        }
+        *actions = kept;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coalescing_should_keep_the_last_of_redundant_actions() {
+        let handle = WindowHandle::MockHandle(1);
+        let mut actions: VecDeque<DisplayAction> = vec![
+            DisplayAction::SetCurrentTags("1".to_owned()),
+            DisplayAction::WindowTakeFocus(handle, false),
+            DisplayAction::SetCurrentTags("2".to_owned()),
+            DisplayAction::WindowTakeFocus(handle, false),
+            DisplayAction::Unfocus,
+            DisplayAction::SetCurrentTags("3".to_owned()),
+        ]
+        .into();
+        DisplayAction::coalesce(&mut actions, true);
+        let kept: Vec<String> = actions.iter().map(|a| format!("{:?}", a)).collect();
+        assert_eq!(kept.len(), 3);
+        assert!(kept[0].starts_with("WindowTakeFocus"));
+        assert_eq!(kept[1], "Unfocus");
+        assert_eq!(kept[2], "SetCurrentTags(\"3\")");
+    }
+
+    #[test]
+    fn coalescing_should_only_focus_the_last_window_of_a_burst() {
+        let burst: VecDeque<DisplayAction> = vec![
+            DisplayAction::WindowTakeFocus(WindowHandle::MockHandle(1), false),
+            DisplayAction::AddedWindow(WindowHandle::MockHandle(2), false),
+            DisplayAction::WindowTakeFocus(WindowHandle::MockHandle(2), false),
+            DisplayAction::AddedWindow(WindowHandle::MockHandle(3), false),
+            DisplayAction::WindowTakeFocus(WindowHandle::MockHandle(3), false),
+        ]
+        .into();
+        let mut actions = burst.clone();
+        DisplayAction::coalesce(&mut actions, false);
+        assert_eq!(actions.len(), 5);
+        let mut actions = burst;
+        DisplayAction::coalesce(&mut actions, true);
+        let kept: Vec<String> = actions.iter().map(|a| format!("{:?}", a)).collect();
+        assert_eq!(
+            kept,
+            [
+                "AddedWindow(MockHandle(2), false)",
+                "AddedWindow(MockHandle(3), false)",
+                "WindowTakeFocus(MockHandle(3), false)"
+            ]
+        );
+    }
+
+    #[test]
+    fn coalescing_should_keep_the_last_restack_of_each_workspace() {
+        let handle = WindowHandle::MockHandle(1);
+        let mut actions: VecDeque<DisplayAction> = vec![
+            DisplayAction::RestackWorkspace(Some(0), vec![]),
+            DisplayAction::RestackWorkspace(Some(1), vec![]),
+            DisplayAction::RestackWorkspace(Some(0), vec![handle]),
+        ]
+        .into();
+        DisplayAction::coalesce(&mut actions, true);
+        let kept: Vec<String> = actions.iter().map(|a| format!("{:?}", a)).collect();
+        assert_eq!(
+            kept,
+            [
+                "RestackWorkspace(Some(1), [])",
+                "RestackWorkspace(Some(0), [MockHandle(1)])"
+            ]
+        );
+    }
+
+    #[test]
+    fn coalescing_should_only_draw_the_last_outline() {
+        let mut actions: VecDeque<DisplayAction> = vec![
+            DisplayAction::DrawOutline(Some(Xyhw::default())),
+            DisplayAction::DrawOutline(Some(Xyhw::default())),
+            DisplayAction::NormalMode,
+            DisplayAction::DrawOutline(None),
+        ]
+        .into();
+        DisplayAction::coalesce(&mut actions, true);
+        let kept: Vec<String> = actions.iter().map(|a| format!("{:?}", a)).collect();
+        assert_eq!(kept, ["NormalMode", "DrawOutline(None)"]);
+    }
 }
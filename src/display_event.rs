@@ -1,25 +1,66 @@
 use std::os::raw::c_ulong;
 
 use super::{models::Screen, models::Window, models::WindowHandle, Button, ModMask, XKeysym};
-use crate::models::WindowChange;
+use crate::models::{WindowChange, Xyhw, XyhwChange};
 use crate::Command;
+use serde::{Deserialize, Serialize};
 
 #[allow(clippy::large_enum_variant)]
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum DisplayEvent {
     Movement(WindowHandle, i32, i32),
     KeyCombo(ModMask, XKeysym),
-    KeyGrabReload, // Reloads keys for when keyboard changes
+    KeyRepeat(ModMask, XKeysym), //X repeating a held key
+    KeyRelease,                  //A held key let go
+    KeyGrabReload,               // Reloads keys for when keyboard changes
     MouseCombo(ModMask, Button, WindowHandle),
+    TitleBarClick(ModMask, Button, WindowHandle), //Clicked the title bar of this window
+    CloseWindow(WindowHandle),                    //Clicked the close button of this window
     WindowCreate(Window, i32, i32),
     WindowChange(WindowChange),
+    ConfigureRequest(WindowHandle, XyhwChange), //A window asking to be moved or resized
     WindowDestroy(WindowHandle),
     MouseEnteredWindow(WindowHandle),
     VerifyFocusedAt(i32, i32), //Request focus validation at this point
     MoveFocusTo(i32, i32),     //Focus the nearest window to this point
     MoveWindow(WindowHandle, c_ulong, i32, i32),
     ResizeWindow(WindowHandle, c_ulong, i32, i32),
+    StartDrag(WindowHandle, bool), //A window asking to be moved, or resized when true, by the mouse
     ScreenCreate(Screen),
+    OutputPower(String, bool),  //A RandR output was switched on or off
+    SystrayStrut(Option<Xyhw>), //The space the built-in tray keeps free, none once it is gone
     SendCommand(Command, Option<String>),
-    ChangeToNormalMode,
+    ChangeToNormalMode(i32, i32), //A mouse button was let go at this point
+}
+
+impl DisplayEvent {
+    /// The name of the variant, for profiling.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Movement(..) => "Movement",
+            Self::KeyCombo(..) => "KeyCombo",
+            Self::KeyRepeat(..) => "KeyRepeat",
+            Self::KeyRelease => "KeyRelease",
+            Self::KeyGrabReload => "KeyGrabReload",
+            Self::MouseCombo(..) => "MouseCombo",
+            Self::TitleBarClick(..) => "TitleBarClick",
+            Self::CloseWindow(..) => "CloseWindow",
+            Self::WindowCreate(..) => "WindowCreate",
+            Self::WindowChange(..) => "WindowChange",
+            Self::ConfigureRequest(..) => "ConfigureRequest",
+            Self::WindowDestroy(..) => "WindowDestroy",
+            Self::MouseEnteredWindow(..) => "MouseEnteredWindow",
+            Self::VerifyFocusedAt(..) => "VerifyFocusedAt",
+            Self::MoveFocusTo(..) => "MoveFocusTo",
+            Self::MoveWindow(..) => "MoveWindow",
+            Self::ResizeWindow(..) => "ResizeWindow",
+            Self::StartDrag(..) => "StartDrag",
+            Self::ScreenCreate(..) => "ScreenCreate",
+            Self::OutputPower(..) => "OutputPower",
+            Self::SystrayStrut(..) => "SystrayStrut",
+            Self::SendCommand(..) => "SendCommand",
+            Self::ChangeToNormalMode(..) => "ChangeToNormalMode",
+        }
+    }
 }
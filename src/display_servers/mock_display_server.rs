@@ -2,21 +2,224 @@ use super::Config;
 use super::DisplayEvent;
 use super::DisplayServer;
 use super::ThemeSetting;
+use crate::display_action::DisplayAction;
+use crate::errors::Result;
 use crate::models::Screen;
+use std::future::Future;
+use std::io::BufRead;
+use std::pin::Pin;
 use std::sync::Arc;
 
-#[derive(Clone)]
+/// A headless display server. It hands out the events queued in `events` and records every
+/// action the manager asks for in `actions`, so the handlers can be driven without a display.
+#[derive(Default)]
 pub struct MockDisplayServer {
     pub screens: Vec<Screen>,
+    pub events: Vec<DisplayEvent>,
+    pub actions: Vec<DisplayAction>,
+}
+
+impl MockDisplayServer {
+    /// Queues a trace of events, one JSON encoded `DisplayEvent` per line. Blank lines and lines
+    /// starting with `#` are skipped. Anything readable works, e.g. a file or a unix socket.
+    ///
+    /// # Errors
+    ///
+    /// Will error if the trace can't be read or a line isn't a valid event. Nothing is queued
+    /// in that case.
+    pub fn load_events<R: BufRead>(&mut self, reader: R) -> Result<()> {
+        let mut events = vec![];
+        for line in reader.lines() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            events.push(serde_json::from_str(line)?);
+        }
+        self.events.append(&mut events);
+        Ok(())
+    }
 }
 
 impl<C: Config> DisplayServer<C> for MockDisplayServer {
     fn new(_: C, _: Arc<ThemeSetting>) -> Self {
-        Self { screens: vec![] }
+        Self::default()
     }
 
-    //testing a couple mock event
     fn get_next_events(&mut self) -> Vec<DisplayEvent> {
-        vec![]
+        let mut events: Vec<DisplayEvent> = self
+            .screens
+            .drain(..)
+            .map(DisplayEvent::ScreenCreate)
+            .collect();
+        events.append(&mut self.events);
+        events
+    }
+
+    fn execute_action(&mut self, act: DisplayAction) -> Option<DisplayEvent> {
+        self.actions.push(act);
+        None
+    }
+
+    fn wait_readable(&mut self) -> Pin<Box<dyn Future<Output = ()> + '_>> {
+        if self.screens.is_empty() && self.events.is_empty() {
+            Box::pin(std::future::pending())
+        } else {
+            Box::pin(std::future::ready(()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Manager, Window, WindowHandle};
+    use crate::state::State;
+    use crate::DisplayEventHandler;
+
+    struct TestConfig;
+
+    impl Config for TestConfig {
+        fn mapped_bindings(&self) -> Vec<crate::config::Keybind> {
+            vec![]
+        }
+        fn mapped_bindings_for(&self, _modkey: &str) -> Vec<crate::config::Keybind> {
+            vec![]
+        }
+        fn create_list_of_tags(&self) -> Vec<String> {
+            vec![]
+        }
+        fn workspaces(&self) -> Option<&[crate::config::Workspace]> {
+            None
+        }
+        fn focus_behaviour(&self) -> crate::config::FocusBehaviour {
+            crate::config::FocusBehaviour::default()
+        }
+        fn mapped_mousebinds(&self) -> Vec<crate::config::Mousebind> {
+            vec![]
+        }
+        fn mapped_mousebinds_for(
+            &self,
+            _modkey: Option<&str>,
+            _mousekey: Option<&str>,
+        ) -> Vec<crate::config::Mousebind> {
+            vec![]
+        }
+        fn disable_current_tag_swap(&self) -> bool {
+            false
+        }
+        fn focus_tag_wraparound(&self) -> bool {
+            true
+        }
+        fn focus_tag_skip_empty(&self) -> bool {
+            false
+        }
+        fn independent_tags(&self) -> bool {
+            false
+        }
+        fn tag_display_behavior(&self) -> crate::config::TagDisplayBehavior {
+            crate::config::TagDisplayBehavior::Swap
+        }
+        fn drag_refresh_rate(&self) -> u32 {
+            60
+        }
+        fn drag_threshold(&self) -> i32 {
+            0
+        }
+        fn double_click_time(&self) -> u64 {
+            400
+        }
+        fn drop_zones(&self) -> bool {
+            false
+        }
+        fn drag_outline(&self) -> bool {
+            false
+        }
+        fn close_transients_with_parent(&self) -> bool {
+            false
+        }
+        fn csd_shadows(&self) -> bool {
+            true
+        }
+        fn systray(&self) -> Option<crate::config::Systray> {
+            None
+        }
+        fn named_layouts(&self) -> Vec<crate::config::NamedLayout> {
+            vec![]
+        }
+        fn lock_command(&self) -> Option<String> {
+            None
+        }
+        fn edge_actions(&self) -> Vec<crate::config::EdgeAction> {
+            vec![]
+        }
+    }
+
+    struct TestState;
+
+    impl State for TestState {
+        fn save(&self, _manager: &Manager) -> Result<()> {
+            unimplemented!()
+        }
+        fn load(&self, _manager: &mut Manager) {
+            unimplemented!()
+        }
+    }
+
+    #[test]
+    fn headless_server_should_drive_the_handlers() {
+        let mut manager = Manager::new_test();
+        let handler = DisplayEventHandler { config: TestConfig };
+        let mut server: MockDisplayServer =
+            DisplayServer::<TestConfig>::new(TestConfig, manager.theme_setting.clone());
+        server.screens.push(Screen::default());
+        server.events.push(DisplayEvent::WindowCreate(
+            Window::new(WindowHandle::MockHandle(1), None, None),
+            0,
+            0,
+        ));
+
+        for event in DisplayServer::<TestConfig>::get_next_events(&mut server) {
+            handler.process(&mut manager, &TestState, event);
+        }
+        while let Some(act) = manager.actions.pop_front() {
+            DisplayServer::<TestConfig>::execute_action(&mut server, act);
+        }
+
+        assert_eq!(manager.workspaces.len(), 1);
+        assert_eq!(manager.windows.len(), 1);
+        assert!(server.actions.iter().any(|a| matches!(
+            a,
+            DisplayAction::AddedWindow(WindowHandle::MockHandle(1), _)
+        )));
+        assert!(DisplayServer::<TestConfig>::get_next_events(&mut server).is_empty());
+    }
+
+    #[test]
+    fn events_should_be_loaded_from_a_trace() {
+        let window = Window::new(WindowHandle::MockHandle(1), None, None);
+        let create = serde_json::to_string(&DisplayEvent::WindowCreate(window, 0, 0)).unwrap();
+        let trace = format!(
+            r#"# a screen, then a window on it
+{{"ScreenCreate":{{"root":{{"MockHandle":0}},"x":0,"y":0,"width":1920,"height":1080,"wsid":null,"max_window_width":null}}}}
+
+{}
+{{"WindowDestroy":{{"MockHandle":1}}}}
+"#,
+            create
+        );
+        let mut server = MockDisplayServer::default();
+        server.load_events(trace.as_bytes()).unwrap();
+        let events = DisplayServer::<TestConfig>::get_next_events(&mut server);
+        assert_eq!(events.len(), 3);
+        assert!(matches!(events[0], DisplayEvent::ScreenCreate(_)));
+        assert!(matches!(
+            events[2],
+            DisplayEvent::WindowDestroy(WindowHandle::MockHandle(1))
+        ));
+
+        assert!(server.load_events("not an event".as_bytes()).is_err());
+        assert!(server.events.is_empty());
     }
 }
@@ -4,15 +4,21 @@ use crate::models::Manager;
 use crate::models::Window;
 use crate::models::Workspace;
 use crate::DisplayEvent;
-#[cfg(test)]
 mod mock_display_server;
+#[cfg(feature = "x11")]
 pub mod xlib_display_server;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Duration;
 
-#[cfg(test)]
 pub use self::mock_display_server::MockDisplayServer;
+#[cfg(feature = "x11")]
 pub use self::xlib_display_server::XlibDisplayServer;
 
+/// The boundary between the manager and whatever draws the windows. Events come in through
+/// `get_next_events`, and the handlers' `DisplayAction`s go out through `execute_action`, so a
+/// backend never needs to know about the handler logic.
 pub trait DisplayServer<C: Config> {
     fn new(config: C, theme: Arc<ThemeSetting>) -> Self;
 
@@ -33,4 +39,26 @@ pub trait DisplayServer<C: Config> {
     fn execute_action(&mut self, _act: DisplayAction) -> Option<DisplayEvent> {
         None
     }
+
+    /// Resolves once `get_next_events` has something to return.
+    fn wait_readable(&mut self) -> Pin<Box<dyn Future<Output = ()> + '_>>;
+
+    /// Pushes any buffered requests out to the display.
+    fn flush(&self) {}
+
+    /// Asks the display where the pointer is, in case a focus event was missed.
+    fn verify_focused_window(&mut self) -> Vec<DisplayEvent> {
+        vec![]
+    }
+
+    /// Asks the display where the pointer is, wherever it is over.
+    fn pointer_position(&self) -> Option<(i32, i32)> {
+        None
+    }
+
+    /// How long since the user last gave any input, to any window. `None` when the display
+    /// cannot tell, then only the events the window manager sees count as input.
+    fn idle_time(&self) -> Option<Duration> {
+        None
+    }
 }
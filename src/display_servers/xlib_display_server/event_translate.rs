@@ -7,8 +7,9 @@ use crate::models::Window;
 use crate::models::WindowChange;
 use crate::models::WindowHandle;
 use crate::models::WindowType;
-use crate::models::Xyhw;
 use crate::models::XyhwChange;
+use crate::utils::xkeysym_lookup::ModMask;
+use std::os::raw::c_ulong;
 use x11_dl::xlib;
 
 pub struct XEvent<'a>(pub &'a XWrap, pub xlib::XEvent);
@@ -26,7 +27,10 @@ impl<'a> From<XEvent<'a>> for Option<DisplayEvent> {
             xlib::MappingNotify => from_mapping_notify(raw_event, xw),
 
             // window is deleted
-            xlib::UnmapNotify | xlib::DestroyNotify => Some(from_unmap_event(raw_event)),
+            xlib::UnmapNotify | xlib::DestroyNotify => from_unmap_event(raw_event, xw),
+
+            // notifications shown during presentation mode are held back
+            xlib::MapNotify => from_map_notify(raw_event, xw),
 
             xlib::ClientMessage => {
                 match &xw.mode {
@@ -42,10 +46,26 @@ impl<'a> From<XEvent<'a>> for Option<DisplayEvent> {
                 let h = WindowHandle::XlibHandle(event.window);
                 let mut mod_mask = event.state;
                 mod_mask &= !(xlib::Mod2Mask | xlib::LockMask);
-                xw.replay_click(mod_mask);
+                if let Some(owner) = xw.get_title_bar_owner(event.window) {
+                    return Some(from_title_bar_click(xw, event, mod_mask, owner));
+                }
+                xw.replay_click(mod_mask, event.button);
                 Some(DisplayEvent::MouseCombo(mod_mask, event.button, h))
             }
-            xlib::ButtonRelease => Some(DisplayEvent::ChangeToNormalMode),
+            xlib::ButtonRelease => {
+                let event = xlib::XButtonReleasedEvent::from(raw_event);
+                Some(DisplayEvent::ChangeToNormalMode(event.x_root, event.y_root))
+            }
+
+            xlib::Expose => {
+                let event = xlib::XExposeEvent::from(raw_event);
+                //only redraw once the last pending expose arrives
+                if event.count == 0 {
+                    xw.redraw_title_bar(event.window);
+                    xw.redraw_popup(event.window);
+                }
+                None
+            }
 
             xlib::EnterNotify => from_enter_notify(xw, raw_event),
 
@@ -61,9 +81,14 @@ impl<'a> From<XEvent<'a>> for Option<DisplayEvent> {
             xlib::KeyPress => {
                 let event = xlib::XKeyEvent::from(raw_event);
                 let sym = xw.keycode_to_keysym(event.keycode);
+                if xw.key_repeated {
+                    return Some(DisplayEvent::KeyRepeat(event.state, sym));
+                }
                 Some(DisplayEvent::KeyCombo(event.state, sym))
             }
 
+            xlib::KeyRelease => Some(DisplayEvent::KeyRelease),
+
             xlib::MotionNotify => Some(from_motion_notify(raw_event, xw)),
 
             xlib::ConfigureRequest => from_configure_request(xw, raw_event),
@@ -86,12 +111,17 @@ fn from_map_request(raw_event: xlib::XEvent, xw: &XWrap) -> Option<DisplayEvent>
     let name = xw.get_window_name(event.window);
     let pid = xw.get_window_pid(event.window);
     let mut w = Window::new(handle, name, pid);
+    w.res_class = xw.get_window_class(event.window);
     let trans = xw.get_transient_for(event.window);
     if let Some(hint) = xw.get_hint_sizing_as_xyhw(event.window) {
         hint.update_window_floating(&mut w);
         w.set_requested(hint);
     }
+    w.requested_geometry = xw.get_requested_geometry(event.window);
     w.set_states(xw.get_window_states(event.window));
+    if xw.get_urgency_hint(event.window) {
+        w.set_urgent();
+    }
     if w.floating() {
         if let Ok(geo) = xw.get_window_geometry(event.window) {
             log::debug!("geo: {geo:?}", geo = geo);
@@ -102,10 +132,32 @@ fn from_map_request(raw_event: xlib::XEvent, xw: &XWrap) -> Option<DisplayEvent>
         w.transient = Some(WindowHandle::XlibHandle(trans));
     }
     w.type_ = xw.get_window_type(event.window);
+    w.frame_extents = xw.get_frame_extents(event.window);
+    if let Some(input) = xw.get_input_hint(event.window) {
+        //windows taking no input may still ask for the focus by `WM_TAKE_FOCUS`
+        w.never_focus = !input && !xw.can_take_focus(event.window);
+        w.refuses_input = w.never_focus;
+    }
+    w.launch_age = launch_age(xw, event.window);
     let cursor = xw.get_cursor_point().unwrap_or_default();
     Some(DisplayEvent::WindowCreate(w, cursor.0, cursor.1))
 }
 
+// How long before the user's latest input the window was launched, in ms. A `_NET_WM_USER_TIME`
+// of 0 asks for the window to not be focused when mapped.
+fn launch_age(xw: &XWrap, window: xlib::Window) -> Option<u32> {
+    let user_time = xw.get_window_user_time(window)?;
+    if user_time == 0 {
+        return Some(u32::MAX);
+    }
+    let focused_time = xw
+        .get_input_focus()
+        .and_then(|focused| xw.get_window_user_time(focused));
+    // X timestamps are 32 bit
+    let latest_input = (xw.last_input_time as u32).max(focused_time.unwrap_or(0));
+    Some(latest_input.saturating_sub(user_time))
+}
+
 fn from_mapping_notify(raw_event: xlib::XEvent, xw: &XWrap) -> Option<DisplayEvent> {
     let mut event = xlib::XMappingEvent::from(raw_event);
     if event.request == xlib::MappingModifier || event.request == xlib::MappingKeyboard {
@@ -120,10 +172,41 @@ fn from_mapping_notify(raw_event: xlib::XEvent, xw: &XWrap) -> Option<DisplayEve
     }
 }
 
-fn from_unmap_event(raw_event: xlib::XEvent) -> DisplayEvent {
+fn from_map_notify(raw_event: xlib::XEvent, xw: &XWrap) -> Option<DisplayEvent> {
+    let event = xlib::XMapEvent::from(raw_event);
+    if event.override_redirect > 0 {
+        xw.hold_notification(event.window);
+    }
+    None
+}
+
+fn from_unmap_event(raw_event: xlib::XEvent, xw: &XWrap) -> Option<DisplayEvent> {
     let event = xlib::XUnmapEvent::from(raw_event);
+    //title bars are ours, they come and go with their window
+    if xw.get_title_bar_owner(event.window).is_some() {
+        return None;
+    }
+    xw.release_notification(event.window, event.type_ == xlib::DestroyNotify);
+    //windows on tags that are not shown are unmapped by us, a client withdrawing one sends an
+    //unmap of its own
+    if event.type_ == xlib::UnmapNotify && event.send_event == 0 && xw.is_hidden(event.window) {
+        return None;
+    }
     let h = WindowHandle::XlibHandle(event.window);
-    DisplayEvent::WindowDestroy(h)
+    Some(DisplayEvent::WindowDestroy(h))
+}
+
+fn from_title_bar_click(
+    xw: &XWrap,
+    event: xlib::XButtonPressedEvent,
+    mod_mask: ModMask,
+    owner: xlib::Window,
+) -> DisplayEvent {
+    let h = WindowHandle::XlibHandle(owner);
+    if event.button == xlib::Button1 && xw.is_close_button(event.window, event.x) {
+        return DisplayEvent::CloseWindow(h);
+    }
+    DisplayEvent::TitleBarClick(mod_mask, event.button, h)
 }
 
 fn from_enter_notify(xw: &XWrap, raw_event: xlib::XEvent) -> Option<DisplayEvent> {
@@ -160,11 +243,30 @@ fn from_configure_request(xw: &XWrap, raw_event: xlib::XEvent) -> Option<Display
         Mode::Normal => {}
     };
     let event = xlib::XConfigureRequestEvent::from(raw_event);
-    let window_type = xw.get_window_type(event.window);
-    if window_type == WindowType::Normal {
+    if xw.is_unmanaged(event.window) {
+        xw.configure_unmanaged(&event);
         return None;
     }
+    let window_type = xw.get_window_type(event.window);
     let handle = WindowHandle::XlibHandle(event.window);
+    if window_type == WindowType::Normal {
+        //only what the window asks to change
+        let mask = event.value_mask;
+        let requested = |flag: u16, value: i32| {
+            if mask & c_ulong::from(flag) == 0 {
+                return None;
+            }
+            Some(value)
+        };
+        let xyhw = XyhwChange {
+            w: requested(xlib::CWWidth, event.width),
+            h: requested(xlib::CWHeight, event.height),
+            x: requested(xlib::CWX, event.x),
+            y: requested(xlib::CWY, event.y),
+            ..XyhwChange::default()
+        };
+        return Some(DisplayEvent::ConfigureRequest(handle, xyhw));
+    }
     let mut change = WindowChange::new(handle);
     let xyhw = XyhwChange {
         w: Some(event.width),
@@ -175,22 +277,7 @@ fn from_configure_request(xw: &XWrap, raw_event: xlib::XEvent) -> Option<Display
     };
     change.floating = Some(xyhw);
     if window_type == WindowType::Dock || window_type == WindowType::Desktop {
-        if let Some(dock_area) = xw.get_window_strut_array(event.window) {
-            let dems = xw.screens_area_dimensions();
-            let screen = xw
-                .get_screens()
-                .iter()
-                .find(|s| s.contains_dock_area(dock_area, dems))?
-                .clone();
-
-            if let Some(xyhw) = dock_area.as_xyhw(dems.0, dems.1, &screen) {
-                change.strut = Some(xyhw.into());
-            }
-        } else if let Ok(geo) = xw.get_window_geometry(event.window) {
-            let mut xyhw = Xyhw::default();
-            geo.update(&mut xyhw);
-            change.strut = Some(xyhw.into());
-        }
+        change.strut = xw.get_window_strut(event.window).map(Into::into);
     }
     Some(DisplayEvent::WindowChange(change))
 }
@@ -2,6 +2,7 @@ use std::os::raw::c_long;
 
 use super::DisplayEvent;
 use super::XWrap;
+use crate::models::Mode;
 use crate::models::WindowChange;
 use crate::models::WindowHandle;
 use crate::Command;
@@ -15,30 +16,14 @@ pub fn from_event(xw: &XWrap, event: xlib::XClientMessageEvent) -> Option<Displa
         return goto_tag_by_index(xw, event.data.get_long(0));
     }
 
-    //if the client is trying to toggle fullscreen without changing the window state, change it too
-    if event.message_type == xw.atoms.NetWMState
-        && (event.data.get_long(1) == xw.atoms.NetWMStateFullscreen as c_long
-            || event.data.get_long(2) == xw.atoms.NetWMStateFullscreen as c_long)
-    {
-        let set_fullscreen = event.data.get_long(0) == 1;
-        let toggle_fullscreen = event.data.get_long(0) == 2;
-        let mut states = xw.get_window_states_atoms(event.window);
-        //determine what to change the state to
-        let fullscreen = if toggle_fullscreen {
-            !states.contains(&xw.atoms.NetWMStateFullscreen)
-        } else {
-            set_fullscreen
-        };
-        //update the list of states
-        if fullscreen {
-            states.push(xw.atoms.NetWMStateFullscreen);
-        } else {
-            states.retain(|x| x != &xw.atoms.NetWMStateFullscreen);
-        }
-        states.sort_unstable();
-        states.dedup();
-        //set the windows state
-        xw.set_window_states_atoms(event.window, &states);
+    if event.message_type == xw.atoms.NetWMMoveResize {
+        return move_resize(xw, &event);
+    }
+
+    //if the client is trying to toggle a state without changing the window state, change it too
+    if event.message_type == xw.atoms.NetWMState {
+        toggle_state(xw, &event, xw.atoms.NetWMStateFullscreen);
+        toggle_state(xw, &event, xw.atoms.NetWMStateDemandsAttention);
     }
 
     //update the window states
@@ -53,6 +38,52 @@ pub fn from_event(xw: &XWrap, event: xlib::XClientMessageEvent) -> Option<Displa
     None
 }
 
+fn toggle_state(xw: &XWrap, event: &xlib::XClientMessageEvent, state: xlib::Atom) {
+    if event.data.get_long(1) != state as c_long && event.data.get_long(2) != state as c_long {
+        return;
+    }
+    let set_state = event.data.get_long(0) == 1;
+    let toggle_state = event.data.get_long(0) == 2;
+    let mut states = xw.get_window_states_atoms(event.window);
+    //determine what to change the state to
+    let enabled = if toggle_state {
+        !states.contains(&state)
+    } else {
+        set_state
+    };
+    //update the list of states
+    if enabled {
+        states.push(state);
+    } else {
+        states.retain(|x| x != &state);
+    }
+    states.sort_unstable();
+    states.dedup();
+    //set the windows state
+    xw.set_window_states_atoms(event.window, &states);
+}
+
+//a window with its own title bar asking to be dragged, directions 0 to 7 are the edge or corner
+//grabbed, which leftwm resizes from the bottom right like any other
+fn move_resize(xw: &XWrap, event: &xlib::XClientMessageEvent) -> Option<DisplayEvent> {
+    const SIZE_BOTTOMLEFT: c_long = 7;
+    const MOVE: c_long = 8;
+    const CANCEL: c_long = 11;
+    let handle = WindowHandle::XlibHandle(event.window);
+    //the button may already be let go, the drag would then never end
+    let held = xw.is_button_held();
+    match event.data.get_long(2) {
+        0..=SIZE_BOTTOMLEFT if held => Some(DisplayEvent::StartDrag(handle, true)),
+        MOVE if held => Some(DisplayEvent::StartDrag(handle, false)),
+        CANCEL if xw.mode != Mode::Normal => {
+            let (x, y) = xw.get_cursor_point().ok()?;
+            Some(DisplayEvent::ChangeToNormalMode(x, y))
+        }
+        //moving and resizing with the keyboard are not supported
+        _ => None,
+    }
+}
+
 fn goto_tag_by_index(xw: &XWrap, index: c_long) -> Option<DisplayEvent> {
     if index >= 0 && index < xw.tags.len() as c_long {
         let tag_num = index + 1;
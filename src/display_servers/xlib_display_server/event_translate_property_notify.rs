@@ -3,7 +3,6 @@ use super::XWrap;
 use crate::models::WindowChange;
 use crate::models::WindowHandle;
 use crate::models::WindowType;
-use crate::models::Xyhw;
 use x11_dl::xlib;
 
 pub fn from_event(xw: &XWrap, event: xlib::XPropertyEvent) -> Option<DisplayEvent> {
@@ -33,22 +32,23 @@ pub fn from_event(xw: &XWrap, event: xlib::XPropertyEvent) -> Option<DisplayEven
         xlib::XA_WM_NORMAL_HINTS => {
             build_change_for_size_hints(xw, event.window).map(DisplayEvent::WindowChange)
         }
-        xlib::XA_WM_HINTS => match xw.get_wmhints(event.window) {
-            Some(hints) if hints.flags & xlib::InputHint != 0 => {
-                let handle = WindowHandle::XlibHandle(event.window);
-                let mut change = WindowChange::new(handle);
-                change.never_focus = Some(hints.input == 0);
-                Some(DisplayEvent::WindowChange(change))
-            }
-            Some(_hints) => None,
-            None => None,
-        },
+        xlib::XA_WM_HINTS => Some(build_change_for_hints(xw, event.window)),
         xlib::XA_WM_NAME => Some(update_title(xw, event.window)),
         _ => {
             if event.atom == xw.atoms.NetWMName {
                 return Some(update_title(xw, event.window));
             }
 
+            if event.atom == xw.atoms.GTKFrameExtents {
+                let mut change = WindowChange::new(WindowHandle::XlibHandle(event.window));
+                change.frame_extents = Some(xw.get_frame_extents(event.window));
+                return Some(DisplayEvent::WindowChange(change));
+            }
+
+            if event.atom == xw.atoms.WMProtocols {
+                return build_change_for_input(xw, event.window);
+            }
+
             if event.atom == xw.atoms.NetWMStrut
                 || event.atom == xw.atoms.NetWMStrutPartial
                     && xw.get_window_type(event.window) == WindowType::Dock
@@ -66,29 +66,30 @@ pub fn from_event(xw: &XWrap, event: xlib::XPropertyEvent) -> Option<DisplayEven
 fn build_change_for_size_strut_partial(xw: &XWrap, window: xlib::Window) -> Option<WindowChange> {
     let handle = WindowHandle::XlibHandle(window);
     let mut change = WindowChange::new(handle);
-    let type_ = xw.get_window_type(window);
+    change.strut = Some(xw.get_window_strut(window)?.into());
+    change.type_ = Some(xw.get_window_type(window));
+    Some(change)
+}
 
-    if let Some(dock_area) = xw.get_window_strut_array(window) {
-        let dems = xw.screens_area_dimensions();
-        let screen = xw
-            .get_screens()
-            .iter()
-            .find(|s| s.contains_dock_area(dock_area, dems))?
-            .clone();
+// Whether the window takes the input focus, and whether it takes input at all.
+fn build_change_for_input(xw: &XWrap, window: xlib::Window) -> Option<DisplayEvent> {
+    let input = xw.get_input_hint(window)?;
+    let handle = WindowHandle::XlibHandle(window);
+    let mut change = WindowChange::new(handle);
+    let never_focus = !input && !xw.can_take_focus(window);
+    change.never_focus = Some(never_focus);
+    change.refuses_input = Some(never_focus);
+    Some(DisplayEvent::WindowChange(change))
+}
 
-        if let Some(xyhw) = dock_area.as_xyhw(dems.0, dems.1, &screen) {
-            change.floating = Some(xyhw.into());
-            change.type_ = Some(type_);
-            return Some(change);
-        }
-    } else if let Ok(geo) = xw.get_window_geometry(window) {
-        let mut xyhw = Xyhw::default();
-        geo.update(&mut xyhw);
-        change.floating = Some(xyhw.into());
-        change.type_ = Some(type_);
-        return Some(change);
-    }
-    None
+// The input of `build_change_for_input` and the urgency hint, both in `WM_HINTS`.
+fn build_change_for_hints(xw: &XWrap, window: xlib::Window) -> DisplayEvent {
+    let mut change = match build_change_for_input(xw, window) {
+        Some(DisplayEvent::WindowChange(change)) => change,
+        _ => WindowChange::new(WindowHandle::XlibHandle(window)),
+    };
+    change.urgent = Some(xw.get_urgency_hint(window));
+    DisplayEvent::WindowChange(change)
 }
 
 fn build_change_for_size_hints(xw: &XWrap, window: xlib::Window) -> Option<WindowChange> {
@@ -104,6 +105,7 @@ fn build_change_for_size_hints(xw: &XWrap, window: xlib::Window) -> Option<Windo
 }
 
 fn update_title(xw: &XWrap, window: xlib::Window) -> DisplayEvent {
+    xw.redraw_title_bar_of(window);
     let title = xw.get_window_name(window);
     let handle = WindowHandle::XlibHandle(window);
     let mut change = WindowChange::new(handle);
@@ -1,4 +1,6 @@
 use crate::config::Config;
+use crate::config::MouseTarget;
+use crate::config::Mousebind;
 use crate::config::ThemeSetting;
 use crate::display_action::DisplayAction;
 use crate::models::Manager;
@@ -7,11 +9,17 @@ use crate::models::Screen;
 use crate::models::Window;
 use crate::models::WindowHandle;
 use crate::models::Workspace;
+#[cfg(feature = "systray")]
+use crate::models::Xyhw;
 use crate::utils;
+use crate::utils::xkeysym_lookup::{Button, ModMask};
 use crate::DisplayEvent;
 use crate::DisplayServer;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
 use std::sync::Once;
+use std::time::Duration;
 use x11_dl::xlib;
 
 mod event_translate;
@@ -26,11 +34,27 @@ mod xcursor;
 
 static SETUP: Once = Once::new();
 
+// The buttons and modifiers grabbed on the focused window.
+fn mouse_grabs(mousebinds: &[Mousebind]) -> Vec<(ModMask, Button)> {
+    mousebinds
+        .iter()
+        //clicks on the root window are delivered without a grab
+        .filter(|m| m.target == MouseTarget::Window)
+        .filter_map(|m| {
+            let button = utils::xkeysym_lookup::into_button(&m.button)?;
+            Some((utils::xkeysym_lookup::into_modmask(&m.modifier), button))
+        })
+        .collect()
+}
+
 pub struct XlibDisplayServer<C> {
     xw: XWrap,
     root: xlib::Window,
     config: C,
     theme: Arc<ThemeSetting>,
+    //the space of the built-in tray the manager was last told about
+    #[cfg(feature = "systray")]
+    systray_area: Option<Xyhw>,
 }
 
 impl<C> DisplayServer<C> for XlibDisplayServer<C>
@@ -41,7 +65,7 @@ where
         let mut wrap = XWrap::new();
 
         wrap.focus_behaviour = config.focus_behaviour();
-        wrap.mouse_key_mask = utils::xkeysym_lookup::into_mod(config.mousekey());
+        wrap.mousebinds = mouse_grabs(&config.mapped_mousebinds());
         wrap.init(&config, &theme); //setup events masks
 
         let root = wrap.get_default_root();
@@ -51,6 +75,8 @@ where
             root,
             theme,
             config,
+            #[cfg(feature = "systray")]
+            systray_area: None,
         }
     }
 
@@ -63,32 +89,20 @@ where
         &self,
         windows: Vec<&Window>,
         focused_window: Option<&Window>,
-        manager: &Manager,
+        _manager: &Manager,
     ) {
-        let tags: Vec<&String> = manager.workspaces.iter().flat_map(|w| &w.tags).collect();
-
-        let max_tag_index: Option<usize> = tags.iter().filter_map(|&t| manager.tag_index(t)).max();
-        let to_the_right = manager
-            .screens
-            .iter()
-            .map(|s| s.bbox.width + s.bbox.x + 100)
-            .max();
-        let max_screen_width = manager.screens.iter().map(|s| s.bbox.width).max();
-
         for window in windows {
             let is_focused = match focused_window {
                 Some(f) => f.handle == window.handle,
                 None => false,
             };
 
-            let hide_offset = right_offset(max_tag_index, to_the_right, manager, window)
-                .unwrap_or_else(|| left_offset(max_screen_width, window));
-
-            self.xw.update_window(window, is_focused, hide_offset);
+            self.xw.update_window(window, is_focused);
             if window.is_fullscreen() {
                 self.xw.move_to_top(&window.handle);
             }
         }
+        self.xw.draw_focus_indicator(focused_window);
     }
 
     fn update_workspaces(&self, _workspaces: Vec<&Workspace>, focused: Option<&Workspace>) {
@@ -111,7 +125,35 @@ where
         let event_in_queue = self.xw.queue_len();
 
         for _ in 0..event_in_queue {
-            let xlib_event = self.xw.get_next_event();
+            let mut xlib_event = self.xw.get_next_event();
+            if self.xw.is_output_change(&xlib_event) {
+                events.append(&mut self.xw.output_changes(&mut xlib_event));
+                #[cfg(feature = "systray")]
+                self.xw.layout_systray();
+                continue;
+            }
+            #[cfg(feature = "systray")]
+            if self.xw.systray_event(&xlib_event) {
+                continue;
+            }
+            match xlib_event.get_type() {
+                //any key closes the popup, without doing what it is bound to
+                xlib::KeyPress if self.xw.close_popup() => continue,
+                xlib::KeyPress => {
+                    let event = xlib::XKeyEvent::from(xlib_event);
+                    self.xw.last_input_time = event.time;
+                    self.xw.track_key(&event);
+                }
+                xlib::KeyRelease => self.xw.track_key(&xlib::XKeyEvent::from(xlib_event)),
+                xlib::ButtonPress => {
+                    self.xw.last_input_time = xlib::XButtonEvent::from(xlib_event).time;
+                }
+                xlib::DestroyNotify => {
+                    let event = xlib::XDestroyWindowEvent::from(xlib_event);
+                    self.xw.forget_unmanaged(event.window);
+                }
+                _ => {}
+            }
             let event = XEvent(&self.xw, xlib_event).into();
             if let Some(e) = event {
                 log::trace!("DisplayEvent: {:?}", e);
@@ -125,9 +167,16 @@ where
             }
         }
 
+        #[cfg(feature = "systray")]
+        if self.xw.systray_area() != self.systray_area {
+            self.systray_area = self.xw.systray_area();
+            events.push(DisplayEvent::SystrayStrut(self.systray_area));
+        }
+
         events
     }
 
+    #[allow(clippy::too_many_lines)]
     fn execute_action(&mut self, act: DisplayAction) -> Option<DisplayEvent> {
         log::trace!("DisplayAction: {:?}", act);
         let event: Option<DisplayEvent> = match act {
@@ -135,9 +184,25 @@ where
                 self.xw.kill_window(&w);
                 None
             }
+            DisplayAction::CloseWindow(w) => {
+                self.xw.close_window(&w);
+                None
+            }
             DisplayAction::AddedWindow(w, follow_mouse) => {
                 self.xw.setup_managed_window(w, follow_mouse)
             }
+            DisplayAction::ConfigureWindow(handle, xyhw) => {
+                if let WindowHandle::XlibHandle(win) = handle {
+                    self.xw.configure_window(win, &xyhw);
+                }
+                None
+            }
+            DisplayAction::MapUnmanaged(handle) => {
+                if let WindowHandle::XlibHandle(win) = handle {
+                    self.xw.map_unmanaged(win);
+                }
+                None
+            }
             DisplayAction::MoveMouseOver(handle) => {
                 if let WindowHandle::XlibHandle(win) = handle {
                     let _ = self.xw.move_cursor_to_window(win);
@@ -152,8 +217,8 @@ where
                 self.xw.teardown_managed_window(&w);
                 None
             }
-            DisplayAction::WindowTakeFocus(w) => {
-                self.xw.window_take_focus(&w);
+            DisplayAction::WindowTakeFocus(handle, never_focus) => {
+                self.xw.window_take_focus(handle, never_focus);
                 None
             }
             DisplayAction::Unfocus => {
@@ -180,6 +245,14 @@ where
                 self.xw.restack(all);
                 None
             }
+            DisplayAction::RestackWorkspace(_, wins) => {
+                #[cfg(feature = "systray")]
+                let top = wins.first().copied();
+                self.xw.restack_on_top(wins);
+                #[cfg(feature = "systray")]
+                self.xw.raise_systray(top);
+                None
+            }
             DisplayAction::FocusWindowUnderCursor => {
                 let point = self.xw.get_cursor_point().ok()?;
                 let evt = DisplayEvent::MoveFocusTo(point.0, point.1);
@@ -211,12 +284,48 @@ where
                 self.xw.reset_grabs(&keybinds);
                 None
             }
+            DisplayAction::ReloadMouseGrabs(mousebinds) => {
+                self.xw.reset_mouse_grabs(mouse_grabs(&mousebinds));
+                None
+            }
+            DisplayAction::ShowKeybindHelp(lines) | DisplayAction::ShowMessage(lines) => {
+                self.xw.show_popup(lines);
+                None
+            }
+            DisplayAction::DrawOutline(xyhw) => {
+                self.xw.draw_outline(xyhw);
+                None
+            }
+            DisplayAction::SetPresenting(presenting) => {
+                self.xw.set_presenting(presenting);
+                None
+            }
         };
         if event.is_some() {
             log::trace!("DisplayEvent: {:?}", event);
         }
         event
     }
+
+    fn wait_readable(&mut self) -> Pin<Box<dyn Future<Output = ()> + '_>> {
+        Box::pin(self.xw.wait_readable())
+    }
+
+    fn flush(&self) {
+        self.xw.flush();
+    }
+
+    fn verify_focused_window(&mut self) -> Vec<DisplayEvent> {
+        self.verify_focused_window_work().unwrap_or_default()
+    }
+
+    fn pointer_position(&self) -> Option<(i32, i32)> {
+        self.xw.get_cursor_point().ok()
+    }
+
+    fn idle_time(&self) -> Option<Duration> {
+        self.xw.idle_time()
+    }
 }
 
 impl<C> XlibDisplayServer<C>
@@ -234,9 +343,18 @@ where
                     events.push(e);
                 });
             } else {
+                let outputs = self.xw.get_outputs();
                 for wsc in workspaces.iter() {
                     let mut screen = Screen::from(wsc);
                     screen.root = WindowHandle::XlibHandle(self.root);
+                    //the output showing the workspace, or its top left corner if it spans several
+                    let (x, y) = (screen.bbox.x, screen.bbox.y);
+                    screen.output = outputs
+                        .iter()
+                        .find(|((ox, oy, w, h), _)| {
+                            (*ox..ox + w).contains(&x) && (*oy..oy + h).contains(&y)
+                        })
+                        .map(|(_, name)| name.clone());
                     let e = DisplayEvent::ScreenCreate(screen);
                     events.push(e);
                 }
@@ -253,10 +371,6 @@ where
         events
     }
 
-    pub fn verify_focused_window(&mut self) -> Vec<DisplayEvent> {
-        self.verify_focused_window_work().unwrap_or_default()
-    }
-
     fn verify_focused_window_work(&mut self) -> Option<Vec<DisplayEvent>> {
         let point = self.xw.get_cursor_point().ok()?;
         Some(vec![DisplayEvent::VerifyFocusedAt(point.0, point.1)])
@@ -270,14 +384,36 @@ where
                     Ok(x) => x,
                     Err(_) => return,
                 };
+                //windows another window manager minimized are adopted as well
+                let shown = attrs.map_state == xlib::IsViewable || self.xw.is_iconic(handle);
                 let managed = match self.xw.get_transient_for(handle) {
-                    Some(_) => attrs.map_state == 2,
-                    None => attrs.override_redirect <= 0 && attrs.map_state == 2,
+                    Some(_) => shown,
+                    None => attrs.override_redirect <= 0 && shown,
                 };
+                //the icons the previous worker's tray had dock in this one again
+                #[cfg(feature = "systray")]
+                let managed = managed && !self.xw.is_systray_icon(handle);
                 if managed {
                     let name = self.xw.get_window_name(handle);
                     let pid = self.xw.get_window_pid(handle);
-                    let w = Window::new(WindowHandle::XlibHandle(handle), name, pid);
+                    let mut w = Window::new(WindowHandle::XlibHandle(handle), name, pid);
+                    w.res_class = self.xw.get_window_class(handle);
+                    w.type_ = self.xw.get_window_type(handle);
+                    w.transient = self
+                        .xw
+                        .get_transient_for(handle)
+                        .map(WindowHandle::XlibHandle);
+                    w.set_states(self.xw.get_window_states(handle));
+                    //floating windows stay where they are
+                    w.requested_geometry = self.xw.get_window_geometry(handle).ok();
+                    //a previous worker left the tag the window was on
+                    w.previous_tags = self
+                        .xw
+                        .get_window_desktop(handle)
+                        .and_then(|index| self.xw.tags.get(index as usize))
+                        .into_iter()
+                        .cloned()
+                        .collect();
                     all.push(w);
                 }
             }),
@@ -287,40 +423,4 @@ where
         }
         all
     }
-
-    pub async fn wait_readable(&mut self) {
-        self.xw.wait_readable().await;
-    }
-
-    pub fn flush(&self) {
-        self.xw.flush();
-    }
-}
-
-//return an offset to hide the window in the right, if it should be hidden on the right
-fn right_offset(
-    max_tag_index: Option<usize>,
-    max_right_screen: Option<i32>,
-    manager: &Manager,
-    window: &Window,
-) -> Option<i32> {
-    let max_tag_index = max_tag_index?;
-    let max_right_screen = max_right_screen?;
-    for tag in &window.tags {
-        let index = manager.tag_index(tag)?;
-        if index > max_tag_index {
-            return Some(max_right_screen + window.x());
-        }
-    }
-    None
-}
-
-//return an offset to hide the window on the left
-fn left_offset(max_screen_width: Option<i32>, window: &Window) -> i32 {
-    let mut left = -(window.width());
-    if let Some(screen_width) = max_screen_width {
-        let best_left = window.x() - screen_width;
-        left = std::cmp::min(best_left, left);
-    }
-    left
 }
@@ -14,6 +14,9 @@ pub struct XAtom {
     pub NetWMName: xlib::Atom,
     pub NetWMState: xlib::Atom,
     pub NetWMPid: xlib::Atom,
+    pub NetWMUserTime: xlib::Atom,
+    pub NetWMUserTimeWindow: xlib::Atom,
+    pub NetWMMoveResize: xlib::Atom,
 
     //pub NetWMStateSticky: xlib::Atom,
     //pub NetWMStateAbove: xlib::Atom,
@@ -39,6 +42,7 @@ pub struct XAtom {
     pub NetWMWindowTypeUtility: xlib::Atom,
     pub NetWMWindowTypeSplash: xlib::Atom,
     pub NetWMWindowTypeDialog: xlib::Atom,
+    pub NetWMWindowTypeNotification: xlib::Atom,
 
     pub NetSupportingWmCheck: xlib::Atom,
     pub NetClientList: xlib::Atom,
@@ -49,6 +53,7 @@ pub struct XAtom {
     pub NetWMDesktop: xlib::Atom,
     pub NetWMStrutPartial: xlib::Atom, //net version - Reserve Screen Space
     pub NetWMStrut: xlib::Atom,        //old version
+    pub GTKFrameExtents: xlib::Atom,
 }
 
 impl XAtom {
@@ -59,6 +64,9 @@ impl XAtom {
             self.NetWMName,
             self.NetWMState,
             self.NetWMPid,
+            self.NetWMUserTime,
+            self.NetWMUserTimeWindow,
+            self.NetWMMoveResize,
             self.NetWMStateModal,
             self.NetWMStateSticky,
             self.NetWMStateMaximizedVert,
@@ -79,6 +87,7 @@ impl XAtom {
             self.NetWMWindowTypeUtility,
             self.NetWMWindowTypeSplash,
             self.NetWMWindowTypeDialog,
+            self.NetWMWindowTypeNotification,
             self.NetSupportingWmCheck,
             self.NetClientList,
             self.NetDesktopViewport,
@@ -88,9 +97,11 @@ impl XAtom {
             self.NetWMDesktop,
             self.NetWMStrutPartial,
             self.NetWMStrut,
+            self.GTKFrameExtents,
         ]
     }
 
+    #[allow(clippy::too_many_lines)]
     pub const fn get_name(&self, atom: xlib::Atom) -> &str {
         if atom == self.WMProtocols {
             return "WM_PROTOCOLS";
@@ -119,6 +130,15 @@ impl XAtom {
         if atom == self.NetWMPid {
             return "_NET_WM_PID";
         }
+        if atom == self.NetWMUserTime {
+            return "_NET_WM_USER_TIME";
+        }
+        if atom == self.NetWMUserTimeWindow {
+            return "_NET_WM_USER_TIME_WINDOW";
+        }
+        if atom == self.NetWMMoveResize {
+            return "_NET_WM_MOVERESIZE";
+        }
 
         if atom == self.NetWMStateModal {
             return "NetWMStateModal";
@@ -160,6 +180,9 @@ impl XAtom {
         if atom == self.NetWMWindowTypeDialog {
             return "_NET_WM_WINDOW_TYPE_DIALOG";
         }
+        if atom == self.NetWMWindowTypeNotification {
+            return "_NET_WM_WINDOW_TYPE_NOTIFICATION";
+        }
         if atom == self.NetWMWindowTypeDock {
             return "_NET_WM_WINDOW_TYPE_DOCK";
         }
@@ -187,6 +210,9 @@ impl XAtom {
         if atom == self.NetWMStrut {
             return "_NET_WM_STRUT";
         }
+        if atom == self.GTKFrameExtents {
+            return "_GTK_FRAME_EXTENTS";
+        }
         "(UNKNOWN)"
     }
 
@@ -200,6 +226,9 @@ impl XAtom {
             NetSupported: from(xlib, dpy, "_NET_SUPPORTED"),
             NetWMName: from(xlib, dpy, "_NET_WM_NAME"),
             NetWMPid: from(xlib, dpy, "_NET_WM_PID"),
+            NetWMUserTime: from(xlib, dpy, "_NET_WM_USER_TIME"),
+            NetWMUserTimeWindow: from(xlib, dpy, "_NET_WM_USER_TIME_WINDOW"),
+            NetWMMoveResize: from(xlib, dpy, "_NET_WM_MOVERESIZE"),
 
             NetWMState: from(xlib, dpy, "_NET_WM_STATE"),
             NetWMStateModal: from(xlib, dpy, "_NET_WM_STATE_MODAL"),
@@ -223,6 +252,7 @@ impl XAtom {
             NetWMWindowTypeUtility: from(xlib, dpy, "_NET_WM_WINDOW_TYPE_UTILITY"),
             NetWMWindowTypeSplash: from(xlib, dpy, "_NET_WM_WINDOW_TYPE_SPLASH"),
             NetWMWindowTypeDialog: from(xlib, dpy, "_NET_WM_WINDOW_TYPE_DIALOG"),
+            NetWMWindowTypeNotification: from(xlib, dpy, "_NET_WM_WINDOW_TYPE_NOTIFICATION"),
             NetSupportingWmCheck: from(xlib, dpy, "_NET_SUPPORTING_WM_CHECK"),
 
             NetClientList: from(xlib, dpy, "_NET_CLIENT_LIST"),
@@ -233,6 +263,7 @@ impl XAtom {
             NetWMDesktop: from(xlib, dpy, "_NET_WM_DESKTOP"),
             NetWMStrutPartial: from(xlib, dpy, "_NET_WM_STRUT_PARTIAL"),
             NetWMStrut: from(xlib, dpy, "_NET_WM_STRUT"),
+            GTKFrameExtents: from(xlib, dpy, "_GTK_FRAME_EXTENTS"),
         }
     }
 }
@@ -13,18 +13,22 @@ use super::Config;
 use super::Screen;
 use super::Window;
 use super::WindowHandle;
+use crate::config::FocusIndicator;
 use crate::config::Keybind;
 use crate::models::DockArea;
+use crate::models::Margins;
 use crate::models::Mode;
+use crate::models::Tag;
 use crate::models::WindowChange;
 use crate::models::WindowState;
 use crate::models::WindowType;
 use crate::models::Xyhw;
 use crate::models::XyhwChange;
-use crate::utils::xkeysym_lookup::ModMask;
+use crate::utils::xkeysym_lookup::{Button, ModMask};
 use crate::DisplayEvent;
 use crate::{config::ThemeSetting, models::FocusBehaviour};
-use std::ffi::CString;
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
 use std::os::raw::{c_char, c_int, c_long, c_uchar, c_uint, c_ulong};
 use std::ptr;
 use std::slice;
@@ -33,6 +37,9 @@ use tokio::sync::{oneshot, Notify};
 use tokio::time::Duration;
 use x11_dl::xlib;
 
+#[cfg(feature = "systray")]
+mod systray;
+
 //type WindowStateConst = u8;
 //const WITHDRAWN_STATE: WindowStateConst = 0;
 //const NORMAL_STATE: WindowStateConst = 1;
@@ -41,6 +48,10 @@ const MAX_PROPERTY_VALUE_LEN: c_long = 4096;
 
 const BUTTONMASK: c_long = xlib::ButtonPressMask | xlib::ButtonReleaseMask;
 const MOUSEMASK: c_long = BUTTONMASK | xlib::PointerMotionMask;
+//`WM_STATE` of a window that is shown
+const NORMAL_STATE: c_long = 1;
+//`WM_STATE` of a window that is minimized rather than withdrawn
+const ICONIC_STATE: c_long = 3;
 
 pub struct Colors {
     normal: c_ulong,
@@ -48,6 +59,14 @@ pub struct Colors {
     active: c_ulong,
 }
 
+/// Colors and size of the title bars drawn above floating windows.
+pub struct TitleBarTheme {
+    height: u32,
+    normal: c_ulong,
+    active: c_ulong,
+    text: c_ulong,
+}
+
 #[derive(Debug, Clone)]
 pub enum XlibError {
     FailedStatus,
@@ -63,12 +82,40 @@ pub struct XWrap {
     pub atoms: XAtom,
     cursors: XCursor,
     colors: Colors,
+    title_bar: Option<TitleBarTheme>,
+    title_bars: Vec<(xlib::Window, xlib::Window)>,
+    //the popup with the keybind help or a message and its lines, while it is shown
+    popup: Option<(xlib::Window, Vec<String>)>,
+    //the edges of the outline of a window being dragged with `drag_outline`, while it is shown
+    outline: Option<[xlib::Window; 4]>,
+    //the built-in system tray, while it runs
+    #[cfg(feature = "systray")]
+    systray: Option<systray::Systray>,
+    //the theme's focus indicator and its color
+    focus_indicator: Option<(FocusIndicator, c_ulong)>,
+    //the windows drawing the focus indicator, moved along to the next focused window
+    focus_marks: RefCell<Vec<xlib::Window>>,
+    //the number of the first `RandR` event, `None` without `RandR`
+    randr_event_base: Option<c_int>,
+    //the outputs showing something, to tell when one is switched off or back on
+    outputs: Vec<String>,
     managed_windows: Vec<xlib::Window>,
+    //windows window rules say to leave alone
+    unmanaged_windows: Vec<xlib::Window>,
+    //windows unmapped because their tags are not shown, their unmaps are not them closing
+    hidden_windows: RefCell<Vec<xlib::Window>>,
+    //the notifications unmapped until presentation mode ends, `None` while it is off
+    held_notifications: RefCell<Option<Vec<(xlib::Window, bool)>>>,
     pub tags: Vec<String>,
     pub mode: Mode,
     pub focus_behaviour: FocusBehaviour,
-    pub mouse_key_mask: ModMask,
+    pub mousebinds: Vec<(ModMask, Button)>,
     pub mode_origin: (i32, i32),
+    //time of the latest key press or click the WM received
+    pub last_input_time: xlib::Time,
+    //the key held down, a press of it before its release is X repeating it
+    pub held_key: Option<c_uint>,
+    pub key_repeated: bool,
     _task_guard: oneshot::Receiver<()>,
     task_notify: Arc<Notify>,
 }
@@ -139,12 +186,28 @@ impl XWrap {
             atoms,
             cursors,
             colors,
+            title_bar: None,
+            title_bars: vec![],
+            popup: None,
+            outline: None,
+            #[cfg(feature = "systray")]
+            systray: None,
+            focus_indicator: None,
+            focus_marks: RefCell::default(),
+            randr_event_base: None,
+            outputs: vec![],
             managed_windows: vec![],
+            unmanaged_windows: vec![],
+            hidden_windows: RefCell::default(),
+            held_notifications: RefCell::default(),
             tags: vec![],
             mode: Mode::Normal,
             focus_behaviour: FocusBehaviour::Sloppy,
-            mouse_key_mask: 0,
+            mousebinds: vec![],
             mode_origin: (0, 0),
+            last_input_time: 0,
+            held_key: None,
+            key_repeated: false,
             _task_guard,
             task_notify,
         };
@@ -210,11 +273,17 @@ impl XWrap {
             //take ownership of the array
             let xinerama_infos: &[XineramaScreenInfo] =
                 unsafe { slice::from_raw_parts(info_array_raw, screen_count as usize) };
+            let outputs = self.get_outputs();
             xinerama_infos
                 .iter()
                 .map(|i| {
                     let mut s = Screen::from(i);
                     s.root = root;
+                    let geometry = (s.bbox.x, s.bbox.y, s.bbox.width, s.bbox.height);
+                    s.output = outputs
+                        .iter()
+                        .find(|(g, _)| *g == geometry)
+                        .map(|(_, name)| name.clone());
                     s
                 })
                 .collect()
@@ -230,6 +299,150 @@ impl XWrap {
         }
     }
 
+    /// The names of the `RandR` outputs showing something, like `eDP-1`, with the geometry
+    /// (x, y, width, height) they show. Empty without `RandR`.
+    #[must_use]
+    pub fn get_outputs(&self) -> Vec<((i32, i32, i32, i32), String)> {
+        let xrandr = match x11_dl::xrandr::Xrandr::open() {
+            Ok(xrandr) => xrandr,
+            Err(_) => return vec![],
+        };
+        let mut outputs = vec![];
+        unsafe {
+            let root = self.get_default_root();
+            let resources = (xrandr.XRRGetScreenResourcesCurrent)(self.display, root);
+            if resources.is_null() {
+                return outputs;
+            }
+            if (*resources).ncrtc <= 0 {
+                (xrandr.XRRFreeScreenResources)(resources);
+                return outputs;
+            }
+            let crtcs = slice::from_raw_parts((*resources).crtcs, (*resources).ncrtc as usize);
+            for crtc in crtcs {
+                let info = (xrandr.XRRGetCrtcInfo)(self.display, resources, *crtc);
+                if info.is_null() {
+                    continue;
+                }
+                if (*info).noutput <= 0 {
+                    (xrandr.XRRFreeCrtcInfo)(info);
+                    continue;
+                }
+                let geometry = (
+                    (*info).x,
+                    (*info).y,
+                    (*info).width as i32,
+                    (*info).height as i32,
+                );
+                let crtc_outputs = slice::from_raw_parts((*info).outputs, (*info).noutput as usize);
+                for output in crtc_outputs {
+                    let output_info = (xrandr.XRRGetOutputInfo)(self.display, resources, *output);
+                    if output_info.is_null() {
+                        continue;
+                    }
+                    let name = slice::from_raw_parts(
+                        (*output_info).name.cast::<u8>(),
+                        (*output_info).nameLen as usize,
+                    );
+                    outputs.push((geometry, String::from_utf8_lossy(name).into_owned()));
+                    (xrandr.XRRFreeOutputInfo)(output_info);
+                }
+                (xrandr.XRRFreeCrtcInfo)(info);
+            }
+            (xrandr.XRRFreeScreenResources)(resources);
+        }
+        outputs
+    }
+
+    /// How long since the last keyboard or pointer input, to any window, as the `XScreenSaver`
+    /// extension counts it. `None` without the extension.
+    #[must_use]
+    pub fn idle_time(&self) -> Option<Duration> {
+        let xss = x11_dl::xss::Xss::open().ok()?;
+        unsafe {
+            let mut event_base = 0;
+            let mut error_base = 0;
+            if (xss.XScreenSaverQueryExtension)(self.display, &mut event_base, &mut error_base) == 0
+            {
+                return None;
+            }
+            let info = (xss.XScreenSaverAllocInfo)();
+            if info.is_null() {
+                return None;
+            }
+            let status = (xss.XScreenSaverQueryInfo)(self.display, self.get_default_root(), info);
+            let idle = Duration::from_millis((*info).idle);
+            (self.xlib.XFree)(info.cast());
+            if status == 0 {
+                return None;
+            }
+            Some(idle)
+        }
+    }
+
+    //asks `RandR` to tell when outputs are switched on or off
+    fn watch_outputs(&mut self) {
+        let xrandr = match x11_dl::xrandr::Xrandr::open() {
+            Ok(xrandr) => xrandr,
+            Err(_) => return,
+        };
+        let mut event_base = 0;
+        let mut error_base = 0;
+        unsafe {
+            if (xrandr.XRRQueryExtension)(self.display, &mut event_base, &mut error_base) == 0 {
+                return;
+            }
+            (xrandr.XRRSelectInput)(
+                self.display,
+                self.get_default_root(),
+                x11_dl::xrandr::RRScreenChangeNotifyMask | x11_dl::xrandr::RROutputChangeNotifyMask,
+            );
+        }
+        self.randr_event_base = Some(event_base);
+        self.outputs = self
+            .get_outputs()
+            .into_iter()
+            .map(|(_, name)| name)
+            .collect();
+    }
+
+    /// Whether the event is `RandR` telling that the outputs changed.
+    #[must_use]
+    pub fn is_output_change(&self, event: &xlib::XEvent) -> bool {
+        self.randr_event_base.map_or(false, |base| {
+            let type_ = event.get_type();
+            type_ == base + x11_dl::xrandr::RRScreenChangeNotify
+                || type_ == base + x11_dl::xrandr::RRNotify
+        })
+    }
+
+    /// The outputs switched off or back on since the last call, like by `xrandr --off`, once Xlib
+    /// is told of the `RandR` event. DPMS sleep is not seen, `RandR` keeps the outputs on through
+    /// it.
+    pub fn output_changes(&mut self, event: &mut xlib::XEvent) -> Vec<DisplayEvent> {
+        if let Ok(xrandr) = x11_dl::xrandr::Xrandr::open() {
+            //keeps the screen sizes Xlib knows of up to date
+            unsafe { (xrandr.XRRUpdateConfiguration)(event) };
+        }
+        let outputs: Vec<String> = self
+            .get_outputs()
+            .into_iter()
+            .map(|(_, name)| name)
+            .collect();
+        let off = self
+            .outputs
+            .iter()
+            .filter(|name| !outputs.contains(name))
+            .map(|name| DisplayEvent::OutputPower(name.clone(), false));
+        let on = outputs
+            .iter()
+            .filter(|name| !self.outputs.contains(name))
+            .map(|name| DisplayEvent::OutputPower(name.clone(), true));
+        let events = off.chain(on).collect();
+        self.outputs = outputs;
+        events
+    }
+
     //returns all the screens the display
     #[must_use]
     pub fn get_xscreens(&self) -> Vec<xlib::Screen> {
@@ -398,7 +611,7 @@ impl XWrap {
     }
 
     pub fn set_window_states_atoms(&self, window: xlib::Window, states: &[xlib::Atom]) {
-        let data: Vec<u32> = states.iter().map(|x| *x as u32).collect();
+        //Xlib takes format 32 properties as longs
         unsafe {
             (self.xlib.XChangeProperty)(
                 self.display,
@@ -407,10 +620,9 @@ impl XWrap {
                 xlib::XA_ATOM,
                 32,
                 xlib::PropModeReplace,
-                data.as_ptr().cast::<u8>(),
-                data.len() as i32,
+                states.as_ptr().cast::<u8>(),
+                states.len() as i32,
             );
-            std::mem::forget(data);
         }
     }
 
@@ -463,6 +675,7 @@ impl XWrap {
                 x if x == &self.atoms.NetWMStateFullscreen => WindowState::Fullscreen,
                 x if x == &self.atoms.NetWMStateAbove => WindowState::Above,
                 x if x == &self.atoms.NetWMStateBelow => WindowState::Below,
+                x if x == &self.atoms.NetWMStateDemandsAttention => WindowState::DemandsAttention,
                 _ => WindowState::Modal,
             })
             .collect()
@@ -472,9 +685,8 @@ impl XWrap {
     ///  # Panics
     ///
     ///  Panics if a new Cstring cannot be formed
-    pub fn init_desktops_hints(&self) {
-        let tags = &self.tags;
-        let tag_length = tags.len();
+    pub fn init_desktops_hints(&self, names: &[String]) {
+        let tag_length = names.len();
         //set the number of desktop
         let data = vec![tag_length as u32];
         self.set_desktop_prop(&data, self.atoms.NetNumberOfDesktops);
@@ -484,7 +696,7 @@ impl XWrap {
         //set desktop names
         let mut text: xlib::XTextProperty = unsafe { std::mem::zeroed() };
         unsafe {
-            let mut clist_tags: Vec<*mut c_char> = tags
+            let mut clist_tags: Vec<*mut c_char> = names
                 .iter()
                 .map(|x| CString::new(x.clone()).unwrap_or_default().into_raw())
                 .collect();
@@ -645,7 +857,7 @@ impl XWrap {
         }
     }
 
-    pub fn update_window(&self, window: &Window, is_focused: bool, hide_offset: i32) {
+    pub fn update_window(&self, window: &Window, is_focused: bool) {
         if let WindowHandle::XlibHandle(h) = window.handle {
             if window.visible() {
                 let mut changes = xlib::XWindowChanges {
@@ -680,21 +892,199 @@ impl XWrap {
 
                     (self.xlib.XSetWindowBorder)(self.display, h, color);
                 }
+                self.update_title_bar(window, is_focused);
                 if !is_focused && self.focus_behaviour == FocusBehaviour::ClickTo {
                     self.ungrab_buttons(h);
                     self.grab_buttons(h, xlib::Button1, xlib::AnyModifier);
                 }
                 self.send_config(window);
+                self.show_window(h);
             } else {
-                unsafe {
-                    //if not visible x is <---- way over there <----
-                    //(self.xlib.XMoveWindow)(self.display, h, window.width() * -2, window.y());
-                    (self.xlib.XMoveWindow)(self.display, h, hide_offset, window.y());
-                }
+                self.hide_window(h);
+                self.update_title_bar(window, false);
+            }
+        }
+    }
+
+    // Unmaps a window and marks it hidden, the way minimized windows are, so taskbars and
+    // compositors don't see it lingering somewhere off the screen.
+    fn hide_window(&self, window: xlib::Window) {
+        if self.is_hidden(window) {
+            return;
+        }
+        self.hidden_windows.borrow_mut().push(window);
+        self.set_wm_state(window, ICONIC_STATE);
+        let mut states = self.get_window_states_atoms(window);
+        states.push(self.atoms.NetWMStateHidden);
+        self.set_window_states_atoms(window, &states);
+        unsafe {
+            (self.xlib.XUnmapWindow)(self.display, window);
+            //the unmap has to be read while the window is still known to be hidden
+            (self.xlib.XSync)(self.display, 0);
+        }
+    }
+
+    fn show_window(&self, window: xlib::Window) {
+        if !self.is_hidden(window) {
+            return;
+        }
+        self.hidden_windows.borrow_mut().retain(|w| *w != window);
+        self.set_wm_state(window, NORMAL_STATE);
+        let mut states = self.get_window_states_atoms(window);
+        states.retain(|s| *s != self.atoms.NetWMStateHidden);
+        self.set_window_states_atoms(window, &states);
+        unsafe {
+            (self.xlib.XMapWindow)(self.display, window);
+        }
+    }
+
+    /// Whether leftwm unmapped the window to hide it.
+    #[must_use]
+    pub fn is_hidden(&self, window: xlib::Window) -> bool {
+        self.hidden_windows.borrow().contains(&window)
+    }
+
+    /// Keeps notifications that draw themselves over everything, such as dunst's, off the screen
+    /// while presentation mode is on. The ones held back are shown when it ends.
+    pub fn set_presenting(&mut self, presenting: bool) {
+        if presenting {
+            self.held_notifications
+                .get_mut()
+                .get_or_insert_with(Vec::new);
+            for window in self.get_all_windows().unwrap_or_default() {
+                self.hold_notification(window);
+            }
+            return;
+        }
+        for (window, _) in self.held_notifications.get_mut().take().unwrap_or_default() {
+            unsafe { (self.xlib.XMapWindow)(self.display, window) };
+        }
+    }
+
+    /// Unmaps the window if it is a notification shown while presentation mode is on, every time
+    /// it is mapped again: notification daemons such as dunst reuse one window.
+    pub fn hold_notification(&self, window: xlib::Window) {
+        if self.held_notifications.borrow().is_none() {
+            return;
+        }
+        let shown = matches!(
+            self.get_window_attrs(window),
+            Ok(attrs) if attrs.override_redirect > 0 && attrs.map_state == xlib::IsViewable
+        );
+        let type_ = self.get_atom_prop_value(window, self.atoms.NetWMWindowType);
+        if shown && type_ == Some(self.atoms.NetWMWindowTypeNotification) {
+            if let Some(held) = self.held_notifications.borrow_mut().as_mut() {
+                held.retain(|(w, _)| *w != window);
+                held.push((window, true));
+            }
+            unsafe { (self.xlib.XUnmapWindow)(self.display, window) };
+        }
+    }
+
+    /// Forgets a notification held back by presentation mode that was closed, or that its client
+    /// unmapped itself, so it isn't shown again when the mode ends. The unmap of holding it back
+    /// is passed over.
+    pub fn release_notification(&self, window: xlib::Window, destroyed: bool) {
+        if let Some(held) = self.held_notifications.borrow_mut().as_mut() {
+            match held.iter_mut().find(|(w, _)| *w == window) {
+                Some((_, unmapping)) if *unmapping && !destroyed => *unmapping = false,
+                Some(_) => held.retain(|(w, _)| *w != window),
+                None => {}
             }
         }
     }
 
+    fn set_wm_state(&self, window: xlib::Window, state: c_long) {
+        let data: [c_long; 2] = [state, 0];
+        unsafe {
+            (self.xlib.XChangeProperty)(
+                self.display,
+                window,
+                self.atoms.WMState,
+                self.atoms.WMState,
+                32,
+                xlib::PropModeReplace,
+                data.as_ptr().cast::<u8>(),
+                2,
+            );
+        }
+    }
+
+    /// Maps a window without managing it, it keeps the geometry it asks for and leftwm stops
+    /// listening to it.
+    pub fn map_unmanaged(&mut self, window: xlib::Window) {
+        if !self.unmanaged_windows.contains(&window) {
+            self.unmanaged_windows.push(window);
+        }
+        self.subscribe_to_event(window, xlib::NoEventMask);
+        unsafe {
+            (self.xlib.XMapRaised)(self.display, window);
+        }
+    }
+
+    /// Whether a window rule says to leave the window alone.
+    #[must_use]
+    pub fn is_unmanaged(&self, window: xlib::Window) -> bool {
+        self.unmanaged_windows.contains(&window)
+    }
+
+    /// Forgets a window leftwm left alone once it is destroyed.
+    pub fn forget_unmanaged(&mut self, window: xlib::Window) {
+        self.unmanaged_windows.retain(|w| *w != window);
+    }
+
+    /// Lets a window leftwm leaves alone move and resize as it asks.
+    pub fn configure_unmanaged(&self, event: &xlib::XConfigureRequestEvent) {
+        let mut changes = xlib::XWindowChanges {
+            x: event.x,
+            y: event.y,
+            width: event.width,
+            height: event.height,
+            border_width: event.border_width,
+            sibling: event.above,
+            stack_mode: event.detail,
+        };
+        unsafe {
+            (self.xlib.XConfigureWindow)(
+                self.display,
+                event.window,
+                event.value_mask as u32,
+                &mut changes,
+            );
+        }
+    }
+
+    /// Moves and resizes a managed window outside of its layout, and tells it where it ended up.
+    pub fn configure_window(&self, window: xlib::Window, xyhw: &Xyhw) {
+        let config = xlib::XConfigureEvent {
+            type_: xlib::ConfigureNotify,
+            serial: 0, //not used
+            send_event: 0,
+            display: self.display,
+            event: window,
+            window,
+            x: xyhw.x(),
+            y: xyhw.y(),
+            width: xyhw.w(),
+            height: xyhw.h(),
+            border_width: 0,
+            above: 0,
+            override_redirect: 0,
+        };
+        unsafe {
+            let (w, h) = (xyhw.w().max(1) as u32, xyhw.h().max(1) as u32);
+            (self.xlib.XMoveResizeWindow)(self.display, window, xyhw.x(), xyhw.y(), w, h);
+            let mut event: xlib::XEvent = xlib::XConfigureEvent::into(config);
+            (self.xlib.XSendEvent)(
+                self.display,
+                window,
+                0,
+                xlib::StructureNotifyMask,
+                &mut event,
+            );
+        }
+    }
+
     //this code is run once when a window is added to the managers list of windows
     pub fn setup_managed_window(
         &mut self,
@@ -705,7 +1095,11 @@ impl XWrap {
         if let WindowHandle::XlibHandle(handle) = h {
             self.managed_windows.push(handle);
 
-            //make sure the window is mapped
+            //make sure the window is mapped, and not left marked hidden by a previous wm or session
+            self.set_wm_state(handle, NORMAL_STATE);
+            let mut states = self.get_window_states_atoms(handle);
+            states.retain(|s| *s != self.atoms.NetWMStateHidden);
+            self.set_window_states_atoms(handle, &states);
             unsafe {
                 (self.xlib.XMapWindow)(self.display, handle);
             }
@@ -732,23 +1126,7 @@ impl XWrap {
 
             let type_ = self.get_window_type(handle);
             if type_ == WindowType::Dock || type_ == WindowType::Desktop {
-                if let Some(dock_area) = self.get_window_strut_array(handle) {
-                    let dems = self.screens_area_dimensions();
-                    let screen = self
-                        .get_screens()
-                        .iter()
-                        .find(|s| s.contains_dock_area(dock_area, dems))?
-                        .clone();
-
-                    if let Some(xyhw) = dock_area.as_xyhw(dems.0, dems.1, &screen) {
-                        let mut change = WindowChange::new(h);
-                        change.strut = Some(xyhw.into());
-                        change.type_ = Some(type_);
-                        return Some(DisplayEvent::WindowChange(change));
-                    }
-                } else if let Ok(geo) = self.get_window_geometry(handle) {
-                    let mut xyhw = Xyhw::default();
-                    geo.update(&mut xyhw);
+                if let Some(xyhw) = self.get_window_strut(handle) {
                     let mut change = WindowChange::new(h);
                     change.strut = Some(xyhw.into());
                     change.type_ = Some(type_);
@@ -762,21 +1140,31 @@ impl XWrap {
                     self.ungrab_buttons(handle);
                     self.grab_buttons(handle, xlib::Button1, xlib::AnyModifier);
                 }
+                if type_ == WindowType::Normal || type_ == WindowType::Dialog {
+                    self.create_title_bar(handle);
+                }
             }
-            //make sure there is at least an empty list of _NET_WM_STATE
-            let states = self.get_window_states_atoms(handle);
-            self.set_window_states_atoms(handle, &states);
         }
         None
     }
 
+    /// Grabs these mousebinds instead, on the focused window right away.
+    pub fn reset_mouse_grabs(&mut self, mousebinds: Vec<(ModMask, Button)>) {
+        self.mousebinds = mousebinds;
+        if let Some(window) = self.get_input_focus() {
+            if self.managed_windows.contains(&window) {
+                self.grab_mouse_clicks(window);
+            }
+        }
+    }
+
     fn grab_mouse_clicks(&self, handle: xlib::Window) {
         self.ungrab_buttons(handle);
         //just watchout for these mouse combos so we can act on them
-        self.grab_buttons(handle, xlib::Button1, self.mouse_key_mask);
-        self.grab_buttons(handle, xlib::Button1, self.mouse_key_mask | xlib::ShiftMask);
-        self.grab_buttons(handle, xlib::Button3, self.mouse_key_mask);
-        self.grab_buttons(handle, xlib::Button3, self.mouse_key_mask | xlib::ShiftMask);
+        for &(mask, button) in &self.mousebinds {
+            self.grab_buttons(handle, button, mask);
+            self.grab_buttons(handle, button, mask | xlib::ShiftMask);
+        }
     }
 
     fn ungrab_buttons(&self, handle: xlib::Window) {
@@ -829,6 +1217,23 @@ impl XWrap {
     ///
     /// Will error if root window cannot be found.
     pub fn get_cursor_point(&self) -> Result<(i32, i32), XlibError> {
+        self.query_pointer().map(|(point, _)| point)
+    }
+
+    /// Whether a mouse button is held down.
+    #[must_use]
+    pub fn is_button_held(&self) -> bool {
+        let buttons = xlib::Button1Mask
+            | xlib::Button2Mask
+            | xlib::Button3Mask
+            | xlib::Button4Mask
+            | xlib::Button5Mask;
+        self.query_pointer()
+            .map_or(false, |(_, mask)| mask & buttons != 0)
+    }
+
+    //the cursor position and the state of the modifiers and buttons
+    fn query_pointer(&self) -> Result<((i32, i32), c_uint), XlibError> {
         let roots = self.get_roots(); //each screen
         for w in roots {
             let mut root_return: xlib::Window = 0;
@@ -852,7 +1257,7 @@ impl XWrap {
                 )
             };
             if success > 0 {
-                return Ok((win_x_return, win_y_return));
+                return Ok(((win_x_return, win_y_return), mask_return));
             }
         }
         Err(XlibError::RootWindowNotFound)
@@ -869,6 +1274,31 @@ impl XWrap {
         (height, width)
     }
 
+    /// Returns the area a dock keeps free, in root coordinates, or the dock itself if it sets no
+    /// struts. Struts without a range, such as those of `_NET_WM_STRUT`, span the dock only, so
+    /// a bar on one monitor leaves the others alone.
+    #[must_use]
+    pub fn get_window_strut(&self, window: xlib::Window) -> Option<Xyhw> {
+        let geometry = self.get_window_geometry(window).ok().map(|geo| {
+            let mut xyhw = Xyhw::default();
+            geo.update(&mut xyhw);
+            xyhw
+        });
+        let mut dock_area = match self.get_window_strut_array(window) {
+            Some(dock_area) => dock_area,
+            None => return geometry,
+        };
+        if let Some(geometry) = &geometry {
+            dock_area = dock_area.with_ranges_of(geometry);
+        }
+        let dems = self.screens_area_dimensions();
+        let screen = self
+            .get_screens()
+            .into_iter()
+            .find(|s| s.contains_dock_area(dock_area, dems))?;
+        dock_area.as_xyhw(dems.0, dems.1, &screen)
+    }
+
     #[must_use]
     pub fn get_window_strut_array(&self, window: xlib::Window) -> Option<DockArea> {
         if let Some(d) = self.get_window_strut_array_strut_partial(window) {
@@ -918,6 +1348,53 @@ impl XWrap {
         }
     }
 
+    /// The invisible shadow a window drawing its own title bar has around it, from
+    /// `_GTK_FRAME_EXTENTS`. `None` if it has none.
+    #[must_use]
+    pub fn get_frame_extents(&self, window: xlib::Window) -> Option<Margins> {
+        let mut format_return: i32 = 0;
+        let mut nitems_return: c_ulong = 0;
+        let mut type_return: xlib::Atom = 0;
+        let mut bytes_after_return: c_ulong = 0;
+        let mut prop_return: *mut c_uchar = ptr::null_mut();
+        unsafe {
+            let status = (self.xlib.XGetWindowProperty)(
+                self.display,
+                window,
+                self.atoms.GTKFrameExtents,
+                0,
+                4,
+                xlib::False,
+                xlib::XA_CARDINAL,
+                &mut type_return,
+                &mut format_return,
+                &mut nitems_return,
+                &mut bytes_after_return,
+                &mut prop_return,
+            );
+            if status != i32::from(xlib::Success) || prop_return.is_null() {
+                return None;
+            }
+            #[allow(clippy::cast_ptr_alignment)]
+            let slice = slice::from_raw_parts(prop_return as *const c_long, nitems_return as usize);
+            //left, right, top, bottom
+            let extents: Vec<u32> = slice.iter().map(|&e| e.max(0) as u32).collect();
+            (self.xlib.XFree)(prop_return.cast());
+            match extents.as_slice() {
+                &[left, right, top, bottom]
+                    if left
+                        .saturating_add(right)
+                        .saturating_add(top)
+                        .saturating_add(bottom)
+                        > 0 =>
+                {
+                    Some(Margins::Vec(vec![top, right, bottom, left]))
+                }
+                _ => None,
+            }
+        }
+    }
+
     //old way to get strut
     fn get_window_strut_array_strut(&self, window: xlib::Window) -> Option<DockArea> {
         let mut format_return: i32 = 0;
@@ -944,8 +1421,14 @@ impl XWrap {
                 #[allow(clippy::cast_ptr_alignment)]
                 let array_ptr = prop_return as *const c_long;
                 let slice = slice::from_raw_parts(array_ptr, nitems_return as usize);
-                if slice.len() == 12 {
-                    return Some(DockArea::from(slice));
+                if slice.len() == 4 {
+                    return Some(DockArea {
+                        left: slice[0] as i32,
+                        right: slice[1] as i32,
+                        top: slice[2] as i32,
+                        bottom: slice[3] as i32,
+                        ..DockArea::default()
+                    });
                 }
                 None
             } else {
@@ -962,7 +1445,9 @@ impl XWrap {
 
                 //remove this window from the list of managed windows
                 self.managed_windows.retain(|x| *x != *handle);
+                self.hidden_windows.borrow_mut().retain(|w| *w != *handle);
                 self.update_client_list();
+                self.destroy_title_bar(*handle);
 
                 //ungrab all buttons for this window
                 (self.xlib.XUngrabButton)(
@@ -978,10 +1463,12 @@ impl XWrap {
     }
 
     pub fn force_unmapped(&mut self, window: xlib::Window) {
+        self.hidden_windows.borrow_mut().retain(|w| *w != window);
         let managed = self.managed_windows.contains(&window);
         if managed {
             self.managed_windows.retain(|x| *x != window);
             self.update_client_list();
+            self.destroy_title_bar(window);
         }
     }
 
@@ -1090,6 +1577,108 @@ impl XWrap {
         None
     }
 
+    /// Get the `_NET_WM_USER_TIME` of a window, the time of the last user activity in it. It is
+    /// read from the window `_NET_WM_USER_TIME_WINDOW` names, if the window has one.
+    #[must_use]
+    pub fn get_window_user_time(&self, window: xlib::Window) -> Option<u32> {
+        let time_window = self
+            .get_window_prop(window, self.atoms.NetWMUserTimeWindow)
+            .unwrap_or(window);
+        self.get_cardinal_prop(time_window, self.atoms.NetWMUserTime)
+            .ok()
+    }
+
+    //the window a property of type `WINDOW` names
+    fn get_window_prop(&self, window: xlib::Window, atom: xlib::Atom) -> Option<xlib::Window> {
+        let mut format_return: i32 = 0;
+        let mut nitems_return: c_ulong = 0;
+        let mut bytes_remaining: c_ulong = 0;
+        let mut type_return: xlib::Atom = 0;
+        let mut prop_return: *mut c_uchar = unsafe { std::mem::zeroed() };
+        unsafe {
+            let status = (self.xlib.XGetWindowProperty)(
+                self.display,
+                window,
+                atom,
+                0,
+                1,
+                xlib::False,
+                xlib::XA_WINDOW,
+                &mut type_return,
+                &mut format_return,
+                &mut nitems_return,
+                &mut bytes_remaining,
+                &mut prop_return,
+            );
+            if status != i32::from(xlib::Success) || prop_return.is_null() {
+                return None;
+            }
+            #[allow(clippy::cast_ptr_alignment)]
+            let found = if nitems_return > 0 {
+                Some(*prop_return.cast::<xlib::Window>())
+            } else {
+                None
+            };
+            (self.xlib.XFree)(prop_return.cast());
+            found.filter(|w| *w != 0)
+        }
+    }
+
+    /// Get the `_NET_WM_DESKTOP` of a window, the index of the tag it is on.
+    #[must_use]
+    pub fn get_window_desktop(&self, window: xlib::Window) -> Option<u32> {
+        self.get_cardinal_prop(window, self.atoms.NetWMDesktop).ok()
+    }
+
+    /// Whether the `WM_STATE` of a window says it is minimized, as window managers leave windows
+    /// they hid.
+    #[must_use]
+    pub fn is_iconic(&self, window: xlib::Window) -> bool {
+        let mut format_return: i32 = 0;
+        let mut nitems_return: c_ulong = 0;
+        let mut bytes_remaining: c_ulong = 0;
+        let mut type_return: xlib::Atom = 0;
+        let mut prop_return: *mut c_uchar = unsafe { std::mem::zeroed() };
+        unsafe {
+            let status = (self.xlib.XGetWindowProperty)(
+                self.display,
+                window,
+                self.atoms.WMState,
+                0,
+                2,
+                xlib::False,
+                self.atoms.WMState,
+                &mut type_return,
+                &mut format_return,
+                &mut nitems_return,
+                &mut bytes_remaining,
+                &mut prop_return,
+            );
+            if status == i32::from(xlib::Success) && !prop_return.is_null() && nitems_return > 0 {
+                #[allow(clippy::cast_ptr_alignment)]
+                let state = *(prop_return as *const c_long);
+                (self.xlib.XFree)(prop_return.cast());
+                return state == ICONIC_STATE;
+            }
+        }
+        false
+    }
+
+    /// Returns the window that has the input focus.
+    #[must_use]
+    pub fn get_input_focus(&self) -> Option<xlib::Window> {
+        let mut focused: xlib::Window = 0;
+        let mut revert_to: c_int = 0;
+        unsafe {
+            (self.xlib.XGetInputFocus)(self.display, &mut focused, &mut revert_to);
+        }
+        if focused == 0 {
+            None
+        } else {
+            Some(focused)
+        }
+    }
+
     /// Get the `WMPid` of a window
     /// # Errors
     ///
@@ -1127,6 +1716,31 @@ impl XWrap {
         Err(XlibError::FailedStatus)
     }
 
+    /// Returns the class part of the `WM_CLASS` of a window.
+    #[must_use]
+    pub fn get_window_class(&self, window: xlib::Window) -> Option<String> {
+        unsafe {
+            let mut class_hint: xlib::XClassHint = std::mem::zeroed();
+            if (self.xlib.XGetClassHint)(self.display, window, &mut class_hint) == 0 {
+                return None;
+            }
+            let class = if class_hint.res_class.is_null() {
+                None
+            } else {
+                CStr::from_ptr(class_hint.res_class)
+                    .to_str()
+                    .ok()
+                    .map(ToOwned::to_owned)
+            };
+            for ptr in [class_hint.res_name, class_hint.res_class] {
+                if !ptr.is_null() {
+                    (self.xlib.XFree)(ptr.cast());
+                }
+            }
+            class
+        }
+    }
+
     #[must_use]
     pub fn get_window_name(&self, window: xlib::Window) -> Option<String> {
         if let Ok(text) = self.get_text_prop(window, self.atoms.NetWMName) {
@@ -1192,6 +1806,10 @@ impl XWrap {
         let mut windows = vec![];
         for handle in handles {
             if let WindowHandle::XlibHandle(window) = handle {
+                //keep title bars directly above their window
+                if let Some(bar) = self.get_title_bar(window) {
+                    windows.push(bar);
+                }
                 windows.push(window);
             }
         }
@@ -1202,11 +1820,513 @@ impl XWrap {
         }
     }
 
+    /// Raises the windows above the other managed ones and stacks them in the given order, first
+    /// top most. The windows not managed, like menus and notifications, stay above them.
+    pub fn restack_on_top(&self, handles: Vec<WindowHandle>) {
+        if let Some(WindowHandle::XlibHandle(top)) = handles.first() {
+            //restacking leaves the first window where it is, its title bar if it has one
+            let top = self.get_title_bar(*top).unwrap_or(*top);
+            unsafe {
+                (self.xlib.XRaiseWindow)(self.display, top);
+            }
+        }
+        self.restack(handles);
+        //put the unmanaged windows back over them, bottom most first to keep their order
+        let unmanaged = self.get_all_windows().unwrap_or_default().into_iter().filter(|w| {
+            !self.managed_windows.contains(w)
+                && self.get_title_bar_owner(*w).is_none()
+                && !self.focus_marks.borrow().contains(w)
+                && matches!(self.get_window_attrs(*w), Ok(attrs) if attrs.map_state == xlib::IsViewable)
+        });
+        for window in unmanaged {
+            unsafe {
+                (self.xlib.XRaiseWindow)(self.display, window);
+            }
+        }
+    }
+
     pub fn move_to_top(&self, handle: &WindowHandle) {
         if let WindowHandle::XlibHandle(window) = handle {
             unsafe {
                 (self.xlib.XRaiseWindow)(self.display, *window);
+                if let Some(bar) = self.get_title_bar(*window) {
+                    (self.xlib.XRaiseWindow)(self.display, bar);
+                }
+            }
+        }
+    }
+
+    fn create_title_bar(&mut self, window: xlib::Window) {
+        if self.title_bar.is_none() || self.get_title_bar(window).is_some() {
+            return;
+        }
+        unsafe {
+            let mut attrs: xlib::XSetWindowAttributes = std::mem::zeroed();
+            attrs.override_redirect = xlib::True;
+            attrs.event_mask = xlib::ButtonPressMask | xlib::ButtonReleaseMask | xlib::ExposureMask;
+            attrs.cursor = self.cursors.normal;
+            let bar = (self.xlib.XCreateWindow)(
+                self.display,
+                self.root,
+                0,
+                0,
+                1,
+                1,
+                0,
+                xlib::CopyFromParent,
+                xlib::InputOutput as c_uint,
+                ptr::null_mut(),
+                xlib::CWOverrideRedirect | xlib::CWEventMask | xlib::CWCursor,
+                &mut attrs,
+            );
+            self.title_bars.push((window, bar));
+        }
+    }
+
+    fn destroy_title_bar(&mut self, window: xlib::Window) {
+        if let Some(bar) = self.get_title_bar(window) {
+            unsafe {
+                (self.xlib.XDestroyWindow)(self.display, bar);
+            }
+            self.title_bars.retain(|(w, _)| *w != window);
+        }
+    }
+
+    fn get_title_bar(&self, window: xlib::Window) -> Option<xlib::Window> {
+        self.title_bars
+            .iter()
+            .find(|(w, _)| *w == window)
+            .map(|(_, bar)| *bar)
+    }
+
+    /// Returns the window a title bar belongs to.
+    #[must_use]
+    pub fn get_title_bar_owner(&self, bar: xlib::Window) -> Option<xlib::Window> {
+        self.title_bars
+            .iter()
+            .find(|(_, b)| *b == bar)
+            .map(|(w, _)| *w)
+    }
+
+    /// Returns true if the point on a title bar is over its close button.
+    #[must_use]
+    pub fn is_close_button(&self, bar: xlib::Window, x: i32) -> bool {
+        match (&self.title_bar, self.get_window_attrs(bar)) {
+            (Some(theme), Ok(attrs)) => x >= attrs.width - theme.height as i32,
+            _ => false,
+        }
+    }
+
+    //only floating windows get a title bar, it sits right above the window
+    fn update_title_bar(&self, window: &Window, is_focused: bool) {
+        let (theme, handle) = match (&self.title_bar, window.handle) {
+            (Some(theme), WindowHandle::XlibHandle(h)) => (theme, h),
+            _ => return,
+        };
+        let bar = match self.get_title_bar(handle) {
+            Some(bar) => bar,
+            None => return,
+        };
+        unsafe {
+            if !window.visible() || !window.floating() || window.is_fullscreen() {
+                (self.xlib.XUnmapWindow)(self.display, bar);
+                return;
+            }
+            let width = (window.width() + window.border() * 2).max(1) as u32;
+            let y = window.y() - theme.height as i32;
+            (self.xlib.XMoveResizeWindow)(self.display, bar, window.x(), y, width, theme.height);
+            let mut changes = xlib::XWindowChanges {
+                x: 0,
+                y: 0,
+                width: 0,
+                height: 0,
+                border_width: 0,
+                sibling: handle,
+                stack_mode: xlib::Above,
+            };
+            let unlock = xlib::CWSibling | xlib::CWStackMode;
+            (self.xlib.XConfigureWindow)(self.display, bar, u32::from(unlock), &mut changes);
+            (self.xlib.XMapWindow)(self.display, bar);
+        }
+        let title = window.name.as_deref().unwrap_or_default();
+        self.draw_title_bar(bar, title, is_focused);
+    }
+
+    /// Redraws the title bar of a window, used when the name of the window has changed.
+    pub fn redraw_title_bar_of(&self, window: xlib::Window) {
+        if let Some(bar) = self.get_title_bar(window) {
+            self.redraw_title_bar(bar);
+        }
+    }
+
+    /// Redraws a title bar, used when it has been exposed.
+    pub fn redraw_title_bar(&self, bar: xlib::Window) {
+        if let Some(window) = self.get_title_bar_owner(bar) {
+            let title = self.get_window_name(window).unwrap_or_default();
+            self.draw_title_bar(bar, &title, self.get_input_focus() == Some(window));
+        }
+    }
+
+    fn draw_title_bar(&self, bar: xlib::Window, title: &str, is_focused: bool) {
+        let theme = match &self.title_bar {
+            Some(theme) => theme,
+            None => return,
+        };
+        let width = match self.get_window_attrs(bar) {
+            Ok(attrs) => attrs.width,
+            Err(_) => return,
+        };
+        let height = theme.height as i32;
+        let padding = height / 4;
+        let background = if is_focused {
+            theme.active
+        } else {
+            theme.normal
+        };
+        unsafe {
+            let gc = (self.xlib.XCreateGC)(self.display, bar, 0, ptr::null_mut());
+            (self.xlib.XSetForeground)(self.display, gc, background);
+            (self.xlib.XFillRectangle)(self.display, bar, gc, 0, 0, width as u32, height as u32);
+            (self.xlib.XSetForeground)(self.display, gc, theme.text);
+
+            //the close button is a cross in a square on the right
+            let left = width - height + padding;
+            let right = width - padding;
+            (self.xlib.XDrawLine)(
+                self.display,
+                bar,
+                gc,
+                left,
+                padding,
+                right,
+                height - padding,
+            );
+            (self.xlib.XDrawLine)(
+                self.display,
+                bar,
+                gc,
+                left,
+                height - padding,
+                right,
+                padding,
+            );
+
+            //shorten the title until it fits next to the close button
+            let font = (self.xlib.XQueryFont)(self.display, (self.xlib.XGContextFromGC)(gc));
+            if !font.is_null() {
+                let available = width - height - padding * 2;
+                //whole characters are taken off, never part of one
+                let mut text = title.to_owned();
+                while !text.is_empty()
+                    && (self.xlib.XTextWidth)(font, text.as_ptr().cast(), text.len() as c_int)
+                        > available
+                {
+                    text.pop();
+                }
+                let baseline = (height + (*font).ascent - (*font).descent) / 2;
+                (self.xlib.XDrawString)(
+                    self.display,
+                    bar,
+                    gc,
+                    padding,
+                    baseline,
+                    text.as_ptr().cast(),
+                    text.len() as c_int,
+                );
+                (self.xlib.XFreeFontInfo)(ptr::null_mut(), font, 1);
+            }
+            (self.xlib.XFreeGC)(self.display, gc);
+        }
+    }
+
+    /// Shows a popup with the lines in the middle of the screen the pointer is on, and grabs the
+    /// keyboard so that the next key press closes it.
+    pub fn show_popup(&mut self, lines: Vec<String>) {
+        self.close_popup();
+        let (x, y) = self.get_cursor_point().unwrap_or_default();
+        let screens = self.get_screens();
+        let screen = match screens.iter().find(|s| s.contains_point(x, y)) {
+            Some(screen) => screen.bbox,
+            None => match screens.first() {
+                Some(screen) => screen.bbox,
+                None => return,
+            },
+        };
+        unsafe {
+            let gc = (self.xlib.XCreateGC)(self.display, self.root, 0, ptr::null_mut());
+            let font = (self.xlib.XQueryFont)(self.display, (self.xlib.XGContextFromGC)(gc));
+            (self.xlib.XFreeGC)(self.display, gc);
+            if font.is_null() {
+                return;
+            }
+            let line_height = (*font).ascent + (*font).descent;
+            let text_width = lines
+                .iter()
+                .map(|line| {
+                    let text = CString::new(line.as_str()).unwrap_or_default();
+                    let text = text.as_bytes();
+                    (self.xlib.XTextWidth)(font, text.as_ptr().cast(), text.len() as c_int)
+                })
+                .max()
+                .unwrap_or_default();
+            (self.xlib.XFreeFontInfo)(ptr::null_mut(), font, 1);
+
+            let padding = line_height;
+            let width = (text_width + padding * 2).min(screen.width).max(1);
+            let height = (line_height * lines.len() as i32 + padding * 2)
+                .min(screen.height)
+                .max(1);
+            let mut attrs: xlib::XSetWindowAttributes = std::mem::zeroed();
+            attrs.override_redirect = xlib::True;
+            attrs.event_mask = xlib::ExposureMask;
+            attrs.background_pixel = (self.xlib.XBlackPixel)(self.display, 0);
+            attrs.border_pixel = self.colors.active;
+            let popup = (self.xlib.XCreateWindow)(
+                self.display,
+                self.root,
+                screen.x + (screen.width - width) / 2,
+                screen.y + (screen.height - height) / 2,
+                width as u32,
+                height as u32,
+                2,
+                xlib::CopyFromParent,
+                xlib::InputOutput as c_uint,
+                ptr::null_mut(),
+                xlib::CWOverrideRedirect
+                    | xlib::CWEventMask
+                    | xlib::CWBackPixel
+                    | xlib::CWBorderPixel,
+                &mut attrs,
+            );
+            (self.xlib.XMapRaised)(self.display, popup);
+            (self.xlib.XGrabKeyboard)(
+                self.display,
+                self.root,
+                xlib::False,
+                xlib::GrabModeAsync,
+                xlib::GrabModeAsync,
+                xlib::CurrentTime,
+            );
+            self.popup = Some((popup, lines));
+        }
+    }
+
+    /// Closes the popup and gives the keyboard back. Returns false if it wasn't shown.
+    pub fn close_popup(&mut self) -> bool {
+        match self.popup.take() {
+            Some((popup, _)) => {
+                unsafe {
+                    (self.xlib.XUngrabKeyboard)(self.display, xlib::CurrentTime);
+                    (self.xlib.XDestroyWindow)(self.display, popup);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Redraws the popup, used when it has been exposed.
+    pub fn redraw_popup(&self, window: xlib::Window) {
+        let (popup, lines) = match &self.popup {
+            Some((popup, lines)) if *popup == window => (*popup, lines),
+            _ => return,
+        };
+        unsafe {
+            let gc = (self.xlib.XCreateGC)(self.display, popup, 0, ptr::null_mut());
+            (self.xlib.XSetForeground)(self.display, gc, (self.xlib.XWhitePixel)(self.display, 0));
+            let font = (self.xlib.XQueryFont)(self.display, (self.xlib.XGContextFromGC)(gc));
+            if !font.is_null() {
+                let line_height = (*font).ascent + (*font).descent;
+                for (i, line) in lines.iter().enumerate() {
+                    let text = CString::new(line.as_str()).unwrap_or_default();
+                    let text = text.as_bytes();
+                    (self.xlib.XDrawString)(
+                        self.display,
+                        popup,
+                        gc,
+                        line_height,
+                        line_height + (*font).ascent + line_height * i as i32,
+                        text.as_ptr().cast(),
+                        text.len() as c_int,
+                    );
+                }
+                (self.xlib.XFreeFontInfo)(ptr::null_mut(), font, 1);
+            }
+            (self.xlib.XFreeGC)(self.display, gc);
+        }
+    }
+
+    /// Outlines the box with four thin windows in the active border color, or hides the outline.
+    pub fn draw_outline(&mut self, xyhw: Option<Xyhw>) {
+        const WIDTH: i32 = 2;
+        let xyhw = if let Some(xyhw) = xyhw {
+            xyhw
+        } else {
+            for edge in self.outline.take().iter().flatten() {
+                unsafe { (self.xlib.XDestroyWindow)(self.display, *edge) };
+            }
+            return;
+        };
+        if self.outline.is_none() {
+            let color = self.colors.active;
+            self.outline = Some([
+                self.create_outline_edge(color),
+                self.create_outline_edge(color),
+                self.create_outline_edge(color),
+                self.create_outline_edge(color),
+            ]);
+        }
+        let edges = self.outline.unwrap_or_default();
+        let (x, y) = (xyhw.x(), xyhw.y());
+        let (w, h) = (xyhw.w().max(WIDTH * 2), xyhw.h().max(WIDTH * 2));
+        let rects = [
+            (x, y, w, WIDTH),
+            (x, y + h - WIDTH, w, WIDTH),
+            (x, y, WIDTH, h),
+            (x + w - WIDTH, y, WIDTH, h),
+        ];
+        for (edge, (x, y, w, h)) in edges.iter().zip(&rects) {
+            unsafe {
+                (self.xlib.XMoveResizeWindow)(self.display, *edge, *x, *y, *w as u32, *h as u32);
+                (self.xlib.XMapRaised)(self.display, *edge);
+            }
+        }
+    }
+
+    /// Draws the theme's focus indicator over the edges of the window, or hides it if there is
+    /// no focused window or it is not shown. Fullscreen windows are left unmarked.
+    pub fn draw_focus_indicator(&self, window: Option<&Window>) {
+        let (indicator, color) = match &self.focus_indicator {
+            Some((indicator, color)) => (indicator, *color),
+            None => return,
+        };
+        let (rects, below) = match window {
+            Some(window) if window.visible() && !window.is_fullscreen() => {
+                let border = window.border() * 2;
+                let (w, h) = (window.width() + border, window.height() + border);
+                let below = match window.handle {
+                    WindowHandle::XlibHandle(h) => self.get_title_bar(h).unwrap_or(h),
+                    WindowHandle::MockHandle(_) => 0,
+                };
+                (indicator.rects(window.x(), window.y(), w, h), below)
+            }
+            _ => (vec![], 0),
+        };
+        let mut marks = self.focus_marks.borrow_mut();
+        while marks.len() < rects.len() {
+            let mark = self.create_outline_edge(color);
+            self.pass_input_through(mark);
+            marks.push(mark);
+        }
+        for (index, mark) in marks.iter().enumerate() {
+            unsafe {
+                match rects.get(index) {
+                    Some(&(x, y, w, h)) => {
+                        //just above the focused window, not over the windows above it
+                        let mut changes = xlib::XWindowChanges {
+                            x,
+                            y,
+                            width: w,
+                            height: h,
+                            border_width: 0,
+                            sibling: below,
+                            stack_mode: xlib::Above,
+                        };
+                        let mask = xlib::CWX
+                            | xlib::CWY
+                            | xlib::CWWidth
+                            | xlib::CWHeight
+                            | xlib::CWSibling
+                            | xlib::CWStackMode;
+                        (self.xlib.XConfigureWindow)(
+                            self.display,
+                            *mark,
+                            u32::from(mask),
+                            &mut changes,
+                        );
+                        (self.xlib.XMapWindow)(self.display, *mark);
+                    }
+                    None => {
+                        (self.xlib.XUnmapWindow)(self.display, *mark);
+                    }
+                }
+            }
+        }
+    }
+
+    //gives the window an empty input shape, so clicks on it go to the window under it
+    fn pass_input_through(&self, window: xlib::Window) {
+        type QueryExtension =
+            unsafe extern "C" fn(*mut xlib::Display, *mut c_int, *mut c_int) -> c_int;
+        type CombineRectangles = unsafe extern "C" fn(
+            *mut xlib::Display,
+            xlib::Window,
+            c_int,
+            c_int,
+            c_int,
+            *mut xlib::XRectangle,
+            c_int,
+            c_int,
+            c_int,
+        );
+        //ShapeInput, ShapeSet and Unsorted of X11/extensions/shape.h
+        const SHAPE_INPUT: c_int = 2;
+        const SHAPE_SET: c_int = 0;
+        const UNSORTED: c_int = 0;
+        // SAFETY: the symbols are the ones of libXext, cast to the signatures of shape.h. The
+        // library stays loaded, like the ones of x11-dl.
+        unsafe {
+            let lib = libc::dlopen(
+                b"libXext.so.6\0".as_ptr().cast(),
+                libc::RTLD_NOW | libc::RTLD_LOCAL,
+            );
+            if lib.is_null() {
+                return;
+            }
+            let query = libc::dlsym(lib, b"XShapeQueryExtension\0".as_ptr().cast());
+            let combine = libc::dlsym(lib, b"XShapeCombineRectangles\0".as_ptr().cast());
+            if query.is_null() || combine.is_null() {
+                return;
+            }
+            let query: QueryExtension = std::mem::transmute(query);
+            let combine: CombineRectangles = std::mem::transmute(combine);
+            let (mut event_base, mut error_base) = (0, 0);
+            if query(self.display, &mut event_base, &mut error_base) == 0 {
+                return;
             }
+            combine(
+                self.display,
+                window,
+                SHAPE_INPUT,
+                0,
+                0,
+                ptr::null_mut(),
+                0,
+                SHAPE_SET,
+                UNSORTED,
+            );
+        }
+    }
+
+    fn create_outline_edge(&self, color: c_ulong) -> xlib::Window {
+        unsafe {
+            let mut attrs: xlib::XSetWindowAttributes = std::mem::zeroed();
+            attrs.override_redirect = xlib::True;
+            attrs.background_pixel = color;
+            (self.xlib.XCreateWindow)(
+                self.display,
+                self.root,
+                0,
+                0,
+                1,
+                1,
+                0,
+                xlib::CopyFromParent,
+                xlib::InputOutput as c_uint,
+                ptr::null_mut(),
+                xlib::CWOverrideRedirect | xlib::CWBackPixel,
+                &mut attrs,
+            )
         }
     }
 
@@ -1247,11 +2367,12 @@ impl XWrap {
         })
     }
 
-    pub fn window_take_focus(&self, window: &Window) {
-        if let WindowHandle::XlibHandle(handle) = window.handle {
+    pub fn window_take_focus(&self, handle: WindowHandle, never_focus: bool) {
+        if let WindowHandle::XlibHandle(handle) = handle {
             self.grab_mouse_clicks(handle);
 
-            if !window.never_focus {
+            //a window taking no input only gets `WM_TAKE_FOCUS`, it sets the focus itself
+            if !never_focus && self.get_input_hint(handle) != Some(false) {
                 //mark this window as the NetActiveWindow
                 unsafe {
                     (self.xlib.XSetInputFocus)(
@@ -1275,6 +2396,13 @@ impl XWrap {
                 }
             }
 
+            //the window has our attention now
+            let mut states = self.get_window_states_atoms(handle);
+            if states.contains(&self.atoms.NetWMStateDemandsAttention) {
+                states.retain(|x| x != &self.atoms.NetWMStateDemandsAttention);
+                self.set_window_states_atoms(handle, &states);
+            }
+
             //tell the window to take focus
             self.send_xevent_atom(handle, self.atoms.WMTakeFocus);
         }
@@ -1313,6 +2441,13 @@ impl XWrap {
         }
     }
 
+    /// Asks the window to close, without killing its client if it can't be asked.
+    pub fn close_window(&self, h: &WindowHandle) {
+        if let WindowHandle::XlibHandle(handle) = h {
+            self.send_xevent_atom(*handle, self.atoms.WMDelete);
+        }
+    }
+
     pub fn subscribe_to_event(&self, window: xlib::Window, mask: c_long) {
         unsafe {
             (self.xlib.XSelectInput)(self.display, window, mask);
@@ -1341,6 +2476,29 @@ impl XWrap {
         }
     }
 
+    /// The ICCCM input hint of the window, whether it wants the input focus set on it. None if it
+    /// gives none.
+    #[must_use]
+    pub fn get_input_hint(&self, window: xlib::Window) -> Option<bool> {
+        let hints = self.get_wmhints(window)?;
+        if hints.flags & xlib::InputHint == 0 {
+            return None;
+        }
+        Some(hints.input != 0)
+    }
+
+    /// Whether the window sets the urgency hint of `WM_HINTS`, asking for the user's attention.
+    #[must_use]
+    pub fn get_urgency_hint(&self, window: xlib::Window) -> bool {
+        matches!(self.get_wmhints(window), Some(hints) if hints.flags & xlib::XUrgencyHint != 0)
+    }
+
+    /// Whether the window can be asked to take the focus itself, by `WM_TAKE_FOCUS`.
+    #[must_use]
+    pub fn can_take_focus(&self, window: xlib::Window) -> bool {
+        self.can_send_xevent_atom(window, self.atoms.WMTakeFocus)
+    }
+
     #[must_use]
     pub fn get_hint_sizing(&self, window: xlib::Window) -> Option<xlib::XSizeHints> {
         let mut xsize: xlib::XSizeHints = unsafe { std::mem::zeroed() };
@@ -1353,6 +2511,30 @@ impl XWrap {
         }
     }
 
+    /// Returns the position and size a window was mapped with, if the program or the user asked
+    /// for them in its size hints.
+    #[must_use]
+    pub fn get_requested_geometry(&self, window: xlib::Window) -> Option<XyhwChange> {
+        let hint = self.get_hint_sizing(window)?;
+        let geo = self.get_window_geometry(window).ok()?;
+        let user_position = hint.flags & xlib::USPosition != 0;
+        //most toolkits set PPosition without caring where the window goes, leaving it at 0,0
+        let program_position =
+            hint.flags & xlib::PPosition != 0 && (geo.x, geo.y) != (Some(0), Some(0));
+        let position = user_position || program_position;
+        let size = hint.flags & (xlib::USSize | xlib::PSize) != 0;
+        if !position && !size {
+            return None;
+        }
+        Some(XyhwChange {
+            x: geo.x.filter(|_| position),
+            y: geo.y.filter(|_| position),
+            w: geo.w.filter(|_| size),
+            h: geo.h.filter(|_| size),
+            ..XyhwChange::default()
+        })
+    }
+
     #[must_use]
     pub fn get_hint_sizing_as_xyhw(&self, window: xlib::Window) -> Option<XyhwChange> {
         let hint = self.get_hint_sizing(window);
@@ -1450,6 +2632,47 @@ impl XWrap {
             floating: self.get_color(&theme.floating_border_color),
             active: self.get_color(&theme.focused_border_color),
         };
+        self.title_bar = theme.title_bar_height.map(|height| TitleBarTheme {
+            height,
+            normal: self.get_color(
+                theme
+                    .title_bar_color
+                    .as_ref()
+                    .unwrap_or(&theme.default_border_color),
+            ),
+            active: self.get_color(
+                theme
+                    .title_bar_focused_color
+                    .as_ref()
+                    .unwrap_or(&theme.focused_border_color),
+            ),
+            text: self.get_color(theme.title_bar_text_color.as_deref().unwrap_or("#FFFFFF")),
+        });
+        //windows managed before title bars were turned on get theirs, and lose them when turned off
+        if self.title_bar.is_some() {
+            for window in self.managed_windows.clone() {
+                let type_ = self.get_window_type(window);
+                if type_ == WindowType::Normal || type_ == WindowType::Dialog {
+                    self.create_title_bar(window);
+                }
+            }
+        } else {
+            for (window, _) in self.title_bars.clone() {
+                self.destroy_title_bar(window);
+            }
+        }
+        //the marks are made again in the new color when next drawn
+        for mark in self.focus_marks.get_mut().drain(..) {
+            unsafe { (self.xlib.XDestroyWindow)(self.display, mark) };
+        }
+        self.focus_indicator = theme.focus_indicator.clone().map(|indicator| {
+            let color = indicator
+                .color
+                .as_ref()
+                .unwrap_or(&theme.focused_border_color);
+            let color = self.get_color(color);
+            (indicator, color)
+        });
     }
 
     fn get_color(&self, color: &str) -> c_ulong {
@@ -1494,7 +2717,11 @@ impl XWrap {
 
         //EWMH junk
         unsafe {
-            let supported = self.atoms.net_supported();
+            let mut supported = self.atoms.net_supported();
+            //GTK only draws shadows around its own title bars if the WM knows to leave them out
+            if !config.csd_shadows() {
+                supported.retain(|&atom| atom != self.atoms.GTKFrameExtents);
+            }
             let supported_ptr: *const xlib::Atom = supported.as_ptr();
             let size = supported.len() as i32;
             (self.xlib.XChangeProperty)(
@@ -1512,17 +2739,49 @@ impl XWrap {
             (self.xlib.XDeleteProperty)(self.display, root, self.atoms.NetClientList);
         }
 
+        self.watch_outputs();
+
         //EWMH stuff for desktops
-        self.tags = config.create_list_of_tags();
-        self.init_desktops_hints();
+        let tags: Vec<Tag> = config
+            .create_list_of_tags()
+            .iter()
+            .map(|t| Tag::from_config(t))
+            .collect();
+        self.tags = tags.iter().map(|t| t.id.clone()).collect();
+        let names: Vec<String> = tags.iter().map(|t| t.display_name().to_owned()).collect();
+        self.init_desktops_hints(&names);
 
         self.reset_grabs(&config.mapped_bindings());
 
+        #[cfg(feature = "systray")]
+        if let Some(systray) = config.systray() {
+            self.start_systray(&systray);
+        }
+        #[cfg(not(feature = "systray"))]
+        if config.systray().is_some() {
+            log::warn!("`systray` is set but leftwm was built without the `systray` feature");
+        }
+
+        //held keys repeat as presses without releases in between
+        unsafe {
+            (self.xlib.XkbSetDetectableAutoRepeat)(self.display, xlib::True, ptr::null_mut());
+        }
+
         unsafe {
             (self.xlib.XSync)(self.display, 0);
         }
     }
 
+    /// Keeps track of the held key, to tell presses from X repeating them.
+    pub fn track_key(&mut self, event: &xlib::XKeyEvent) {
+        if event.type_ == xlib::KeyPress {
+            self.key_repeated = self.held_key == Some(event.keycode);
+            self.held_key = Some(event.keycode);
+        } else if self.held_key == Some(event.keycode) {
+            self.held_key = None;
+        }
+    }
+
     /// Cleans first all old keygrabs and then reaplies them from the config
     pub fn reset_grabs(&self, keybinds: &[Keybind]) {
         //cleanup grabs
@@ -1597,13 +2856,13 @@ impl XWrap {
         }
     }
 
-    pub fn replay_click(&self, mod_mask: ModMask) {
+    pub fn replay_click(&self, mod_mask: ModMask, button: Button) {
         // Only replay the click when in ClickToFocus and we are not trying to move/resize the
         // window
-        if self.focus_behaviour == FocusBehaviour::ClickTo
-            && !(mod_mask == self.mouse_key_mask
-                || mod_mask == (self.mouse_key_mask | xlib::ShiftMask))
-        {
+        let is_mousebind = self.mousebinds.iter().any(|&(mask, b)| {
+            b == button && (mod_mask == mask || mod_mask == (mask | xlib::ShiftMask))
+        });
+        if self.focus_behaviour == FocusBehaviour::ClickTo && !is_mousebind {
             unsafe {
                 (self.xlib.XAllowEvents)(self.display, xlib::ReplayPointer, xlib::CurrentTime);
                 (self.xlib.XSync)(self.display, 0);
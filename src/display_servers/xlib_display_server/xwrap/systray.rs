@@ -0,0 +1,493 @@
+//! The system tray built into leftwm. Programs dock their icons in it by the `XEmbed` protocol, as
+//! the freedesktop system tray spec describes. The tray itself is a dock like any bar, so the
+//! space it takes is kept free the same way.
+use super::XWrap;
+use crate::config;
+use crate::models::{BBox, Side, WindowHandle, WindowState, Xyhw, XyhwBuilder};
+use std::ffi::CString;
+use std::os::raw::{c_long, c_uchar, c_uint, c_ulong};
+use std::ptr;
+use x11_dl::xlib;
+
+const SYSTEM_TRAY_REQUEST_DOCK: c_long = 0;
+const SYSTEM_TRAY_ORIENTATION_HORZ: c_long = 0;
+const XEMBED_EMBEDDED_NOTIFY: c_long = 0;
+const XEMBED_MAPPED: c_long = 1;
+
+/// The tray window and the icons docked in it, from right to left.
+pub struct Systray {
+    window: xlib::Window,
+    selection: xlib::Atom,
+    opcode: xlib::Atom,
+    xembed: xlib::Atom,
+    xembed_info: xlib::Atom,
+    icons: Vec<xlib::Window>,
+    config: config::Systray,
+    //the row of the screen kept free, once laid out
+    area: Option<Xyhw>,
+}
+
+/// Where the tray goes, where its icons go in it and the space it keeps free.
+#[derive(Debug, PartialEq)]
+struct Layout {
+    x: i32,
+    y: i32,
+    width: i32,
+    size: i32,
+    //the left of each icon in the tray, from right to left
+    icons: Vec<i32>,
+    //left, right, top, bottom and the start and end of each, as `_NET_WM_STRUT_PARTIAL` has them
+    strut: [c_long; 12],
+    area: Xyhw,
+}
+
+// Lines the icons up from the right end of the screen's edge.
+fn layout(screen: &BBox, config: &config::Systray, icons: usize, screens_height: i32) -> Layout {
+    let size = config.icon_size.max(1) as i32;
+    let width = size * icons.max(1) as i32;
+    let x = screen.x + screen.width - width;
+    let y = match config.side {
+        Side::Bottom => screen.y + screen.height - size,
+        _ => screen.y,
+    };
+    let mut strut: [c_long; 12] = [0; 12];
+    if config.side == Side::Bottom {
+        strut[3] = c_long::from(screens_height - screen.y - screen.height + size);
+        strut[10] = c_long::from(x);
+        strut[11] = c_long::from(x + width - 1);
+    } else {
+        strut[2] = c_long::from(screen.y + size);
+        strut[8] = c_long::from(x);
+        strut[9] = c_long::from(x + width - 1);
+    }
+    //the whole row, windows aren't tiled beside the tray and a workspace is trimmed by the
+    //longer side of what it avoids
+    let area = XyhwBuilder {
+        x: screen.x,
+        y,
+        w: screen.width,
+        h: size,
+        ..XyhwBuilder::default()
+    }
+    .into();
+    Layout {
+        x,
+        y,
+        width,
+        size,
+        icons: (1..=icons as i32).map(|i| width - size * i).collect(),
+        strut,
+        area,
+    }
+}
+
+impl XWrap {
+    /// Opens the tray at the edge of its screen, takes the tray selection and tells the programs
+    /// waiting for a tray that it is there. Does nothing if another tray is running.
+    pub fn start_systray(&mut self, config: &config::Systray) {
+        let screen = unsafe { (self.xlib.XDefaultScreen)(self.display) };
+        let selection = self.intern_atom(&format!("_NET_SYSTEM_TRAY_S{}", screen));
+        if unsafe { (self.xlib.XGetSelectionOwner)(self.display, selection) } != 0 {
+            log::warn!("Another system tray is running, leftwm's own is not started");
+            return;
+        }
+        let window = unsafe {
+            let mut attrs: xlib::XSetWindowAttributes = std::mem::zeroed();
+            attrs.background_pixel = self.get_color(&config.background_color);
+            (self.xlib.XCreateWindow)(
+                self.display,
+                self.root,
+                0,
+                0,
+                config.icon_size.max(1),
+                config.icon_size.max(1),
+                0,
+                xlib::CopyFromParent,
+                xlib::InputOutput as c_uint,
+                ptr::null_mut(),
+                xlib::CWBackPixel,
+                &mut attrs,
+            )
+        };
+        let orientation = self.intern_atom("_NET_SYSTEM_TRAY_ORIENTATION");
+        self.set_window_longs(
+            window,
+            orientation,
+            xlib::XA_CARDINAL,
+            &[SYSTEM_TRAY_ORIENTATION_HORZ],
+        );
+        let dock = self.atoms.NetWMWindowTypeDock as c_long;
+        self.set_window_longs(window, self.atoms.NetWMWindowType, xlib::XA_ATOM, &[dock]);
+        if let Ok(name) = CString::new("leftwm-systray") {
+            unsafe { (self.xlib.XStoreName)(self.display, window, name.as_ptr()) };
+        }
+        self.systray = Some(Systray {
+            window,
+            selection,
+            opcode: self.intern_atom("_NET_SYSTEM_TRAY_OPCODE"),
+            xembed: self.intern_atom("_XEMBED"),
+            xembed_info: self.intern_atom("_XEMBED_INFO"),
+            icons: vec![],
+            config: config.clone(),
+            area: None,
+        });
+        self.layout_systray();
+
+        unsafe {
+            (self.xlib.XMapWindow)(self.display, window);
+            (self.xlib.XSetSelectionOwner)(self.display, selection, window, xlib::CurrentTime);
+            if (self.xlib.XGetSelectionOwner)(self.display, selection) != window {
+                log::error!("Couldn't take the system tray selection");
+                self.stop_systray();
+                return;
+            }
+            let manager = self.intern_atom("MANAGER");
+            let data = [
+                xlib::CurrentTime as c_long,
+                selection as c_long,
+                window as c_long,
+            ];
+            self.send_client_message(self.root, manager, &data, xlib::StructureNotifyMask);
+            (self.xlib.XSync)(self.display, xlib::False);
+        }
+    }
+
+    /// Gives the icons back to the root and closes the tray.
+    fn stop_systray(&mut self) {
+        let tray = match self.systray.take() {
+            Some(tray) => tray,
+            None => return,
+        };
+        unsafe {
+            for icon in tray.icons {
+                (self.xlib.XUnmapWindow)(self.display, icon);
+                (self.xlib.XReparentWindow)(self.display, icon, self.root, 0, 0);
+                (self.xlib.XRemoveFromSaveSet)(self.display, icon);
+            }
+            (self.xlib.XDestroyWindow)(self.display, tray.window);
+        }
+    }
+
+    /// Handles the dock requests sent to the tray and the events of its icons. Returns true if
+    /// the event was one of those, which are no business of the rest of leftwm.
+    pub fn systray_event(&mut self, event: &xlib::XEvent) -> bool {
+        let tray = match &self.systray {
+            Some(tray) => tray,
+            None => return false,
+        };
+        match event.get_type() {
+            xlib::ClientMessage => {
+                let event = xlib::XClientMessageEvent::from(*event);
+                if event.window != tray.window || event.message_type != tray.opcode {
+                    return false;
+                }
+                if event.data.get_long(1) == SYSTEM_TRAY_REQUEST_DOCK {
+                    self.dock_icon(event.data.get_long(2) as xlib::Window);
+                }
+                true
+            }
+            xlib::SelectionClear => {
+                let event = xlib::XSelectionClearEvent::from(*event);
+                if event.selection != tray.selection {
+                    return false;
+                }
+                log::warn!("Another system tray took over");
+                self.stop_systray();
+                true
+            }
+            xlib::DestroyNotify => {
+                let event = xlib::XDestroyWindowEvent::from(*event);
+                self.undock_icon(event.window)
+            }
+            xlib::ReparentNotify => {
+                let event = xlib::XReparentEvent::from(*event);
+                if event.parent == tray.window {
+                    return tray.icons.contains(&event.window);
+                }
+                self.undock_icon(event.window)
+            }
+            xlib::PropertyNotify => {
+                let event = xlib::XPropertyEvent::from(*event);
+                if !tray.icons.contains(&event.window) {
+                    return false;
+                }
+                if event.atom == tray.xembed_info {
+                    self.map_icon(event.window);
+                }
+                true
+            }
+            xlib::UnmapNotify | xlib::MapNotify | xlib::ConfigureNotify => {
+                let window = unsafe { event.any.window };
+                tray.icons.contains(&window)
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the window is the icon of a tray, such as one the previous worker had docked.
+    /// These dock in the tray again instead of being managed.
+    #[must_use]
+    pub fn is_systray_icon(&self, window: xlib::Window) -> bool {
+        match &self.systray {
+            Some(tray) => self.xembed_flags(tray.xembed_info, window).is_some(),
+            None => false,
+        }
+    }
+
+    /// Moves the tray to the edge of its screen, as wide as its icons, and keeps the space it
+    /// takes free. An empty tray keeps the space of one icon.
+    pub fn layout_systray(&mut self) {
+        let screens = self.get_screens();
+        let (screens_height, _) = self.screens_area_dimensions();
+        let tray = match &mut self.systray {
+            Some(tray) => tray,
+            None => return,
+        };
+        let screen = match screens.get(tray.config.screen).or_else(|| screens.first()) {
+            Some(screen) => screen.bbox,
+            None => return,
+        };
+        let layout = layout(&screen, &tray.config, tray.icons.len(), screens_height);
+        tray.area = Some(layout.area);
+        let (window, size) = (tray.window, layout.size as c_uint);
+        unsafe {
+            (self.xlib.XMoveResizeWindow)(
+                self.display,
+                window,
+                layout.x,
+                layout.y,
+                layout.width as c_uint,
+                size,
+            );
+            for (icon, icon_x) in tray.icons.iter().zip(&layout.icons) {
+                (self.xlib.XMoveResizeWindow)(self.display, *icon, *icon_x, 0, size, size);
+            }
+        }
+        let partial = self.atoms.NetWMStrutPartial;
+        self.set_window_longs(window, partial, xlib::XA_CARDINAL, &layout.strut);
+    }
+
+    /// The row of its screen the tray keeps free, until it is closed.
+    #[must_use]
+    pub fn systray_area(&self) -> Option<Xyhw> {
+        self.systray.as_ref().and_then(|tray| tray.area)
+    }
+
+    /// Raises the tray above a window restacked over its row, unless that window is fullscreen.
+    pub fn raise_systray(&self, top: Option<WindowHandle>) {
+        let (window, area) = match &self.systray {
+            Some(Systray {
+                window,
+                area: Some(area),
+                ..
+            }) => (*window, *area),
+            _ => return,
+        };
+        let top = match top {
+            Some(WindowHandle::XlibHandle(top)) => top,
+            _ => return,
+        };
+        let mut top_xyhw = Xyhw::default();
+        match self.get_window_geometry(top) {
+            Ok(geometry) => geometry.update(&mut top_xyhw),
+            Err(_) => return,
+        };
+        if top_xyhw.overlap_volume(&area) == 0
+            || self
+                .get_window_states(top)
+                .contains(&WindowState::Fullscreen)
+        {
+            return;
+        }
+        unsafe { (self.xlib.XRaiseWindow)(self.display, window) };
+    }
+
+    fn dock_icon(&mut self, icon: xlib::Window) {
+        let tray = match &mut self.systray {
+            Some(tray) if icon != 0 && !tray.icons.contains(&icon) => tray,
+            _ => return,
+        };
+        tray.icons.push(icon);
+        let (window, xembed) = (tray.window, tray.xembed);
+        unsafe {
+            let mask = xlib::StructureNotifyMask | xlib::PropertyChangeMask;
+            (self.xlib.XSelectInput)(self.display, icon, mask);
+            (self.xlib.XAddToSaveSet)(self.display, icon);
+            (self.xlib.XReparentWindow)(self.display, icon, window, 0, 0);
+        }
+        let data = [
+            xlib::CurrentTime as c_long,
+            XEMBED_EMBEDDED_NOTIFY,
+            0,
+            window as c_long,
+            0,
+        ];
+        self.send_client_message(icon, xembed, &data, xlib::NoEventMask);
+        self.layout_systray();
+        self.map_icon(icon);
+    }
+
+    // Forgets the icon, returns false if it wasn't one.
+    fn undock_icon(&mut self, icon: xlib::Window) -> bool {
+        let tray = match &mut self.systray {
+            Some(tray) => tray,
+            None => return false,
+        };
+        let index = match tray.icons.iter().position(|i| *i == icon) {
+            Some(index) => index,
+            None => return false,
+        };
+        tray.icons.remove(index);
+        self.layout_systray();
+        true
+    }
+
+    // Maps or unmaps the icon as its `_XEMBED_INFO` asks, icons without it are mapped.
+    fn map_icon(&self, icon: xlib::Window) {
+        let xembed_info = match &self.systray {
+            Some(tray) => tray.xembed_info,
+            None => return,
+        };
+        let mapped = self
+            .xembed_flags(xembed_info, icon)
+            .map_or(true, |flags| flags & XEMBED_MAPPED != 0);
+        unsafe {
+            if mapped {
+                (self.xlib.XMapRaised)(self.display, icon);
+            } else {
+                (self.xlib.XUnmapWindow)(self.display, icon);
+            }
+        }
+    }
+
+    // The flags of the window's `_XEMBED_INFO`, which follow its version.
+    fn xembed_flags(&self, xembed_info: xlib::Atom, window: xlib::Window) -> Option<c_long> {
+        let mut type_return: xlib::Atom = 0;
+        let mut format_return: i32 = 0;
+        let mut nitems_return: c_ulong = 0;
+        let mut bytes_after_return: c_ulong = 0;
+        let mut prop_return: *mut c_uchar = ptr::null_mut();
+        unsafe {
+            let status = (self.xlib.XGetWindowProperty)(
+                self.display,
+                window,
+                xembed_info,
+                0,
+                2,
+                xlib::False,
+                xlib::AnyPropertyType as xlib::Atom,
+                &mut type_return,
+                &mut format_return,
+                &mut nitems_return,
+                &mut bytes_after_return,
+                &mut prop_return,
+            );
+            if prop_return.is_null() {
+                return None;
+            }
+            #[allow(clippy::cast_ptr_alignment)]
+            let values = prop_return as *const c_long;
+            let flags = if status == i32::from(xlib::Success) && nitems_return >= 2 {
+                Some(*values.add(1))
+            } else {
+                None
+            };
+            (self.xlib.XFree)(prop_return.cast());
+            flags
+        }
+    }
+
+    fn intern_atom(&self, name: &str) -> xlib::Atom {
+        let name = CString::new(name).unwrap_or_default();
+        unsafe { (self.xlib.XInternAtom)(self.display, name.as_ptr(), xlib::False) }
+    }
+
+    fn set_window_longs(
+        &self,
+        window: xlib::Window,
+        atom: xlib::Atom,
+        type_: xlib::Atom,
+        data: &[c_long],
+    ) {
+        unsafe {
+            (self.xlib.XChangeProperty)(
+                self.display,
+                window,
+                atom,
+                type_,
+                32,
+                xlib::PropModeReplace,
+                data.as_ptr().cast::<u8>(),
+                data.len() as i32,
+            );
+        }
+    }
+
+    fn send_client_message(
+        &self,
+        window: xlib::Window,
+        message_type: xlib::Atom,
+        data: &[c_long],
+        mask: c_long,
+    ) {
+        let mut msg: xlib::XClientMessageEvent = unsafe { std::mem::zeroed() };
+        msg.type_ = xlib::ClientMessage;
+        msg.window = window;
+        msg.message_type = message_type;
+        msg.format = 32;
+        for (i, value) in data.iter().enumerate() {
+            msg.data.set_long(i, *value);
+        }
+        let mut event: xlib::XEvent = msg.into();
+        unsafe { (self.xlib.XSendEvent)(self.display, window, xlib::False, mask, &mut event) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: BBox = BBox {
+        x: 1920,
+        y: 0,
+        width: 1280,
+        height: 1024,
+    };
+
+    #[test]
+    fn icons_should_line_up_from_the_right_end_of_the_top_edge() {
+        let config = config::Systray::default();
+        let layout = layout(&SCREEN, &config, 3, 1080);
+        assert_eq!(
+            (layout.x, layout.y, layout.width, layout.size),
+            (3140, 0, 60, 20)
+        );
+        assert_eq!(layout.icons, vec![40, 20, 0]);
+        assert_eq!(layout.strut[2], 20);
+        assert_eq!((layout.strut[8], layout.strut[9]), (3140, 3199));
+        let area: Xyhw = XyhwBuilder {
+            x: 1920,
+            w: 1280,
+            h: 20,
+            ..XyhwBuilder::default()
+        }
+        .into();
+        assert_eq!(layout.area, area);
+    }
+
+    #[test]
+    fn an_empty_tray_should_keep_the_space_of_an_icon_at_the_bottom() {
+        let config = config::Systray {
+            side: Side::Bottom,
+            icon_size: 24,
+            ..config::Systray::default()
+        };
+        let layout = layout(&SCREEN, &config, 0, 1080);
+        assert_eq!((layout.x, layout.y, layout.width), (3176, 1000, 24));
+        assert!(layout.icons.is_empty());
+        //the strut counts from the bottom of all screens, 56 pixels below this one
+        assert_eq!(layout.strut[3], 80);
+        assert_eq!((layout.strut[10], layout.strut[11]), (3176, 3199));
+        assert_eq!((layout.area.y(), layout.area.h()), (1000, 24));
+    }
+}
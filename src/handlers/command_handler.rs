@@ -6,15 +6,19 @@
 // https://github.com/rust-lang/rust-clippy/issues/6563
 
 use super::*;
+use crate::config::{Config, ExecOptions, Keybind, TagDisplayBehavior};
 use crate::display_action::DisplayAction;
 use crate::layouts::Layout;
-use crate::models::TagId;
+use crate::models::FocusBehaviour;
+use crate::models::{Margins, Presentation, Snapshot, SplitDirection, Tag, TagId, UndoEntry};
 use crate::state::State;
-use crate::utils::{child_process::exec_shell, helpers};
-use crate::{config::Config, models::FocusBehaviour};
+use crate::utils::child_process::{exec_shell, exec_shell_with};
+use crate::utils::{self, helpers};
+use regex::Regex;
+use std::convert::TryFrom;
 use std::str::FromStr;
 
-/* Please also update src/bin/leftwm-check if any of the following apply after your update:
+/* Please also update `requires_value` in src/config/validation.rs if any of the following apply after your update:
  * - a command now requires a value
  * - a command no longer requires a value
  * - a new command is introduced that requires a value
@@ -26,7 +30,96 @@ pub fn process(
     command: &Command,
     val: &Option<String>,
 ) -> bool {
-    process_internal(manager, state, config, command, val).unwrap_or(false)
+    process_internal(
+        manager,
+        state,
+        config,
+        command,
+        val,
+        &ExecOptions::default(),
+    )
+    .unwrap_or(false)
+}
+
+/// Processes the command of a keybind, `Execute` starts the program with the environment and
+/// directory of the keybind.
+pub fn process_keybind(
+    manager: &mut Manager,
+    state: &impl State,
+    config: &impl Config,
+    keybind: &Keybind,
+) -> bool {
+    let command = &keybind.command;
+    process_internal(
+        manager,
+        state,
+        config,
+        command,
+        &keybind.value,
+        &keybind.exec,
+    )
+    .unwrap_or(false)
+}
+
+/// Runs the command on two headless managers with the configured tags, one with a single screen
+/// and one with two, both with two windows. Returns false if it changed nothing on either, the
+/// command would most likely do nothing for real either.
+/// Commands that reach outside of the manager, such as `Execute` or `SaveSnapshot`, are not run
+/// and count as doing something, as is `SetWindowTag` whose regex can't match the made up windows.
+pub fn dry_run(config: &impl Config, command: &Command, val: &Option<String>) -> bool {
+    if reaches_outside(command) || *command == Command::SetWindowTag {
+        return true;
+    }
+    (1..=2).any(|screens| {
+        let mut manager = Manager::new_test();
+        manager.tags = config
+            .create_list_of_tags()
+            .iter()
+            .map(|t| Tag::from_config(t))
+            .collect();
+        for i in 0..screens {
+            let mut screen = Screen::default();
+            screen.bbox.x = i * screen.bbox.width;
+            screen_create_handler::process(&mut manager, screen);
+        }
+        for i in 1..=2 {
+            let window = Window::new(WindowHandle::MockHandle(i), None, Some(i as u32));
+            window_handler::created(&mut manager, window, -1, -1);
+        }
+        let tags: Vec<TagId> = manager.tags.iter().map(|t| t.id.clone()).collect();
+        for window in &mut manager.windows {
+            window.tags = tags.clone();
+        }
+        manager.actions.clear();
+
+        let before = serde_json::to_value(&manager).ok();
+        let exec = ExecOptions::default();
+        process_internal(&mut manager, &DryRunState, config, command, val, &exec);
+        before != serde_json::to_value(&manager).ok()
+    })
+}
+
+/// Commands that start programs or read and write files, which a dry run must not do.
+const fn reaches_outside(command: &Command) -> bool {
+    matches!(
+        command,
+        Command::Execute
+            | Command::ToggleScratchPad
+            | Command::LockScreen
+            | Command::SoftReload
+            | Command::HardReload
+            | Command::SaveSnapshot
+            | Command::LoadSnapshot
+    )
+}
+
+struct DryRunState;
+
+impl State for DryRunState {
+    fn save(&self, _manager: &Manager) -> crate::errors::Result<()> {
+        Ok(())
+    }
+    fn load(&self, _manager: &mut Manager) {}
 }
 
 /// Processes a command and invokes the associated function.
@@ -36,40 +129,61 @@ pub fn process_internal(
     config: &impl Config,
     command: &Command,
     val: &Option<String>,
+    exec: &ExecOptions,
 ) -> Option<bool> {
-    match command {
-        Command::Execute => execute(manager, val),
+    log::debug!("Command: {:?} {:?}", command, val);
+    let undo = if is_reversible(command) {
+        Some(UndoEntry::new(manager))
+    } else {
+        None
+    };
+    let result = match command {
+        Command::Execute => execute(manager, val, exec),
 
         Command::ToggleScratchPad => toggle_scratchpad(manager, val),
+        Command::AttachToScratchPad => attach_to_scratchpad(manager, val),
+        Command::ReleaseScratchPad => release_scratchpad(manager),
 
         Command::ToggleFullScreen => toggle_fullscreen(manager),
+        Command::ToggleFakeFullscreen => toggle_fake_fullscreen(manager),
 
         Command::MoveToTag => move_to_tag(val, manager),
+        Command::MoveToTagAndFollow => move_to_tag_and_follow(val, manager),
+        Command::MoveFloatingToNextTag => move_floating_to_next_tag(manager),
+        Command::ToggleWindowTag => toggle_window_tag(manager, val),
+        Command::SetWindowTag => set_window_tag(manager, val),
+
+        Command::GroupWindow => group_window(manager),
+        Command::UngroupWindow => ungroup_window(manager),
+        Command::CycleGroup => cycle_group(manager),
 
         Command::MoveWindowUp => move_focus_common_vars(move_window_change, manager, -1),
         Command::MoveWindowDown => move_focus_common_vars(move_window_change, manager, 1),
         Command::MoveWindowTop => move_focus_common_vars(move_window_top, manager, 0),
+        Command::ZoomWindow => move_focus_common_vars(zoom_window, manager, 0),
 
         Command::GotoTag => goto_tag(manager, val, config),
+        Command::PeekTag => peek_tag(manager, val),
+        Command::ToggleTagView => toggle_tag_view(manager, val),
 
         Command::CloseWindow => close_window(manager),
-        Command::SwapTags => swap_tags(manager),
+        Command::SwapTags => swap_tags(manager, val),
         Command::MoveToLastWorkspace => move_to_last_workspace(manager),
         Command::NextLayout => next_layout(manager),
         Command::PreviousLayout => previous_layout(manager),
 
-        Command::SetLayout => set_layout(val, manager),
+        Command::SetLayout => set_layout(val, manager, config),
 
         Command::FloatingToTile => floating_to_tile(manager),
 
-        Command::FocusNextTag => focus_tag_change(manager, 1),
-        Command::FocusPreviousTag => focus_tag_change(manager, -1),
+        Command::FocusNextTag => focus_tag_change(manager, config, 1),
+        Command::FocusPreviousTag => focus_tag_change(manager, config, -1),
         Command::FocusWindowUp => move_focus_common_vars(focus_window_change, manager, -1),
         Command::FocusWindowDown => move_focus_common_vars(focus_window_change, manager, 1),
-        Command::FocusWorkspaceNext => focus_workspace_change(manager, 1),
-        Command::FocusWorkspacePrevious => focus_workspace_change(manager, -1),
+        Command::FocusWorkspaceNext => focus_workspace_change(manager, 1, val),
+        Command::FocusWorkspacePrevious => focus_workspace_change(manager, -1, val),
 
-        Command::MouseMoveWindow => None,
+        Command::MouseMoveWindow | Command::MouseResizeWindow => None,
 
         Command::SoftReload => {
             if let Err(err) = state.save(manager) {
@@ -85,25 +199,77 @@ pub fn process_internal(
 
         Command::RotateTag => rotate_tag(manager),
 
+        Command::ToggleTiling => toggle_tiling(manager),
+
         Command::IncreaseMainWidth => change_main_width(manager, val, 1),
         Command::DecreaseMainWidth => change_main_width(manager, val, -1),
+        Command::SetMainWidth => set_main_width(manager, val),
         Command::SetMarginMultiplier => set_margin_multiplier(manager, val),
+        Command::SetModKey => set_modkey(manager, config, val),
+        Command::SetMouseKey => set_mousekey(manager, config, val),
+        Command::UndoWindowOperation => Some(manager.undo()),
+        Command::LockScreen => lock_screen(manager, config),
+        Command::HideDock => set_docks_hidden(manager, Some(true)),
+        Command::ShowDock => set_docks_hidden(manager, Some(false)),
+        Command::ToggleDock => set_docks_hidden(manager, None),
+        Command::ShowKeybindHelp => {
+            show_keybind_help(manager, config);
+            Some(false)
+        }
+        Command::TogglePresentationMode => toggle_presentation_mode(manager, val),
+        Command::SaveSnapshot => save_snapshot(manager, val),
+        Command::LoadSnapshot => load_snapshot(manager, val),
+        Command::SplitHorizontal => split_container(manager, Some(SplitDirection::Horizontal)),
+        Command::SplitVertical => split_container(manager, Some(SplitDirection::Vertical)),
+        Command::FocusParent => split_container(manager, None),
+    };
+    //commands that got as far as doing something return a value
+    if let (Some(entry), Some(_)) = (undo, result) {
+        manager.record_undo(entry);
+    }
+    manager.metrics.commands_processed += 1;
+    let layout_changed = matches!(
+        command,
+        Command::NextLayout | Command::PreviousLayout | Command::SetLayout
+    );
+    if layout_changed && result.is_some() {
+        manager.metrics.layout_changes += 1;
     }
+    result
 }
 
-fn execute(manager: &mut Manager, val: &Option<String>) -> Option<bool> {
-    let _ = exec_shell(val.as_ref()?, manager);
+/// Commands that move a window to other tags, float or tile windows, swap them or change the
+/// layout, which `UndoWindowOperation` can revert.
+const fn is_reversible(command: &Command) -> bool {
+    matches!(
+        command,
+        Command::MoveToTag
+            | Command::MoveToTagAndFollow
+            | Command::MoveFloatingToNextTag
+            | Command::ToggleWindowTag
+            | Command::ToggleTiling
+            | Command::FloatingToTile
+            | Command::MoveWindowUp
+            | Command::MoveWindowDown
+            | Command::MoveWindowTop
+            | Command::ZoomWindow
+            | Command::NextLayout
+            | Command::PreviousLayout
+            | Command::SetLayout
+            | Command::IncreaseMainWidth
+            | Command::DecreaseMainWidth
+            | Command::SetMainWidth
+    )
+}
+
+fn execute(manager: &mut Manager, val: &Option<String>, exec: &ExecOptions) -> Option<bool> {
+    let _ = exec_shell_with(val.as_ref()?, exec, manager);
     None
 }
 
 fn toggle_scratchpad(manager: &mut Manager, val: &Option<String>) -> Option<bool> {
     let name = val.clone()?;
     let tag = &manager.focused_tag(0)?;
-    let s = manager
-        .scratchpads
-        .iter()
-        .find(|s| name == s.name.clone())?
-        .clone();
 
     let mut handle = None;
     if let Some(ws) = manager.focused_workspace() {
@@ -114,8 +280,8 @@ fn toggle_scratchpad(manager: &mut Manager, val: &Option<String>) -> Option<bool
             .map(|w| w.handle);
     }
 
-    if let Some(id) = manager.active_scratchpads.get(&s.name) {
-        if let Some(w) = manager.windows.iter_mut().find(|w| w.pid == *id) {
+    if let Some(found) = scratchpad_window(manager, &name) {
+        if let Some(w) = manager.windows.iter_mut().find(|w| w.handle == found) {
             let is_tagged = w.has_tag(tag);
             w.clear_tags();
             if is_tagged {
@@ -140,12 +306,75 @@ fn toggle_scratchpad(manager: &mut Manager, val: &Option<String>) -> Option<bool
             return Some(true);
         }
     }
-    let name = s.name.clone();
-    let pid = exec_shell(&s.value, manager);
+    let s = manager
+        .scratchpads
+        .iter()
+        .find(|s| name == s.name.clone())?
+        .clone();
+    let pid = exec_shell_with(&s.value, &s.exec, manager);
     manager.active_scratchpads.insert(name, pid);
     None
 }
 
+//the window attached to the scratchpad, or else the first of the process it started
+fn scratchpad_window(manager: &Manager, name: &str) -> Option<WindowHandle> {
+    if let Some(handle) = manager.attached_scratchpads.get(name) {
+        return Some(*handle);
+    }
+    let id = manager.active_scratchpads.get(name)?;
+    manager
+        .windows
+        .iter()
+        .find(|w| w.pid == *id)
+        .map(|w| w.handle)
+}
+
+fn attach_to_scratchpad(manager: &mut Manager, val: &Option<String>) -> Option<bool> {
+    let name = val.clone()?;
+    let handle = manager.focused_window()?.handle;
+    if scratchpad_window(manager, &name).is_some() {
+        log::warn!("Scratchpad {} already has a window", name);
+        return None;
+    }
+    let ws = manager.focused_workspace()?.clone();
+    let next = window_handler::get_next_or_previous(manager, &handle);
+
+    //hide it like a toggled off scratchpad
+    let window = manager.focused_window_mut()?;
+    window.clear_tags();
+    window.tag("NSP");
+    window.set_floating(true);
+    window.normal = ws.xyhw;
+    window.set_floating_exact(ws.center_halfed());
+    let act = DisplayAction::SetWindowTags(handle, "NSP".to_owned());
+    manager.actions.push_back(act);
+    //the window is found by its handle, windows of one process are told apart
+    manager.active_scratchpads.remove(&name);
+    manager.attached_scratchpads.insert(name, handle);
+
+    manager.sort_windows();
+    if let Some(h) = next {
+        handle_focus(manager, h);
+    }
+    Some(true)
+}
+
+fn release_scratchpad(manager: &mut Manager) -> Option<bool> {
+    let handle = manager.focused_window()?.handle;
+    let names: Vec<String> = manager
+        .attached_scratchpads
+        .keys()
+        .chain(manager.active_scratchpads.keys())
+        .filter(|name| scratchpad_window(manager, name) == Some(handle))
+        .cloned()
+        .collect();
+    for name in &names {
+        manager.attached_scratchpads.remove(name);
+        manager.active_scratchpads.remove(name);
+    }
+    Some(!names.is_empty())
+}
+
 fn toggle_fullscreen(manager: &mut Manager) -> Option<bool> {
     let window = manager.focused_window_mut()?;
     let handle = window.handle;
@@ -154,9 +383,24 @@ fn toggle_fullscreen(manager: &mut Manager) -> Option<bool> {
     Some(handle_focus(manager, handle))
 }
 
+fn toggle_fake_fullscreen(manager: &mut Manager) -> Option<bool> {
+    let window = manager.focused_window_mut()?;
+    let handle = window.handle;
+    let act = window.toggle_fake_fullscreen();
+    manager.actions.push_back(act);
+    Some(handle_focus(manager, handle))
+}
+
+/// Moves the focused window to a tag, given by its number, id or name or as `next`, `previous` or `last`, the
+/// tag focused before the current one.
 fn move_to_tag(val: &Option<String>, manager: &mut Manager) -> Option<bool> {
-    let tag_num: usize = val.as_ref()?.parse().ok()?;
-    let tag = manager.tags.get(tag_num - 1)?.clone();
+    let tag_num: usize = match val.as_deref()? {
+        "next" => relative_tag(manager, 1, true, false)?,
+        "previous" => relative_tag(manager, -1, true, false)?,
+        "last" => manager.tag_number(&manager.focused_tag(1)?)?,
+        tag => manager.tag_number_of(tag)?,
+    };
+    let tag = manager.numbered_tag(tag_num)?.clone();
 
     // In order to apply the correct margin multiplier we want to copy this value
     // from any window already present on the target tag
@@ -184,29 +428,273 @@ fn move_to_tag(val: &Option<String>, manager: &mut Manager) -> Option<bool> {
     Some(true)
 }
 
+/// Moves the focused window like `MoveToTag`, then shows its new tag and keeps it focused.
+fn move_to_tag_and_follow(val: &Option<String>, manager: &mut Manager) -> Option<bool> {
+    let handle = manager.focused_window()?.handle;
+    move_to_tag(val, manager)?;
+    let window = manager.windows.iter().find(|w| w.handle == handle)?;
+    let tag_num = manager.tag_number(window.tags.first()?)?;
+    goto_tag_handler::process(manager, tag_num);
+    focus_handler::focus_window(manager, &handle);
+    Some(true)
+}
+
+/// Moves the focused floating window to the next tag, at the same place relative to the workspace
+/// showing that tag as it had on its own, scaled if the workspaces differ in size.
+fn move_floating_to_next_tag(manager: &mut Manager) -> Option<bool> {
+    let window = manager.focused_window()?;
+    if !window.floating() {
+        return None;
+    }
+    let handle = window.handle;
+    let from = manager.focused_workspace()?.xyhw;
+    let tag_num = relative_tag(manager, 1, true, false)?;
+    let tag = manager.numbered_tag(tag_num)?.id.clone();
+    //a tag that isn't shown will be on this workspace when it is
+    let to = manager
+        .workspaces
+        .iter()
+        .find(|ws| ws.has_tag(&tag))
+        .map_or(from, |ws| ws.xyhw);
+    let new_handle = window_handler::get_next_or_previous(manager, &handle);
+
+    let window = manager.focused_window_mut()?;
+    let xyhw = window.calculated_xyhw().moved_between(from, to);
+    window.clear_tags();
+    window.tag(&tag);
+    window.set_floating_exact(xyhw);
+    let act = DisplayAction::SetWindowTags(handle, tag);
+    manager.actions.push_back(act);
+
+    manager.sort_windows();
+    if let Some(new_handle) = new_handle {
+        focus_handler::focus_window(manager, &new_handle);
+    }
+    Some(true)
+}
+
+/// Adds the tag to the focused window, or removes it if the window has other tags.
+fn toggle_window_tag(manager: &mut Manager, val: &Option<String>) -> Option<bool> {
+    let tag_num = manager.tag_number_of(val.as_ref()?)?;
+    let tag = manager.numbered_tag(tag_num)?.id.clone();
+    let handle = manager.focused_window()?.handle;
+    //Focus the next or previous window on the workspace, in case this one leaves it
+    let new_handle = window_handler::get_next_or_previous(manager, &handle);
+
+    let window = manager.focused_window_mut()?;
+    if !window.has_tag(&tag) {
+        window.tag(&tag);
+    } else if window.tags.len() > 1 {
+        window.untag(&tag);
+    } else {
+        return None;
+    }
+    let window = window.clone();
+    let act = DisplayAction::SetWindowTags(handle, window.tags[0].clone());
+    manager.actions.push_back(act);
+
+    let visible = matches!(manager.focused_workspace(), Some(ws) if ws.is_displaying(&window));
+    if !visible {
+        if let Some(new_handle) = new_handle {
+            focus_handler::focus_window(manager, &new_handle);
+        }
+    }
+    Some(true)
+}
+
+/// Moves every window whose class or title matches a regex to a tag, given as "TAG REGEX" with
+/// the tag by id, name or 1 based number. Floating windows stay floating.
+fn set_window_tag(manager: &mut Manager, val: &Option<String>) -> Option<bool> {
+    let (tag, pattern) = val.as_deref()?.trim().split_once(' ')?;
+    let tag = match manager
+        .tags
+        .iter()
+        .find(|t| t.id == tag || t.name.as_deref() == Some(tag))
+    {
+        Some(t) => t.id.clone(),
+        None => manager
+            .numbered_tag(manager.tag_number_of(tag)?)?
+            .id
+            .clone(),
+    };
+    let regex = match Regex::new(pattern.trim()) {
+        Ok(regex) => regex,
+        Err(err) => {
+            log::error!("SetWindowTag: invalid regex: {}", err);
+            return None;
+        }
+    };
+    let matches = |name: &Option<String>| name.as_deref().map_or(false, |n| regex.is_match(n));
+    //scratchpads and the like stay on their hidden tags
+    let hidden: Vec<TagId> = manager
+        .tags
+        .iter()
+        .filter(|t| t.hidden)
+        .map(|t| t.id.clone())
+        .collect();
+    let mut moved = vec![];
+    for window in manager
+        .windows
+        .iter_mut()
+        .filter(|w| !w.is_unmanaged() && !helpers::intersect(&w.tags, &hidden))
+        .filter(|w| matches(&w.res_class) || matches(&w.name))
+    {
+        window.clear_tags();
+        window.tag(&tag);
+        moved.push(window.handle);
+    }
+    if moved.is_empty() {
+        return None;
+    }
+    for handle in &moved {
+        let act = DisplayAction::SetWindowTags(*handle, tag.clone());
+        manager.actions.push_back(act);
+    }
+    manager.sort_windows();
+    keep_focus_on_workspace(manager, &moved);
+    Some(true)
+}
+
+/// After moving windows to other tags the focus stays on the workspace, on a window still shown
+/// there.
+fn keep_focus_on_workspace(manager: &mut Manager, moved: &[WindowHandle]) -> Option<()> {
+    let workspace = manager.focused_workspace()?.clone();
+    let focus_moved = manager.focused_window().map_or(false, |w| {
+        moved.contains(&w.handle) && !workspace.is_displaying(w)
+    });
+    if focus_moved {
+        focus_handler::focus_top_window(manager, &workspace);
+    }
+    Some(())
+}
+
+fn save_snapshot(manager: &Manager, val: &Option<String>) -> Option<bool> {
+    let name = val.as_deref()?.trim();
+    let saved = Snapshot::path(name).and_then(|path| Snapshot::new(manager).save(&path));
+    if let Err(err) = saved {
+        log::error!("SaveSnapshot {}: {}", name, err);
+        return None;
+    }
+    Some(false)
+}
+
+fn load_snapshot(manager: &mut Manager, val: &Option<String>) -> Option<bool> {
+    let name = val.as_deref()?.trim();
+    let snapshot = match Snapshot::path(name).and_then(|path| Snapshot::load(&path)) {
+        Ok(snapshot) => snapshot,
+        Err(err) => {
+            log::error!("LoadSnapshot {}: {}", name, err);
+            return None;
+        }
+    };
+    let moved = snapshot.apply(manager);
+    keep_focus_on_workspace(manager, &moved);
+    Some(true)
+}
+
+/// Shows the tag on the focused workspace together with its other tags, or stops showing it.
+/// `GotoTag` goes back to showing a single tag.
+fn toggle_tag_view(manager: &mut Manager, val: &Option<String>) -> Option<bool> {
+    let tag_num = manager.tag_number_of(val.as_ref()?)?;
+    let tag = manager.numbered_tag(tag_num)?;
+    if tag.hidden {
+        return None;
+    }
+    let tag = tag.id.clone();
+    let ws_id = manager.focused_workspace()?.id;
+    let shown_elsewhere = manager
+        .workspaces
+        .iter()
+        .any(|ws| ws.id != ws_id && ws.has_tag(&tag));
+
+    let workspace = manager.focused_workspace_mut()?;
+    if !workspace.has_tag(&tag) && !shown_elsewhere {
+        workspace.tags.push(tag);
+    } else if workspace.has_tag(&tag) && workspace.tags.len() > 1 {
+        workspace.tags.retain(|t| t != &tag);
+    } else {
+        return None;
+    }
+    let workspace = workspace.clone();
+
+    focus_handler::focus_tag(manager, &workspace.tags[0]);
+    if !matches!(manager.focused_window(), Some(w) if workspace.is_displaying(w)) {
+        let handle = manager
+            .windows
+            .iter()
+            .find(|w| workspace.is_managed(w))
+            .map(|w| w.handle);
+        if let Some(handle) = handle {
+            focus_handler::focus_window(manager, &handle);
+        }
+    }
+    manager.update_docks();
+    Some(true)
+}
+
 fn goto_tag(manager: &mut Manager, val: &Option<String>, config: &impl Config) -> Option<bool> {
-    let current_tag = manager.tag_index(&manager.focused_tag(0).unwrap_or_default());
-    let previous_tag = manager.tag_index(&manager.focused_tag(1).unwrap_or_default());
+    let current_tag = manager.tag_number(&manager.focused_tag(0).unwrap_or_default());
+    let previous_tag = manager.tag_number(&manager.focused_tag(1).unwrap_or_default());
 
-    let input_tag = val.as_ref()?.parse().ok()?;
+    let input_tag = manager.tag_number_of(val.as_ref()?)?;
     let destination_tag = if config.disable_current_tag_swap() {
         input_tag
     } else {
         match (current_tag, previous_tag, input_tag) {
-            (Some(curr_tag), Some(prev_tag), inp_tag) if curr_tag + 1 == inp_tag => prev_tag + 1, // if current tag is the same as the destination tag, go to the previous tag instead
+            (Some(curr_tag), Some(prev_tag), inp_tag) if curr_tag == inp_tag => prev_tag, // if current tag is the same as the destination tag, go to the previous tag instead
             (_, _, _) => input_tag, // go to the input tag tag
         }
     };
+    if config.tag_display_behavior() == TagDisplayBehavior::Steal
+        && goto_tag_handler::focus_if_visible(manager, destination_tag)
+    {
+        return Some(true);
+    }
     Some(goto_tag_handler::process(manager, destination_tag))
 }
 
-fn focus_tag_change(manager: &mut Manager, delta: i8) -> Option<bool> {
+/// Shows the tag on the focused workspace until the key is let go, see `end_peek`.
+fn peek_tag(manager: &mut Manager, val: &Option<String>) -> Option<bool> {
+    let tag_num = manager.tag_number_of(val.as_ref()?)?;
+    manager.numbered_tag(tag_num)?;
+    //a key repeat or a second peek keeps the tag shown first
+    if manager.peek_origin.is_none() {
+        manager.peek_origin = manager.focused_workspace()?.tags.first().cloned();
+    }
+    Some(goto_tag_handler::process(manager, tag_num))
+}
+
+/// Goes back to the tag shown before `PeekTag`, if a peek is going on.
+pub fn end_peek(manager: &mut Manager) -> bool {
+    let tag_num = match manager.peek_origin.take() {
+        Some(tag) => manager.tag_number(&tag),
+        None => return false,
+    };
+    tag_num.map_or(false, |tag_num| goto_tag_handler::process(manager, tag_num))
+}
+
+fn focus_tag_change(manager: &mut Manager, config: &impl Config, delta: i8) -> Option<bool> {
+    let wrap = config.focus_tag_wraparound();
+    let next = relative_tag(manager, delta, wrap, config.focus_tag_skip_empty())?;
+    Some(goto_tag_handler::process(manager, next))
+}
+
+/// The number of the visible tag `delta` tags away from the focused one, going around past the
+/// first or last tag if `wrap` is set, and passing over tags without windows if `skip_empty` is.
+fn relative_tag(manager: &Manager, delta: i8, wrap: bool, skip_empty: bool) -> Option<usize> {
     let current = manager.focused_tag(0)?;
+    let ws_id = manager.focused_workspace()?.id;
+    let has_windows = |tag: &Tag| {
+        manager
+            .windows
+            .iter()
+            .any(|w| w.has_tag(&tag.id) && !w.is_unmanaged())
+    };
     let active_tags: Vec<(usize, TagId)> = manager
-        .tags
-        .iter()
+        .workspace_tags(ws_id)
         .enumerate()
         .filter(|(_, tag)| !tag.hidden)
+        .filter(|(_, tag)| !skip_empty || tag.id == current || has_windows(tag))
         .map(|(i, tag)| (i + 1, tag.id.clone()))
         .collect();
     let mut index = active_tags
@@ -215,22 +703,42 @@ fn focus_tag_change(manager: &mut Manager, delta: i8) -> Option<bool> {
     if delta.is_negative() {
         index = match index.checked_sub(delta.abs() as usize) {
             Some(i) => i,
-            None => active_tags.len() - 1,
+            None if wrap => active_tags.len() - 1,
+            None => return None,
         }
     } else {
         index += delta as usize;
         if index >= active_tags.len() {
+            if !wrap {
+                return None;
+            }
             index = 0;
         }
     }
     let (next, _) = *active_tags.get(index)?;
-    Some(goto_tag_handler::process(manager, next))
+    Some(next)
 }
 
-fn swap_tags(manager: &mut Manager) -> Option<bool> {
-    if manager.workspaces.len() >= 2 && manager.focus_manager.workspace_history.len() >= 2 {
-        let hist_a = *manager.focus_manager.workspace_history.get(0)?;
-        let hist_b = *manager.focus_manager.workspace_history.get(1)?;
+fn swap_tags(manager: &mut Manager, val: &Option<String>) -> Option<bool> {
+    //keybinds written with `value = ""` swap as if no value was given
+    let val = val.as_deref().filter(|val| !val.trim().is_empty());
+    if manager.workspaces.len() >= 2 {
+        let (hist_a, hist_b) = if let Some(val) = val {
+            workspace_pair(manager, val)?
+        } else {
+            let history = &manager.focus_manager.workspace_history;
+            let focused = history.get(0).copied().unwrap_or_default();
+            //with no other workspace focused yet, swap with the next one
+            let other = history
+                .get(1)
+                .copied()
+                .unwrap_or((focused + 1) % manager.workspaces.len());
+            (focused, other)
+        };
+        let len = manager.workspaces.len();
+        if hist_a == hist_b || hist_a >= len || hist_b >= len {
+            return Some(false);
+        }
         //Update workspace tags
         let mut temp = vec![];
         std::mem::swap(&mut manager.workspaces.get_mut(hist_a)?.tags, &mut temp);
@@ -240,19 +748,33 @@ fn swap_tags(manager: &mut Manager) -> Option<bool> {
         manager.update_docks();
         return Some(true);
     }
-    if manager.workspaces.len() == 1 {
+    if manager.workspaces.len() == 1 && val.is_none() {
         let last = manager
             .focus_manager
             .tag_history
             .get(1)
             .map(std::string::ToString::to_string)?;
 
-        let tag_index = manager.tags.iter().position(|x| x.id == last)? + 1;
-        return Some(goto_tag_handler::process(manager, tag_index));
+        let tag_num = manager.tag_number(&last)?;
+        return Some(goto_tag_handler::process(manager, tag_num));
     }
     None
 }
 
+/// Reads "a b" as the workspaces at index a and b, or "b" as the focused workspace and b.
+fn workspace_pair(manager: &Manager, val: &str) -> Option<(usize, usize)> {
+    let indices = val
+        .split_whitespace()
+        .map(str::parse)
+        .collect::<Result<Vec<usize>, _>>()
+        .ok()?;
+    match indices[..] {
+        [other] => Some((*manager.focus_manager.workspace_history.get(0)?, other)),
+        [a, b] => Some((a, b)),
+        _ => None,
+    }
+}
+
 fn close_window(manager: &mut Manager) -> Option<bool> {
     let window = manager.focused_window()?;
     if !window.is_unmanaged() {
@@ -289,12 +811,20 @@ fn previous_layout(manager: &mut Manager) -> Option<bool> {
     Some(true)
 }
 
-fn set_layout(val: &Option<String>, manager: &mut Manager) -> Option<bool> {
-    let layout = Layout::from_str(val.as_ref()?).ok()?;
+fn set_layout(val: &Option<String>, manager: &mut Manager, config: &impl Config) -> Option<bool> {
+    let name = val.as_ref()?;
+    let named = config.named_layouts().into_iter().find(|l| &l.name == name);
+    let layout = match &named {
+        Some(named) => named.base.clone(),
+        None => Layout::from_str(name).ok()?,
+    };
     let workspace = manager
         .focus_manager
         .workspace_mut(&mut manager.workspaces)?;
     workspace.set_layout(&mut manager.tags, layout);
+    if let Some(main_width) = named.and_then(|l| l.main_width) {
+        workspace.set_main_width(&mut manager.tags, main_width);
+    }
     Some(true)
 }
 
@@ -322,8 +852,9 @@ where
     let w = manager.focused_workspace()?;
     let (tags, layout) = (w.tags.clone(), Some(w.layout.clone()));
 
-    let for_active_workspace =
-        |x: &Window| -> bool { helpers::intersect(&tags, &x.tags) && !x.is_unmanaged() };
+    let for_active_workspace = |x: &Window| -> bool {
+        helpers::intersect(&tags, &x.tags) && !x.is_unmanaged() && !x.group_hidden
+    };
 
     let to_reorder = helpers::vec_extract(&mut manager.windows, for_active_workspace);
     func(manager, val, handle, &layout, to_reorder)
@@ -389,6 +920,34 @@ fn move_window_top(
     Some(true)
 }
 
+/// Swaps the focused window with the main one, the first tiled window, like dwm's zoom. The main
+/// window itself swaps with the first window of the stack. The focus stays on the window.
+fn zoom_window(
+    manager: &mut Manager,
+    _val: i32,
+    handle: WindowHandle,
+    _layout: &Option<Layout>,
+    mut to_reorder: Vec<Window>,
+) -> Option<bool> {
+    let tiled: Vec<usize> = to_reorder
+        .iter()
+        .enumerate()
+        .filter(|(_, w)| !w.floating())
+        .map(|(i, _)| i)
+        .collect();
+    let index = tiled.iter().position(|i| to_reorder[*i].handle == handle);
+    let swap = match index {
+        Some(0) => tiled.get(1).map(|next| (tiled[0], *next)),
+        Some(index) => Some((tiled[0], tiled[index])),
+        None => None,
+    };
+    if let Some((main, other)) = swap {
+        to_reorder.swap(main, other);
+    }
+    manager.windows.append(&mut to_reorder);
+    swap.map(|_| handle_focus(manager, handle))
+}
+
 fn focus_window_change(
     manager: &mut Manager,
     val: i32,
@@ -426,12 +985,24 @@ fn focus_window_change(
     Some(handle_focus(manager, handle))
 }
 
-fn focus_workspace_change(manager: &mut Manager, val: i32) -> Option<bool> {
+// `warp` or `nowarp` decide whether the cursor follows, by default it does with sloppy focus.
+fn focus_workspace_change(manager: &mut Manager, val: i32, warp: &Option<String>) -> Option<bool> {
     let current = manager.focused_workspace()?;
-    let workspace =
-        helpers::relative_find(&manager.workspaces, |w| w == current, val, true)?.clone();
+    //workspaces of outputs switched off are skipped
+    let workspaces: Vec<Workspace> = manager
+        .workspaces
+        .iter()
+        .filter(|w| !w.inactive || w == &current)
+        .cloned()
+        .collect();
+    let workspace = helpers::relative_find(&workspaces, |w| w == current, val, true)?.clone();
     focus_handler::focus_workspace(manager, &workspace);
-    if manager.focus_manager.behaviour == FocusBehaviour::Sloppy {
+    let warp = match warp.as_deref() {
+        Some("warp") => true,
+        Some("nowarp") => false,
+        _ => manager.focus_manager.behaviour == FocusBehaviour::Sloppy,
+    };
+    if warp {
         let act = DisplayAction::MoveMouseOverPoint(workspace.xyhw.center());
         manager.actions.push_back(act);
     }
@@ -451,6 +1022,34 @@ fn rotate_tag(manager: &mut Manager) -> Option<bool> {
     Some(true)
 }
 
+fn toggle_tiling(manager: &mut Manager) -> Option<bool> {
+    let tag_id = manager.focused_tag(0)?;
+    let tag = manager.tags.iter_mut().find(|t| t.id == tag_id)?;
+    if let Some(handles) = tag.paused_tiling.take() {
+        //tile the windows again that were tiled before
+        manager
+            .windows
+            .iter_mut()
+            .filter(|w| handles.contains(&w.handle))
+            .for_each(|w| w.set_floating(false));
+    } else {
+        //float the tiled windows where they floated before, or else where they are
+        let mut handles = vec![];
+        for w in &mut manager.windows {
+            if w.has_tag(&tag_id) && !w.is_unmanaged() && !w.floating() {
+                if !w.restore_floating() {
+                    let container = w.container_size.unwrap_or_default();
+                    w.set_floating_offsets(Some(w.normal - container));
+                    w.set_floating(true);
+                }
+                handles.push(w.handle);
+            }
+        }
+        tag.paused_tiling = Some(handles);
+    }
+    Some(true)
+}
+
 fn change_main_width(manager: &mut Manager, val: &Option<String>, factor: i8) -> Option<bool> {
     let workspace = manager
         .focus_manager
@@ -460,58 +1059,317 @@ fn change_main_width(manager: &mut Manager, val: &Option<String>, factor: i8) ->
     Some(true)
 }
 
-fn set_margin_multiplier(manager: &mut Manager, val: &Option<String>) -> Option<bool> {
-    let margin_multiplier: f32 = val.as_ref()?.parse().ok()?;
-    let ws = manager.focused_workspace_mut()?;
-    ws.set_margin_multiplier(margin_multiplier);
-    let tags = ws.tags.clone();
-    if manager
-        .windows
-        .iter()
-        .any(|w| w.type_ == WindowType::Normal)
-    {
-        let for_active_workspace = |x: &Window| -> bool {
-            helpers::intersect(&tags, &x.tags) && x.type_ == WindowType::Normal
-        };
-        let mut to_apply_margin_multiplier =
-            helpers::vec_extract(&mut manager.windows, for_active_workspace);
-        for w in &mut to_apply_margin_multiplier {
-            if let Some(ws) = manager.focused_workspace() {
-                w.apply_margin_multiplier(ws.margin_multiplier());
-            }
+/// Sets the width of the main column to a percentage, like `60`, or to pixels, like `800px`.
+fn set_main_width(manager: &mut Manager, val: &Option<String>) -> Option<bool> {
+    let val = val.as_deref()?.trim();
+    let workspace = manager
+        .focus_manager
+        .workspace_mut(&mut manager.workspaces)?;
+    let percent: u32 = match val.strip_suffix("px") {
+        Some(pixels) => {
+            let pixels: u64 = pixels.parse().ok()?;
+            let width = u64::try_from(workspace.xyhw.w()).ok().filter(|w| *w > 0)?;
+            let percent = pixels.saturating_mul(100).saturating_add(width / 2) / width;
+            u32::try_from(percent.min(100)).ok()?
         }
-        manager.windows.append(&mut to_apply_margin_multiplier);
-    }
+        None => val.trim_end_matches('%').parse().ok()?,
+    };
+    let percent = u8::try_from(percent.min(100)).ok()?;
+    workspace.set_main_width(&mut manager.tags, percent);
     Some(true)
 }
 
-fn handle_focus(manager: &mut Manager, handle: WindowHandle) -> bool {
-    match manager.focus_manager.behaviour {
-        FocusBehaviour::Sloppy => {
-            let act = DisplayAction::MoveMouseOver(handle);
-            manager.actions.push_back(act);
-            true
-        }
-        _ => focus_handler::focus_window(manager, &handle),
+/// Splits the selected window or container of the `Manual` layout on the focused workspace, so
+/// the next window opens beside it in `direction`, or selects the container around it.
+fn split_container(manager: &mut Manager, direction: Option<SplitDirection>) -> Option<bool> {
+    let workspace = manager.focused_workspace()?;
+    if workspace.layout != Layout::Manual {
+        return None;
+    }
+    let tags = workspace.tags.clone();
+    let tree = &mut manager
+        .tags
+        .iter_mut()
+        .find(|t| tags.contains(&t.id))?
+        .split_tree;
+    let done = match direction {
+        Some(direction) => tree.split(direction),
+        None => tree.focus_parent(),
+    };
+    //nothing moves until the next window opens
+    if done {
+        return Some(false);
     }
+    None
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::config::{Config, FocusBehaviour, Keybind, Workspace};
-    use crate::errors::Result;
-    use crate::models::Tag;
-    use crate::state::State;
-
-    struct TestConfig;
+/// Hides the docks on the focused workspace and lets the windows take their space, shows them
+/// again or toggles between the two when `hidden` is `None`.
+fn set_docks_hidden(manager: &mut Manager, hidden: Option<bool>) -> Option<bool> {
+    let workspace = manager.focused_workspace_mut()?;
+    let hidden = hidden.unwrap_or(!workspace.docks_hidden);
+    if workspace.docks_hidden == hidden {
+        return Some(false);
+    }
+    workspace.docks_hidden = hidden;
+    workspace.update_avoided_areas();
+    manager.sort_windows();
+    Some(true)
+}
+
+// Keeps the screen still for presenting or recording: the mouse no longer moves the focus, new
+// windows don't take it and the focused window stays above everything. A value pads the focused
+// workspace by that many more pixels. Toggling it again puts everything back.
+fn toggle_presentation_mode(manager: &mut Manager, val: &Option<String>) -> Option<bool> {
+    if let Some(presentation) = manager.presentation.take() {
+        manager.focus_manager.behaviour = presentation.behaviour;
+        manager.focus_manager.focus_new_windows = presentation.focus_new_windows;
+        if let Some((index, margin)) = presentation.padded {
+            if let Some(workspace) = manager.workspaces.get_mut(index) {
+                workspace.margin = margin;
+            }
+        }
+        manager
+            .actions
+            .push_back(DisplayAction::SetPresenting(false));
+        manager.sort_windows();
+        return Some(true);
+    }
+
+    let padding: Option<u32> = match val {
+        Some(val) => Some(val.parse().ok()?),
+        None => None,
+    };
+    let focus = &mut manager.focus_manager;
+    let behaviour = focus.behaviour;
+    if behaviour == FocusBehaviour::Sloppy {
+        focus.behaviour = FocusBehaviour::Driven;
+    }
+    let focus_new_windows = std::mem::replace(&mut focus.focus_new_windows, false);
+    let above = manager.focused_window().map(|w| w.handle);
+    let focused = manager.focus_manager.workspace_history.front().copied();
+    let padded = match (padding, focused) {
+        (Some(padding), Some(index)) => {
+            let workspace = &mut manager.workspaces[index];
+            let margin = workspace.margin.clone();
+            let padded = margin
+                .clone()
+                .into_vec()
+                .iter()
+                .map(|m| m + padding)
+                .collect();
+            workspace.margin = Margins::Vec(padded);
+            Some((index, margin))
+        }
+        _ => None,
+    };
+    manager.presentation = Some(Presentation {
+        behaviour,
+        focus_new_windows,
+        above,
+        padded,
+    });
+    manager
+        .actions
+        .push_back(DisplayAction::SetPresenting(true));
+    manager.sort_windows();
+    Some(true)
+}
+
+fn lock_screen(manager: &mut Manager, config: &impl Config) -> Option<bool> {
+    if manager.screen_lock.is_some() {
+        return Some(false);
+    }
+    let pid = exec_shell(&config.lock_command()?, manager)?;
+    manager.screen_lock = Some(pid);
+    Some(true)
+}
+
+fn show_keybind_help(manager: &mut Manager, config: &impl Config) {
+    let keybinds = match &manager.modkey {
+        Some(modkey) => config.mapped_bindings_for(modkey),
+        None => config.mapped_bindings(),
+    };
+    let lines = keybind_help_lines(&keybinds);
+    manager
+        .actions
+        .push_back(DisplayAction::ShowKeybindHelp(lines));
+}
+
+/// The lines of the keybind help, a heading per set of modifiers followed by the keys bound with
+/// exactly those modifiers.
+fn keybind_help_lines(keybinds: &[Keybind]) -> Vec<String> {
+    let mut groups: Vec<(String, Vec<String>)> = vec![];
+    for keybind in keybinds {
+        let modifier = match keybind.modifier.join("+") {
+            m if m.is_empty() => "No modifier".to_owned(),
+            m => m,
+        };
+        let line = match &keybind.value {
+            Some(value) => format!("  {:<12} {:?} {}", keybind.key, keybind.command, value),
+            None => format!("  {:<12} {:?}", keybind.key, keybind.command),
+        };
+        match groups.iter_mut().find(|(m, _)| *m == modifier) {
+            Some((_, lines)) => lines.push(line),
+            None => groups.push((modifier, vec![line])),
+        }
+    }
+    groups.sort_by(|a, b| a.0.cmp(&b.0));
+    groups
+        .into_iter()
+        .flat_map(|(modifier, lines)| std::iter::once(modifier).chain(lines))
+        .collect()
+}
+
+fn set_margin_multiplier(manager: &mut Manager, val: &Option<String>) -> Option<bool> {
+    let margin_multiplier: f32 = val.as_ref()?.parse().ok()?;
+    let ws = manager.focused_workspace_mut()?;
+    ws.set_margin_multiplier(margin_multiplier);
+    let tags = ws.tags.clone();
+    if manager
+        .windows
+        .iter()
+        .any(|w| w.type_ == WindowType::Normal)
+    {
+        let for_active_workspace = |x: &Window| -> bool {
+            helpers::intersect(&tags, &x.tags) && x.type_ == WindowType::Normal
+        };
+        let mut to_apply_margin_multiplier =
+            helpers::vec_extract(&mut manager.windows, for_active_workspace);
+        for w in &mut to_apply_margin_multiplier {
+            if let Some(ws) = manager.focused_workspace() {
+                w.apply_margin_multiplier(ws.margin_multiplier());
+            }
+        }
+        manager.windows.append(&mut to_apply_margin_multiplier);
+    }
+    Some(true)
+}
+
+fn set_modkey(manager: &mut Manager, config: &impl Config, val: &Option<String>) -> Option<bool> {
+    let modkey = val.as_ref()?;
+    if utils::xkeysym_lookup::into_mod(modkey) == 0 {
+        log::warn!("Ignoring invalid modkey: {}", modkey);
+        return None;
+    }
+    manager.modkey = Some(modkey.clone());
+    let bindings = display_event_handler::key_grabs(manager, config);
+    manager
+        .actions
+        .push_back(DisplayAction::ReloadKeyGrabs(bindings));
+    //mousebinds can use the modkey too
+    let mousebinds = mouse_combo_handler::active_mousebinds(manager, config);
+    manager
+        .actions
+        .push_back(DisplayAction::ReloadMouseGrabs(mousebinds));
+    Some(false)
+}
+
+fn set_mousekey(manager: &mut Manager, config: &impl Config, val: &Option<String>) -> Option<bool> {
+    let mousekey = val.as_ref()?;
+    if utils::xkeysym_lookup::into_mod(mousekey) == 0 {
+        log::warn!("Ignoring invalid mousekey: {}", mousekey);
+        return None;
+    }
+    manager.mousekey = Some(mousekey.clone());
+    let mousebinds = mouse_combo_handler::active_mousebinds(manager, config);
+    manager
+        .actions
+        .push_back(DisplayAction::ReloadMouseGrabs(mousebinds));
+    Some(false)
+}
+
+/// Puts the window below the focused one in the same tile, behind the focused window.
+fn group_window(manager: &mut Manager) -> Option<bool> {
+    let focused = manager.focused_window()?;
+    let handle = focused.handle;
+    if focused.group.is_some() || focused.floating() {
+        return Some(false);
+    }
+    let workspace = manager.focused_workspace()?;
+    let tiled: Vec<&Window> = manager
+        .windows
+        .iter()
+        .filter(|w| workspace.is_managed(w) && !w.floating())
+        .collect();
+    let target = helpers::relative_find(&tiled, |w| w.handle == handle, 1, true)?;
+    if target.handle == handle {
+        return Some(false);
+    }
+    let (target, group) = (target.handle, target.group.unwrap_or(target.handle));
+    for window in &mut manager.windows {
+        if window.handle == target {
+            window.group = Some(group);
+            window.group_hidden = true;
+        } else if window.handle == handle {
+            window.group = Some(group);
+        }
+    }
+    Some(true)
+}
+
+fn ungroup_window(manager: &mut Manager) -> Option<bool> {
+    let focused = manager.focused_window()?;
+    if focused.group.is_none() {
+        return Some(false);
+    }
+    let handle = focused.handle;
+    manager.leave_group(&handle);
+    Some(true)
+}
+
+/// Shows the next window of the focused window's group in its place.
+fn cycle_group(manager: &mut Manager) -> Option<bool> {
+    let focused = manager.focused_window()?;
+    let (handle, group) = (focused.handle, focused.group?);
+    let members: Vec<&Window> = manager
+        .windows
+        .iter()
+        .filter(|w| w.group == Some(group))
+        .collect();
+    let next = helpers::relative_find(&members, |w| w.handle == handle, 1, true)?.handle;
+    let index = manager.windows.iter().position(|w| w.handle == handle)?;
+    let next_index = manager.windows.iter().position(|w| w.handle == next)?;
+    let tags = manager.windows[index].tags.clone();
+    manager.windows[index].group_hidden = true;
+    manager.windows[next_index].group_hidden = false;
+    manager.windows[next_index].tags = tags;
+    //the next window takes over the tile
+    manager.windows.swap(index, next_index);
+    Some(focus_handler::focus_window(manager, &next))
+}
+
+fn handle_focus(manager: &mut Manager, handle: WindowHandle) -> bool {
+    match manager.focus_manager.behaviour {
+        FocusBehaviour::Sloppy => {
+            let act = DisplayAction::MoveMouseOver(handle);
+            manager.actions.push_back(act);
+            true
+        }
+        _ => focus_handler::focus_window(manager, &handle),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, FocusBehaviour, Keybind, Mousebind, NamedLayout, Workspace};
+    use crate::errors::Result;
+    use crate::models::{Tag, XyhwBuilder};
+    use crate::state::State;
+    use crate::utils::testing::TestManager;
+
+    struct TestConfig;
 
     impl Config for TestConfig {
         fn mapped_bindings(&self) -> Vec<Keybind> {
             unimplemented!()
         }
+        fn mapped_bindings_for(&self, _modkey: &str) -> Vec<Keybind> {
+            vec![]
+        }
         fn create_list_of_tags(&self) -> Vec<String> {
-            unimplemented!()
+            ["1", "2", "3"].iter().map(|t| (*t).to_owned()).collect()
         }
         fn workspaces(&self) -> Option<&[Workspace]> {
             unimplemented!()
@@ -519,12 +1377,68 @@ mod tests {
         fn focus_behaviour(&self) -> FocusBehaviour {
             unimplemented!()
         }
-        fn mousekey(&self) -> &str {
+        fn mapped_mousebinds(&self) -> Vec<Mousebind> {
             unimplemented!()
         }
+        fn mapped_mousebinds_for(
+            &self,
+            _modkey: Option<&str>,
+            _mousekey: Option<&str>,
+        ) -> Vec<Mousebind> {
+            vec![]
+        }
         fn disable_current_tag_swap(&self) -> bool {
             false
         }
+        fn focus_tag_wraparound(&self) -> bool {
+            true
+        }
+        fn focus_tag_skip_empty(&self) -> bool {
+            false
+        }
+        fn independent_tags(&self) -> bool {
+            false
+        }
+        fn tag_display_behavior(&self) -> TagDisplayBehavior {
+            TagDisplayBehavior::Swap
+        }
+        fn drag_refresh_rate(&self) -> u32 {
+            60
+        }
+        fn drag_threshold(&self) -> i32 {
+            0
+        }
+        fn double_click_time(&self) -> u64 {
+            400
+        }
+        fn drop_zones(&self) -> bool {
+            false
+        }
+        fn drag_outline(&self) -> bool {
+            false
+        }
+        fn close_transients_with_parent(&self) -> bool {
+            false
+        }
+        fn csd_shadows(&self) -> bool {
+            true
+        }
+        fn systray(&self) -> Option<crate::config::Systray> {
+            None
+        }
+        fn lock_command(&self) -> Option<String> {
+            Some("sleep 0.1".to_owned())
+        }
+        fn edge_actions(&self) -> Vec<crate::config::EdgeAction> {
+            vec![]
+        }
+        fn named_layouts(&self) -> Vec<NamedLayout> {
+            vec![NamedLayout {
+                name: "WideMain".to_owned(),
+                base: Layout::MainAndVertStack,
+                main_width: Some(70),
+            }]
+        }
     }
 
     struct TestState;
@@ -538,6 +1452,97 @@ mod tests {
         }
     }
 
+    #[test]
+    fn dry_run_should_catch_values_that_do_nothing() {
+        let config = TestConfig;
+        let dry_run = |command: Command, val: &str| dry_run(&config, &command, &Some(val.into()));
+        assert!(dry_run(Command::GotoTag, "3"));
+        assert!(!dry_run(Command::GotoTag, "4"));
+        assert!(dry_run(Command::PeekTag, "3"));
+        assert!(!dry_run(Command::PeekTag, "4"));
+        assert!(dry_run(Command::MoveToTag, "2"));
+        assert!(!dry_run(Command::MoveToTag, "0"));
+        assert!(dry_run(Command::ToggleTagView, "2"));
+        assert!(dry_run(Command::SwapTags, "0 1"));
+        assert!(!dry_run(Command::SwapTags, "0 5"));
+        assert!(dry_run(Command::SwapTags, ""));
+        assert!(dry_run(Command::SetLayout, "Monocle"));
+        assert!(!dry_run(Command::SetLayout, "Monocel"));
+        assert!(dry_run(Command::SetLayout, "WideMain"));
+        assert!(dry_run(Command::SetModKey, "Mod1"));
+        assert!(!dry_run(Command::SetModKey, "Mod9"));
+        assert!(!dry_run(Command::IncreaseMainWidth, "five"));
+        assert!(dry_run(Command::SetMainWidth, "60%"));
+        assert!(!dry_run(Command::SetMainWidth, "60pt"));
+        assert!(dry_run(Command::SaveSnapshot, "dry-run"));
+        assert!(dry_run(Command::SetWindowTag, "2 ^zoom$"));
+    }
+
+    #[test]
+    fn focus_workspace_change_should_warp_the_cursor_on_request() {
+        let mut manager = Manager::new_test();
+        manager.focus_manager.behaviour = FocusBehaviour::ClickTo;
+        screen_create_handler::process(&mut manager, Screen::default());
+        screen_create_handler::process(&mut manager, Screen::default());
+        let warped = |manager: &Manager| {
+            manager
+                .actions
+                .iter()
+                .any(|a| matches!(a, DisplayAction::MoveMouseOverPoint(_)))
+        };
+
+        let focus_next = |manager: &mut Manager, val: Option<&str>| {
+            manager.actions.clear();
+            let val = val.map(str::to_owned);
+            process(
+                manager,
+                &TestState,
+                &TestConfig,
+                &Command::FocusWorkspaceNext,
+                &val,
+            );
+        };
+        focus_next(&mut manager, None);
+        assert!(!warped(&manager));
+        focus_next(&mut manager, Some("warp"));
+        assert!(warped(&manager));
+        manager.focus_manager.behaviour = FocusBehaviour::Sloppy;
+        focus_next(&mut manager, Some("nowarp"));
+        assert!(!warped(&manager));
+    }
+
+    #[test]
+    fn set_layout_should_apply_named_layouts() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        let config = TestConfig;
+        let mut set_layout = |name: &str| {
+            process(
+                &mut manager,
+                &TestState,
+                &config,
+                &Command::SetLayout,
+                &Some(name.to_owned()),
+            )
+        };
+
+        assert!(set_layout("WideMain"));
+        assert!(!set_layout("NarrowMain"));
+        let workspace = manager.workspaces[0].clone();
+        assert_eq!(workspace.layout, Layout::MainAndVertStack);
+        assert!((workspace.main_width(&mut manager.tags) - 70.0).abs() < f32::EPSILON);
+
+        process(
+            &mut manager,
+            &TestState,
+            &config,
+            &Command::SetLayout,
+            &Some("MainAndVertStack".to_owned()),
+        );
+        let workspace = manager.workspaces[0].clone();
+        assert!((workspace.main_width(&mut manager.tags) - 50.0).abs() < f32::EPSILON);
+    }
+
     #[test]
     fn go_to_tag_should_return_false_if_no_screen_is_created() {
         let mut manager = Manager::new_test();
@@ -701,4 +1706,863 @@ mod tests {
             Some(5)
         );
     }
+
+    #[test]
+    fn set_modkey_should_reload_keygrabs() {
+        let mut manager = Manager::new_test();
+        let config = TestConfig;
+        process(
+            &mut manager,
+            &TestState,
+            &config,
+            &Command::SetModKey,
+            &Some("Mod1".to_string()),
+        );
+        assert_eq!(manager.modkey, Some("Mod1".to_string()));
+        assert!(matches!(
+            manager.actions.pop_front(),
+            Some(DisplayAction::ReloadKeyGrabs(_))
+        ));
+        assert!(matches!(
+            manager.actions.pop_front(),
+            Some(DisplayAction::ReloadMouseGrabs(_))
+        ));
+    }
+
+    #[test]
+    fn set_mousekey_should_reload_mouse_grabs() {
+        let mut manager = Manager::new_test();
+        let config = TestConfig;
+        let val = Some("Mod1".to_string());
+        process(
+            &mut manager,
+            &TestState,
+            &config,
+            &Command::SetMouseKey,
+            &val,
+        );
+        assert_eq!(manager.mousekey, val);
+        assert_eq!(manager.modkey, None);
+        assert!(matches!(
+            manager.actions.pop_back(),
+            Some(DisplayAction::ReloadMouseGrabs(_))
+        ));
+    }
+
+    #[test]
+    fn set_modkey_should_ignore_invalid_modifiers() {
+        let mut manager = Manager::new_test();
+        let config = TestConfig;
+        process(
+            &mut manager,
+            &TestState,
+            &config,
+            &Command::SetModKey,
+            &Some("Hyper".to_string()),
+        );
+        assert_eq!(manager.modkey, None);
+        assert!(manager.actions.is_empty());
+    }
+
+    #[test]
+    fn toggle_tag_view_should_show_tags_together_until_goto_tag() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        manager.tags.push(Tag::new("2"));
+        let run = |manager: &mut Manager, command: Command, tag: &str| {
+            process(
+                manager,
+                &TestState,
+                &TestConfig,
+                &command,
+                &Some(tag.to_owned()),
+            )
+        };
+
+        assert!(run(&mut manager, Command::ToggleTagView, "2"));
+        assert!(run(&mut manager, Command::ToggleTagView, "1"));
+        assert!(!run(&mut manager, Command::ToggleTagView, "2"));
+        assert!(run(&mut manager, Command::ToggleTagView, "1"));
+        assert_eq!(manager.workspaces[0].tags, ["2".to_owned(), "1".to_owned()]);
+        assert!(run(&mut manager, Command::GotoTag, "1"));
+        assert_eq!(manager.workspaces[0].tags, ["1".to_owned()]);
+    }
+
+    #[test]
+    fn tag_commands_should_take_the_number_id_or_name_of_a_tag() {
+        let mut manager = Manager::new_test();
+        manager.tags = ["1:web", "2:dev", "chat"]
+            .iter()
+            .map(|t| Tag::from_config(t))
+            .collect();
+        screen_create_handler::process(&mut manager, Screen::default());
+        let mut goto_tag = |tag: &str| {
+            process(
+                &mut manager,
+                &TestState,
+                &TestConfig,
+                &Command::GotoTag,
+                &Some(tag.to_owned()),
+            );
+            manager.workspaces[0].tags.clone()
+        };
+
+        assert_eq!(goto_tag("dev"), ["2".to_owned()]);
+        assert_eq!(goto_tag("chat"), ["chat".to_owned()]);
+        assert_eq!(goto_tag("1"), ["1".to_owned()]);
+        //going to the tag shown goes back to the one before, whichever way it is given
+        assert_eq!(goto_tag("web"), ["chat".to_owned()]);
+        assert_eq!(goto_tag("mail"), ["chat".to_owned()]);
+    }
+
+    #[test]
+    fn tag_ids_that_are_numbers_should_win_over_positions() {
+        let mut manager = Manager::new_test();
+        manager.tags = ["2:dev", "1:web", "9"]
+            .iter()
+            .map(|t| Tag::from_config(t))
+            .collect();
+        screen_create_handler::process(&mut manager, Screen::default());
+        let val = Some("1".to_owned());
+        process(
+            &mut manager,
+            &TestState,
+            &TestConfig,
+            &Command::GotoTag,
+            &val,
+        );
+        assert_eq!(manager.workspaces[0].tags, ["1".to_owned()]);
+        let val = Some("3".to_owned());
+        process(
+            &mut manager,
+            &TestState,
+            &TestConfig,
+            &Command::GotoTag,
+            &val,
+        );
+        assert_eq!(manager.workspaces[0].tags, ["9".to_owned()]);
+    }
+
+    #[test]
+    fn toggle_window_tag_should_keep_at_least_one_tag() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        manager.tags.push(Tag::new("2"));
+        let window = Window::new(WindowHandle::MockHandle(1), None, None);
+        window_handler::created(&mut manager, window, -1, -1);
+        let config = TestConfig;
+        let mut toggle = |tag: &str| {
+            process(
+                &mut manager,
+                &TestState,
+                &config,
+                &Command::ToggleWindowTag,
+                &Some(tag.to_owned()),
+            )
+        };
+
+        assert!(toggle("2"));
+        assert!(toggle("1"));
+        assert!(!toggle("2"));
+        assert_eq!(manager.windows[0].tags, ["2".to_owned()]);
+    }
+
+    #[test]
+    fn zoom_window_should_swap_with_the_main_window_and_keep_focus() {
+        let mut manager = TestManager::with_screens(1).with_windows(4).build();
+        manager.focus_manager.behaviour = FocusBehaviour::ClickTo;
+        manager.windows[1].set_floating(true);
+        focus_handler::focus_window(&mut manager, &WindowHandle::MockHandle(4));
+        let mut zoom = || {
+            let zoomed = process(
+                &mut manager,
+                &TestState,
+                &TestConfig,
+                &Command::ZoomWindow,
+                &None,
+            );
+            let order: Vec<WindowHandle> = manager.windows.iter().map(|w| w.handle).collect();
+            let focused = manager.focused_window().map(|w| w.handle);
+            (zoomed, order, focused)
+        };
+        let handles = |ids: &[i32]| -> Vec<WindowHandle> {
+            ids.iter().map(|id| WindowHandle::MockHandle(*id)).collect()
+        };
+
+        let (zoomed, order, focused) = zoom();
+        assert!(zoomed);
+        assert_eq!(order, handles(&[4, 2, 3, 1]));
+        assert_eq!(focused, Some(WindowHandle::MockHandle(4)));
+        //the main window goes back to the stack, the floating window is passed over
+        let (_, order, focused) = zoom();
+        assert_eq!(order, handles(&[3, 2, 4, 1]));
+        assert_eq!(focused, Some(WindowHandle::MockHandle(4)));
+    }
+
+    #[test]
+    fn set_window_tag_should_move_every_matching_window() {
+        let mut manager = Manager::new_test();
+        let mut nsp = Tag::new("NSP");
+        nsp.hidden = true;
+        manager.tags = vec![Tag::new("1"), Tag::new("meet"), nsp];
+        screen_create_handler::process(&mut manager, Screen::default());
+        for (id, class) in &[(1, "zoom"), (2, "Firefox"), (3, "zoom"), (4, "zoom")] {
+            let mut window = Window::new(WindowHandle::MockHandle(*id), None, None);
+            window.res_class = Some((*class).to_owned());
+            window_handler::created(&mut manager, window, -1, -1);
+        }
+        manager.windows[3].clear_tags();
+        manager.windows[3].tag("NSP");
+        focus_handler::focus_window(&mut manager, &WindowHandle::MockHandle(3));
+        let mut set_window_tag = |val: &str| {
+            process(
+                &mut manager,
+                &TestState,
+                &TestConfig,
+                &Command::SetWindowTag,
+                &Some(val.to_owned()),
+            )
+        };
+
+        assert!(set_window_tag("meet ^zoom$"));
+        assert!(!set_window_tag("meet ^slack$"));
+        assert!(!set_window_tag("meet ("));
+        let tags: Vec<&str> = manager.windows.iter().map(|w| w.tags[0].as_str()).collect();
+        assert_eq!(tags, ["meet", "1", "meet", "NSP"]);
+        assert_eq!(
+            manager.focused_window().map(|w| w.handle),
+            Some(WindowHandle::MockHandle(2))
+        );
+    }
+
+    #[test]
+    fn toggle_tiling_should_float_tiled_windows_in_place_and_restore_them() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        let mut floating = Window::new(WindowHandle::MockHandle(1), None, None);
+        floating.set_floating(true);
+        window_handler::created(&mut manager, floating, -1, -1);
+        let mut tiled = Window::new(WindowHandle::MockHandle(2), None, None);
+        tiled.normal.set_x(100);
+        window_handler::created(&mut manager, tiled, -1, -1);
+        let is_tiled = |w: &Window| w.handle == WindowHandle::MockHandle(2);
+
+        let config = TestConfig;
+        process(
+            &mut manager,
+            &TestState,
+            &config,
+            &Command::ToggleTiling,
+            &None,
+        );
+        assert!(manager.windows.iter().all(Window::floating));
+        let window = manager.windows.iter().find(|w| is_tiled(w)).unwrap();
+        assert_eq!(window.get_floating_offsets().map(|o| o.x()), Some(100));
+
+        process(
+            &mut manager,
+            &TestState,
+            &config,
+            &Command::ToggleTiling,
+            &None,
+        );
+        assert!(manager.windows.iter().all(|w| w.floating() != is_tiled(w)));
+    }
+
+    #[test]
+    fn toggle_tiling_should_tile_windows_created_while_paused() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        let config = TestConfig;
+        process(
+            &mut manager,
+            &TestState,
+            &config,
+            &Command::ToggleTiling,
+            &None,
+        );
+        let window = Window::new(WindowHandle::MockHandle(1), None, None);
+        window_handler::created(&mut manager, window, -1, -1);
+        assert!(manager.windows[0].floating());
+
+        process(
+            &mut manager,
+            &TestState,
+            &config,
+            &Command::ToggleTiling,
+            &None,
+        );
+        assert!(!manager.windows[0].floating());
+    }
+
+    #[test]
+    fn retiled_windows_should_float_again_where_they_were() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        let mut window = Window::new(WindowHandle::MockHandle(1), None, None);
+        window.set_floating(true);
+        window_handler::created(&mut manager, window, -1, -1);
+        let geometry = XyhwBuilder {
+            x: 30,
+            y: 40,
+            h: 200,
+            w: 300,
+            ..XyhwBuilder::default()
+        };
+        manager.windows[0].set_floating_exact(geometry.into());
+        let config = TestConfig;
+
+        process(
+            &mut manager,
+            &TestState,
+            &config,
+            &Command::FloatingToTile,
+            &None,
+        );
+        assert!(!manager.windows[0].floating());
+        manager.windows[0].normal = XyhwBuilder::default().into();
+
+        process(
+            &mut manager,
+            &TestState,
+            &config,
+            &Command::ToggleTiling,
+            &None,
+        );
+        let window = &manager.windows[0];
+        let restored = window.normal + window.get_floating_offsets().unwrap();
+        assert!(window.floating());
+        assert_eq!(
+            (restored.x(), restored.y(), restored.h(), restored.w()),
+            (30, 40, 200, 300)
+        );
+    }
+
+    #[test]
+    fn attached_windows_should_toggle_like_scratchpads() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        let window = Window::new(WindowHandle::MockHandle(1), None, Some(42));
+        window_handler::created(&mut manager, window, -1, -1);
+        let config = TestConfig;
+        let name = Some("term".to_string());
+
+        process(
+            &mut manager,
+            &TestState,
+            &config,
+            &Command::AttachToScratchPad,
+            &name,
+        );
+        assert_eq!(
+            manager.attached_scratchpads.get("term"),
+            Some(&WindowHandle::MockHandle(1))
+        );
+        assert!(manager.windows[0].has_tag("NSP"));
+        assert!(manager.windows[0].floating());
+
+        process(
+            &mut manager,
+            &TestState,
+            &config,
+            &Command::ToggleScratchPad,
+            &name,
+        );
+        assert!(manager.windows[0].has_tag("1"));
+
+        process(
+            &mut manager,
+            &TestState,
+            &config,
+            &Command::ReleaseScratchPad,
+            &None,
+        );
+        assert!(manager.attached_scratchpads.is_empty());
+    }
+
+    #[test]
+    fn an_attached_window_should_be_told_apart_from_the_other_windows_of_its_process() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        for handle in 1..=2 {
+            let window = Window::new(WindowHandle::MockHandle(handle), None, Some(42));
+            window_handler::created(&mut manager, window, -1, -1);
+        }
+        let config = TestConfig;
+        let name = Some("term".to_string());
+        let attached = manager.focused_window().map(|w| w.handle).unwrap();
+        process(
+            &mut manager,
+            &TestState,
+            &config,
+            &Command::AttachToScratchPad,
+            &name,
+        );
+
+        let hidden: Vec<WindowHandle> = manager
+            .windows
+            .iter()
+            .filter(|w| w.has_tag("NSP"))
+            .map(|w| w.handle)
+            .collect();
+        assert_eq!(hidden, [attached]);
+        let other = manager
+            .windows
+            .iter()
+            .find(|w| w.handle != attached)
+            .map(|w| w.handle)
+            .unwrap();
+        crate::handlers::focus_handler::focus_window(&mut manager, &other);
+        process(
+            &mut manager,
+            &TestState,
+            &config,
+            &Command::ReleaseScratchPad,
+            &None,
+        );
+        assert_eq!(manager.attached_scratchpads.len(), 1);
+    }
+
+    #[test]
+    fn swap_tags_should_swap_with_the_next_or_the_given_workspace() {
+        let mut manager = Manager::new_test();
+        for _ in 0..3 {
+            screen_create_handler::process(&mut manager, Screen::default());
+        }
+        manager.focus_manager.workspace_history.clear();
+        manager.focus_manager.workspace_history.push_front(0);
+        let tags = |manager: &Manager| -> Vec<String> {
+            manager
+                .workspaces
+                .iter()
+                .flat_map(|ws| ws.tags.clone())
+                .collect()
+        };
+        let swap = |manager: &mut Manager, val: Option<&str>| {
+            process(
+                manager,
+                &TestState,
+                &TestConfig,
+                &Command::SwapTags,
+                &val.map(ToOwned::to_owned),
+            )
+        };
+
+        assert!(swap(&mut manager, None));
+        assert_eq!(tags(&manager), ["2", "1", "3"]);
+        assert!(swap(&mut manager, Some("2")));
+        assert_eq!(tags(&manager), ["3", "1", "2"]);
+        assert!(swap(&mut manager, Some("1 2")));
+        assert_eq!(tags(&manager), ["3", "2", "1"]);
+        assert!(!swap(&mut manager, Some("0 5")));
+        assert!(!swap(&mut manager, Some("one")));
+        assert_eq!(tags(&manager), ["3", "2", "1"]);
+    }
+
+    #[test]
+    fn grouped_windows_should_share_a_tile() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        for i in 1..=3 {
+            let window = Window::new(WindowHandle::MockHandle(i), None, None);
+            window_handler::created(&mut manager, window, -1, -1);
+        }
+        let run = |manager: &mut Manager, command: Command| {
+            process(manager, &TestState, &TestConfig, &command, &None)
+        };
+        let shown = |manager: &Manager| -> Vec<WindowHandle> {
+            let workspace = &manager.workspaces[0];
+            manager
+                .windows
+                .iter()
+                .filter(|w| workspace.is_displaying(w))
+                .map(|w| w.handle)
+                .collect()
+        };
+        let (one, two, three) = (
+            WindowHandle::MockHandle(1),
+            WindowHandle::MockHandle(2),
+            WindowHandle::MockHandle(3),
+        );
+        focus_handler::focus_window(&mut manager, &one);
+
+        assert!(run(&mut manager, Command::GroupWindow));
+        assert_eq!(shown(&manager), [one, three]);
+        assert!(!run(&mut manager, Command::GroupWindow));
+
+        assert!(run(&mut manager, Command::CycleGroup));
+        assert_eq!(shown(&manager), [two, three]);
+        assert_eq!(manager.focused_window().map(|w| w.handle), Some(two));
+
+        window_handler::destroyed(&mut manager, &two);
+        assert_eq!(shown(&manager), [one, three]);
+        assert!(manager.windows.iter().all(|w| w.group.is_none()));
+
+        focus_handler::focus_window(&mut manager, &three);
+        assert!(run(&mut manager, Command::GroupWindow));
+        assert_eq!(shown(&manager), [three]);
+        //the window shown next keeps the tile, the ungrouped one takes its old place
+        assert!(run(&mut manager, Command::UngroupWindow));
+        assert_eq!(shown(&manager), [three, one]);
+        assert!(!run(&mut manager, Command::UngroupWindow));
+    }
+
+    #[test]
+    fn undo_should_revert_the_latest_reversible_operations() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        manager.tags.push(Tag::new("2"));
+        for i in 1..=2 {
+            let window = Window::new(WindowHandle::MockHandle(i), None, None);
+            window_handler::created(&mut manager, window, -1, -1);
+        }
+        let one = WindowHandle::MockHandle(1);
+        focus_handler::focus_window(&mut manager, &one);
+        let run = |manager: &mut Manager, command: Command, val: Option<&str>| {
+            let val = val.map(str::to_owned);
+            process(manager, &TestState, &TestConfig, &command, &val)
+        };
+        let layout = manager.workspaces[0].layout.clone();
+
+        assert!(run(&mut manager, Command::MoveToTag, Some("2")));
+        //focusing another window can't be undone
+        assert!(run(&mut manager, Command::FocusWindowDown, None));
+        assert!(run(&mut manager, Command::SetLayout, Some("Monocle")));
+        assert_eq!(manager.undo_journal.len(), 2);
+
+        assert!(run(&mut manager, Command::UndoWindowOperation, None));
+        assert_eq!(manager.workspaces[0].layout, layout);
+        assert!(run(&mut manager, Command::UndoWindowOperation, None));
+        let window = manager.windows.iter().find(|w| w.handle == one).unwrap();
+        assert_eq!(window.tags, ["1".to_owned()]);
+        assert!(!run(&mut manager, Command::UndoWindowOperation, None));
+    }
+
+    #[test]
+    fn a_locked_screen_should_unlock_when_the_lock_command_exits() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        let lock = |manager: &mut Manager| {
+            process(
+                manager,
+                &TestState,
+                &TestConfig,
+                &Command::LockScreen,
+                &None,
+            )
+        };
+        assert!(lock(&mut manager));
+        assert!(manager.screen_lock.is_some());
+        assert!(!lock(&mut manager));
+
+        let started = std::time::Instant::now();
+        while manager.screen_lock.is_some() && started.elapsed().as_secs() < 5 {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            manager.reap_children();
+        }
+        assert!(manager.screen_lock.is_none());
+    }
+
+    #[test]
+    fn hidden_docks_should_leave_their_space_to_the_windows() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        let mut dock = Window::new(WindowHandle::MockHandle(1), None, None);
+        dock.type_ = WindowType::Dock;
+        window_handler::created(&mut manager, dock, -1, -1);
+        let width = manager.workspaces[0].xyhw.w();
+        let strut = crate::models::XyhwBuilder {
+            w: width,
+            h: 20,
+            ..crate::models::XyhwBuilder::default()
+        };
+        manager.windows[0].strut = Some(strut.into());
+        window_handler::update_workspace_avoid_list(&mut manager);
+        let height = manager.workspaces[0].height();
+        let run = |manager: &mut Manager, command: Command| {
+            process(manager, &TestState, &TestConfig, &command, &None)
+        };
+
+        assert!(run(&mut manager, Command::HideDock));
+        assert!(!run(&mut manager, Command::HideDock));
+        assert_eq!(manager.workspaces[0].height(), height + 20);
+        utils::window_updater::update_windows(&mut manager);
+        assert!(!manager.windows[0].visible());
+
+        assert!(run(&mut manager, Command::ToggleDock));
+        assert_eq!(manager.workspaces[0].height(), height);
+        utils::window_updater::update_windows(&mut manager);
+        assert!(manager.windows[0].visible());
+    }
+
+    #[test]
+    fn presentation_mode_should_be_put_back_when_toggled_off() {
+        let mut manager = TestManager::with_screens(1)
+            .with_windows(3)
+            .focus_window(2)
+            .build();
+        manager.workspaces[0].margin = Margins::Int(10);
+        manager.focus_manager.focus_new_windows = true;
+        let padding = Some("40".to_owned());
+        let command = Command::TogglePresentationMode;
+
+        assert!(process(
+            &mut manager,
+            &TestState,
+            &TestConfig,
+            &command,
+            &padding
+        ));
+        assert_eq!(manager.focus_manager.behaviour, FocusBehaviour::Driven);
+        assert!(!manager.focus_manager.focus_new_windows);
+        assert_eq!(manager.workspaces[0].margin.clone().into_vec(), [50; 4]);
+        assert_eq!(manager.windows[0].handle, WindowHandle::MockHandle(2));
+
+        assert!(process(
+            &mut manager,
+            &TestState,
+            &TestConfig,
+            &command,
+            &None
+        ));
+        assert_eq!(manager.focus_manager.behaviour, FocusBehaviour::Sloppy);
+        assert!(manager.focus_manager.focus_new_windows);
+        assert_eq!(manager.workspaces[0].margin, Margins::Int(10));
+        assert!(manager.presentation.is_none());
+    }
+
+    #[test]
+    fn move_to_tag_should_take_relative_tags() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        manager.tags.push(Tag::new("2"));
+        manager.tags.push(Tag::new("3"));
+        let run = |manager: &mut Manager, command: Command, val: &str| {
+            process(
+                manager,
+                &TestState,
+                &TestConfig,
+                &command,
+                &Some(val.to_owned()),
+            )
+        };
+        assert!(run(&mut manager, Command::GotoTag, "2"));
+        assert!(run(&mut manager, Command::GotoTag, "1"));
+        let tags_after = |manager: &mut Manager, i: i32, val: &str| -> Vec<TagId> {
+            let handle = WindowHandle::MockHandle(i);
+            window_handler::created(manager, Window::new(handle, None, None), -1, -1);
+            focus_handler::focus_window(manager, &handle);
+            assert!(run(manager, Command::MoveToTag, val));
+            let window = manager.windows.iter().find(|w| w.handle == handle);
+            window.map(|w| w.tags.clone()).unwrap_or_default()
+        };
+
+        assert_eq!(tags_after(&mut manager, 1, "next"), ["2".to_owned()]);
+        assert_eq!(tags_after(&mut manager, 2, "previous"), ["3".to_owned()]);
+        assert_eq!(tags_after(&mut manager, 3, "last"), ["2".to_owned()]);
+    }
+
+    #[test]
+    fn move_to_tag_and_follow_should_focus_the_new_tag() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        manager.tags.push(Tag::new("2"));
+        let first = WindowHandle::MockHandle(1);
+        let second = WindowHandle::MockHandle(2);
+        window_handler::created(&mut manager, Window::new(first, None, None), -1, -1);
+        window_handler::created(&mut manager, Window::new(second, None, None), -1, -1);
+        focus_handler::focus_window(&mut manager, &second);
+
+        assert!(process(
+            &mut manager,
+            &TestState,
+            &TestConfig,
+            &Command::MoveToTagAndFollow,
+            &Some("2".to_owned()),
+        ));
+        assert_eq!(manager.workspaces[0].tags, ["2".to_owned()]);
+        assert_eq!(manager.focused_window().map(|w| w.handle), Some(second));
+        let window = manager.windows.iter().find(|w| w.handle == second).unwrap();
+        assert_eq!(window.tags, ["2".to_owned()]);
+    }
+
+    #[test]
+    fn a_floating_window_moved_to_the_next_tag_should_keep_its_place() {
+        let mut manager = Manager::new_test();
+        manager.tags = vec![Tag::new("1"), Tag::new("2")];
+        screen_create_handler::process(&mut manager, Screen::default());
+        let mut screen = Screen::default();
+        screen.bbox.x = 800;
+        screen.bbox.width = 1600;
+        screen.bbox.height = 1200;
+        screen_create_handler::process(&mut manager, screen);
+        let first = manager.workspaces[0].clone();
+        focus_handler::focus_workspace(&mut manager, &first);
+        let handle = WindowHandle::MockHandle(1);
+        window_handler::created(&mut manager, Window::new(handle, None, None), -1, -1);
+        focus_handler::focus_window(&mut manager, &handle);
+        //tiled windows move with `MoveToTag`
+        assert!(!process(
+            &mut manager,
+            &TestState,
+            &TestConfig,
+            &Command::MoveFloatingToNextTag,
+            &None,
+        ));
+
+        let window = &mut manager.windows[0];
+        window.set_floating(true);
+        let mut xyhw = window.calculated_xyhw();
+        xyhw.set_x(400);
+        xyhw.set_y(300);
+        window.set_floating_exact(xyhw);
+        assert!(process(
+            &mut manager,
+            &TestState,
+            &TestConfig,
+            &Command::MoveFloatingToNextTag,
+            &None,
+        ));
+        let window = &manager.windows[0];
+        assert_eq!(window.tags, ["2".to_owned()]);
+        assert_eq!((window.x(), window.y()), (1600, 600));
+    }
+
+    #[test]
+    fn set_main_width_should_take_percentages_and_pixels() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        manager.workspaces[0].xyhw.set_w(1600);
+        let mut set_main_width = |val: &str| {
+            process(
+                &mut manager,
+                &TestState,
+                &TestConfig,
+                &Command::SetMainWidth,
+                &Some(val.to_owned()),
+            );
+            let workspace = manager.workspaces[0].clone();
+            workspace.main_width(&mut manager.tags)
+        };
+
+        assert!((set_main_width("60") - 60.0).abs() < f32::EPSILON);
+        assert!((set_main_width("35%") - 35.0).abs() < f32::EPSILON);
+        assert!((set_main_width("400px") - 25.0).abs() < f32::EPSILON);
+        assert!((set_main_width("250") - 100.0).abs() < f32::EPSILON);
+        assert!((set_main_width("50000000px") - 100.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn split_commands_should_place_new_windows_in_the_manual_layout() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        let run = |manager: &mut Manager, command: Command| {
+            process_internal(
+                manager,
+                &TestState,
+                &TestConfig,
+                &command,
+                &None,
+                &ExecOptions::default(),
+            )
+        };
+        let create = |manager: &mut Manager, id: i32| {
+            let window = Window::new(WindowHandle::MockHandle(id), None, None);
+            window_handler::created(manager, window, -1, -1);
+            utils::window_updater::update_windows(manager);
+        };
+        create(&mut manager, 1);
+        assert_eq!(run(&mut manager, Command::SplitVertical), None);
+
+        manager.workspaces[0].layout = Layout::Manual;
+        create(&mut manager, 2);
+        assert_eq!(run(&mut manager, Command::SplitVertical), Some(false));
+        create(&mut manager, 3);
+        let (one, two, three) = (
+            &manager.windows[0],
+            &manager.windows[1],
+            &manager.windows[2],
+        );
+        assert!(one.x() < two.x());
+        assert_eq!(two.x(), three.x());
+        assert!(two.y() < three.y());
+        assert_eq!(one.height(), manager.workspaces[0].height());
+
+        assert_eq!(run(&mut manager, Command::FocusParent), Some(false));
+        create(&mut manager, 4);
+        let four = &manager.windows[3];
+        assert_eq!(four.height(), manager.workspaces[0].height());
+        assert!(four.x() > manager.windows[1].x());
+    }
+
+    #[test]
+    fn peek_tag_should_go_back_when_the_key_is_let_go() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        manager.tags.push(Tag::new("2"));
+        manager.tags.push(Tag::new("3"));
+        let peek = |manager: &mut Manager, val: &str| {
+            process(
+                manager,
+                &TestState,
+                &TestConfig,
+                &Command::PeekTag,
+                &Some(val.to_owned()),
+            )
+        };
+
+        assert!(peek(&mut manager, "2"));
+        assert!(peek(&mut manager, "3"));
+        assert_eq!(manager.workspaces[0].tags, ["3".to_owned()]);
+        assert!(end_peek(&mut manager));
+        assert_eq!(manager.workspaces[0].tags, ["1".to_owned()]);
+        assert!(!end_peek(&mut manager));
+    }
+
+    #[test]
+    fn relative_tag_should_honour_wraparound_and_skip_empty() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        manager.tags.push(Tag::new("2"));
+        manager.tags.push(Tag::new("3"));
+        assert!(goto_tag_handler::process(&mut manager, 3));
+
+        assert_eq!(relative_tag(&manager, 1, true, false), Some(1));
+        assert_eq!(relative_tag(&manager, 1, false, false), None);
+        assert_eq!(relative_tag(&manager, -1, false, false), Some(2));
+        assert_eq!(relative_tag(&manager, -1, false, true), None);
+
+        let handle = WindowHandle::MockHandle(1);
+        let mut window = Window::new(handle, None, None);
+        window.tag("1");
+        manager.windows.push(window);
+        assert_eq!(relative_tag(&manager, -1, false, true), Some(1));
+    }
+
+    #[test]
+    fn keybind_help_should_group_keys_by_modifier() {
+        let keybind = |command, value: Option<&str>, modifier: &[&str], key: &str| Keybind {
+            command,
+            value: value.map(str::to_owned),
+            modifier: modifier.iter().map(|m| (*m).to_owned()).collect(),
+            key: key.to_owned(),
+            repeat: false,
+            essential: false,
+            exec: ExecOptions::default(),
+        };
+        let keybinds = vec![
+            keybind(Command::GotoTag, Some("1"), &["Mod4"], "1"),
+            keybind(Command::CloseWindow, None, &["Mod4", "Shift"], "q"),
+            keybind(Command::GotoTag, Some("2"), &["Mod4"], "2"),
+            keybind(Command::LockScreen, None, &[], "F12"),
+        ];
+        let lines = keybind_help_lines(&keybinds);
+        assert_eq!(lines[0], "Mod4");
+        assert!(lines[1].starts_with("  1 ") && lines[1].ends_with("GotoTag 1"));
+        assert!(lines[2].ends_with("GotoTag 2"));
+        assert_eq!(lines[3], "Mod4+Shift");
+        assert!(lines[4].ends_with("CloseWindow"));
+        assert_eq!(lines[5], "No modifier");
+        assert!(lines[6].ends_with("LockScreen"));
+    }
 }
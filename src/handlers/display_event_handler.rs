@@ -1,12 +1,16 @@
 use super::{
-    command_handler, focus_handler, mouse_combo_handler, screen_create_handler, window_handler,
-    window_move_handler, window_resize_handler, CommandBuilder, Config, DisplayEvent, Manager,
-    Mode,
+    command_handler, focus_handler, mouse_combo_handler, output_power_handler,
+    screen_create_handler, window_handler, window_move_handler, window_resize_handler,
+    CommandBuilder, Config, DisplayEvent, Manager, Mode, WindowHandle,
 };
+use crate::config::{EdgeAction, Keybind, MouseTarget};
+use crate::display_action::DisplayAction;
+use crate::models::{FocusBehaviour, Side, WindowType};
 use crate::state::State;
-use crate::utils;
 use crate::utils::window_updater::update_windows;
-use crate::{display_action::DisplayAction, models::FocusBehaviour};
+use crate::utils::xkeysym_lookup::{ModMask, XKeysym};
+use std::os::raw::c_ulong;
+use std::time::{Duration, Instant};
 
 /// Configuration container for processing `DisplayEvents`.
 pub struct DisplayEventHandler<C> {
@@ -16,22 +20,49 @@ pub struct DisplayEventHandler<C> {
 impl<C: Config> DisplayEventHandler<C> {
     /// Process a collection of events, and apply them changes to a manager.
     /// Returns true if changes need to be rendered.
+    #[allow(clippy::too_many_lines)]
     pub fn process(&self, manager: &mut Manager, state: &impl State, event: DisplayEvent) -> bool {
         let update_needed = match event {
             DisplayEvent::ScreenCreate(s) => screen_create_handler::process(manager, s),
+            DisplayEvent::OutputPower(output, on) => {
+                output_power_handler::process(manager, &output, on)
+            }
+            DisplayEvent::SystrayStrut(strut) => {
+                manager.systray_strut = strut;
+                window_handler::update_workspace_avoid_list(manager);
+                true
+            }
             DisplayEvent::WindowCreate(w, x, y) => window_handler::created(manager, w, x, y),
             DisplayEvent::WindowChange(w) => window_handler::changed(manager, w),
+            DisplayEvent::ConfigureRequest(handle, change) => {
+                window_handler::configure_requested(manager, handle, change)
+            }
 
             //The window has been focused, do we want to do anything about it?
+            //A fullscreen game or the like keeps focus while the mouse wanders off.
+            DisplayEvent::MouseEnteredWindow(_) | DisplayEvent::VerifyFocusedAt(..)
+                if manager.focus_locked() =>
+            {
+                return false
+            }
+            //Nothing gets focused behind the lock screen.
+            DisplayEvent::MouseEnteredWindow(_)
+            | DisplayEvent::VerifyFocusedAt(..)
+            | DisplayEvent::Movement(..)
+                if manager.screen_lock.is_some() =>
+            {
+                return false
+            }
             DisplayEvent::MouseEnteredWindow(handle) => match manager.focus_manager.behaviour {
                 FocusBehaviour::Sloppy => return focus_handler::focus_window(manager, &handle),
                 _ => return false,
             },
 
             DisplayEvent::KeyGrabReload => {
+                let bindings = self.mapped_bindings(manager);
                 manager
                     .actions
-                    .push_back(DisplayAction::ReloadKeyGrabs(self.config.mapped_bindings()));
+                    .push_back(DisplayAction::ReloadKeyGrabs(bindings));
                 false
             }
 
@@ -44,29 +75,64 @@ impl<C: Config> DisplayEventHandler<C> {
                 _ => return false,
             },
 
-            DisplayEvent::WindowDestroy(handle) => window_handler::destroyed(manager, &handle),
+            DisplayEvent::WindowDestroy(handle) => {
+                if self.config.close_transients_with_parent() {
+                    window_handler::close_transients(manager, &handle);
+                }
+                window_handler::destroyed(manager, &handle)
+            }
 
             DisplayEvent::KeyCombo(mod_mask, xkeysym) => {
-                //look through the config and build a command if its defined in the config
-                let build = CommandBuilder::new(&self.config);
-                let command = build.xkeyevent(mod_mask, xkeysym);
-                if let Some((cmd, val)) = command {
-                    command_handler::process(manager, state, &self.config, &cmd, &val)
-                } else {
-                    false
-                }
+                self.key_pressed(manager, state, mod_mask, xkeysym, false)
             }
+            DisplayEvent::KeyRepeat(mod_mask, xkeysym) => {
+                self.key_pressed(manager, state, mod_mask, xkeysym, true)
+            }
+            DisplayEvent::KeyRelease => command_handler::end_peek(manager),
 
             DisplayEvent::SendCommand(command, value) => {
                 command_handler::process(manager, state, &self.config, &command, &value)
             }
 
             DisplayEvent::MouseCombo(mod_mask, button, handle) => {
-                let mouse_key = utils::xkeysym_lookup::into_mod(self.config.mousekey());
-                mouse_combo_handler::process(manager, mod_mask, button, handle, mouse_key)
+                let target = if manager.screens.iter().any(|s| s.root == handle) {
+                    MouseTarget::Root
+                } else {
+                    MouseTarget::Window
+                };
+                let config = &self.config;
+                mouse_combo_handler::process(
+                    manager, state, config, mod_mask, button, handle, target,
+                )
+            }
+
+            DisplayEvent::TitleBarClick(mod_mask, button, handle) => {
+                let target = MouseTarget::TitleBar;
+                let config = &self.config;
+                mouse_combo_handler::process(
+                    manager, state, config, mod_mask, button, handle, target,
+                )
+            }
+
+            DisplayEvent::CloseWindow(handle) => {
+                let act = DisplayAction::KillWindow(handle);
+                manager.actions.push_back(act);
+                false
+            }
+
+            DisplayEvent::StartDrag(handle, resize) => {
+                mouse_combo_handler::start_drag(manager, handle, resize)
             }
 
-            DisplayEvent::ChangeToNormalMode => {
+            DisplayEvent::ChangeToNormalMode(x, y) => {
+                if self.config.drag_outline() && manager.mode != Mode::Normal {
+                    manager.actions.push_back(DisplayAction::DrawOutline(None));
+                }
+                if let Mode::MovingWindow(handle) = manager.mode {
+                    if self.config.drop_zones() {
+                        window_move_handler::drop_window(manager, &handle, x, y);
+                    }
+                }
                 manager.mode = Mode::Normal;
                 //look through the config and build a command if its defined in the config
                 let act = DisplayAction::NormalMode;
@@ -75,8 +141,11 @@ impl<C: Config> DisplayEventHandler<C> {
             }
 
             DisplayEvent::Movement(handle, x, y) => {
+                self.track_pointer(manager, x, y);
                 if manager.screens.iter().any(|s| s.root == handle)
                     && manager.focus_manager.behaviour == FocusBehaviour::Sloppy
+                    && !(manager.focus_manager.mouse_focus_ignores_docks
+                        && over_dock(manager, x, y))
                 {
                     return focus_handler::focus_workspace_under_cursor(manager, x, y);
                 }
@@ -84,22 +153,20 @@ impl<C: Config> DisplayEventHandler<C> {
             }
 
             DisplayEvent::MoveWindow(handle, time, x, y) => {
-                //limit the frame rate to 60f/sec. otherwise you get lag
-                let mut refresh = false;
-                if (time - manager.frame_rate_limitor) > (1000 / 60) {
-                    refresh = window_move_handler::process(manager, &handle, x, y);
-                    manager.frame_rate_limitor = time;
-                }
-                refresh
+                let rate = self.config.drag_refresh_rate();
+                let threshold = self.config.drag_threshold();
+                manager.click_tracker.dragged(x, y, threshold)
+                    && drag_frame_due(manager, handle, time, rate)
+                    && window_move_handler::process(manager, &handle, x, y)
+                    && !self.draw_outline(manager, handle)
             }
             DisplayEvent::ResizeWindow(handle, time, x, y) => {
-                //limit the frame rate to 60f/sec. otherwise you get lag
-                let mut refresh = false;
-                if (time - manager.frame_rate_limitor) > (1000 / 60) {
-                    refresh = window_resize_handler::process(manager, &handle, x, y);
-                    manager.frame_rate_limitor = time;
-                }
-                refresh
+                let rate = self.config.drag_refresh_rate();
+                let threshold = self.config.drag_threshold();
+                manager.click_tracker.dragged(x, y, threshold)
+                    && drag_frame_due(manager, handle, time, rate)
+                    && window_resize_handler::process(manager, &handle, x, y)
+                    && !self.draw_outline(manager, handle)
             }
         };
 
@@ -109,4 +176,273 @@ impl<C: Config> DisplayEventHandler<C> {
 
         update_needed
     }
+
+    // With `drag_outline` a window being dragged is only drawn once it is let go, an outline shows
+    // where it goes meanwhile. Returns true if the outline was drawn.
+    fn draw_outline(&self, manager: &mut Manager, handle: WindowHandle) -> bool {
+        if !self.config.drag_outline() {
+            return false;
+        }
+        let window = match manager.windows.iter().find(|w| w.handle == handle) {
+            Some(window) => window,
+            None => return false,
+        };
+        let mut outline = window.calculated_xyhw();
+        outline.set_w(outline.w() + window.border() * 2);
+        outline.set_h(outline.h() + window.border() * 2);
+        let act = DisplayAction::DrawOutline(Some(outline));
+        manager.actions.push_back(act);
+        true
+    }
+
+    /// When the pointer rests on an edge with an `[[edge_action]]`, returns when it is due.
+    #[must_use]
+    pub fn edge_action_due(&self, manager: &Manager) -> Option<Instant> {
+        let (action, since) = self.dwelling_action(manager)?;
+        Some(since + Duration::from_millis(action.delay))
+    }
+
+    /// Runs the action of the edge the pointer rests on, once it is due.
+    /// Returns true if changes need to be rendered.
+    pub fn run_edge_action(&self, manager: &mut Manager, state: &impl State) -> bool {
+        match self.dwelling_action(manager) {
+            Some((action, since)) if since.elapsed() >= Duration::from_millis(action.delay) => {
+                manager.edge_tracker.fired();
+                let config = &self.config;
+                let update_needed = command_handler::process(
+                    manager,
+                    state,
+                    config,
+                    &action.command,
+                    &action.value,
+                );
+                if update_needed {
+                    update_windows(manager);
+                }
+                update_needed
+            }
+            _ => false,
+        }
+    }
+
+    /// Follows the pointer along the edges while it is over windows, where the root reports no
+    /// movement.
+    pub fn track_pointer(&self, manager: &mut Manager, x: i32, y: i32) {
+        let edge = edge_at(manager, x, y);
+        manager.edge_tracker.moved(edge, Instant::now());
+    }
+
+    /// Whether any `[[edge_action]]` is configured.
+    #[must_use]
+    pub fn has_edge_actions(&self) -> bool {
+        !self.config.edge_actions().is_empty()
+    }
+
+    fn dwelling_action(&self, manager: &Manager) -> Option<(EdgeAction, Instant)> {
+        let (edge, since) = manager.edge_tracker.dwelling()?;
+        let actions = self.config.edge_actions();
+        let action = actions.into_iter().find(|a| &a.edge == edge)?;
+        Some((action, since))
+    }
+
+    // Runs the keybind of a key combo. Only keybinds asking for it run again while their key is
+    // held and X repeats it.
+    fn key_pressed(
+        &self,
+        manager: &mut Manager,
+        state: &impl State,
+        mod_mask: ModMask,
+        xkeysym: XKeysym,
+        repeated: bool,
+    ) -> bool {
+        //look through the config and build a command if its defined in the config
+        let build = CommandBuilder::new(self.mapped_bindings(manager));
+        match build.find_keybind_for(mod_mask, xkeysym) {
+            Some(keybind) if keybind.repeat || !repeated => {
+                command_handler::process_keybind(manager, state, &self.config, keybind)
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the keybinds with the mod key mapped, honoring a modkey set at runtime.
+    #[must_use]
+    pub fn mapped_bindings(&self, manager: &Manager) -> Vec<Keybind> {
+        key_grabs(manager, &self.config)
+    }
+
+    /// Grabs only the `essential` keybinds while a window with the `pass_keys` rule is focused,
+    /// and all of them again once another window is.
+    pub fn sync_key_grabs(&self, manager: &mut Manager) {
+        let passed = manager.keys_passed_through();
+        if passed != manager.keys_passed {
+            manager.keys_passed = passed;
+            let bindings = self.mapped_bindings(manager);
+            manager
+                .actions
+                .push_back(DisplayAction::ReloadKeyGrabs(bindings));
+        }
+    }
+}
+
+/// The keybinds to grab, with the modkey set by `SetModKey`.
+pub fn key_grabs(manager: &Manager, config: &impl Config) -> Vec<Keybind> {
+    let mut bindings = match &manager.modkey {
+        Some(modkey) => config.mapped_bindings_for(modkey),
+        None => config.mapped_bindings(),
+    };
+    if manager.keys_passed {
+        bindings.retain(|k| k.essential);
+    }
+    bindings
+}
+
+// The edge of the screen the point is on, if any.
+fn edge_at(manager: &Manager, x: i32, y: i32) -> Option<Side> {
+    let screen = manager.screens.iter().find(|s| s.contains_point(x, y))?;
+    let bbox = &screen.bbox;
+    if x <= bbox.x {
+        Some(Side::Left)
+    } else if x >= bbox.x + bbox.width - 1 {
+        Some(Side::Right)
+    } else if y <= bbox.y {
+        Some(Side::Top)
+    } else if y >= bbox.y + bbox.height - 1 {
+        Some(Side::Bottom)
+    } else {
+        None
+    }
+}
+
+// Whether the point is on a dock, or on the root where a dock keeps space free.
+fn over_dock(manager: &Manager, x: i32, y: i32) -> bool {
+    let on_dock = manager
+        .windows
+        .iter()
+        .any(|w| w.type_ == WindowType::Dock && w.contains_point(x, y));
+    on_dock
+        || !manager
+            .workspaces
+            .iter()
+            .any(|ws| ws.contains_point_clear_of_docks(x, y))
+}
+
+/// Limits the frame rate of a window being moved or resized to `rate` frames per second,
+/// otherwise you get lag. Returns true if a new frame can be drawn at `time`.
+fn drag_frame_due(manager: &mut Manager, handle: WindowHandle, time: c_ulong, rate: u32) -> bool {
+    let interval = c_ulong::from(1000_u32.checked_div(rate).unwrap_or(0));
+    let last = manager.frame_rate_limitors.entry(handle).or_default();
+    if *last != 0 && time.wrapping_sub(*last) < interval {
+        return false;
+    }
+    *last = time;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drag_frames_should_be_limited_per_window() {
+        let mut manager = Manager::new_test();
+        let first = WindowHandle::MockHandle(1);
+        let second = WindowHandle::MockHandle(2);
+        assert!(drag_frame_due(&mut manager, first, 1000, 50));
+        assert!(!drag_frame_due(&mut manager, first, 1010, 50));
+        assert!(drag_frame_due(&mut manager, second, 1010, 50));
+        assert!(drag_frame_due(&mut manager, first, 1020, 50));
+        assert!(drag_frame_due(&mut manager, first, 1020, 0));
+    }
+
+    #[test]
+    fn focus_should_lock_on_fullscreen_ruled_windows() {
+        use crate::config::WindowRule;
+        use crate::models::{Screen, Window, WindowState};
+
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        manager.window_rules.push(WindowRule {
+            window_class: Some("game".to_owned()),
+            window_title: None,
+            spawn_on_tag: 0,
+            follow_window: false,
+            focus_lock: true,
+            pass_keys: false,
+            unmanaged: false,
+        });
+        let handle = WindowHandle::MockHandle(1);
+        let mut game = Window::new(handle, None, None);
+        game.res_class = Some("game".to_owned());
+        window_handler::created(&mut manager, game, -1, -1);
+        focus_handler::focus_window(&mut manager, &handle);
+        assert!(!manager.focus_locked());
+
+        manager.windows[0].set_states(vec![WindowState::Fullscreen]);
+        assert!(manager.focus_locked());
+
+        manager.window_rules[0].focus_lock = false;
+        assert!(!manager.focus_locked());
+    }
+
+    #[test]
+    fn keys_should_pass_through_while_ruled_windows_are_focused() {
+        use crate::config::WindowRule;
+        use crate::models::{Screen, Window};
+
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        manager.window_rules.push(WindowRule {
+            window_class: Some("qemu".to_owned()),
+            window_title: None,
+            spawn_on_tag: 0,
+            follow_window: false,
+            focus_lock: false,
+            pass_keys: true,
+            unmanaged: false,
+        });
+        let vm = WindowHandle::MockHandle(1);
+        let terminal = WindowHandle::MockHandle(2);
+        let mut window = Window::new(vm, None, None);
+        window.res_class = Some("qemu".to_owned());
+        window_handler::created(&mut manager, window, -1, -1);
+        window_handler::created(&mut manager, Window::new(terminal, None, None), -1, -1);
+
+        focus_handler::focus_window(&mut manager, &vm);
+        assert!(manager.keys_passed_through());
+        focus_handler::focus_window(&mut manager, &terminal);
+        assert!(!manager.keys_passed_through());
+    }
+
+    #[test]
+    fn points_on_docks_and_the_space_they_keep_should_be_over_a_dock() {
+        use crate::models::{Window, XyhwBuilder};
+        use crate::utils::testing::TestManager;
+
+        let mut manager = TestManager::with_screens(2).build();
+        let mut bar = Window::new(WindowHandle::MockHandle(1), None, None);
+        bar.type_ = WindowType::Dock;
+        bar.strut = Some(
+            XyhwBuilder {
+                w: 1600,
+                h: 20,
+                ..XyhwBuilder::default()
+            }
+            .into(),
+        );
+        manager.windows.push(bar);
+        window_handler::update_workspace_avoid_list(&mut manager);
+        assert!(over_dock(&manager, 790, 10));
+        assert!(!over_dock(&manager, 790, 300));
+
+        manager.windows[0].normal = XyhwBuilder {
+            x: 700,
+            y: 500,
+            w: 200,
+            h: 100,
+            ..XyhwBuilder::default()
+        }
+        .into();
+        assert!(over_dock(&manager, 800, 550));
+    }
 }
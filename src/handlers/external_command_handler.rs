@@ -1,10 +1,13 @@
-use super::{command_handler, focus_handler, goto_tag_handler, Command, Manager};
+use super::{command_handler, focus_handler, goto_tag_handler, Command, Manager, WindowHandle};
 use crate::config::Config;
 use crate::config::ThemeLoader;
 use crate::config::ThemeSetting;
+use crate::models::Side;
 use crate::state::State;
 use crate::utils::command_pipe::ExternalCommand;
+use crate::utils::diagnostics;
 use crate::utils::window_updater::update_windows;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 pub fn process(
@@ -14,6 +17,7 @@ pub fn process(
     theme_loader: &impl ThemeLoader,
     command: ExternalCommand,
 ) -> bool {
+    log::debug!("External command: {:?}", command);
     let needs_redraw = process_work(manager, state, config, theme_loader, command);
     if needs_redraw {
         update_windows(manager);
@@ -21,6 +25,7 @@ pub fn process(
     needs_redraw
 }
 
+#[allow(clippy::too_many_lines)]
 fn process_work(
     manager: &mut Manager,
     state: &impl State,
@@ -44,15 +49,81 @@ fn process_work(
             &Command::ToggleScratchPad,
             &Some(name),
         ),
+        ExternalCommand::AttachToScratchPad(name) => command_handler::process(
+            manager,
+            state,
+            config,
+            &Command::AttachToScratchPad,
+            &Some(name),
+        ),
+        ExternalCommand::ReleaseScratchPad => {
+            command_handler::process(manager, state, config, &Command::ReleaseScratchPad, &None)
+        }
         ExternalCommand::ToggleFullScreen => {
             command_handler::process(manager, state, config, &Command::ToggleFullScreen, &None)
         }
+        ExternalCommand::ToggleFakeFullscreen => command_handler::process(
+            manager,
+            state,
+            config,
+            &Command::ToggleFakeFullscreen,
+            &None,
+        ),
         ExternalCommand::SendWorkspaceToTag(ws_index, tag_index) => {
             send_workspace_to_tag(manager, ws_index, tag_index)
         }
+        ExternalCommand::FocusWindowByHandle(id) => focus_window_by_handle(manager, id),
+        ExternalCommand::SetLogLevel(level) => {
+            if !diagnostics::set_log_level(&level) {
+                log::warn!("Ignoring unknown log level: {}", level);
+            }
+            false
+        }
+        ExternalCommand::DumpDiagnostics(path) => {
+            dump_diagnostics(manager, path);
+            false
+        }
+        ExternalCommand::ReserveSpace(ws_index, side, value) => {
+            reserve_space(manager, ws_index, side, value)
+        }
+        ExternalCommand::ReleaseSpace(ws_index, side) => {
+            release_space(manager, ws_index, side.as_ref())
+        }
         ExternalCommand::SendWindowToTag(tag_index) => {
             send_window_to_tag(manager, state, config, tag_index)
         }
+        ExternalCommand::ToggleWindowTag(tag_index) => {
+            //tag number as 1 based.
+            let tag_num = format!("{}", tag_index + 1);
+            command_handler::process(
+                manager,
+                state,
+                config,
+                &Command::ToggleWindowTag,
+                &Some(tag_num),
+            )
+        }
+        ExternalCommand::SetWindowTag(tag, pattern) => {
+            let val = format!("{} {}", tag, pattern);
+            command_handler::process(manager, state, config, &Command::SetWindowTag, &Some(val))
+        }
+        ExternalCommand::SaveSnapshot(name) => {
+            command_handler::process(manager, state, config, &Command::SaveSnapshot, &Some(name))
+        }
+        ExternalCommand::LoadSnapshot(name) => {
+            command_handler::process(manager, state, config, &Command::LoadSnapshot, &Some(name))
+        }
+        ExternalCommand::ToggleTagView(tag_index) => {
+            //tag number as 1 based.
+            let tag_num = format!("{}", tag_index + 1);
+            command_handler::process(
+                manager,
+                state,
+                config,
+                &Command::ToggleTagView,
+                &Some(tag_num),
+            )
+        }
         ExternalCommand::SetLayout(layout) => {
             command_handler::process(manager, state, config, &Command::SetLayout, &Some(layout))
         }
@@ -63,21 +134,47 @@ fn process_work(
             &Command::SetMarginMultiplier,
             &Some(margin_multiplier),
         ),
+        ExternalCommand::SetMainWidth(width) => {
+            command_handler::process(manager, state, config, &Command::SetMainWidth, &Some(width))
+        }
+        ExternalCommand::SetModKey(modkey) => {
+            command_handler::process(manager, state, config, &Command::SetModKey, &Some(modkey))
+        }
+        ExternalCommand::SetMouseKey(mousekey) => {
+            let val = Some(mousekey);
+            command_handler::process(manager, state, config, &Command::SetMouseKey, &val)
+        }
         ExternalCommand::SwapScreens => {
             command_handler::process(manager, state, config, &Command::SwapTags, &None)
         }
+        ExternalCommand::SwapTags(ws_index_a, ws_index_b) => {
+            let pair = format!("{} {}", ws_index_a, ws_index_b);
+            command_handler::process(manager, state, config, &Command::SwapTags, &Some(pair))
+        }
         ExternalCommand::MoveWindowToLastWorkspace => {
             command_handler::process(manager, state, config, &Command::MoveToLastWorkspace, &None)
         }
         ExternalCommand::FloatingToTile => {
             command_handler::process(manager, state, config, &Command::FloatingToTile, &None)
         }
+        ExternalCommand::GroupWindow => {
+            command_handler::process(manager, state, config, &Command::GroupWindow, &None)
+        }
+        ExternalCommand::UngroupWindow => {
+            command_handler::process(manager, state, config, &Command::UngroupWindow, &None)
+        }
+        ExternalCommand::CycleGroup => {
+            command_handler::process(manager, state, config, &Command::CycleGroup, &None)
+        }
         ExternalCommand::MoveWindowUp => {
             command_handler::process(manager, state, config, &Command::MoveWindowUp, &None)
         }
         ExternalCommand::MoveWindowTop => {
             command_handler::process(manager, state, config, &Command::MoveWindowTop, &None)
         }
+        ExternalCommand::ZoomWindow => {
+            command_handler::process(manager, state, config, &Command::ZoomWindow, &None)
+        }
         ExternalCommand::MoveWindowDown => {
             command_handler::process(manager, state, config, &Command::MoveWindowDown, &None)
         }
@@ -93,15 +190,15 @@ fn process_work(
         ExternalCommand::FocusPreviousTag => {
             command_handler::process(manager, state, config, &Command::FocusPreviousTag, &None)
         }
-        ExternalCommand::FocusWorkspaceNext => {
-            command_handler::process(manager, state, config, &Command::FocusWorkspaceNext, &None)
+        ExternalCommand::FocusWorkspaceNext(warp) => {
+            command_handler::process(manager, state, config, &Command::FocusWorkspaceNext, &warp)
         }
-        ExternalCommand::FocusWorkspacePrevious => command_handler::process(
+        ExternalCommand::FocusWorkspacePrevious(warp) => command_handler::process(
             manager,
             state,
             config,
             &Command::FocusWorkspacePrevious,
-            &None,
+            &warp,
         ),
         ExternalCommand::NextLayout => {
             command_handler::process(manager, state, config, &Command::NextLayout, &None)
@@ -112,6 +209,34 @@ fn process_work(
         ExternalCommand::RotateTag => {
             command_handler::process(manager, state, config, &Command::RotateTag, &None)
         }
+        ExternalCommand::ToggleTiling => {
+            command_handler::process(manager, state, config, &Command::ToggleTiling, &None)
+        }
+        ExternalCommand::HideDock => {
+            command_handler::process(manager, state, config, &Command::HideDock, &None)
+        }
+        ExternalCommand::ShowDock => {
+            command_handler::process(manager, state, config, &Command::ShowDock, &None)
+        }
+        ExternalCommand::ToggleDock => {
+            command_handler::process(manager, state, config, &Command::ToggleDock, &None)
+        }
+        ExternalCommand::ShowKeybindHelp => {
+            command_handler::process(manager, state, config, &Command::ShowKeybindHelp, &None)
+        }
+        ExternalCommand::TogglePresentationMode(padding) => command_handler::process(
+            manager,
+            state,
+            config,
+            &Command::TogglePresentationMode,
+            &padding,
+        ),
+        ExternalCommand::LockScreen => {
+            command_handler::process(manager, state, config, &Command::LockScreen, &None)
+        }
+        ExternalCommand::UndoWindowOperation => {
+            command_handler::process(manager, state, config, &Command::UndoWindowOperation, &None)
+        }
         ExternalCommand::CloseWindow => {
             command_handler::process(manager, state, config, &Command::CloseWindow, &None)
         }
@@ -133,13 +258,45 @@ fn load_theme(manager: &mut Manager, theme: ThemeSetting) -> bool {
 }
 
 fn send_workspace_to_tag(manager: &mut Manager, ws_index: usize, tag_index: usize) -> bool {
-    if ws_index < manager.workspaces.len() && tag_index < manager.tags.len() {
-        let workspace = &manager.workspaces[ws_index].clone();
-        focus_handler::focus_workspace(manager, workspace);
-        goto_tag_handler::process(manager, tag_index + 1);
-        return true;
+    goto_tag_handler::show_on_workspace(manager, ws_index, tag_index + 1)
+}
+
+/// Focuses the window with the X window id, first showing its tag if no workspace displays it.
+fn focus_window_by_handle(manager: &mut Manager, id: u64) -> bool {
+    let handle = WindowHandle::XlibHandle(id);
+    let window = match manager.windows.iter().find(|w| w.handle == handle) {
+        Some(w) => w,
+        None => return false,
+    };
+    if !manager.workspaces.iter().any(|ws| ws.is_displaying(window)) {
+        //hidden tags, like the one of hidden scratchpads, are never shown
+        let shown_tag = window
+            .tags
+            .iter()
+            .find(|tag| manager.tags.iter().any(|t| &t.id == *tag && !t.hidden));
+        match shown_tag.cloned() {
+            Some(tag) => goto_tag_handler::process_tag(manager, &tag),
+            None => return false,
+        };
+    }
+    focus_handler::focus_window(manager, &handle)
+}
+
+fn reserve_space(manager: &mut Manager, ws_index: usize, side: Side, value: i32) -> bool {
+    match manager.workspaces.get_mut(ws_index) {
+        Some(ws) => {
+            ws.reserve(side, value);
+            true
+        }
+        None => false,
+    }
+}
+
+fn release_space(manager: &mut Manager, ws_index: usize, side: Option<&Side>) -> bool {
+    match manager.workspaces.get_mut(ws_index) {
+        Some(ws) => ws.release(side),
+        None => false,
     }
-    false
 }
 
 fn send_window_to_tag(
@@ -148,7 +305,7 @@ fn send_window_to_tag(
     config: &impl Config,
     tag_index: usize,
 ) -> bool {
-    if tag_index < manager.tags.len() {
+    if manager.numbered_tag(tag_index + 1).is_some() {
         //tag number as 1 based.
         let tag_num = format!("{}", tag_index + 1);
         return command_handler::process(
@@ -161,3 +318,67 @@ fn send_window_to_tag(
     }
     false
 }
+
+fn default_diagnostics_path() -> crate::errors::Result<PathBuf> {
+    let dirs = xdg::BaseDirectories::with_prefix("leftwm")?;
+    Ok(dirs.place_runtime_file("diagnostics.jsonl")?)
+}
+
+fn dump_diagnostics(manager: &Manager, path: Option<PathBuf>) {
+    let path = match path {
+        Some(path) => path,
+        None => match default_diagnostics_path() {
+            Ok(path) => path,
+            Err(err) => {
+                log::error!("Cannot find where to dump the diagnostics: {}", err);
+                return;
+            }
+        },
+    };
+    match manager.diagnostics.dump(&path) {
+        Ok(()) => log::info!("Dumped the latest events and actions to {:?}", path),
+        Err(err) => log::error!("Cannot dump the diagnostics to {:?}: {}", path, err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::screen_create_handler;
+    use crate::models::{Screen, Tag, Window};
+
+    #[test]
+    fn focus_window_by_handle_should_show_the_windows_tag() {
+        let mut manager = Manager::new_test();
+        manager.tags.push(Tag::new("1"));
+        manager.tags.push(Tag::new("2"));
+        screen_create_handler::process(&mut manager, Screen::default());
+        let mut window = Window::new(WindowHandle::XlibHandle(42), None, None);
+        window.tags = vec!["2".to_owned()];
+        manager.windows.push(window);
+
+        assert!(focus_window_by_handle(&mut manager, 42));
+        assert_eq!(manager.workspaces[0].tags, vec!["2".to_owned()]);
+        assert_eq!(
+            manager.focused_window().map(|w| w.handle),
+            Some(WindowHandle::XlibHandle(42))
+        );
+        assert!(!focus_window_by_handle(&mut manager, 7));
+    }
+
+    #[test]
+    fn focus_window_by_handle_should_leave_hidden_scratchpads_hidden() {
+        let mut manager = Manager::new_test();
+        manager.tags.push(Tag::new("1"));
+        let mut nsp = Tag::new("NSP");
+        nsp.hidden = true;
+        manager.tags.push(nsp);
+        screen_create_handler::process(&mut manager, Screen::default());
+        let mut window = Window::new(WindowHandle::XlibHandle(42), None, None);
+        window.tags = vec!["NSP".to_owned()];
+        manager.windows.push(window);
+
+        assert!(!focus_window_by_handle(&mut manager, 42));
+        assert_eq!(manager.workspaces[0].tags, vec!["1".to_owned()]);
+    }
+}
@@ -1,7 +1,10 @@
 #![allow(clippy::wildcard_imports)]
 
 use super::*;
-use crate::{display_action::DisplayAction, models::FocusBehaviour};
+use crate::{
+    display_action::DisplayAction,
+    models::{FocusBehaviour, WindowState, WindowType},
+};
 
 /// Marks a workspace as the focused workspace.
 //NOTE: should only be called externally from this file
@@ -38,7 +41,12 @@ fn focus_workspace_work(manager: &mut Manager, workspace_id: Option<i32>) -> Opt
 
 /// Create a `DisplayAction` to cause this window to become focused
 pub fn focus_window(manager: &mut Manager, handle: &WindowHandle) -> bool {
-    let window = match focus_window_by_handle_work(manager, handle) {
+    //a modal dialog takes the focus of the window it belongs to
+    let handle = &modal_of(manager, *handle);
+    if focus_window_by_handle_work(manager, handle).is_none() {
+        return false;
+    }
+    let window = match manager.windows.iter().find(|w| &w.handle == handle) {
         Some(w) => w,
         None => return false,
     };
@@ -47,7 +55,7 @@ pub fn focus_window(manager: &mut Manager, handle: &WindowHandle) -> bool {
     let (focused_window_tag, workspace_id) = match manager
         .workspaces
         .iter()
-        .find(|ws| ws.is_displaying(&window))
+        .find(|ws| ws.is_displaying(window))
     {
         Some(ws) => (
             ws.tags.iter().find(|t| window.has_tag(t)).cloned(),
@@ -66,25 +74,53 @@ pub fn focus_window(manager: &mut Manager, handle: &WindowHandle) -> bool {
     true
 }
 
-fn focus_window_by_handle_work(manager: &mut Manager, handle: &WindowHandle) -> Option<Window> {
+/// Focuses the top normal window managed on the workspace, or no window if it has none.
+pub fn focus_top_window(manager: &mut Manager, workspace: &Workspace) {
+    let handle = manager
+        .windows
+        .iter()
+        .find(|w| workspace.is_managed(w) && w.type_ == WindowType::Normal && !w.refuses_input)
+        .map(|w| w.handle);
+    focus_window_or_none(manager, handle);
+}
+
+/// Focuses the window, or with `None` leaves no window focused.
+pub fn focus_window_or_none(manager: &mut Manager, handle: Option<WindowHandle>) {
+    if let Some(handle) = handle {
+        focus_window(manager, &handle);
+    } else {
+        manager.actions.push_back(DisplayAction::Unfocus);
+        manager.focus_manager.window_history.push_front(None);
+    }
+}
+
+fn focus_window_by_handle_work(manager: &mut Manager, handle: &WindowHandle) -> Option<()> {
     //Docks don't want to get focus. If they do weird things happen. They don't get events...
     //Do the focus, Add the action to the list of action
-    let found: &Window = manager.windows.iter().find(|w| &w.handle == handle)?;
-    if found.is_unmanaged() {
+    let found = manager.windows.iter_mut().find(|w| &w.handle == handle)?;
+    //windows refusing input, like some splash screens, would swallow the focus
+    if found.is_unmanaged() || found.refuses_input {
         return None;
     }
+    //focusing a window answers its demand for attention
+    found.clear_urgent();
     //NOTE: we are intentionally creating the focus event even if we think this window
     //is already in focus. This is to force the DM to update its knowledge of the focused window
-    let act = DisplayAction::WindowTakeFocus(found.clone());
+    let act = DisplayAction::WindowTakeFocus(*handle, found.never_focus);
     manager.actions.push_back(act);
 
     //no new history if no change
     if let Some(fw) = manager.focused_window() {
         if &fw.handle == handle {
             //NOTE: we still made the action so return some
-            return Some(found.clone());
+            return Some(());
         }
     }
+    manager.metrics.focus_changes += 1;
+    //new windows of the `Manual` layout open next to this one
+    for tag in &mut manager.tags {
+        tag.split_tree.focus(handle);
+    }
     //clean old ones
     manager.focus_manager.window_history.truncate(10);
     //add this focus to the history
@@ -93,7 +129,22 @@ fn focus_window_by_handle_work(manager: &mut Manager, handle: &WindowHandle) ->
         .window_history
         .push_front(Some(*handle));
 
-    Some(found.clone())
+    Some(())
+}
+
+/// Returns the modal dialog open over this window, or the window itself.
+fn modal_of(manager: &Manager, mut handle: WindowHandle) -> WindowHandle {
+    //bounded, in case transients point at each other
+    for _ in 0..manager.windows.len() {
+        let modal = manager.windows.iter().find(|w| {
+            w.transient == Some(handle) && w.has_state(&WindowState::Modal) && !w.is_unmanaged()
+        });
+        match modal {
+            Some(modal) => handle = modal.handle,
+            None => break,
+        }
+    }
+    handle
 }
 
 pub fn validate_focus_at(manager: &mut Manager, x: i32, y: i32) -> bool {
@@ -102,16 +153,15 @@ pub fn validate_focus_at(manager: &mut Manager, x: i32, y: i32) -> bool {
         None => return false,
     };
     //only look at windows we can focus
-    let found: Option<Window> = manager
+    let found: Option<WindowHandle> = manager
         .windows
         .iter()
         .filter(|x| x.can_focus())
         .find(|w| w.contains_point(x, y))
-        .cloned();
+        .map(|w| w.handle);
     match found {
-        Some(window) => {
+        Some(handle) => {
             //only do the focus if we need to
-            let handle = window.handle;
             if current.handle == handle {
                 return false;
             }
@@ -170,7 +220,7 @@ pub fn focus_workspace_under_cursor(manager: &mut Manager, x: i32, y: i32) -> bo
     if let Some(w) = manager
         .workspaces
         .iter()
-        .find(|ws| ws.contains_point(x, y) && ws.id != focused_id)
+        .find(|ws| ws.contains_point(x, y) && ws.id != focused_id && !ws.inactive)
         .cloned()
     {
         return focus_workspace(manager, &w);
@@ -185,14 +235,16 @@ pub fn focus_tag(manager: &mut Manager, tag: &str) -> bool {
         return false;
     }
     // check each workspace, if its displaying this tag it should be focused too
-    let to_focus: Vec<Workspace> = manager
+    let to_focus: Vec<usize> = manager
         .workspaces
         .iter()
-        .filter(|w| w.has_tag(tag))
-        .cloned()
+        .enumerate()
+        .filter(|(_, w)| w.has_tag(tag))
+        .map(|(i, _)| i)
         .collect();
-    for ws in &to_focus {
-        focus_workspace_work(manager, ws.id);
+    for &index in &to_focus {
+        let id = manager.workspaces[index].id;
+        focus_workspace_work(manager, id);
     }
     //make sure the focused window is on this workspace
     if manager.focus_manager.behaviour == FocusBehaviour::Sloppy {
@@ -200,7 +252,8 @@ pub fn focus_tag(manager: &mut Manager, tag: &str) -> bool {
         manager.actions.push_back(act);
     } else if let Some(handle) = manager.focus_manager.tags_last_window.get(tag).copied() {
         focus_window_by_handle_work(manager, &handle);
-    } else if let Some(ws) = to_focus.first() {
+    } else if let Some(&index) = to_focus.first() {
+        let ws = &manager.workspaces[index];
         let handle = manager
             .windows
             .iter()
@@ -212,11 +265,9 @@ pub fn focus_tag(manager: &mut Manager, tag: &str) -> bool {
     }
 
     // Unfocus last window if the target tag is empty
-    if let Some(window) = manager.focused_window().cloned() {
-        if !window.tags.contains(&tag.to_owned()) {
-            manager.actions.push_back(DisplayAction::Unfocus);
-            manager.focus_manager.window_history.push_front(None);
-        }
+    if matches!(manager.focused_window(), Some(w) if !w.has_tag(tag)) {
+        manager.actions.push_back(DisplayAction::Unfocus);
+        manager.focus_manager.window_history.push_front(None);
     }
     true
 }
@@ -314,6 +365,29 @@ mod tests {
         assert_eq!(start_length, end_length, "expected no new history event");
     }
 
+    #[test]
+    fn windows_refusing_input_should_never_be_focused() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        let terminal = Window::new(WindowHandle::MockHandle(1), None, None);
+        let mut splash = Window::new(WindowHandle::MockHandle(2), None, None);
+        splash.refuses_input = true;
+        window_handler::created(&mut manager, terminal.clone(), -1, -1);
+        window_handler::created(&mut manager, splash.clone(), -1, -1);
+        manager.actions.clear();
+
+        assert!(!focus_window(&mut manager, &splash.handle));
+        assert_eq!(
+            manager.focused_window().map(|w| w.handle),
+            Some(terminal.handle)
+        );
+        assert!(!manager
+            .focus_manager
+            .window_history
+            .contains(&Some(splash.handle)));
+        assert!(manager.actions.is_empty());
+    }
+
     #[test]
     fn focusing_a_tag_should_make_it_active() {
         let mut manager = Manager::new_test();
@@ -2,11 +2,10 @@
 use super::*;
 
 pub fn process(manager: &mut Manager, tag_num: usize) -> bool {
-    if tag_num > manager.tags.len() || tag_num < 1 {
-        return false;
-    }
-
-    let tag = manager.tags[tag_num - 1].clone();
+    let tag = match manager.numbered_tag(tag_num) {
+        Some(tag) => tag.clone(),
+        None => return false,
+    };
     let new_tags = vec![tag.id.clone()];
     //no focus safety check
     let old_tags = match manager.focused_workspace() {
@@ -32,12 +31,97 @@ pub fn process(manager: &mut Manager, tag_num: usize) -> bool {
     }
     focus_handler::focus_tag(manager, &tag.id);
     manager.update_docks();
+    manager.restack_workspaces();
+    true
+}
+
+/// Shows the tag with the id on the focused workspace, or with independent tags on the workspace
+/// it belongs to, focusing that workspace first.
+pub fn process_tag(manager: &mut Manager, tag: &str) -> bool {
+    let owner = manager
+        .tags
+        .iter()
+        .find(|t| t.id == tag)
+        .and_then(|t| t.workspace);
+    let focused = manager.focused_workspace().and_then(|ws| ws.id);
+    if owner.is_some() && owner != focused {
+        let ws = manager.workspaces.iter().find(|ws| ws.id == owner).cloned();
+        if let Some(ws) = ws {
+            focus_handler::focus_workspace(manager, &ws);
+        }
+    }
+    match manager.tag_number(tag) {
+        Some(tag_num) => process(manager, tag_num),
+        None => false,
+    }
+}
+
+/// Focuses the workspace that is already displaying the tag, if there is one.
+pub fn focus_if_visible(manager: &mut Manager, tag_num: usize) -> bool {
+    let tag = match manager.numbered_tag(tag_num) {
+        Some(tag) => &tag.id,
+        None => return false,
+    };
+    let workspace = manager
+        .workspaces
+        .iter()
+        .find(|ws| ws.has_tag(tag))
+        .cloned();
+    match workspace {
+        Some(ws) => focus_handler::focus_workspace(manager, &ws),
+        None => false,
+    }
+}
+
+/// Displays the tag on the workspace at `ws_index` without moving focus to it. A workspace
+/// already displaying the tag is given the old tags of the target workspace.
+pub fn show_on_workspace(manager: &mut Manager, ws_index: usize, tag_num: usize) -> bool {
+    let ws_id = match manager.workspaces.get(ws_index) {
+        Some(ws) => ws.id,
+        None => return false,
+    };
+    let tag = tag_num
+        .checked_sub(1)
+        .and_then(|i| manager.workspace_tags(ws_id).nth(i));
+    //hidden tags, like the one of scratchpads, are never shown
+    let new_tags = match tag {
+        Some(tag) if !tag.hidden => vec![tag.id.clone()],
+        _ => return false,
+    };
+    let old_tags = manager.workspaces[ws_index].tags.clone();
+    if old_tags == new_tags {
+        return false;
+    }
+    //the workspace showing the tag, even among others, gets the tags of this one
+    if let Some(ws) = manager
+        .workspaces
+        .iter_mut()
+        .enumerate()
+        .find(|(i, ws)| *i != ws_index && ws.tags.contains(&new_tags[0]))
+        .map(|(_, ws)| ws)
+    {
+        ws.tags = old_tags;
+    }
+    manager.workspaces[ws_index].tags = new_tags;
+
+    // the focused workspace may have been either side of the swap
+    let focused_tag = manager
+        .focused_workspace()
+        .and_then(|ws| ws.tags.first().cloned());
+    if let Some(tag) = focused_tag {
+        if manager.focused_tag(0).as_ref() != Some(&tag) {
+            focus_handler::focus_tag(manager, &tag);
+        }
+    }
+    manager.update_docks();
+    manager.restack_workspaces();
     true
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::Tag;
 
     #[test]
     fn going_to_a_workspace_that_is_already_visible_should_not_duplicate_the_workspace() {
@@ -47,6 +131,134 @@ mod tests {
         assert_eq!(manager.workspaces[1].tags, ["1".to_owned()]);
     }
 
+    #[test]
+    fn focusing_a_visible_tag_should_focus_its_workspace_without_swapping() {
+        let mut manager = two_screen_mock_manager();
+        assert!(focus_if_visible(&mut manager, 1));
+        assert_eq!(manager.workspaces[0].tags, ["1".to_owned()]);
+        assert_eq!(manager.workspaces[1].tags, ["2".to_owned()]);
+        assert_eq!(manager.focused_workspace().map(|w| w.id), Some(Some(0)));
+        assert!(!focus_if_visible(&mut manager, 3));
+    }
+
+    #[test]
+    fn showing_a_tag_on_another_workspace_should_not_move_focus() {
+        let mut manager = two_screen_mock_manager();
+        manager.tags.push(Tag::new("3"));
+        let first = manager.workspaces[0].clone();
+        focus_handler::focus_workspace(&mut manager, &first);
+        assert!(show_on_workspace(&mut manager, 1, 3));
+        assert_eq!(manager.workspaces[0].tags, ["1".to_owned()]);
+        assert_eq!(manager.workspaces[1].tags, ["3".to_owned()]);
+        assert_eq!(manager.focused_workspace().map(|w| w.id), Some(Some(0)));
+    }
+
+    #[test]
+    fn showing_a_visible_tag_on_another_workspace_should_swap_them() {
+        let mut manager = two_screen_mock_manager();
+        let first = manager.workspaces[0].clone();
+        focus_handler::focus_workspace(&mut manager, &first);
+        assert!(show_on_workspace(&mut manager, 1, 1));
+        assert_eq!(manager.workspaces[0].tags, ["2".to_owned()]);
+        assert_eq!(manager.workspaces[1].tags, ["1".to_owned()]);
+        assert_eq!(manager.focused_workspace().map(|w| w.id), Some(Some(0)));
+        assert_eq!(manager.focused_tag(0), Some("2".to_owned()));
+        assert!(!show_on_workspace(&mut manager, 2, 1));
+    }
+
+    #[test]
+    fn showing_a_tag_shown_among_others_should_swap_them() {
+        let mut manager = two_screen_mock_manager();
+        manager.tags.push(Tag::new("3"));
+        manager.workspaces[0].tags = vec!["1".to_owned(), "3".to_owned()];
+        assert!(show_on_workspace(&mut manager, 1, 3));
+        assert_eq!(manager.workspaces[0].tags, ["2".to_owned()]);
+        assert_eq!(manager.workspaces[1].tags, ["3".to_owned()]);
+    }
+
+    #[test]
+    fn a_hidden_tag_should_not_be_shown() {
+        let mut manager = two_screen_mock_manager();
+        let mut nsp = Tag::new("NSP");
+        nsp.hidden = true;
+        manager.tags.push(nsp);
+        assert!(!show_on_workspace(&mut manager, 1, 3));
+        assert_eq!(manager.workspaces[1].tags, ["2".to_owned()]);
+    }
+
+    #[test]
+    fn going_to_a_tag_should_restack_it_with_fullscreen_windows_on_top() {
+        use crate::display_action::DisplayAction;
+        use crate::models::WindowState;
+        let mut manager = two_screen_mock_manager();
+        manager.tags.push(Tag::new("3"));
+        for i in 1..=2 {
+            let mut window = Window::new(WindowHandle::MockHandle(i), None, None);
+            window.tag("3");
+            manager.windows.push(window);
+        }
+        manager.windows[1].set_states(vec![WindowState::Fullscreen]);
+        let first = manager.workspaces[0].clone();
+        focus_handler::focus_workspace(&mut manager, &first);
+        manager.actions.clear();
+
+        assert!(process(&mut manager, 3));
+        let restack = manager.actions.iter().find_map(|a| match a {
+            DisplayAction::RestackWorkspace(Some(0), order) => Some(order.clone()),
+            _ => None,
+        });
+        assert_eq!(
+            restack,
+            Some(vec![
+                WindowHandle::MockHandle(2),
+                WindowHandle::MockHandle(1)
+            ])
+        );
+    }
+
+    #[test]
+    fn going_to_a_tag_with_independent_tags_should_only_change_the_focused_workspace() {
+        let mut manager = Manager::new_test();
+        manager.independent_tags = true;
+        manager.tags = vec![Tag::new("1"), Tag::new("2")];
+        screen_create_handler::process(&mut manager, Screen::default());
+        screen_create_handler::process(&mut manager, Screen::default());
+        assert_eq!(manager.workspaces[0].tags, ["1".to_owned()]);
+        assert_eq!(manager.workspaces[1].tags, ["1@1".to_owned()]);
+
+        assert!(process(&mut manager, 2));
+        assert_eq!(manager.workspaces[0].tags, ["1".to_owned()]);
+        assert_eq!(manager.workspaces[1].tags, ["2@1".to_owned()]);
+        let first = manager.workspaces[0].clone();
+        focus_handler::focus_workspace(&mut manager, &first);
+        assert!(process(&mut manager, 2));
+        assert_eq!(manager.workspaces[0].tags, ["2".to_owned()]);
+        assert_eq!(manager.workspaces[1].tags, ["2@1".to_owned()]);
+        assert!(!process(&mut manager, 3));
+    }
+
+    #[test]
+    fn going_to_a_tag_of_another_workspace_should_focus_that_workspace() {
+        let mut manager = Manager::new_test();
+        manager.independent_tags = true;
+        manager.tags = vec![Tag::new("1"), Tag::new("2")];
+        screen_create_handler::process(&mut manager, Screen::default());
+        screen_create_handler::process(&mut manager, Screen::default());
+
+        assert!(process_tag(&mut manager, "2"));
+        assert_eq!(
+            manager.focused_workspace().unwrap().id,
+            manager.workspaces[0].id
+        );
+        assert_eq!(manager.workspaces[0].tags, ["2".to_owned()]);
+        assert!(process_tag(&mut manager, "2@1"));
+        assert_eq!(
+            manager.focused_workspace().unwrap().id,
+            manager.workspaces[1].id
+        );
+        assert_eq!(manager.workspaces[1].tags, ["2@1".to_owned()]);
+    }
+
     fn two_screen_mock_manager() -> Manager {
         let mut manager = Manager::new_test();
         screen_create_handler::process(&mut manager, Screen::default());
@@ -1,11 +1,12 @@
-mod command_handler;
+pub(crate) mod command_handler;
 pub mod display_event_handler;
 pub mod external_command_handler;
-mod focus_handler;
-mod goto_tag_handler;
-mod mouse_combo_handler;
-mod screen_create_handler;
-mod window_handler;
+pub(crate) mod focus_handler;
+pub(crate) mod goto_tag_handler;
+pub(crate) mod mouse_combo_handler;
+mod output_power_handler;
+pub(crate) mod screen_create_handler;
+pub(crate) mod window_handler;
 mod window_move_handler;
 mod window_resize_handler;
 
@@ -1,71 +1,113 @@
-use crate::handlers::focus_handler;
+use crate::config::{Config, MouseTarget, Mousebind};
+use crate::handlers::{command_handler, focus_handler};
 use crate::models::Manager;
 use crate::models::Mode;
 use crate::models::WindowHandle;
-use crate::utils::xkeysym_lookup::Button;
-use crate::utils::xkeysym_lookup::ModMask;
+use crate::state::State;
+use crate::utils::xkeysym_lookup::{self, Button, ModMask};
+use crate::Command;
 use crate::{display_action::DisplayAction, models::FocusBehaviour};
+use std::time::{Duration, Instant};
 use x11_dl::xlib;
 
 pub fn process(
     manager: &mut Manager,
+    state: &impl State,
+    config: &impl Config,
     modmask: ModMask,
     button: Button,
     handle: WindowHandle,
-    modifier: ModMask,
+    target: MouseTarget,
 ) -> bool {
+    let double_click_time = Duration::from_millis(config.double_click_time());
+    let double_click =
+        manager
+            .click_tracker
+            .pressed(modmask, button, handle, Instant::now(), double_click_time);
+    let mousebinds = active_mousebinds(manager, config);
+    let mousebind = find_mousebind(&mousebinds, modmask, button, target, double_click);
+
+    //any other command is handed off as if it came from a keybind
+    if let Some(m) = mousebind.filter(|m| !is_mouse_command(&m.command)) {
+        if target != MouseTarget::Root {
+            focus_handler::focus_window(manager, &handle);
+        }
+        command_handler::process(manager, state, config, &m.command, &m.value);
+        return true;
+    }
+
     //look through the config and build a command if its defined in the config
-    let act = build_action(manager, modmask, button, handle, modifier);
+    let act = build_action(manager, button, handle, mousebind.map(|m| &m.command));
     if let Some(act) = act {
-        //save off the info about position of the window when we started to move/resize
-        manager
-            .windows
-            .iter_mut()
-            .filter(|w| w.handle == handle)
-            .for_each(|w| {
-                if w.floating() {
-                    let offset = w.get_floating_offsets().unwrap_or_default();
-                    w.start_loc = Some(offset);
-                } else {
-                    let container = w.container_size.unwrap_or_default();
-                    let normal = w.normal;
-                    let floating = normal - container;
-                    w.set_floating_offsets(Some(floating));
-                    w.start_loc = Some(floating);
-                    w.set_floating(true);
-                }
-            });
-        manager.move_to_top(&handle);
-        manager.actions.push_back(act);
+        drag(manager, handle, act);
         return false;
     }
 
     true
 }
 
+/// The mousebinds, with the modkey and mousekey set by `SetModKey` and `SetMouseKey`.
+pub fn active_mousebinds(manager: &Manager, config: &impl Config) -> Vec<Mousebind> {
+    config.mapped_mousebinds_for(manager.modkey.as_deref(), manager.mousekey.as_deref())
+}
+
+/// Moves or resizes the window with the mouse as `MouseMoveWindow` and `MouseResizeWindow` do,
+/// for a window that asks to be dragged by its own title bar.
+/// Returns `true` if changes need to be rendered.
+pub fn start_drag(manager: &mut Manager, handle: WindowHandle, resize: bool) -> bool {
+    if manager.mode != Mode::Normal {
+        return false;
+    }
+    let command = if resize {
+        Command::MouseResizeWindow
+    } else {
+        Command::MouseMoveWindow
+    };
+    if let Some(act) = build_action(manager, xlib::Button1, handle, Some(&command)) {
+        drag(manager, handle, act);
+    }
+    false
+}
+
+fn drag(manager: &mut Manager, handle: WindowHandle, act: DisplayAction) {
+    //save off the info about position of the window when we started to move/resize
+    manager
+        .windows
+        .iter_mut()
+        .filter(|w| w.handle == handle)
+        .for_each(|w| {
+            if w.floating() {
+                let offset = w.get_floating_offsets().unwrap_or_default();
+                w.start_loc = Some(offset);
+            } else {
+                //it floats once the drag gets past `drag_threshold`
+                let container = w.container_size.unwrap_or_default();
+                let normal = w.normal;
+                let floating = normal - container;
+                w.set_floating_offsets(Some(floating));
+                w.start_loc = Some(floating);
+            }
+        });
+    manager.move_to_top(&handle);
+    manager.actions.push_back(act);
+}
+
 fn build_action(
     manager: &mut Manager,
-    mod_mask: ModMask,
     button: Button,
     window: WindowHandle,
-    modifier: ModMask,
+    command: Option<&Command>,
 ) -> Option<DisplayAction> {
-    match button {
-        xlib::Button1 => {
-            if mod_mask == modifier || mod_mask == (modifier | xlib::ShiftMask) {
-                let _ = manager
-                    .windows
-                    .iter()
-                    .find(|w| w.handle == window && w.can_move())?;
-                manager.mode = Mode::MovingWindow(window);
-                return Some(DisplayAction::StartMovingWindow(window));
-            }
-            if manager.focus_manager.behaviour == FocusBehaviour::ClickTo {
-                focus_handler::focus_window(manager, &window);
-            }
-            None
+    match command {
+        Some(Command::MouseMoveWindow) => {
+            let _ = manager
+                .windows
+                .iter()
+                .find(|w| w.handle == window && w.can_move())?;
+            manager.mode = Mode::MovingWindow(window);
+            Some(DisplayAction::StartMovingWindow(window))
         }
-        xlib::Button3 => {
+        Some(Command::MouseResizeWindow) => {
             let _ = manager
                 .windows
                 .iter()
@@ -73,6 +115,180 @@ fn build_action(
             manager.mode = Mode::ResizingWindow(window);
             Some(DisplayAction::StartResizingWindow(window))
         }
-        _ => None,
+        _ => {
+            if button == xlib::Button1 && manager.focus_manager.behaviour == FocusBehaviour::ClickTo
+            {
+                focus_handler::focus_window(manager, &window);
+            }
+            None
+        }
+    }
+}
+
+fn is_mouse_command(command: &Command) -> bool {
+    matches!(
+        command,
+        Command::MouseMoveWindow | Command::MouseResizeWindow
+    )
+}
+
+//an exact match wins, otherwise a held Shift is ignored. the second click of a double click
+//triggers a `double_click` bind if there is one, and acts like any other click if not
+fn find_mousebind(
+    mousebinds: &[Mousebind],
+    mod_mask: ModMask,
+    button: Button,
+    target: MouseTarget,
+    double_click: bool,
+) -> Option<&Mousebind> {
+    let find = |mask: ModMask, double: bool| {
+        mousebinds.iter().find(|m| {
+            m.target == target
+                && m.double_click == double
+                && xkeysym_lookup::into_button(&m.button) == Some(button)
+                && xkeysym_lookup::into_modmask(&m.modifier) == mask
+        })
+    };
+    let find =
+        |double: bool| find(mod_mask, double).or_else(|| find(mod_mask & !xlib::ShiftMask, double));
+    if double_click {
+        find(true).or_else(|| find(false))
+    } else {
+        find(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::TestManager;
+
+    fn mousebind(command: Command, modifier: &[&str], button: &str) -> Mousebind {
+        Mousebind {
+            command,
+            value: None,
+            modifier: modifier.iter().map(|m| (*m).to_owned()).collect(),
+            button: button.to_owned(),
+            target: MouseTarget::Window,
+            double_click: false,
+        }
+    }
+
+    #[test]
+    fn mousebinds_should_use_their_own_modifiers() {
+        let mousebinds = vec![
+            mousebind(Command::MouseMoveWindow, &["Alt"], "Button1"),
+            mousebind(Command::MouseResizeWindow, &["Mod4"], "Button3"),
+        ];
+        let found = find_mousebind(
+            &mousebinds,
+            xlib::Mod1Mask,
+            xlib::Button1,
+            MouseTarget::Window,
+            false,
+        );
+        assert_eq!(found.map(|m| &m.command), Some(&Command::MouseMoveWindow));
+        let found = find_mousebind(
+            &mousebinds,
+            xlib::Mod4Mask,
+            xlib::Button3,
+            MouseTarget::Window,
+            false,
+        );
+        assert_eq!(found.map(|m| &m.command), Some(&Command::MouseResizeWindow));
+        assert!(find_mousebind(
+            &mousebinds,
+            xlib::Mod4Mask,
+            xlib::Button1,
+            MouseTarget::Window,
+            false
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn mousebinds_should_ignore_shift_unless_bound() {
+        let mousebinds = vec![
+            mousebind(Command::MouseMoveWindow, &["Mod4"], "Button1"),
+            mousebind(Command::MouseResizeWindow, &["Mod4", "Shift"], "Button1"),
+        ];
+        let found = find_mousebind(
+            &mousebinds,
+            xlib::Mod4Mask | xlib::ShiftMask,
+            xlib::Button1,
+            MouseTarget::Window,
+            false,
+        );
+        assert_eq!(found.map(|m| &m.command), Some(&Command::MouseResizeWindow));
+        let found = find_mousebind(
+            &mousebinds[..1],
+            xlib::Mod4Mask | xlib::ShiftMask,
+            xlib::Button1,
+            MouseTarget::Window,
+            false,
+        );
+        assert_eq!(found.map(|m| &m.command), Some(&Command::MouseMoveWindow));
+    }
+
+    #[test]
+    fn mousebinds_should_only_match_their_target() {
+        let mut scroll = mousebind(Command::FocusNextTag, &[], "ScrollUp");
+        scroll.target = MouseTarget::Root;
+        let mousebinds = vec![scroll];
+        let found = find_mousebind(&mousebinds, 0, xlib::Button4, MouseTarget::Root, false);
+        assert_eq!(found.map(|m| &m.command), Some(&Command::FocusNextTag));
+        assert!(
+            find_mousebind(&mousebinds, 0, xlib::Button4, MouseTarget::Window, false).is_none()
+        );
+    }
+
+    #[test]
+    fn double_click_binds_should_only_match_the_second_click() {
+        let mut toggle = mousebind(Command::FloatingToTile, &["Mod4"], "Button1");
+        toggle.double_click = true;
+        let mousebinds = vec![
+            toggle,
+            mousebind(Command::MouseMoveWindow, &["Mod4"], "Button1"),
+        ];
+        let find = |double_click| {
+            find_mousebind(
+                &mousebinds,
+                xlib::Mod4Mask,
+                xlib::Button1,
+                MouseTarget::Window,
+                double_click,
+            )
+            .map(|m| &m.command)
+        };
+        assert_eq!(find(false), Some(&Command::MouseMoveWindow));
+        assert_eq!(find(true), Some(&Command::FloatingToTile));
+        assert_eq!(
+            find_mousebind(
+                &mousebinds[1..],
+                xlib::Mod4Mask,
+                xlib::Button1,
+                MouseTarget::Window,
+                true
+            )
+            .map(|m| &m.command),
+            Some(&Command::MouseMoveWindow)
+        );
+    }
+
+    #[test]
+    fn a_window_asking_to_be_dragged_should_be_moved_like_with_a_mousebind() {
+        let mut manager = TestManager::with_screens(1).with_windows(2).build();
+        let handle = WindowHandle::MockHandle(1);
+        assert!(!start_drag(&mut manager, handle, false));
+        assert_eq!(manager.mode, Mode::MovingWindow(handle));
+        assert!(manager.windows[0].start_loc.is_some());
+        assert!(matches!(
+            manager.actions.back(),
+            Some(DisplayAction::StartMovingWindow(h)) if h == &handle
+        ));
+
+        //one drag at a time
+        start_drag(&mut manager, WindowHandle::MockHandle(2), true);
+        assert_eq!(manager.mode, Mode::MovingWindow(handle));
     }
 }
@@ -0,0 +1,82 @@
+use super::{focus_handler, Manager};
+use crate::display_action::DisplayAction;
+
+/// Marks the workspaces of an output inactive when it is switched off, moving the focus to one
+/// still shown, and active again when it is back. Their windows keep their tags meanwhile, so they
+/// are arranged as before when the output returns.
+/// Returns `true` if changes need to be rendered.
+pub fn process(manager: &mut Manager, output: &str, on: bool) -> bool {
+    let mut changed = false;
+    for workspace in manager
+        .workspaces
+        .iter_mut()
+        .filter(|ws| ws.output.as_deref() == Some(output))
+    {
+        changed |= workspace.inactive == on;
+        workspace.inactive = !on;
+    }
+    if changed && !on {
+        focus_active_workspace(manager);
+    }
+    changed
+}
+
+fn focus_active_workspace(manager: &mut Manager) -> Option<()> {
+    if !manager.focused_workspace()?.inactive {
+        return None;
+    }
+    let workspace = manager.workspaces.iter().find(|ws| !ws.inactive)?.clone();
+    focus_handler::focus_workspace(manager, &workspace);
+    focus_handler::focus_top_window(manager, &workspace);
+    //the cursor would otherwise be left on the dark screen
+    let act = DisplayAction::MoveMouseOverPoint(workspace.xyhw.center());
+    manager.actions.push_back(act);
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::WindowHandle;
+    use crate::utils::testing::TestManager;
+
+    fn with_outputs() -> Manager {
+        let mut manager = TestManager::with_screens(2)
+            .with_windows(1)
+            .focus_workspace(1)
+            .with_windows(1)
+            .build();
+        manager.workspaces[0].output = Some("DP-1".to_owned());
+        manager.workspaces[1].output = Some("HDMI-1".to_owned());
+        manager
+    }
+
+    #[test]
+    fn focus_should_leave_an_output_switched_off() {
+        let mut manager = with_outputs();
+        assert!(process(&mut manager, "HDMI-1", false));
+        assert!(manager.workspaces[1].inactive);
+        assert_eq!(manager.focused_workspace().map(|ws| ws.xyhw.x()), Some(0));
+        assert_eq!(
+            manager.focused_window().map(|w| w.handle),
+            Some(WindowHandle::MockHandle(1))
+        );
+        assert!(manager
+            .actions
+            .iter()
+            .any(|a| matches!(a, DisplayAction::MoveMouseOverPoint(_))));
+    }
+
+    #[test]
+    fn windows_should_stay_on_an_output_until_it_is_back() {
+        let mut manager = with_outputs();
+        process(&mut manager, "HDMI-1", false);
+        assert!(!process(&mut manager, "HDMI-1", false));
+        assert!(manager.workspaces[1].is_displaying(&manager.windows[1]));
+
+        assert!(process(&mut manager, "HDMI-1", true));
+        assert!(!manager.workspaces[1].inactive);
+        assert!(manager.workspaces[1].is_displaying(&manager.windows[1]));
+        assert!(!process(&mut manager, "eDP-1", true));
+    }
+}
@@ -1,5 +1,5 @@
 use super::{focus_handler, Manager, Screen, Workspace};
-use crate::models::Tag;
+use crate::models::{Tag, TagId};
 
 /// Process a collection of events, and apply them changes to a manager.
 /// Returns `true` if changes need to be rendered.
@@ -27,16 +27,38 @@ pub fn process(manager: &mut Manager, screen: Screen) -> bool {
     if workspace.id.unwrap_or(0) as usize >= manager.tags.len() {
         dbg!("Workspace ID needs to be less than or equal to the number of tags available.");
     }
+    workspace.output.clone_from(&screen.output);
     workspace.update_for_theme(&manager.theme_setting);
-    //make sure are enough tags for this new screen
-    if manager.tags.len() <= tag_index {
-        let id = (tag_index + 1).to_string();
-        manager.tags.push(Tag::new(&id));
+    let mut next_tag = if manager.independent_tags {
+        own_tags(manager, workspace.id)
+    } else {
+        //make sure are enough tags for this new screen
+        if manager.tags.len() <= tag_index {
+            let id = (tag_index + 1).to_string();
+            manager.tags.push(Tag::new(&id));
+        }
+        manager.tags[tag_index].clone()
+    };
+    let mut shown = configured_tags(manager, workspace.id, &screen.tags);
+    if !shown.is_empty() {
+        let first = shown.remove(0);
+        if let Some(tag) = manager.tags.iter().find(|t| t.id == first) {
+            next_tag = tag.clone();
+        }
+    } else if is_shown(manager, &next_tag.id) {
+        //an earlier workspace was configured to show it
+        let free = manager
+            .tags
+            .iter()
+            .find(|t| !t.hidden && !is_shown(manager, &t.id) && own(manager, t, workspace.id));
+        if let Some(free) = free {
+            next_tag = free.clone();
+        }
     }
-    let next_tag = manager.tags[tag_index].clone();
     focus_handler::focus_workspace(manager, &workspace);
     focus_handler::focus_tag(manager, &next_tag.id);
     workspace.show_tag(&mut manager.tags, &next_tag);
+    workspace.tags.append(&mut shown);
     manager.workspaces.push(workspace.clone());
     manager
         .workspaces
@@ -46,6 +68,74 @@ pub fn process(manager: &mut Manager, screen: Screen) -> bool {
     false
 }
 
+/// The tags a `[[workspace]]` in the config is to start out showing, found by id or name, leaving
+/// out those another workspace already shows.
+fn configured_tags(manager: &Manager, ws_id: Option<i32>, wanted: &[String]) -> Vec<TagId> {
+    let mut tags: Vec<TagId> = vec![];
+    for wanted in wanted {
+        let copy = format!("{}@{}", wanted, ws_id.unwrap_or_default());
+        let found = manager.tags.iter().find(|t| {
+            !t.hidden
+                && own(manager, t, ws_id)
+                && (t.id == *wanted || t.id == copy || t.name.as_ref() == Some(wanted))
+        });
+        match found {
+            Some(tag) if !is_shown(manager, &tag.id) && !tags.contains(&tag.id) => {
+                tags.push(tag.id.clone());
+            }
+            Some(_) => log::warn!("Tag {} is already shown, leaving it out", wanted),
+            None => log::warn!("No tag {} to show on workspace {:?}", wanted, ws_id),
+        }
+    }
+    tags
+}
+
+fn is_shown(manager: &Manager, tag: &str) -> bool {
+    manager.workspaces.iter().any(|ws| ws.has_tag(tag))
+}
+
+//with independent tags a workspace only shows its own
+fn own(manager: &Manager, tag: &Tag, ws_id: Option<i32>) -> bool {
+    !manager.independent_tags || tag.workspace == ws_id
+}
+
+/// Gives the workspace the visible tags if it is the first one, or else copies of the tags of the
+/// first one, named after them and the workspace id. Returns the first tag of the workspace.
+fn own_tags(manager: &mut Manager, ws_id: Option<i32>) -> Tag {
+    if let Some(tag) = manager.tags.iter().find(|t| t.workspace == ws_id) {
+        return tag.clone();
+    }
+    let first_owner = manager.tags.iter().find_map(|t| t.workspace);
+    let mut tags: Vec<Tag> = match first_owner {
+        Some(owner) => manager
+            .tags
+            .iter()
+            .filter(|t| t.workspace == Some(owner))
+            .map(|t| Tag {
+                name: t.name.clone(),
+                ..Tag::new(&format!("{}@{}", t.id, ws_id.unwrap_or_default()))
+            })
+            .collect(),
+        None => manager.tags.iter().filter(|t| !t.hidden).cloned().collect(),
+    };
+    if tags.is_empty() {
+        tags.push(Tag::new(&format!("1@{}", ws_id.unwrap_or_default())));
+    }
+    for tag in &mut tags {
+        tag.workspace = ws_id;
+    }
+    let first = tags[0].clone();
+    //the hidden tags stay last
+    manager.tags.retain(|t| t.workspace.is_some() || t.hidden);
+    let at = manager
+        .tags
+        .iter()
+        .position(|t| t.hidden)
+        .unwrap_or(manager.tags.len());
+    manager.tags.splice(at..at, tags);
+    first
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -70,4 +160,24 @@ mod tests {
         assert!(manager.workspaces[0].has_tag("web"));
         assert!(manager.workspaces[1].has_tag("console"));
     }
+
+    #[test]
+    fn configured_workspaces_should_start_out_with_their_tags() {
+        let mut manager = Manager::new_test();
+        manager.tags = ["1", "2", "3:web", "4"]
+            .iter()
+            .map(|t| Tag::from_config(t))
+            .collect();
+        let screen = |tags: &[&str]| Screen {
+            tags: tags.iter().map(|t| (*t).to_owned()).collect(),
+            ..Screen::default()
+        };
+        process(&mut manager, screen(&["web", "2"]));
+        process(&mut manager, screen(&["3"]));
+        process(&mut manager, screen(&[]));
+        assert_eq!(manager.workspaces[0].tags, ["3", "2"]);
+        //already shown, so the next one in order
+        assert_eq!(manager.workspaces[1].tags, ["1"]);
+        assert_eq!(manager.workspaces[2].tags, ["4"]);
+    }
 }
@@ -1,9 +1,15 @@
-use super::{focus_handler, Manager, Window, WindowChange, WindowType, Workspace};
+use super::{
+    focus_handler, goto_tag_handler, Manager, Window, WindowChange, WindowType, Workspace,
+};
+use crate::config::TiledResizeRequest;
 use crate::display_action::DisplayAction;
 use crate::layouts::Layout;
-use crate::models::WindowHandle;
+use crate::models::{TagId, WindowHandle, Xyhw, XyhwBuilder, XyhwChange};
 use crate::utils::helpers;
-use crate::{child_process::exec_shell, models::FocusBehaviour};
+use crate::{
+    child_process::exec_shell,
+    models::{FocusBehaviour, FocusOnClose},
+};
 
 /// Process a collection of events, and apply them changes to a manager.
 /// Returns true if changes need to be rendered.
@@ -12,7 +18,18 @@ pub fn created(mut manager: &mut Manager, mut window: Window, x: i32, y: i32) ->
     if manager.windows.iter().any(|w| w.handle == window.handle) {
         return false;
     }
+    //or if a rule says to leave it alone
+    if manager
+        .window_rules
+        .iter()
+        .any(|r| r.unmanaged && r.matches(&window))
+    {
+        let act = DisplayAction::MapUnmanaged(window.handle);
+        manager.actions.push_back(act);
+        return false;
+    }
 
+    manager.metrics.windows_created += 1;
     let mut is_first = false;
     //Random value
     let mut layout: Layout = Layout::MainAndVertStack;
@@ -26,6 +43,11 @@ pub fn created(mut manager: &mut Manager, mut window: Window, x: i32, y: i32) ->
         &mut layout,
         &mut is_first,
     );
+    let follow_rule = if is_scratchpad {
+        None
+    } else {
+        apply_window_rules(manager, &mut window)
+    };
     insert_window(manager, &mut window, is_scratchpad, &layout);
 
     let follow_mouse = manager.focus_manager.focus_new_windows
@@ -44,8 +66,19 @@ pub fn created(mut manager: &mut Manager, mut window: Window, x: i32, y: i32) ->
     //new windows should be on the top of the stack
     manager.sort_windows();
 
-    if manager.focus_manager.focus_new_windows || is_first {
-        focus_handler::focus_window(manager, &window.handle);
+    match follow_rule {
+        Some(true) => {
+            goto_tag_handler::process_tag(manager, &window.tags[0]);
+            focus_handler::focus_window(manager, &window.handle);
+        }
+        Some(false) => {}
+        None => {
+            if (manager.focus_manager.focus_new_windows || is_first)
+                && may_take_focus(manager, &window)
+            {
+                focus_handler::focus_window(manager, &window.handle);
+            }
+        }
     }
 
     if let Some(cmd) = &manager.theme_setting.on_new_window_cmd.clone() {
@@ -55,6 +88,32 @@ pub fn created(mut manager: &mut Manager, mut window: Window, x: i32, y: i32) ->
     true
 }
 
+// A window launched too long before the user's latest input would steal focus from whatever the
+// user moved on to, unless its class is allowed to.
+fn may_take_focus(manager: &Manager, window: &Window) -> bool {
+    let focus = &manager.focus_manager;
+    match (window.launch_age, focus.focus_stealing_threshold) {
+        (Some(age), Some(threshold)) if age > threshold => match &window.res_class {
+            Some(class) => focus.focus_stealing_allowed.contains(class),
+            None => false,
+        },
+        _ => true,
+    }
+}
+
+// Whether the window's type or class is one of those that float when they appear. Splash screens
+// float centered instead.
+fn floats_by_default(manager: &Manager, window: &Window) -> bool {
+    if window.type_ == WindowType::Splash {
+        return false;
+    }
+    manager.floating_types.contains(&window.type_)
+        || window
+            .res_class
+            .as_ref()
+            .map_or(false, |class| manager.floating_classes.contains(class))
+}
+
 fn setup_window(
     manager: &mut Manager,
     window: &mut Window,
@@ -76,6 +135,8 @@ fn setup_window(
         })
         .or_else(|| manager.focused_workspace()); //backup plan
 
+    let area = ws.map(|ws| ws.xyhw);
+    let mut tile_on_resume = false;
     if let Some(ws) = ws {
         let for_active_workspace =
             |x: &Window| -> bool { helpers::intersect(&ws.tags, &x.tags) && !x.is_unmanaged() };
@@ -92,16 +153,39 @@ fn setup_window(
         if window.type_ == WindowType::Normal {
             window.apply_margin_multiplier(ws.margin_multiplier);
         }
-        //if dialog, center in workspace
-        if window.type_ == WindowType::Dialog {
+        let floating_spot = || {
+            let others: Vec<Xyhw> = manager
+                .windows
+                .iter()
+                .filter(|w| ws.is_managed(w) && w.floating())
+                .map(Window::calculated_xyhw)
+                .collect();
+            ws.floating_spot(manager.floating_placement, (x, y), &others)
+        };
+        //dialogs and the like float where `floating_placement` puts them
+        if floats_by_default(manager, window) {
             window.set_floating(true);
-            let new_float_exact = ws.center_halfed();
+            let new_float_exact = floating_spot();
             window.normal = ws.xyhw;
             window.set_floating_exact(new_float_exact);
         }
-        if window.type_ == WindowType::Splash {
+        //tiling is paused on this tag, new windows float until it resumes
+        let paused = manager
+            .tags
+            .iter()
+            .any(|t| ws.tags.contains(&t.id) && t.paused_tiling.is_some());
+        if paused && window.type_ == WindowType::Normal && !window.floating() {
+            window.set_floating(true);
+            let new_float_exact = floating_spot();
+            window.normal = ws.xyhw;
+            window.set_floating_exact(new_float_exact);
+            tile_on_resume = true;
+        }
+        if window.type_ == WindowType::Splash && manager.floating_types.contains(&window.type_) {
             if let Some(requested) = window.requested {
                 window.normal = ws.xyhw;
+                //only a floating window takes the geometry it asks for
+                window.set_floating(true);
                 requested.update_window_floating(window);
                 let mut xhyw = window.get_floating_offsets().unwrap_or_default();
                 xhyw.center_relative(ws.xyhw, window.border, window.requested);
@@ -121,15 +205,112 @@ fn setup_window(
         }
     }
 
+    //windows adopted from a previous worker go back to their tags
+    let known = |tag: &TagId| manager.tags.iter().any(|t| &t.id == tag);
+    if !window.previous_tags.is_empty() && window.previous_tags.iter().all(known) {
+        window.tags = window.previous_tags.clone();
+    }
+
     if let Some(parent) = find_transient_parent(manager, window) {
+        //dialogs go where the window they belong to is
+        window.tags = parent.tags.clone();
         window.set_floating(true);
         let new_float_exact = parent.calculated_xyhw().center_halfed();
         window.normal = parent.normal;
         window.set_floating_exact(new_float_exact);
     }
 
+    if let (Some(requested), Some(area)) = (window.requested_geometry, area) {
+        if window.floating() && !is_scratchpad {
+            place_as_requested(window, requested, area);
+        }
+    }
+    if let Some(ws) = ws {
+        if window.floating() && !is_scratchpad && manager.floating_cascade_offset > 0 {
+            cascade(manager, ws, window);
+        }
+    }
+    if tile_on_resume {
+        tile_when_resumed(manager, window);
+    }
+
     window.update_for_theme(&manager.theme_setting);
 }
+
+// Moves the window down and right, `floating_cascade_offset` at a time, off the spots where
+// floating windows of its class already are, so a burst of them don't hide each other. One that
+// would leave the workspace starts over from its top left corner.
+fn cascade(manager: &Manager, ws: &Workspace, window: &mut Window) {
+    if window.res_class.is_none() {
+        return;
+    }
+    let taken: Vec<(i32, i32)> = manager
+        .windows
+        .iter()
+        .filter(|w| ws.is_managed(w) && w.floating() && w.res_class == window.res_class)
+        .map(|w| (w.x(), w.y()))
+        .collect();
+    let offset = manager.floating_cascade_offset;
+    let mut xyhw = window.calculated_xyhw();
+    if !taken.contains(&(xyhw.x(), xyhw.y())) {
+        return;
+    }
+    let area = ws.xyhw;
+    //every spot tried is taken until one is free or they repeat, so this many tries is enough
+    for _ in 0..=taken.len() {
+        xyhw.set_x(xyhw.x() + offset);
+        xyhw.set_y(xyhw.y() + offset);
+        if xyhw.x() + xyhw.w() > area.x() + area.w() || xyhw.y() + xyhw.h() > area.y() + area.h() {
+            xyhw.set_x(area.x());
+            xyhw.set_y(area.y());
+        }
+        xyhw.clamp_into(area);
+        if !taken.contains(&(xyhw.x(), xyhw.y())) {
+            break;
+        }
+    }
+    window.set_floating_exact(xyhw);
+}
+
+//a window floated by paused tiling gets tiled along with the others when it resumes
+fn tile_when_resumed(manager: &mut Manager, window: &Window) {
+    manager
+        .tags
+        .iter_mut()
+        .filter(|t| window.tags.contains(&t.id))
+        .filter_map(|t| t.paused_tiling.as_mut())
+        .for_each(|handles| handles.push(window.handle));
+}
+
+// Floats the window where the program asked for it, as far as it fits on the workspace.
+fn place_as_requested(window: &mut Window, requested: XyhwChange, area: Xyhw) {
+    let mut xyhw = window.calculated_xyhw();
+    requested.update(&mut xyhw);
+    xyhw.clamp_into(area);
+    window.set_floating_exact(xyhw);
+}
+/// Moves the window to the tag of the first rule it matches, unless that tag is already where
+/// the window is going. Returns whether to follow the window to its tag, otherwise the window is
+/// marked urgent.
+fn apply_window_rules(manager: &Manager, window: &mut Window) -> Option<bool> {
+    if window.transient.is_some() {
+        return None;
+    }
+    let rule = manager
+        .window_rules
+        .iter()
+        .find(|r| r.spawn_on_tag > 0 && r.matches(window))?;
+    let tag = manager.numbered_tag(rule.spawn_on_tag)?;
+    if window.has_tag(&tag.id) {
+        return None;
+    }
+    window.tags = vec![tag.id.clone()];
+    if !rule.follow_window {
+        window.set_urgent();
+    }
+    Some(rule.follow_window)
+}
+
 fn insert_window(manager: &mut Manager, window: &mut Window, is_scratchpad: bool, layout: &Layout) {
     // If the tag contains a fullscreen window, minimize it
     let for_active_workspace =
@@ -177,17 +358,24 @@ fn is_scratchpad(manager: &Manager, window: &Window) -> bool {
         .active_scratchpads
         .iter()
         .any(|(_, &id)| window.pid == id)
+        || manager
+            .attached_scratchpads
+            .values()
+            .any(|h| *h == window.handle)
 }
 
 /// Process a collection of events, and apply them changes to a manager.
 /// Returns true if changes need to be rendered.
 pub fn destroyed(manager: &mut Manager, handle: &WindowHandle) -> bool {
-    //Find the next or previous window on the workspace
-    let new_handle = get_next_or_previous(manager, handle);
+    //Find the next or previous window on the workspace, a window sharing the tile comes first
+    let next_or_previous = get_next_or_previous(manager, handle);
     manager
         .focus_manager
         .tags_last_window
         .retain(|_, h| h != handle);
+    manager.frame_rate_limitors.remove(handle);
+    manager.attached_scratchpads.retain(|_, h| h != handle);
+    let in_group = manager.leave_group(handle);
     manager.windows.retain(|w| &w.handle != handle);
 
     //make sure the workspaces do not draw on the docks
@@ -199,14 +387,106 @@ pub fn destroyed(manager: &mut Manager, handle: &WindowHandle) -> bool {
         if manager.focus_manager.behaviour == FocusBehaviour::Sloppy {
             let act = DisplayAction::FocusWindowUnderCursor;
             manager.actions.push_back(act);
-        } else if let Some(h) = new_handle {
-            focus_handler::focus_window(manager, &h);
+        } else {
+            focus_after_close(manager, in_group, next_or_previous);
         }
     }
 
     true
 }
 
+//focuses the window `focus_on_close` says, a window of its group takes the tile of the closed
+//window so it comes first, and the one next to it is used when the focus history runs out
+fn focus_after_close(
+    manager: &mut Manager,
+    in_group: Option<WindowHandle>,
+    next: Option<WindowHandle>,
+) {
+    let workspace = manager.focused_workspace().cloned();
+    let shown = |h: &WindowHandle| {
+        manager
+            .windows
+            .iter()
+            .find(|w| &w.handle == h)
+            .map_or(false, |w| {
+                !w.refuses_input && workspace.as_ref().map_or(false, |ws| ws.is_managed(w))
+            })
+    };
+    let handle = match manager.focus_manager.focus_on_close {
+        FocusOnClose::Previous => in_group.or_else(|| {
+            manager
+                .focus_manager
+                .window_history
+                .iter()
+                .flatten()
+                .find(|h| shown(h))
+                .copied()
+                .or_else(|| next.filter(|h| shown(h)))
+        }),
+        FocusOnClose::Top => in_group.or_else(|| {
+            manager
+                .windows
+                .iter()
+                .find(|w| shown(&w.handle))
+                .map(|w| w.handle)
+        }),
+        FocusOnClose::None => None,
+    };
+    focus_handler::focus_window_or_none(manager, handle);
+}
+
+/// Asks the transient windows of a window, such as its dialogs, to close. The ones that can only
+/// be killed are left open.
+pub fn close_transients(manager: &mut Manager, handle: &WindowHandle) {
+    for window in &manager.windows {
+        if window.transient.as_ref() == Some(handle) {
+            let act = DisplayAction::CloseWindow(window.handle);
+            manager.actions.push_back(act);
+        }
+    }
+}
+
+/// Answers a window asking to be moved or resized. Floating windows get what they ask for, tiled
+/// ones what `tiled_resize_request` says. Returns true if the windows have to be drawn again,
+/// which also puts a denied window back in its place.
+pub fn configure_requested(
+    manager: &mut Manager,
+    handle: WindowHandle,
+    change: XyhwChange,
+) -> bool {
+    let behaviour = manager.tiled_resize_request;
+    let window = match manager.windows.iter_mut().find(|w| w.handle == handle) {
+        Some(w) => w,
+        None => return false,
+    };
+    if window.floating() {
+        change.update_window_floating(window);
+        return true;
+    }
+    match behaviour {
+        TiledResizeRequest::Deny => true,
+        TiledResizeRequest::AllowTemporarily => {
+            let mut xyhw = XyhwBuilder {
+                x: window.x(),
+                y: window.y(),
+                w: window.width(),
+                h: window.height(),
+                ..XyhwBuilder::default()
+            }
+            .into();
+            change.update(&mut xyhw);
+            let act = DisplayAction::ConfigureWindow(handle, xyhw);
+            manager.actions.push_back(act);
+            false
+        }
+        TiledResizeRequest::Float => {
+            window.set_floating(true);
+            change.update_window_floating(window);
+            true
+        }
+    }
+}
+
 pub fn changed(manager: &mut Manager, change: WindowChange) -> bool {
     let mut changed = false;
     let strut_changed = change.strut.is_some();
@@ -216,6 +496,8 @@ pub fn changed(manager: &mut Manager, change: WindowChange) -> bool {
         .find(|w| w.handle == change.handle)
     {
         log::debug!("WINDOW CHANGED {:?} {:?}", &w, change);
+        //a new title needs no render, but the state output shows it
+        manager.state_dirty |= matches!(&change.name, Some(name) if name != &w.name);
         changed = change.update(w);
         if w.type_ == WindowType::Dock {
             update_workspace_avoid_list(manager);
@@ -254,14 +536,13 @@ pub fn update_workspace_avoid_list(manager: &mut Manager) {
             log::debug!("AVOID STRUT:[{:?}] {:?}", w.handle, to_avoid);
             avoid.push(to_avoid);
         });
+    avoid.extend(manager.systray_strut);
     for ws in &mut manager.workspaces {
         let struts = avoid
             .clone()
             .into_iter()
-            .filter(|s| {
-                let (x, y) = s.center();
-                ws.contains_point(x, y)
-            })
+            //a bar spanning several monitors keeps its space on all of them
+            .filter(|s| ws.xyhw.overlap_volume(s) > 0)
             .collect();
         ws.avoid = struts;
         ws.update_avoided_areas();
@@ -307,3 +588,417 @@ pub fn get_next_or_previous(manager: &mut Manager, handle: &WindowHandle) -> Opt
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::WindowRule;
+    use crate::handlers::screen_create_handler;
+    use crate::models::BBox;
+    use crate::models::Screen;
+    use crate::models::Tag;
+    use crate::models::WindowChange;
+    use crate::models::WindowState;
+    use crate::utils::testing::TestManager;
+
+    fn manager_with_rule(follow_window: bool) -> Manager {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        manager.tags.push(Tag::new("2"));
+        manager.window_rules.push(WindowRule {
+            window_class: Some("Firefox".to_owned()),
+            window_title: None,
+            spawn_on_tag: 2,
+            follow_window,
+            focus_lock: false,
+            pass_keys: false,
+            unmanaged: false,
+        });
+        manager
+    }
+
+    fn firefox() -> Window {
+        let mut window = Window::new(WindowHandle::MockHandle(1), None, None);
+        window.res_class = Some("Firefox".to_owned());
+        window
+    }
+
+    #[test]
+    fn a_strut_should_only_trim_the_workspaces_it_is_on() {
+        let mut manager = Manager::new_test();
+        manager.tags.push(Tag::new("2"));
+        for x in &[0, 800] {
+            let bbox = BBox {
+                x: *x,
+                y: 0,
+                width: 800,
+                height: 600,
+            };
+            screen_create_handler::process(&mut manager, Screen::new(bbox));
+        }
+        let mut bar = Window::new(WindowHandle::MockHandle(1), None, None);
+        bar.type_ = WindowType::Dock;
+        bar.strut = Some(
+            XyhwBuilder {
+                x: 800,
+                w: 800,
+                h: 20,
+                ..XyhwBuilder::default()
+            }
+            .into(),
+        );
+        manager.windows.push(bar);
+
+        update_workspace_avoid_list(&mut manager);
+        assert!(manager.workspaces[0].avoid.is_empty());
+        assert_eq!(manager.workspaces[1].avoid.len(), 1);
+
+        manager.windows[0].strut = Some(
+            XyhwBuilder {
+                w: 1600,
+                h: 20,
+                ..XyhwBuilder::default()
+            }
+            .into(),
+        );
+        update_workspace_avoid_list(&mut manager);
+        assert_eq!(manager.workspaces[0].avoid.len(), 1);
+        assert_eq!(manager.workspaces[1].avoid.len(), 1);
+    }
+
+    #[test]
+    fn the_systray_strut_should_trim_the_workspace_it_is_on() {
+        let mut manager = TestManager::with_screens(2).build();
+        manager.systray_strut = Some(
+            XyhwBuilder {
+                x: 800,
+                w: 800,
+                h: 20,
+                ..XyhwBuilder::default()
+            }
+            .into(),
+        );
+        update_workspace_avoid_list(&mut manager);
+        assert!(manager.workspaces[0].avoid.is_empty());
+        assert_eq!(
+            manager.workspaces[1].avoid,
+            manager.systray_strut.into_iter().collect::<Vec<_>>()
+        );
+        assert_eq!(manager.workspaces[1].y(), 20);
+    }
+
+    #[test]
+    fn a_splash_asking_for_a_size_should_float_with_it() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        let mut splash = Window::new(WindowHandle::MockHandle(1), None, None);
+        splash.type_ = WindowType::Splash;
+        splash.requested = Some(XyhwChange {
+            w: Some(300),
+            h: Some(200),
+            ..XyhwChange::default()
+        });
+        created(&mut manager, splash, 0, 0);
+        let splash = &manager.windows[0];
+        assert!(splash.floating());
+        assert_eq!((splash.width(), splash.height()), (300, 200));
+    }
+
+    #[test]
+    fn a_changed_window_title_should_be_stored_without_a_render() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        created(&mut manager, firefox(), 0, 0);
+        let mut change = WindowChange::new(WindowHandle::MockHandle(1));
+        change.name = Some(Some("Mozilla Firefox".to_owned()));
+        manager.state_dirty = false;
+        assert!(!changed(&mut manager, change));
+        assert_eq!(manager.windows[0].name.as_deref(), Some("Mozilla Firefox"));
+        assert!(manager.state_dirty);
+    }
+
+    #[test]
+    fn the_urgency_hint_should_mark_a_window_urgent_until_cleared() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        created(&mut manager, firefox(), 0, 0);
+        let mut change = WindowChange::new(WindowHandle::MockHandle(1));
+        change.urgent = Some(true);
+        assert!(changed(&mut manager, change.clone()));
+        assert!(manager.windows[0].is_urgent());
+        assert!(!changed(&mut manager, change.clone()));
+        change.urgent = Some(false);
+        assert!(changed(&mut manager, change));
+        assert!(!manager.windows[0].is_urgent());
+    }
+
+    #[test]
+    fn a_tiled_window_asking_to_be_resized_should_get_what_the_config_says() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        created(&mut manager, firefox(), 0, 0);
+        let handle = WindowHandle::MockHandle(1);
+        let change = XyhwChange {
+            w: Some(300),
+            h: Some(200),
+            ..XyhwChange::default()
+        };
+        manager.actions.clear();
+
+        assert!(configure_requested(&mut manager, handle, change));
+        assert!(!manager.windows[0].floating());
+        assert!(manager.actions.is_empty());
+
+        manager.tiled_resize_request = TiledResizeRequest::AllowTemporarily;
+        assert!(!configure_requested(&mut manager, handle, change));
+        assert!(!manager.windows[0].floating());
+        match manager.actions.pop_front() {
+            Some(DisplayAction::ConfigureWindow(h, xyhw)) => {
+                assert_eq!(h, handle);
+                assert_eq!((xyhw.w(), xyhw.h()), (300, 200));
+            }
+            other => panic!("unexpected action {:?}", other),
+        }
+
+        manager.tiled_resize_request = TiledResizeRequest::Float;
+        assert!(configure_requested(&mut manager, handle, change));
+        let window = &manager.windows[0];
+        assert!(window.floating());
+        assert_eq!((window.width(), window.height()), (300, 200));
+    }
+
+    #[test]
+    fn utility_windows_and_floating_classes_should_float() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        manager.floating_classes = vec!["Pinentry".to_owned()];
+        let mut palette = Window::new(WindowHandle::MockHandle(1), None, None);
+        palette.type_ = WindowType::Utility;
+        let mut prompt = Window::new(WindowHandle::MockHandle(2), None, None);
+        prompt.res_class = Some("Pinentry".to_owned());
+        let mut terminal = Window::new(WindowHandle::MockHandle(3), None, None);
+        terminal.res_class = Some("Alacritty".to_owned());
+        created(&mut manager, palette, 0, 0);
+        created(&mut manager, prompt, 0, 0);
+        created(&mut manager, terminal, 0, 0);
+
+        let floating = |i: i32| {
+            let handle = WindowHandle::MockHandle(i);
+            manager
+                .windows
+                .iter()
+                .any(|w| w.handle == handle && w.floating())
+        };
+        assert!(floating(1));
+        assert!(floating(2));
+        assert!(!floating(3));
+    }
+
+    #[test]
+    fn a_window_sent_by_a_rule_should_mark_its_tag_urgent() {
+        let mut manager = manager_with_rule(false);
+        created(&mut manager, firefox(), 0, 0);
+        assert_eq!(manager.windows[0].tags, ["2".to_owned()]);
+        assert!(manager.windows[0].is_urgent());
+        assert_eq!(manager.workspaces[0].tags, ["1".to_owned()]);
+    }
+
+    #[test]
+    fn a_window_sent_by_a_following_rule_should_be_focused_on_its_tag() {
+        let mut manager = manager_with_rule(true);
+        created(&mut manager, firefox(), 0, 0);
+        assert_eq!(manager.windows[0].tags, ["2".to_owned()]);
+        assert!(!manager.windows[0].is_urgent());
+        assert_eq!(manager.workspaces[0].tags, ["2".to_owned()]);
+        assert_eq!(
+            manager.focused_window().map(|w| w.handle),
+            Some(WindowHandle::MockHandle(1))
+        );
+    }
+
+    #[test]
+    fn a_window_launched_long_before_the_latest_input_should_not_take_focus() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        manager.focus_manager.focus_new_windows = true;
+        manager.focus_manager.focus_stealing_threshold = Some(1000);
+        manager.focus_manager.focus_stealing_allowed = vec!["Firefox".to_owned()];
+        let first = Some(WindowHandle::MockHandle(1));
+
+        let mut window = Window::new(WindowHandle::MockHandle(1), None, None);
+        window.launch_age = Some(500);
+        created(&mut manager, window, 0, 0);
+        assert_eq!(manager.focused_window().map(|w| w.handle), first);
+
+        let mut window = Window::new(WindowHandle::MockHandle(2), None, None);
+        window.launch_age = Some(5000);
+        created(&mut manager, window, 0, 0);
+        assert_eq!(manager.focused_window().map(|w| w.handle), first);
+
+        let mut window = firefox();
+        window.handle = WindowHandle::MockHandle(3);
+        window.launch_age = Some(5000);
+        created(&mut manager, window, 0, 0);
+        assert_eq!(
+            manager.focused_window().map(|w| w.handle),
+            Some(WindowHandle::MockHandle(3))
+        );
+    }
+
+    #[test]
+    fn a_modal_dialog_should_stay_with_its_parent() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        manager.tags.push(Tag::new("2"));
+        let parent = WindowHandle::MockHandle(1);
+        created(&mut manager, Window::new(parent, None, None), 0, 0);
+        manager.windows[0].tags = vec!["2".to_owned()];
+        //a floating parent shares the dialog's stacking level
+        manager.windows[0].set_floating(true);
+        let mut dialog = Window::new(WindowHandle::MockHandle(2), None, None);
+        dialog.transient = Some(parent);
+        dialog.set_states(vec![WindowState::Modal]);
+        created(&mut manager, dialog, 0, 0);
+
+        let order: Vec<WindowHandle> = manager.windows.iter().map(|w| w.handle).collect();
+        assert_eq!(order, [WindowHandle::MockHandle(2), parent]);
+        assert_eq!(manager.windows[0].tags, ["2".to_owned()]);
+        assert!(manager.windows[0].floating());
+
+        focus_handler::focus_window(&mut manager, &parent);
+        let focused = manager.focused_window().map(|w| w.handle);
+        assert_eq!(focused, Some(WindowHandle::MockHandle(2)));
+
+        manager.actions.clear();
+        close_transients(&mut manager, &parent);
+        assert!(matches!(
+            manager.actions.front(),
+            Some(DisplayAction::CloseWindow(WindowHandle::MockHandle(2)))
+        ));
+    }
+
+    #[test]
+    fn a_floating_window_should_get_its_requested_geometry_inside_the_workspace() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        let mut dialog = Window::new(WindowHandle::MockHandle(1), None, None);
+        dialog.type_ = WindowType::Dialog;
+        dialog.requested_geometry = Some(XyhwChange {
+            x: Some(700),
+            y: Some(20),
+            w: Some(300),
+            h: Some(200),
+            ..XyhwChange::default()
+        });
+        created(&mut manager, dialog, 0, 0);
+
+        let xyhw = manager.windows[0].calculated_xyhw();
+        let area = manager.workspaces[0].xyhw;
+        assert_eq!((xyhw.w(), xyhw.h()), (300, 200));
+        assert_eq!(xyhw.x(), area.x() + area.w() - 300);
+        assert_eq!(xyhw.y(), 20.max(area.y()));
+    }
+
+    #[test]
+    fn floating_windows_of_one_class_should_cascade() {
+        let mut manager = Manager::new_test();
+        manager.floating_cascade_offset = 30;
+        screen_create_handler::process(&mut manager, Screen::default());
+        for i in 1..=3 {
+            let mut viewer = Window::new(WindowHandle::MockHandle(i), None, None);
+            viewer.type_ = WindowType::Dialog;
+            viewer.res_class = Some("feh".to_owned());
+            created(&mut manager, viewer, 0, 0);
+        }
+        let mut spots: Vec<(i32, i32)> = manager.windows.iter().map(|w| (w.x(), w.y())).collect();
+        spots.sort_unstable();
+        let (x, y) = spots[0];
+        assert_eq!(spots, [(x, y), (x + 30, y + 30), (x + 60, y + 60)]);
+    }
+
+    #[test]
+    fn cascading_windows_should_start_over_at_the_edge_of_the_workspace() {
+        let mut manager = Manager::new_test();
+        manager.floating_cascade_offset = 100;
+        screen_create_handler::process(&mut manager, Screen::default());
+        for i in 1..=4 {
+            let mut viewer = Window::new(WindowHandle::MockHandle(i), None, None);
+            viewer.type_ = WindowType::Dialog;
+            viewer.res_class = Some("feh".to_owned());
+            created(&mut manager, viewer, 0, 0);
+        }
+        let area = manager.workspaces[0].xyhw;
+        let mut spots: Vec<(i32, i32)> = manager.windows.iter().map(|w| (w.x(), w.y())).collect();
+        assert!(manager
+            .windows
+            .iter()
+            .all(|w| w.x() + w.width() <= area.x() + area.w()
+                && w.y() + w.height() <= area.y() + area.h()));
+        spots.sort_unstable();
+        spots.dedup();
+        assert_eq!(spots.len(), 4);
+    }
+
+    #[test]
+    fn adopted_windows_should_go_back_to_their_tags() {
+        let mut manager = Manager::new_test();
+        manager.tags = vec![Tag::new("1"), Tag::new("2")];
+        screen_create_handler::process(&mut manager, Screen::default());
+        let mut window = Window::new(WindowHandle::MockHandle(1), None, None);
+        window.previous_tags = vec!["2".to_owned()];
+        created(&mut manager, window, 0, 0);
+        let mut unknown = Window::new(WindowHandle::MockHandle(2), None, None);
+        unknown.previous_tags = vec!["9".to_owned()];
+        created(&mut manager, unknown, 0, 0);
+        assert_eq!(manager.windows[0].tags, ["2"]);
+        assert_eq!(manager.windows[1].tags, ["1"]);
+    }
+
+    #[test]
+    fn windows_ruled_unmanaged_should_only_be_mapped() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        manager.window_rules.push(WindowRule {
+            window_class: Some("screenkey".to_owned()),
+            window_title: None,
+            spawn_on_tag: 0,
+            follow_window: false,
+            focus_lock: false,
+            pass_keys: false,
+            unmanaged: true,
+        });
+        let mut overlay = Window::new(WindowHandle::MockHandle(1), None, None);
+        overlay.res_class = Some("screenkey".to_owned());
+        assert!(!created(&mut manager, overlay, 0, 0));
+        assert!(manager.windows.is_empty());
+        assert!(matches!(
+            manager.actions.back(),
+            Some(DisplayAction::MapUnmanaged(WindowHandle::MockHandle(1)))
+        ));
+    }
+
+    #[test]
+    fn closing_the_focused_window_should_focus_what_focus_on_close_says() {
+        let focused_after_close = |policy: FocusOnClose| {
+            let mut manager = TestManager::with_screens(1)
+                .with_windows(4)
+                .focus_window(2)
+                .focus_window(4)
+                .focus_window(1)
+                .build();
+            manager.focus_manager.behaviour = FocusBehaviour::ClickTo;
+            manager.focus_manager.focus_on_close = policy;
+            destroyed(&mut manager, &WindowHandle::MockHandle(1));
+            manager.focused_window().map(|w| w.handle)
+        };
+        assert_eq!(
+            focused_after_close(FocusOnClose::Previous),
+            Some(WindowHandle::MockHandle(4))
+        );
+        assert_eq!(
+            focused_after_close(FocusOnClose::Top),
+            Some(WindowHandle::MockHandle(2))
+        );
+        assert_eq!(focused_after_close(FocusOnClose::None), None);
+    }
+}
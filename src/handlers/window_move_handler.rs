@@ -20,6 +20,77 @@ pub fn process(manager: &mut Manager, handle: &WindowHandle, offset_x: i32, offs
     }
 }
 
+/// Where a window being moved can be let go to tile it.
+#[derive(Debug, PartialEq)]
+enum DropZone {
+    //the left side of a workspace, the window becomes the first, main one
+    Main,
+    //the right side of a workspace, the window goes at the end of the stack
+    Stack,
+    //the middle of a tile, the window takes that window's place
+    Slot(WindowHandle),
+}
+
+/// Tiles the window being moved if it is let go at `x`, `y` over a drop zone.
+/// Returns true if it was tiled.
+pub fn drop_window(manager: &mut Manager, handle: &WindowHandle, x: i32, y: i32) -> bool {
+    let workspace = match manager.workspaces.iter().find(|ws| ws.contains_point(x, y)) {
+        Some(ws) => ws.clone(),
+        None => return false,
+    };
+    let zone = match drop_zone(&manager.windows, &workspace, handle, x, y) {
+        Some(zone) => zone,
+        None => return false,
+    };
+    let index = match manager
+        .windows
+        .iter()
+        .position(|w| &w.handle == handle && !w.must_float())
+    {
+        Some(index) => index,
+        None => return false,
+    };
+    let mut window = manager.windows.remove(index);
+    window_handler::snap_to_workspace(&mut window, &workspace);
+    let index = match zone {
+        DropZone::Main => 0,
+        DropZone::Stack => manager.windows.len(),
+        DropZone::Slot(other) => manager
+            .windows
+            .iter()
+            .position(|w| w.handle == other)
+            .unwrap_or_default(),
+    };
+    manager.windows.insert(index, window);
+    true
+}
+
+//the sides are an eighth of the workspace wide, the middle third of a tile is its slot
+fn drop_zone(
+    windows: &[Window],
+    workspace: &Workspace,
+    handle: &WindowHandle,
+    x: i32,
+    y: i32,
+) -> Option<DropZone> {
+    let side = workspace.width() / 8;
+    if x < workspace.x() + side {
+        return Some(DropZone::Main);
+    }
+    if x >= workspace.x() + workspace.width() - side {
+        return Some(DropZone::Stack);
+    }
+    windows
+        .iter()
+        .filter(|w| &w.handle != handle && !w.floating() && workspace.is_managed(w))
+        .find(|w| {
+            let (third_w, third_h) = (w.width() / 3, w.height() / 3);
+            (w.x() + third_w..w.x() + 2 * third_w).contains(&x)
+                && (w.y() + third_h..w.y() + 2 * third_h).contains(&y)
+        })
+        .map(|w| DropZone::Slot(w.handle))
+}
+
 fn process_window(window: &mut Window, offset_x: i32, offset_y: i32) {
     window.set_floating(true);
     let mut offset = window.get_floating_offsets().unwrap_or_default();
@@ -75,3 +146,60 @@ fn should_snap(window: &mut Window, workspace: &Workspace) -> bool {
     }
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::screen_create_handler;
+    use crate::models::Screen;
+    use crate::utils::window_updater::update_windows;
+
+    fn handles(manager: &Manager) -> Vec<WindowHandle> {
+        manager.windows.iter().map(|w| w.handle).collect()
+    }
+
+    #[test]
+    fn a_window_let_go_over_a_drop_zone_should_be_tiled_there() {
+        let mut manager = Manager::new_test();
+        screen_create_handler::process(&mut manager, Screen::default());
+        for id in 1..=3 {
+            let window = Window::new(WindowHandle::MockHandle(id), None, None);
+            window_handler::created(&mut manager, window, -1, -1);
+        }
+        update_windows(&mut manager);
+        let (one, two, three) = (
+            WindowHandle::MockHandle(1),
+            WindowHandle::MockHandle(2),
+            WindowHandle::MockHandle(3),
+        );
+        assert_eq!(handles(&manager), [one, two, three]);
+        let workspace = manager.workspaces[0].clone();
+        let middle_y = workspace.y() + workspace.height() / 2;
+
+        manager.windows[2].set_floating(true);
+        assert!(drop_window(
+            &mut manager,
+            &three,
+            workspace.x() + 1,
+            middle_y
+        ));
+        assert!(!manager.windows[0].floating());
+        assert_eq!(handles(&manager), [three, one, two]);
+        update_windows(&mut manager);
+
+        let tile = manager.windows.iter().find(|w| w.handle == two).unwrap();
+        let (x, y) = (tile.x() + tile.width() / 2, tile.y() + tile.height() / 2);
+        manager.windows[0].set_floating(true);
+        assert!(drop_window(&mut manager, &three, x, y));
+        assert_eq!(handles(&manager), [one, three, two]);
+
+        update_windows(&mut manager);
+
+        //the top of a tile is no drop zone, the window keeps floating
+        let tile = manager.windows.iter().find(|w| w.handle == one).unwrap();
+        let (x, y) = (tile.x() + tile.width() / 2, tile.y() + 1);
+        manager.windows[1].set_floating(true);
+        assert!(!drop_window(&mut manager, &three, x, y));
+        assert!(manager.windows[1].floating());
+    }
+}
@@ -0,0 +1,29 @@
+use crate::models::Tag;
+use crate::models::Window;
+use crate::models::Workspace;
+
+/// Layout which places the windows in containers split by hand with `SplitHorizontal` and
+/// `SplitVertical`, each tag keeping its own tree of them.
+pub fn update(workspace: &Workspace, windows: &mut Vec<&mut Window>, tags: &mut Vec<Tag>) {
+    let tag = match workspace.current_tags(tags).into_iter().next() {
+        Some(tag) => tag,
+        None => return,
+    };
+    let handles: Vec<_> = windows.iter().map(|w| w.handle).collect();
+    tag.split_tree.sync(&handles);
+
+    let placed = tag.split_tree.geometry(
+        workspace.x(),
+        workspace.y(),
+        workspace.width(),
+        workspace.height(),
+    );
+    for (handle, x, y, w, h) in placed {
+        if let Some(window) = windows.iter_mut().find(|window| window.handle == handle) {
+            window.set_height(h);
+            window.set_width(w);
+            window.set_x(x);
+            window.set_y(y);
+        }
+    }
+}
@@ -2,7 +2,8 @@ use crate::models::Tag;
 
 use super::models::Window;
 use super::models::Workspace;
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
 use std::str::FromStr;
 
 mod center_main;
@@ -14,10 +15,12 @@ mod grid_horizontal;
 mod main_and_deck;
 mod main_and_horizontal_stack;
 mod main_and_vert_stack;
+mod manual;
 mod monocle;
+pub mod plugin;
 mod right_main_and_vert_stack;
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Layout {
     MainAndVertStack,
     MainAndHorizontalStack,
@@ -31,6 +34,9 @@ pub enum Layout {
     Monocle,
     RightWiderLeftStack,
     LeftWiderRightStack,
+    Manual,
+    /// A layout from `$XDG_CONFIG_HOME/leftwm/layouts/`, named `plugin:<name>`.
+    Plugin(String),
 }
 
 pub const LAYOUTS: [Layout; 12] = [
@@ -80,6 +86,8 @@ impl Layout {
             Self::RightWiderLeftStack => {
                 right_main_and_vert_stack::update(workspace, windows, tags);
             }
+            Self::Manual => manual::update(workspace, windows, tags),
+            Self::Plugin(name) => plugin::update(name, workspace, windows, tags),
         }
     }
 
@@ -144,11 +152,47 @@ impl FromStr for Layout {
             "Monocle" => Ok(Self::Monocle),
             "RightWiderLeftStack" => Ok(Self::RightWiderLeftStack),
             "LeftWiderRightStack" => Ok(Self::LeftWiderRightStack),
-            _ => Err(()),
+            "Manual" => Ok(Self::Manual),
+            _ => match s.strip_prefix("plugin:") {
+                Some(name) if is_plugin_name(name) => Ok(Self::Plugin(name.to_owned())),
+                _ => Err(()),
+            },
+        }
+    }
+}
+
+/// A plugin is loaded from `layouts/NAME.so`, so its name can't leave that directory.
+fn is_plugin_name(name: &str) -> bool {
+    !(name.is_empty()
+        || name.contains('/')
+        || name.contains('\\')
+        || name.contains("..")
+        || name.starts_with('.'))
+}
+
+/// The name of the layout as written in the config.
+impl fmt::Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Plugin(name) => write!(f, "plugin:{}", name),
+            layout => write!(f, "{:?}", layout),
         }
     }
 }
 
+impl Serialize for Layout {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Layout {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Self::from_str(&name).map_err(|_| de::Error::custom(format!("unknown layout `{}`", name)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -211,5 +255,18 @@ mod tests {
                 &Layout::from_str(layout_strs[i]).expect("Layout String")
             );
         }
+        let plugin = Layout::Plugin("spiral".to_owned());
+        assert_eq!(Layout::from_str("plugin:spiral"), Ok(plugin.clone()));
+        assert_eq!(Layout::from_str("plugin:"), Err(()));
+        assert_eq!(Layout::from_str("plugin:../../bin/evil"), Err(()));
+        assert_eq!(Layout::from_str("plugin:.hidden"), Err(()));
+        assert_eq!(
+            serde_json::to_string(&plugin).unwrap(),
+            r#""plugin:spiral""#
+        );
+        assert_eq!(
+            serde_json::from_str::<Layout>(r#""Monocle""#).unwrap(),
+            Layout::Monocle
+        );
     }
 }
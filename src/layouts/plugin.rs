@@ -0,0 +1,242 @@
+//! Layouts from shared libraries in `$XDG_CONFIG_HOME/leftwm/layouts/`, so that layouts can be
+//! written outside of leftwm, in any language that can export C functions.
+//!
+//! The plugin `spiral` is the file `layouts/spiral.so`, named `"plugin:spiral"` in `layouts` and
+//! `SetLayout`. It exports two functions:
+//!
+//! ```c
+//! // returns the version of the structs below it was built for, 1
+//! uint32_t leftwm_layout_version(void);
+//! // fills `windows` with `input->window_count` rects, in the order of the windows
+//! void leftwm_layout(const struct LayoutInput *input, struct Rect *windows);
+//! ```
+use super::fibonacci;
+use crate::models::{Tag, Window, Workspace};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use xdg::BaseDirectories;
+
+/// The version of `Rect` and `LayoutInput` plugins have to be built for.
+pub const VERSION: u32 = 1;
+
+/// Where a window goes, or the area of the workspace.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+}
+
+/// What a plugin is given to place the windows.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LayoutInput {
+    /// The area of the workspace left for tiled windows.
+    pub workspace: Rect,
+    pub window_count: u32,
+    /// The percentage of the width the main windows take, see `IncreaseMainWidth`.
+    pub main_width: f32,
+    /// Set by `RotateTag`.
+    pub flipped_horizontal: bool,
+    pub flipped_vertical: bool,
+}
+
+type LayoutFn = unsafe extern "C" fn(*const LayoutInput, *mut Rect);
+
+thread_local! {
+    //loaded on first use, `None` for plugins that failed so the error is only logged once
+    static PLUGINS: RefCell<HashMap<String, Option<LayoutFn>>> = RefCell::new(HashMap::new());
+}
+
+/// The names of the plugins in the layouts directory.
+#[must_use]
+pub fn available() -> Vec<String> {
+    let dir = match BaseDirectories::with_prefix("leftwm") {
+        Ok(base) => base.get_config_home().join("layouts"),
+        Err(_) => return vec![],
+    };
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "so" {
+                return None;
+            }
+            Some(path.file_stem()?.to_str()?.to_owned())
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+/// Places the windows with the plugin, or like `Fibonacci` if it can't be loaded.
+pub fn update(
+    name: &str,
+    workspace: &Workspace,
+    windows: &mut Vec<&mut Window>,
+    tags: &mut Vec<Tag>,
+) {
+    let input = LayoutInput {
+        workspace: Rect {
+            x: workspace.x(),
+            y: workspace.y(),
+            w: workspace.width(),
+            h: workspace.height(),
+        },
+        window_count: windows.len() as u32,
+        main_width: workspace.main_width(tags),
+        flipped_horizontal: workspace.flipped_horizontal(tags),
+        flipped_vertical: workspace.flipped_vertical(tags),
+    };
+    let layout = PLUGINS.with(|plugins| {
+        *plugins
+            .borrow_mut()
+            .entry(name.to_owned())
+            .or_insert_with(|| {
+                let path = plugin_path(name);
+                load(&path)
+                    .map_err(|err| log::error!("Cannot load layout {}: {}", path.display(), err))
+                    .ok()
+            })
+    });
+    match layout {
+        Some(layout) => {
+            for (window, rect) in windows.iter_mut().zip(arrange(layout, &input)) {
+                window.set_height(rect.h);
+                window.set_width(rect.w);
+                window.set_x(rect.x);
+                window.set_y(rect.y);
+            }
+        }
+        None => fibonacci::update(workspace, windows, tags),
+    }
+}
+
+fn plugin_path(name: &str) -> PathBuf {
+    let file = format!("layouts/{}.so", name);
+    BaseDirectories::with_prefix("leftwm").map_or_else(
+        |_| PathBuf::from(&file),
+        |base| base.get_config_home().join(&file),
+    )
+}
+
+fn arrange(layout: LayoutFn, input: &LayoutInput) -> Vec<Rect> {
+    let mut rects = vec![Rect::default(); input.window_count as usize];
+    // SAFETY: the plugin writes at most `window_count` rects, as its version promises.
+    unsafe { layout(input, rects.as_mut_ptr()) };
+    rects
+}
+
+//the library stays loaded for as long as the worker runs
+fn load(path: &Path) -> Result<LayoutFn, String> {
+    let c_path = CString::new(path.as_os_str().as_bytes()).map_err(|e| e.to_string())?;
+    // SAFETY: the symbols are checked to exist, and the version to be the one of the signatures
+    // they are cast to.
+    unsafe {
+        let handle = libc::dlopen(c_path.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL);
+        if handle.is_null() {
+            return Err(dl_error());
+        }
+        let version = libc::dlsym(handle, b"leftwm_layout_version\0".as_ptr().cast());
+        let layout = libc::dlsym(handle, b"leftwm_layout\0".as_ptr().cast());
+        if version.is_null() || layout.is_null() {
+            libc::dlclose(handle);
+            return Err("leftwm_layout_version or leftwm_layout is missing".to_owned());
+        }
+        let version: unsafe extern "C" fn() -> u32 = std::mem::transmute(version);
+        let found = version();
+        if found != VERSION {
+            libc::dlclose(handle);
+            return Err(format!(
+                "built for version {} instead of {}",
+                found, VERSION
+            ));
+        }
+        Ok(std::mem::transmute::<*mut libc::c_void, LayoutFn>(layout))
+    }
+}
+
+fn dl_error() -> String {
+    // SAFETY: dlerror returns null or a string that stays valid until the next dl call.
+    unsafe {
+        let error = libc::dlerror();
+        if error.is_null() {
+            return "unknown error".to_owned();
+        }
+        CStr::from_ptr(error).to_string_lossy().into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    //a plugin putting the windows in columns, the first one as wide as `main_width` says
+    const COLUMNS: &str = r"
+#include <stdbool.h>
+#include <stdint.h>
+struct Rect { int32_t x, y, w, h; };
+struct LayoutInput {
+    struct Rect workspace;
+    uint32_t window_count;
+    float main_width;
+    bool flipped_horizontal, flipped_vertical;
+};
+uint32_t leftwm_layout_version(void) { return 1; }
+void leftwm_layout(const struct LayoutInput *input, struct Rect *windows) {
+    struct Rect ws = input->workspace;
+    int32_t main = (int32_t)(ws.w * input->main_width / 100);
+    int32_t rest = input->window_count > 1 ? (ws.w - main) / (int32_t)(input->window_count - 1) : 0;
+    for (uint32_t i = 0; i < input->window_count; i++) {
+        struct Rect r = { ws.x, ws.y, i == 0 ? main : rest, ws.h };
+        if (i > 0) r.x = ws.x + main + rest * (int32_t)(i - 1);
+        windows[i] = r;
+    }
+}
+";
+
+    #[test]
+    fn plugins_should_place_the_windows() {
+        let dir = tempfile::tempdir_in("target").unwrap();
+        let source = dir.path().join("columns.c");
+        let library = dir.path().join("columns.so");
+        fs::write(&source, COLUMNS).unwrap();
+        let built = Command::new("cc")
+            .args(&["-shared", "-fPIC", "-o"])
+            .arg(&library)
+            .arg(&source)
+            .status();
+        if !built.map_or(false, |status| status.success()) {
+            eprintln!("skipped, a C compiler is needed");
+            return;
+        }
+
+        let layout = load(&library).unwrap();
+        let input = LayoutInput {
+            workspace: Rect {
+                x: 100,
+                y: 0,
+                w: 1000,
+                h: 500,
+            },
+            window_count: 3,
+            main_width: 60.0,
+            ..LayoutInput::default()
+        };
+        let widths: Vec<(i32, i32)> = arrange(layout, &input).iter().map(|r| (r.x, r.w)).collect();
+        assert_eq!(widths, [(100, 600), (700, 200), (900, 200)]);
+    }
+
+    #[test]
+    fn missing_plugins_should_be_an_error() {
+        assert!(load(Path::new("target/no-such-layout.so")).is_err());
+    }
+}
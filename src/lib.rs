@@ -34,8 +34,11 @@ use utils::xkeysym_lookup::XKeysym;
 pub use command::Command;
 pub use display_action::DisplayAction;
 pub use display_event::DisplayEvent;
+#[cfg(feature = "x11")]
 pub use display_servers::xlib_display_server::XWrap;
 pub use display_servers::DisplayServer;
+pub use display_servers::MockDisplayServer;
+#[cfg(feature = "x11")]
 pub use display_servers::XlibDisplayServer;
 pub use handlers::display_event_handler::DisplayEventHandler;
 pub use handlers::external_command_handler;
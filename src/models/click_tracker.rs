@@ -0,0 +1,96 @@
+//! Follows the mouse buttons, telling double clicks and drags apart from plain clicks.
+use super::WindowHandle;
+use crate::utils::xkeysym_lookup::{Button, ModMask};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Default)]
+pub struct ClickTracker {
+    //the latest press that could start a double click and when it happened
+    last: Option<(ModMask, Button, WindowHandle, Instant)>,
+    dragging: bool,
+}
+
+impl ClickTracker {
+    /// Follows a button press. Returns whether it is the second click of a double click, which is
+    /// a press of the same button and modifiers on the same window within `double_click_time`.
+    pub fn pressed(
+        &mut self,
+        mod_mask: ModMask,
+        button: Button,
+        handle: WindowHandle,
+        now: Instant,
+        double_click_time: Duration,
+    ) -> bool {
+        self.dragging = false;
+        let double = match self.last {
+            Some((last_mask, last_button, last_handle, at)) => {
+                last_mask == mod_mask
+                    && last_button == button
+                    && last_handle == handle
+                    && now.saturating_duration_since(at) <= double_click_time
+            }
+            None => false,
+        };
+        //a third click starts over instead of making another double click
+        self.last = if double {
+            None
+        } else {
+            Some((mod_mask, button, handle, now))
+        };
+        double
+    }
+
+    /// Follows the pointer `x` and `y` pixels away from where the button was pressed. Returns
+    /// whether the drag has started, which it does once the pointer goes further than `threshold`.
+    pub fn dragged(&mut self, x: i32, y: i32, threshold: i32) -> bool {
+        if !self.dragging && (x.abs() > threshold || y.abs() > threshold) {
+            self.dragging = true;
+            //a press ending in a drag is no click
+            self.last = None;
+        }
+        self.dragging
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use x11_dl::xlib;
+
+    const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
+
+    #[test]
+    fn two_quick_clicks_on_the_same_window_should_be_a_double_click() {
+        let mut tracker = ClickTracker::default();
+        let start = Instant::now();
+        let handle = WindowHandle::MockHandle(1);
+        let press = |tracker: &mut ClickTracker, handle, millis| {
+            let now = start + Duration::from_millis(millis);
+            tracker.pressed(
+                xlib::Mod4Mask,
+                xlib::Button1,
+                handle,
+                now,
+                DOUBLE_CLICK_TIME,
+            )
+        };
+        assert!(!press(&mut tracker, handle, 0));
+        assert!(press(&mut tracker, handle, 200));
+        assert!(!press(&mut tracker, handle, 300));
+        assert!(!press(&mut tracker, handle, 1000));
+        assert!(!press(&mut tracker, WindowHandle::MockHandle(2), 1100));
+    }
+
+    #[test]
+    fn a_drag_should_start_past_the_threshold() {
+        let mut tracker = ClickTracker::default();
+        let handle = WindowHandle::MockHandle(1);
+        let now = Instant::now();
+        tracker.pressed(0, xlib::Button1, handle, now, DOUBLE_CLICK_TIME);
+        assert!(!tracker.dragged(2, -3, 3));
+        assert!(tracker.dragged(0, 4, 3));
+        assert!(tracker.dragged(0, 0, 3));
+        //the drag doesn't count as the first click of a double click
+        assert!(!tracker.pressed(0, xlib::Button1, handle, now, DOUBLE_CLICK_TIME));
+    }
+}
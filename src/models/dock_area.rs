@@ -61,6 +61,31 @@ impl From<&[i32]> for DockArea {
 }
 
 impl DockArea {
+    /// Gives the struts without a range the range of the dock, such as those of
+    /// `_NET_WM_STRUT` which can't say which part of the edge they take.
+    #[must_use]
+    pub const fn with_ranges_of(mut self, dock: &Xyhw) -> Self {
+        let (x, y) = (dock.x(), dock.y());
+        let (end_x, end_y) = (x + dock.w(), y + dock.h());
+        if self.top_start_x == 0 && self.top_end_x == 0 {
+            self.top_start_x = x;
+            self.top_end_x = end_x;
+        }
+        if self.bottom_start_x == 0 && self.bottom_end_x == 0 {
+            self.bottom_start_x = x;
+            self.bottom_end_x = end_x;
+        }
+        if self.left_start_y == 0 && self.left_end_y == 0 {
+            self.left_start_y = y;
+            self.left_end_y = end_y;
+        }
+        if self.right_start_y == 0 && self.right_end_y == 0 {
+            self.right_start_y = y;
+            self.right_end_y = end_y;
+        }
+        self
+    }
+
     #[must_use]
     pub fn as_xyhw(
         &self,
@@ -207,4 +232,31 @@ mod tests {
         .into();
         assert_eq!(area.xyhw_from_right(2000, 2000), expected);
     }
+
+    #[test]
+    fn struts_without_a_range_should_span_the_dock() {
+        let dock: Xyhw = XyhwBuilder {
+            h: 30,
+            w: 1920,
+            x: 1920,
+            y: 0,
+            ..XyhwBuilder::default()
+        }
+        .into();
+        let area = DockArea {
+            top: 30,
+            ..DockArea::default()
+        }
+        .with_ranges_of(&dock);
+        assert_eq!((area.top_start_x, area.top_end_x), (1920, 3840));
+
+        let area = DockArea {
+            top: 30,
+            top_start_x: 100,
+            top_end_x: 200,
+            ..DockArea::default()
+        }
+        .with_ranges_of(&dock);
+        assert_eq!((area.top_start_x, area.top_end_x), (100, 200));
+    }
 }
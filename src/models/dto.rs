@@ -1,7 +1,49 @@
+use crate::config::Keybind;
 use crate::layouts::Layout;
-use crate::models::Manager;
+use crate::models::{Manager, WindowHandle};
+use crate::utils::metrics::Metrics;
 use serde::{Deserialize, Serialize};
 
+/// Version of the `ManagerState` sent over the state socket, raised whenever a change to it could
+/// break clients, such as a field being renamed or removed.
+pub const STATE_SCHEMA_VERSION: u32 = 1;
+
+/// Messages on the state socket other than the `ManagerState` lines.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StateMessage {
+    /// The first line sent to every client, before any state.
+    Hello {
+        schema_version: u32,
+        wm_version: String,
+    },
+    /// The answer to `StateQuery::Keybinds`, with the mod key mapped.
+    Keybinds(Vec<Keybind>),
+    /// The answer to `StateQuery::ListWindows`.
+    Windows(Vec<WindowInfo>),
+    /// The answer to `StateQuery::Metrics`.
+    Metrics(Metrics),
+}
+
+/// Queries clients can send on the state socket, one per line.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StateQuery {
+    Keybinds,
+    ListWindows,
+    Metrics,
+}
+
+impl StateMessage {
+    #[must_use]
+    pub fn hello() -> Self {
+        Self::Hello {
+            schema_version: STATE_SCHEMA_VERSION,
+            wm_version: env!("CARGO_PKG_VERSION").to_owned(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Viewport {
     pub tags: Vec<String>,
@@ -10,26 +52,127 @@ pub struct Viewport {
     pub x: i32,
     pub y: i32,
     pub layout: Layout,
+    //with independent tags the `desktop_names` numbered on this viewport, empty if all of them
+    #[serde(default)]
+    pub own_tags: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct ManagerState {
     pub window_title: Option<String>,
     pub desktop_names: Vec<String>,
+    //the names bars show for the `desktop_names`, the same as them for tags without a name
+    #[serde(default)]
+    pub tag_names: Vec<String>,
     pub viewports: Vec<Viewport>,
     pub active_desktop: Vec<String>,
     pub working_tags: Vec<String>,
+    #[serde(default)]
+    pub urgent_tags: Vec<String>,
+    //number of managed windows on each of the `desktop_names`
+    #[serde(default)]
+    pub window_counts: Vec<usize>,
+    #[serde(default)]
+    pub windows: Vec<WindowTags>,
+    #[serde(default)]
+    pub focused_window: Option<FocusedWindow>,
+}
+
+/// A managed window with all of its tags.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WindowTags {
+    pub title: Option<String>,
+    pub tags: Vec<String>,
+    pub focused: bool,
+}
+
+/// Where the focused window is drawn, so overlays can line up with it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FocusedWindow {
+    pub h: u32,
+    pub w: u32,
+    pub x: i32,
+    pub y: i32,
+    pub floating: bool,
+    //the workspace showing the window and its layout
+    pub workspace: Option<usize>,
+    pub layout: Option<Layout>,
+}
+
+/// A managed window as `StateQuery::ListWindows` reports it, for scripts picking windows.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WindowInfo {
+    //the X window id
+    pub id: u64,
+    pub pid: Option<u32>,
+    pub class: Option<String>,
+    pub title: Option<String>,
+    pub tags: Vec<String>,
+    pub floating: bool,
+    pub fullscreen: bool,
+    pub focused: bool,
+    pub visible: bool,
+    pub h: u32,
+    pub w: u32,
+    pub x: i32,
+    pub y: i32,
+}
+
+impl WindowInfo {
+    /// Every managed window of the manager, unmanaged ones such as docks left out, and the ones
+    /// on hidden tags such as hidden scratchpads.
+    #[must_use]
+    pub fn list(manager: &Manager) -> Vec<Self> {
+        let focused = manager.focused_window().map(|w| w.handle);
+        let hidden: Vec<&String> = manager
+            .tags
+            .iter()
+            .filter(|t| t.hidden)
+            .map(|t| &t.id)
+            .collect();
+        manager
+            .windows
+            .iter()
+            .filter(|w| !w.is_unmanaged() && !w.tags.iter().any(|t| hidden.contains(&t)))
+            .map(|w| {
+                let xyhw = w.calculated_xyhw();
+                Self {
+                    id: match w.handle {
+                        WindowHandle::XlibHandle(h) => h,
+                        WindowHandle::MockHandle(h) => h as u64,
+                    },
+                    pid: w.pid,
+                    class: w.res_class.clone(),
+                    title: w.name.clone(),
+                    tags: w.tags.clone(),
+                    floating: w.floating(),
+                    fullscreen: w.is_fullscreen(),
+                    focused: focused == Some(w.handle),
+                    visible: w.visible(),
+                    h: xyhw.h() as u32,
+                    w: xyhw.w() as u32,
+                    x: xyhw.x(),
+                    y: xyhw.y(),
+                }
+            })
+            .collect()
+    }
 }
 
 #[allow(clippy::struct_excessive_bools)]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TagsForWorkspace {
+    //the id of the tag, and the name to show for it, the id if it has none
+    pub id: String,
     pub name: String,
     pub index: usize,
     pub mine: bool,
     pub visible: bool,
     pub focused: bool,
     pub busy: bool,
+    pub urgent: bool,
+    pub window_count: usize,
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DisplayWorkspace {
@@ -45,6 +188,8 @@ pub struct DisplayWorkspace {
 pub struct DisplayState {
     pub window_title: String,
     pub workspaces: Vec<DisplayWorkspace>,
+    pub windows: Vec<WindowTags>,
+    pub focused_window: Option<FocusedWindow>,
 }
 
 impl From<ManagerState> for DisplayState {
@@ -54,42 +199,43 @@ impl From<ManagerState> for DisplayState {
             .viewports
             .iter()
             .enumerate()
-            .map(|(i, vp)| {
-                viewport_into_display_workspace(
-                    &m.desktop_names,
-                    &m.active_desktop,
-                    &visible,
-                    &m.working_tags,
-                    vp,
-                    i,
-                )
-            })
+            .map(|(i, vp)| viewport_into_display_workspace(&m, &visible, vp, i))
             .collect();
         Self {
             workspaces,
             window_title: m.window_title.unwrap_or_default(),
+            windows: m.windows,
+            focused_window: m.focused_window,
         }
     }
 }
 
 fn viewport_into_display_workspace(
-    all_tags: &[String],
-    focused: &[String],
+    m: &ManagerState,
     visible: &[String],
-    working_tags: &[String],
     viewport: &Viewport,
     ws_index: usize,
 ) -> DisplayWorkspace {
-    let tags: Vec<TagsForWorkspace> = all_tags
+    let tags: Vec<TagsForWorkspace> = m
+        .desktop_names
         .iter()
         .enumerate()
-        .map(|(index, t)| TagsForWorkspace {
-            name: t.clone(),
+        .filter(|(_, t)| viewport.own_tags.is_empty() || viewport.own_tags.contains(t))
+        .enumerate()
+        .map(|(index, (global_index, t))| TagsForWorkspace {
+            id: t.clone(),
+            name: m.tag_names.get(global_index).unwrap_or(t).clone(),
             index,
             mine: viewport.tags.contains(t),
             visible: visible.contains(t),
-            focused: focused.contains(t),
-            busy: working_tags.contains(t),
+            focused: m.active_desktop.contains(t),
+            busy: m.working_tags.contains(t),
+            urgent: m.urgent_tags.contains(t),
+            window_count: m
+                .window_counts
+                .get(global_index)
+                .copied()
+                .unwrap_or_default(),
         })
         .collect();
     DisplayWorkspace {
@@ -113,6 +259,26 @@ impl From<&Manager> for ManagerState {
             .filter(|tag| manager.windows.iter().any(|w| w.has_tag(&tag.id)))
             .map(|t| t.id.clone())
             .collect();
+        let urgent_tags = manager.tags[0..tags_len]
+            .iter()
+            .filter(|tag| {
+                manager
+                    .windows
+                    .iter()
+                    .any(|w| w.has_tag(&tag.id) && w.is_urgent())
+            })
+            .map(|t| t.id.clone())
+            .collect();
+        let window_counts = manager.tags[0..tags_len]
+            .iter()
+            .map(|tag| {
+                manager
+                    .windows
+                    .iter()
+                    .filter(|w| w.has_tag(&tag.id) && !w.is_unmanaged())
+                    .count()
+            })
+            .collect();
         for ws in &manager.workspaces {
             viewports.push(Viewport {
                 tags: ws.tags.clone(),
@@ -121,6 +287,15 @@ impl From<&Manager> for ManagerState {
                 h: ws.xyhw.h() as u32,
                 w: ws.xyhw.w() as u32,
                 layout: ws.layout.clone(),
+                own_tags: if manager.independent_tags {
+                    manager
+                        .workspace_tags(ws.id)
+                        .filter(|t| !t.hidden)
+                        .map(|t| t.id.clone())
+                        .collect()
+                } else {
+                    vec![]
+                },
             });
         }
         let active_desktop = match manager.focused_workspace() {
@@ -131,15 +306,150 @@ impl From<&Manager> for ManagerState {
             Some(win) => win.name.clone(),
             None => None,
         };
+        let focused_window = manager.focused_window().map(|w| {
+            let xyhw = w.calculated_xyhw();
+            let workspace = manager.workspaces.iter().position(|ws| ws.is_displaying(w));
+            FocusedWindow {
+                h: xyhw.h() as u32,
+                w: xyhw.w() as u32,
+                x: xyhw.x(),
+                y: xyhw.y(),
+                floating: w.floating(),
+                workspace,
+                layout: workspace.map(|i| manager.workspaces[i].layout.clone()),
+            }
+        });
+        let focused = manager.focused_window().map(|w| w.handle);
+        let windows = manager
+            .windows
+            .iter()
+            .filter(|w| !w.is_unmanaged())
+            .map(|w| WindowTags {
+                title: w.name.clone(),
+                tags: w.tags.clone(),
+                focused: focused == Some(w.handle),
+            })
+            .collect();
         Self {
             window_title,
             desktop_names: manager.tags[0..tags_len]
                 .iter()
                 .map(|t| t.id.clone())
                 .collect(),
+            tag_names: manager.tags[0..tags_len]
+                .iter()
+                .map(|t| t.display_name().to_owned())
+                .collect(),
             viewports,
             active_desktop,
             working_tags,
+            urgent_tags,
+            window_counts,
+            windows,
+            focused_window,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layouts::Layout;
+    use crate::models::{BBox, Tag, Window, WindowHandle, WindowState, Workspace, XyhwBuilder};
+
+    #[test]
+    fn tags_should_report_their_window_count_and_urgency() {
+        let mut manager = Manager::new_test();
+        //the last tag is never displayed
+        manager.tags = ["1", "2", "NSP"].iter().map(|t| Tag::new(t)).collect();
+        let mut window = Window::new(WindowHandle::MockHandle(1), None, None);
+        window.tag("1");
+        window.set_states(vec![WindowState::DemandsAttention]);
+        manager.windows.push(window);
+        let mut window = Window::new(WindowHandle::MockHandle(2), None, None);
+        window.tag("1");
+        window.tag("2");
+        manager.windows.push(window);
+
+        let state = ManagerState::from(&manager);
+        assert_eq!(state.window_counts, [2, 1]);
+        assert_eq!(state.urgent_tags, ["1".to_owned()]);
+        assert_eq!(state.windows[1].tags, ["1".to_owned(), "2".to_owned()]);
+    }
+
+    #[test]
+    fn tags_should_report_their_name_next_to_their_id() {
+        let mut manager = Manager::new_test();
+        manager.tags = ["1:web", "2", "NSP"]
+            .iter()
+            .map(|t| Tag::from_config(t))
+            .collect();
+        let bbox = BBox {
+            width: 600,
+            height: 800,
+            x: 0,
+            y: 0,
+        };
+        manager.workspaces.push(Workspace::new(
+            Some(0),
+            bbox,
+            manager.tags.clone(),
+            vec![],
+            None,
+        ));
+
+        let state = ManagerState::from(&manager);
+        assert_eq!(state.desktop_names, ["1".to_owned(), "2".to_owned()]);
+        assert_eq!(state.tag_names, ["web".to_owned(), "2".to_owned()]);
+        let display = DisplayState::from(state);
+        let tags: Vec<(&str, &str)> = display.workspaces[0]
+            .tags
+            .iter()
+            .map(|t| (t.id.as_str(), t.name.as_str()))
+            .collect();
+        assert_eq!(tags, [("1", "web"), ("2", "2")]);
+    }
+
+    #[test]
+    fn focused_window_should_report_its_geometry_and_layout() {
+        let mut manager = Manager::new_test();
+        assert_eq!(ManagerState::from(&manager).focused_window, None);
+
+        let bbox = BBox {
+            width: 600,
+            height: 800,
+            x: 0,
+            y: 0,
+        };
+        let mut workspace = Workspace::new(None, bbox, vec![], vec![], None);
+        workspace.tags = vec!["2".to_owned()];
+        workspace.layout = Layout::Monocle;
+        manager.workspaces.push(workspace);
+        let mut window = Window::new(WindowHandle::MockHandle(1), None, None);
+        window.tag("2");
+        window.margin_multiplier = 0.0;
+        window.border = 0;
+        window.normal = XyhwBuilder {
+            h: 100,
+            w: 200,
+            x: 10,
+            y: 20,
+            ..XyhwBuilder::default()
+        }
+        .into();
+        manager.windows.push(window);
+        manager
+            .focus_manager
+            .window_history
+            .push_front(Some(WindowHandle::MockHandle(1)));
+
+        let focused = ManagerState::from(&manager).focused_window.unwrap();
+        assert_eq!(
+            (focused.x, focused.y, focused.w, focused.h),
+            (10, 20, 200, 100)
+        );
+        assert!(!focused.floating);
+        assert_eq!(focused.workspace, Some(0));
+        assert_eq!(focused.layout, Some(Layout::Monocle));
+    }
+}
@@ -0,0 +1,60 @@
+//! Follows the pointer along the edges of the screens, for `[[edge_action]]`.
+use super::Side;
+use std::time::Instant;
+
+#[derive(Debug, Default)]
+pub struct EdgeTracker {
+    //the edge the pointer is on and since when
+    dwell: Option<(Side, Instant)>,
+    fired: bool,
+}
+
+impl EdgeTracker {
+    /// Follows the pointer onto an edge, along it or off it.
+    pub fn moved(&mut self, edge: Option<Side>, now: Instant) {
+        if self.dwell.as_ref().map(|(side, _)| side) == edge.as_ref() {
+            return;
+        }
+        self.dwell = edge.map(|side| (side, now));
+        self.fired = false;
+    }
+
+    /// The edge the pointer rests on and since when, until its action has run.
+    #[must_use]
+    pub fn dwelling(&self) -> Option<(&Side, Instant)> {
+        if self.fired {
+            return None;
+        }
+        self.dwell.as_ref().map(|(side, since)| (side, *since))
+    }
+
+    /// Marks the action of the edge as run, it runs again once the pointer comes back.
+    pub fn fired(&mut self) {
+        self.fired = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn an_edge_should_fire_once_per_visit() {
+        let mut tracker = EdgeTracker::default();
+        let start = Instant::now();
+        tracker.moved(Some(Side::Left), start);
+        tracker.moved(Some(Side::Left), start + Duration::from_millis(100));
+        assert_eq!(tracker.dwelling(), Some((&Side::Left, start)));
+
+        tracker.fired();
+        assert_eq!(tracker.dwelling(), None);
+        tracker.moved(Some(Side::Left), start + Duration::from_millis(200));
+        assert_eq!(tracker.dwelling(), None);
+
+        let back = start + Duration::from_millis(400);
+        tracker.moved(None, start + Duration::from_millis(300));
+        tracker.moved(Some(Side::Left), back);
+        assert_eq!(tracker.dwelling(), Some((&Side::Left, back)));
+    }
+}
@@ -18,10 +18,39 @@ impl Default for FocusBehaviour {
     }
 }
 
+/// The window focused when the focused window closes, unless the focus follows the mouse.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum FocusOnClose {
+    /// The window focused before it that is still shown on the workspace.
+    Previous,
+    /// The window at the top of the stack of the workspace.
+    Top,
+    /// None, until one is focused.
+    None,
+}
+
+impl Default for FocusOnClose {
+    fn default() -> Self {
+        Self::Previous
+    }
+}
+
 #[derive(Default, Serialize, Deserialize, Debug, Clone)]
 pub struct FocusManager {
     pub behaviour: FocusBehaviour,
     pub focus_new_windows: bool,
+    #[serde(default)]
+    pub focus_on_close: FocusOnClose,
+    //new windows launched longer than this many ms before the latest input don't take focus
+    #[serde(default)]
+    pub focus_stealing_threshold: Option<u32>,
+    //classes of windows that always take focus when they appear
+    #[serde(default)]
+    pub focus_stealing_allowed: Vec<String>,
+    //moving the mouse over a dock, or the root where docks keep space free, doesn't focus workspaces
+    #[serde(default)]
+    pub mouse_focus_ignores_docks: bool,
     pub workspace_history: VecDeque<usize>,
     pub window_history: VecDeque<MaybeWindowHandle>,
     pub tag_history: VecDeque<String>,
@@ -1,13 +1,22 @@
-use crate::config::ScratchPad;
+use crate::config::{FloatingPlacement, ScratchPad, TiledResizeRequest, WindowRule};
 use crate::display_action::DisplayAction;
+use crate::models::ClickTracker;
+use crate::models::EdgeTracker;
 use crate::models::FocusManager;
 use crate::models::Mode;
+use crate::models::Presentation;
 use crate::models::Screen;
 use crate::models::Tag;
+use crate::models::TagId;
+use crate::models::UndoEntry;
 use crate::models::Window;
 use crate::models::WindowHandle;
+use crate::models::WindowType;
 use crate::models::Workspace;
+use crate::models::Xyhw;
 use crate::utils::child_process::Children;
+use crate::utils::diagnostics::Diagnostics;
+use crate::utils::metrics::Metrics;
 use crate::{config::ThemeSetting, layouts::Layout};
 
 use serde::{Deserialize, Serialize};
@@ -17,7 +26,11 @@ use std::sync::{atomic::AtomicBool, Arc};
 
 use super::Size;
 
+/// How many operations can be undone.
+const UNDO_LIMIT: usize = 20;
+
 /// Maintains current program state.
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Manager {
     pub screens: Vec<Screen>,
@@ -30,17 +43,79 @@ pub struct Manager {
     pub layouts: Vec<Layout>,
     pub scratchpads: Vec<ScratchPad>,
     pub active_scratchpads: HashMap<String, Option<u32>>,
+    //the windows attached to a scratchpad by `AttachToScratchPad`, by the scratchpad's name
+    #[serde(default)]
+    pub attached_scratchpads: HashMap<String, WindowHandle>,
+    #[serde(default)]
+    pub window_rules: Vec<WindowRule>,
+    //what the display server is asked to do, moved into an `ActionChannel` by the worker once the
+    //events are handled
     pub actions: VecDeque<DisplayAction>,
     pub max_window_width: Option<Size>,
+    //where new floating windows go, see `FloatingPlacement`
+    #[serde(default)]
+    pub floating_placement: FloatingPlacement,
+    //how far a floating window moves off one of its class in the same spot, 0 to not move it
+    #[serde(default)]
+    pub floating_cascade_offset: i32,
+    //window types and classes that float when they appear
+    #[serde(default)]
+    pub floating_types: Vec<WindowType>,
+    #[serde(default)]
+    pub floating_classes: Vec<String>,
+    //what a tiled window asking to be moved or resized gets
+    #[serde(default)]
+    pub tiled_resize_request: TiledResizeRequest,
+    //every workspace numbers its own copy of the tags, see `Config::independent_tags`
+    #[serde(default)]
+    pub independent_tags: bool,
+    //overrides the configured modkey until the next reload, see `Command::SetModKey`
+    #[serde(skip)]
+    pub modkey: Option<String>,
+    //overrides the configured mousekey until the next reload, see `Command::SetMouseKey`
+    #[serde(skip)]
+    pub mousekey: Option<String>,
+    //the space the built-in system tray keeps free, it isn't a window of the manager
+    #[serde(skip)]
+    pub systray_strut: Option<Xyhw>,
+    //how things were before the latest reversible operations, newest first
+    #[serde(skip)]
+    pub undo_journal: VecDeque<UndoEntry>,
 
-    //this is used to limit framerate when resizing/moving windows
-    pub frame_rate_limitor: c_ulong,
+    //time of the last frame of each window being resized/moved, used to limit the framerate
+    #[serde(skip)]
+    pub frame_rate_limitors: HashMap<WindowHandle, c_ulong>,
     #[serde(skip)]
     pub children: Children,
     #[serde(skip)]
     pub reap_requested: Arc<AtomicBool>,
     #[serde(skip)]
     pub reload_requested: bool,
+    //pid of the running `lock_command`, the screen is locked until it exits
+    #[serde(skip)]
+    pub screen_lock: Option<u32>,
+    #[serde(skip)]
+    pub edge_tracker: EdgeTracker,
+    #[serde(skip)]
+    pub click_tracker: ClickTracker,
+    //the latest display events and actions, for `DumpDiagnostics`
+    #[serde(skip)]
+    pub diagnostics: Diagnostics,
+    //counts of what the worker did, for the `metrics` query of the state socket
+    #[serde(skip)]
+    pub metrics: Metrics,
+    //the tag `PeekTag` goes back to when its key is let go
+    #[serde(skip)]
+    pub peek_origin: Option<TagId>,
+    //what `TogglePresentationMode` changed, while it is on
+    #[serde(default)]
+    pub presentation: Option<Presentation>,
+    //something changed since the state was last written to the state socket
+    #[serde(skip)]
+    pub state_dirty: bool,
+    //only the `essential` keybinds are grabbed, see `keys_passed_through`
+    #[serde(skip)]
+    pub keys_passed: bool,
 }
 
 impl Manager {
@@ -68,6 +143,42 @@ impl Manager {
         Some(self.tags.iter().position(|t| t.id == tag)).unwrap_or(None)
     }
 
+    /// Return the tags numbered on a workspace: its own with independent tags, and the shared ones.
+    pub fn workspace_tags(&self, ws_id: Option<i32>) -> impl Iterator<Item = &Tag> {
+        self.tags
+            .iter()
+            .filter(move |t| t.workspace.is_none() || t.workspace == ws_id)
+    }
+
+    /// Return the tag with the given 1 based number on the focused workspace.
+    #[must_use]
+    pub fn numbered_tag(&self, tag_num: usize) -> Option<&Tag> {
+        let ws_id = self.focused_workspace().and_then(|ws| ws.id);
+        self.workspace_tags(ws_id).nth(tag_num.checked_sub(1)?)
+    }
+
+    /// Return the 1 based number of a given tag on the focused workspace.
+    #[must_use]
+    pub fn tag_number(&self, tag: &str) -> Option<usize> {
+        let ws_id = self.focused_workspace().and_then(|ws| ws.id);
+        Some(self.workspace_tags(ws_id).position(|t| t.id == tag)? + 1)
+    }
+
+    /// Return the 1 based number of a tag on the focused workspace given as a command value: its
+    /// id or name, or else its number. A tag with the id `3` is found by id, not as the third tag.
+    #[must_use]
+    pub fn tag_number_of(&self, val: &str) -> Option<usize> {
+        let val = val.trim();
+        let ws_id = self.focused_workspace().and_then(|ws| ws.id);
+        let position = self
+            .workspace_tags(ws_id)
+            .position(|t| t.id == val || t.name.as_deref() == Some(val));
+        match position {
+            Some(position) => Some(position + 1),
+            None => val.parse().ok(),
+        }
+    }
+
     /// Return the currently focused window.
     #[must_use]
     pub fn focused_window(&self) -> Option<&Window> {
@@ -79,6 +190,46 @@ impl Manager {
         self.focus_manager.window_mut(&mut self.windows)
     }
 
+    /// Whether the focused window matches a `pass_keys` window rule, in which case only the
+    /// `essential` keybinds are grabbed.
+    #[must_use]
+    pub fn keys_passed_through(&self) -> bool {
+        match self.focused_window() {
+            Some(w) => self
+                .window_rules
+                .iter()
+                .any(|r| r.pass_keys && r.matches(w)),
+            None => false,
+        }
+    }
+
+    /// Whether the focused window is fullscreen and matches a `focus_lock` window rule, in which
+    /// case the mouse shouldn't move focus away from it.
+    #[must_use]
+    pub fn focus_locked(&self) -> bool {
+        match self.focused_window() {
+            Some(w) => {
+                w.is_fullscreen()
+                    && self
+                        .window_rules
+                        .iter()
+                        .any(|r| r.focus_lock && r.matches(w))
+            }
+            None => false,
+        }
+    }
+
+    /// Reaps the children that exited. The screen gets unlocked once the lock command exits.
+    pub fn reap_children(&mut self) {
+        self.metrics.children_reaped += self.children.reap() as u64;
+        if let Some(pid) = self.screen_lock {
+            if !self.children.contains(pid) {
+                self.screen_lock = None;
+                self.state_dirty = true;
+            }
+        }
+    }
+
     pub fn update_docks(&mut self) {
         let workspaces = self.workspaces.clone();
         self.windows
@@ -95,7 +246,6 @@ impl Manager {
     //sorts the windows and puts them in order of importance
     //keeps the order for each importance level
     pub fn sort_windows(&mut self) {
-        use crate::models::WindowType;
         //first dialogs and modals
         let (level1, other): (Vec<&Window>, Vec<&Window>) = self.windows.iter().partition(|w| {
             w.type_ == WindowType::Dialog
@@ -117,19 +267,112 @@ impl Manager {
         //other is all the reset
 
         //build the updated window list
-        let windows: Vec<Window> = level1
+        let mut windows: Vec<Window> = level1
             .iter()
             .chain(level2.iter())
             .chain(level3.iter())
             .chain(other.iter())
             .map(|&w| w.clone())
             .collect();
+
+        //the window being presented goes over everything else
+        let above = self.presentation.as_ref().and_then(|p| p.above);
+        if let Some(index) = windows.iter().position(|w| Some(w.handle) == above) {
+            let window = windows.remove(index);
+            windows.insert(0, window);
+        }
+
+        //keep transient windows, such as dialogs, right above the window they belong to
+        for i in 0..windows.len() {
+            let parent = windows[i].transient;
+            if let Some(p) = windows[..i].iter().position(|w| Some(w.handle) == parent) {
+                let window = windows.remove(i);
+                windows.insert(p, window);
+            }
+        }
         self.windows = windows;
         let order: Vec<_> = self.windows.iter().map(|w| w.handle).collect();
         let act = DisplayAction::SetWindowOrder(order);
         self.actions.push_back(act);
     }
 
+    /// Tells the display server how the windows on each workspace are stacked: fullscreen windows
+    /// on top, then the others in the order `sort_windows` gave them.
+    pub fn restack_workspaces(&mut self) {
+        for ws in &self.workspaces {
+            let (mut order, rest): (Vec<&Window>, Vec<&Window>) = self
+                .windows
+                .iter()
+                .filter(|w| ws.is_displaying(w))
+                .partition(|w| w.is_fullscreen());
+            order.extend(rest);
+            let order = order.iter().map(|w| w.handle).collect();
+            let act = DisplayAction::RestackWorkspace(ws.id, order);
+            self.actions.push_back(act);
+        }
+    }
+
+    /// Remembers how things were before a reversible operation, forgetting the oldest one past
+    /// the limit.
+    pub fn record_undo(&mut self, entry: UndoEntry) {
+        self.undo_journal.push_front(entry);
+        self.undo_journal.truncate(UNDO_LIMIT);
+    }
+
+    /// Reverts the latest reversible operation. Returns false if there is none.
+    pub fn undo(&mut self) -> bool {
+        match self.undo_journal.pop_front() {
+            Some(entry) => {
+                entry.restore(self);
+                self.sort_windows();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Takes a window out of its group. If it was the member on display, the next member takes
+    /// its place and is returned.
+    pub fn leave_group(&mut self, handle: &WindowHandle) -> Option<WindowHandle> {
+        let index = self.windows.iter().position(|w| &w.handle == handle)?;
+        let group = self.windows[index].group.take()?;
+        let was_hidden = std::mem::replace(&mut self.windows[index].group_hidden, false);
+        let members = |windows: &[Window]| -> Vec<usize> {
+            (0..windows.len())
+                .filter(|&i| windows[i].group == Some(group))
+                .collect()
+        };
+        let mut revealed = None;
+        if !was_hidden {
+            let others = members(&self.windows);
+            if let Some(&next) = others
+                .iter()
+                .find(|&&i| i > index)
+                .or_else(|| others.first())
+            {
+                self.windows[next].group_hidden = false;
+                self.windows[next].tags = self.windows[index].tags.clone();
+                self.windows.swap(index, next);
+                revealed = Some(self.windows[index].handle);
+            }
+        }
+        //a group needs two windows, and is named after one of them
+        match members(&self.windows)[..] {
+            [last] => {
+                self.windows[last].group = None;
+                self.windows[last].group_hidden = false;
+            }
+            ref others if &group == handle => {
+                let id = others.first().map(|&i| self.windows[i].handle);
+                for &i in others {
+                    self.windows[i].group = id;
+                }
+            }
+            _ => {}
+        }
+        revealed
+    }
+
     pub fn move_to_top(&mut self, handle: &WindowHandle) -> Option<()> {
         let index = self.windows.iter().position(|w| &w.handle == handle)?;
         let window = self.windows.remove(index);
@@ -178,8 +421,9 @@ impl Manager {
     }
 }
 
-#[cfg(test)]
 impl Manager {
+    /// A manager without screens, windows or tags, for tests and dry runs.
+    #[must_use]
     pub fn new_test() -> Self {
         use crate::models::Margins;
 
@@ -197,18 +441,47 @@ impl Manager {
                 default_border_color: Default::default(),
                 floating_border_color: Default::default(),
                 focused_border_color: Default::default(),
+                title_bar_height: None,
+                title_bar_color: None,
+                title_bar_focused_color: None,
+                title_bar_text_color: None,
                 on_new_window_cmd: Default::default(),
+                smart_borders: false,
+                smart_gaps: false,
+                reserve_space: vec![],
+                focus_indicator: None,
             }),
             tags: Default::default(),
             layouts: Default::default(),
             scratchpads: Default::default(),
             active_scratchpads: Default::default(),
+            attached_scratchpads: Default::default(),
+            window_rules: Default::default(),
             actions: Default::default(),
-            frame_rate_limitor: Default::default(),
+            frame_rate_limitors: Default::default(),
             children: Default::default(),
             reap_requested: Default::default(),
             reload_requested: Default::default(),
             max_window_width: None,
+            floating_placement: FloatingPlacement::default(),
+            floating_cascade_offset: 0,
+            floating_types: vec![WindowType::Dialog, WindowType::Utility, WindowType::Splash],
+            floating_classes: vec![],
+            tiled_resize_request: TiledResizeRequest::default(),
+            independent_tags: false,
+            modkey: None,
+            mousekey: None,
+            systray_strut: None,
+            undo_journal: Default::default(),
+            screen_lock: None,
+            edge_tracker: Default::default(),
+            click_tracker: Default::default(),
+            diagnostics: Default::default(),
+            metrics: Default::default(),
+            peek_origin: None,
+            presentation: None,
+            state_dirty: false,
+            keys_passed: false,
         }
     }
 }
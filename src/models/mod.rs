@@ -1,13 +1,19 @@
 //! Objects (such as windows) used to develop `LeftWM`.
+mod click_tracker;
 mod dock_area;
+mod edge_tracker;
 mod focus_manager;
 mod gutter;
 mod manager;
 mod margins;
 mod mode;
+mod presentation;
 mod screen;
 mod size;
+mod snapshot;
+mod split_tree;
 mod tag;
+mod undo;
 mod window;
 mod window_change;
 mod window_state;
@@ -19,16 +25,23 @@ mod xyhw_change;
 pub mod dto;
 use crate::layouts;
 
+pub use click_tracker::ClickTracker;
 pub use dock_area::DockArea;
+pub use edge_tracker::EdgeTracker;
 pub use focus_manager::FocusBehaviour;
 pub use focus_manager::FocusManager;
+pub use focus_manager::FocusOnClose;
 pub use gutter::Gutter;
 pub use gutter::Side;
 pub use manager::Manager;
 pub use margins::Margins;
 pub use mode::Mode;
+pub use presentation::Presentation;
 pub use screen::{BBox, Screen};
 pub use size::Size;
+pub use snapshot::{Snapshot, SnapshotWindow};
+pub use split_tree::{SplitDirection, SplitNode, SplitTree};
+pub use undo::UndoEntry;
 pub use window::Window;
 pub use window::WindowHandle;
 pub use window_change::WindowChange;
@@ -0,0 +1,14 @@
+use super::{FocusBehaviour, Margins, WindowHandle};
+use serde::{Deserialize, Serialize};
+
+/// How things were before presentation mode started, put back when it ends. See
+/// `Command::TogglePresentationMode`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Presentation {
+    pub behaviour: FocusBehaviour,
+    pub focus_new_windows: bool,
+    //the window kept above every other one, notifications included
+    pub above: Option<WindowHandle>,
+    //the index of the padded workspace and its margin before
+    pub padded: Option<(usize, Margins)>,
+}
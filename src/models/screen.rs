@@ -11,6 +11,12 @@ pub struct Screen {
     pub bbox: BBox,
     pub wsid: Option<i32>,
     pub max_window_width: Option<Size>,
+    //the RandR output showing the screen, like "eDP-1"
+    #[serde(default)]
+    pub output: Option<String>,
+    //the tags the workspace of the screen starts out showing, see `Workspace::tags` in the config
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 /// Screen Bounding Box
@@ -30,6 +36,8 @@ impl Screen {
             bbox,
             wsid: None,
             max_window_width: None,
+            output: None,
+            tags: Vec::new(),
         }
     }
 
@@ -72,6 +80,8 @@ impl From<&Workspace> for Screen {
             },
             wsid: wsc.id,
             max_window_width: wsc.max_window_width,
+            output: None,
+            tags: wsc.tags.clone(),
         }
     }
 }
@@ -88,6 +98,8 @@ impl From<&xlib::XWindowAttributes> for Screen {
             },
             wsid: None,
             max_window_width: None,
+            output: None,
+            tags: Vec::new(),
         }
     }
 }
@@ -104,6 +116,8 @@ impl From<&x11_dl::xinerama::XineramaScreenInfo> for Screen {
             },
             wsid: None,
             max_window_width: None,
+            output: None,
+            tags: Vec::new(),
         }
     }
 }
@@ -120,6 +134,8 @@ impl Default for Screen {
             },
             wsid: None,
             max_window_width: None,
+            output: None,
+            tags: Vec::new(),
         }
     }
 }
@@ -0,0 +1,155 @@
+//! Which windows are on which tags and the layouts of the workspaces, saved under a name by
+//! `SaveSnapshot` and put back by `LoadSnapshot`.
+use super::{Manager, WindowHandle, WindowType};
+use crate::display_action::DisplayAction;
+use crate::errors::Result;
+use crate::layouts::Layout;
+use crate::models::TagId;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Snapshot {
+    pub windows: Vec<SnapshotWindow>,
+    pub layouts: Vec<(Option<i32>, Layout)>,
+}
+
+/// A window is known by its class, windows of a class are moved in the order they were saved.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SnapshotWindow {
+    pub class: String,
+    pub tag: TagId,
+}
+
+impl Snapshot {
+    /// The tags of the normal windows with a class, and the layouts of the workspaces.
+    #[must_use]
+    pub fn new(manager: &Manager) -> Self {
+        let windows = manager
+            .windows
+            .iter()
+            .filter(|w| w.type_ == WindowType::Normal)
+            .filter_map(|w| {
+                let tag = w.tags.first()?;
+                if manager.tags.iter().any(|t| &t.id == tag && t.hidden) {
+                    return None;
+                }
+                Some(SnapshotWindow {
+                    class: w.res_class.clone()?,
+                    tag: tag.clone(),
+                })
+            })
+            .collect();
+        let layouts = manager
+            .workspaces
+            .iter()
+            .map(|ws| (ws.id, ws.layout.clone()))
+            .collect();
+        Self { windows, layouts }
+    }
+
+    /// Moves every window to the tag saved for its class, and puts back the layouts of the
+    /// workspaces. Windows without a saved tag, or whose tag is gone, stay put.
+    /// Returns the windows moved.
+    pub fn apply(&self, manager: &mut Manager) -> Vec<WindowHandle> {
+        let mut unused: Vec<&SnapshotWindow> = self.windows.iter().collect();
+        let mut moved = vec![];
+        for window in &mut manager.windows {
+            let class = match &window.res_class {
+                Some(class) if window.type_ == WindowType::Normal => class,
+                _ => continue,
+            };
+            let saved = match unused.iter().position(|s| &s.class == class) {
+                Some(index) => unused.remove(index),
+                None => continue,
+            };
+            let exists = manager.tags.iter().any(|t| t.id == saved.tag && !t.hidden);
+            if !exists || window.tags == [saved.tag.clone()] {
+                continue;
+            }
+            window.clear_tags();
+            window.tag(&saved.tag);
+            let act = DisplayAction::SetWindowTags(window.handle, saved.tag.clone());
+            manager.actions.push_back(act);
+            moved.push(window.handle);
+        }
+        for ws in &mut manager.workspaces {
+            if let Some((_, layout)) = self.layouts.iter().find(|(id, _)| *id == ws.id) {
+                ws.set_layout(&mut manager.tags, layout.clone());
+            }
+        }
+        manager.sort_windows();
+        moved
+    }
+
+    /// Where the snapshot of this name is kept, `$XDG_DATA_HOME/leftwm/snapshots/NAME.json`.
+    /// # Errors
+    ///
+    /// Will error if the name could leave the snapshots directory, or the directory can't be made.
+    pub fn path(name: &str) -> Result<PathBuf> {
+        if name.is_empty() || name.contains('/') || name.starts_with('.') {
+            let err = io::Error::new(io::ErrorKind::InvalidInput, "invalid snapshot name");
+            return Err(err.into());
+        }
+        let dirs = xdg::BaseDirectories::with_prefix("leftwm")?;
+        Ok(dirs.place_data_file(format!("snapshots/{}.json", name))?)
+    }
+
+    /// # Errors
+    ///
+    /// Will error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// # Errors
+    ///
+    /// Will error if the file cannot be read or is not a snapshot.
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Tag;
+    use crate::utils::testing::TestManager;
+
+    #[test]
+    fn applying_a_snapshot_should_move_windows_by_class() {
+        let mut manager = TestManager::with_screens(1).with_windows(3).build();
+        manager.tags.push(Tag::new("2"));
+        let classes = ["Firefox", "Alacritty", "Alacritty"];
+        for (window, class) in manager.windows.iter_mut().zip(&classes) {
+            window.res_class = Some((*class).to_owned());
+        }
+        manager.windows[1].tags = vec!["2".to_owned()];
+        let snapshot = Snapshot::new(&manager);
+
+        let dir = tempfile::tempdir_in("target").unwrap();
+        let path = dir.path().join("coding.json");
+        snapshot.save(&path).unwrap();
+        let loaded = Snapshot::load(&path).unwrap();
+        assert_eq!(loaded, snapshot);
+
+        for window in &mut manager.windows {
+            window.tags = vec!["1".to_owned()];
+        }
+        manager.workspaces[0].layout = Layout::Monocle;
+        let moved = loaded.apply(&mut manager);
+        let alacritty = manager.windows.iter().filter(|w| w.tags == ["2"]).count();
+        assert_eq!(moved.len(), 1);
+        assert_eq!(alacritty, 1);
+        assert_eq!(manager.workspaces[0].layout, snapshot.layouts[0].1);
+    }
+
+    #[test]
+    fn snapshot_names_should_not_leave_the_snapshots_directory() {
+        assert!(Snapshot::path("../config").is_err());
+        assert!(Snapshot::path("").is_err());
+    }
+}
@@ -0,0 +1,306 @@
+//! The containers of the `Manual` layout, split by hand like in i3 or bspwm.
+use super::WindowHandle;
+use serde::{Deserialize, Serialize};
+
+/// How the windows of a container are laid out.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum SplitDirection {
+    /// Side by side.
+    Horizontal,
+    /// Above one another.
+    Vertical,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum SplitNode {
+    Window(WindowHandle),
+    Split(SplitDirection, Vec<SplitNode>),
+}
+
+/// The windows of a tag as a tree of containers. New windows open next to the focused one, in
+/// its container.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+pub struct SplitTree {
+    root: Option<SplitNode>,
+    //the window new windows open next to
+    focused: Option<WindowHandle>,
+    //how many containers above the focused window `FocusParent` went
+    parent_levels: usize,
+}
+
+impl SplitTree {
+    /// Makes the window the one new windows open next to, if it is in the tree.
+    pub fn focus(&mut self, handle: &WindowHandle) {
+        if self.path_to(handle).is_some() {
+            self.focused = Some(*handle);
+            self.parent_levels = 0;
+        }
+    }
+
+    /// Selects the container around the selected window or container, so that splits and new
+    /// windows go around it. Returns false if there is none.
+    pub fn focus_parent(&mut self) -> bool {
+        let depth = match self.focused.as_ref().and_then(|h| self.path_to(h)) {
+            Some(path) => path.len(),
+            None => return false,
+        };
+        if self.parent_levels >= depth {
+            return false;
+        }
+        self.parent_levels += 1;
+        true
+    }
+
+    /// Splits the selected window or container, so that the next window opens beside it in the
+    /// given direction. A container holding only the selection just changes direction.
+    /// Returns false if nothing is selected.
+    pub fn split(&mut self, direction: SplitDirection) -> bool {
+        let path = match self.selected_path() {
+            Some(path) => path,
+            None => return false,
+        };
+        if let Some((_, parent)) = path.split_last() {
+            if let Some(SplitNode::Split(dir, children)) = self.node_mut(parent) {
+                if children.len() == 1 {
+                    *dir = direction;
+                    return true;
+                }
+            }
+        }
+        if let Some(node) = self.node_mut(&path) {
+            let inner = std::mem::replace(node, SplitNode::Split(direction, vec![]));
+            *node = SplitNode::Split(direction, vec![inner]);
+        }
+        true
+    }
+
+    /// Drops the windows no longer tiled on the tag and adds the new ones next to the selection.
+    pub fn sync(&mut self, handles: &[WindowHandle]) {
+        if let Some(root) = self.root.take() {
+            self.root = retain(root, handles);
+        }
+        if self
+            .focused
+            .as_ref()
+            .and_then(|h| self.path_to(h))
+            .is_none()
+        {
+            self.focused = None;
+            self.parent_levels = 0;
+        }
+        for handle in handles {
+            if self.path_to(handle).is_none() {
+                self.insert(*handle);
+            }
+        }
+    }
+
+    /// Where each window goes within the given area, as x, y, width and height.
+    #[must_use]
+    pub fn geometry(
+        &self,
+        x: i32,
+        y: i32,
+        w: i32,
+        h: i32,
+    ) -> Vec<(WindowHandle, i32, i32, i32, i32)> {
+        let mut placed = vec![];
+        if let Some(root) = &self.root {
+            place(root, (x, y, w, h), &mut placed);
+        }
+        placed
+    }
+
+    //new windows are focused, so the next one opens next to this one
+    fn insert(&mut self, handle: WindowHandle) {
+        let new = SplitNode::Window(handle);
+        let path = self.selected_path();
+        self.focused = Some(handle);
+        self.parent_levels = 0;
+        if let Some((index, parent)) = path.as_ref().and_then(|p| p.split_last()) {
+            if let Some(SplitNode::Split(_, children)) = self.node_mut(parent) {
+                children.insert(index + 1, new);
+            }
+            return;
+        }
+        //beside the whole tree, or at its end when nothing is selected
+        self.root = match self.root.take() {
+            Some(SplitNode::Split(dir, mut children)) if path.is_none() => {
+                children.push(new);
+                Some(SplitNode::Split(dir, children))
+            }
+            Some(root) => Some(SplitNode::Split(
+                SplitDirection::Horizontal,
+                vec![root, new],
+            )),
+            None => Some(new),
+        };
+    }
+
+    //the path to the selected window or container, as child indices from the root
+    fn selected_path(&self) -> Option<Vec<usize>> {
+        let mut path = self.path_to(self.focused.as_ref()?)?;
+        path.truncate(path.len().saturating_sub(self.parent_levels));
+        Some(path)
+    }
+
+    fn path_to(&self, handle: &WindowHandle) -> Option<Vec<usize>> {
+        fn find(node: &SplitNode, handle: &WindowHandle, path: &mut Vec<usize>) -> bool {
+            match node {
+                SplitNode::Window(h) => h == handle,
+                SplitNode::Split(_, children) => children.iter().enumerate().any(|(i, child)| {
+                    path.push(i);
+                    let found = find(child, handle, path);
+                    if !found {
+                        path.pop();
+                    }
+                    found
+                }),
+            }
+        }
+        let mut path = vec![];
+        if find(self.root.as_ref()?, handle, &mut path) {
+            return Some(path);
+        }
+        None
+    }
+
+    fn node_mut(&mut self, path: &[usize]) -> Option<&mut SplitNode> {
+        let mut node = self.root.as_mut()?;
+        for index in path {
+            node = match node {
+                SplitNode::Split(_, children) => children.get_mut(*index)?,
+                SplitNode::Window(_) => return None,
+            };
+        }
+        Some(node)
+    }
+}
+
+//drops the windows not in `handles`, and the containers left empty
+fn retain(node: SplitNode, handles: &[WindowHandle]) -> Option<SplitNode> {
+    match node {
+        SplitNode::Window(handle) if handles.contains(&handle) => Some(SplitNode::Window(handle)),
+        SplitNode::Window(_) => None,
+        SplitNode::Split(dir, children) => {
+            let children: Vec<SplitNode> = children
+                .into_iter()
+                .filter_map(|child| retain(child, handles))
+                .collect();
+            if children.is_empty() {
+                return None;
+            }
+            Some(SplitNode::Split(dir, children))
+        }
+    }
+}
+
+//the children of a container share it evenly, the last one gets what is left over
+fn place(
+    node: &SplitNode,
+    (left, top, width, height): (i32, i32, i32, i32),
+    placed: &mut Vec<(WindowHandle, i32, i32, i32, i32)>,
+) {
+    match node {
+        SplitNode::Window(handle) => placed.push((*handle, left, top, width, height)),
+        SplitNode::Split(dir, children) => {
+            let count = children.len() as i32;
+            for (index, child) in children.iter().enumerate() {
+                let index = index as i32;
+                let share = |total: i32| {
+                    let size = total / count;
+                    let own = if index == count - 1 {
+                        total - size * index
+                    } else {
+                        size
+                    };
+                    (size * index, own)
+                };
+                let area = match dir {
+                    SplitDirection::Horizontal => {
+                        let (offset, own) = share(width);
+                        (left + offset, top, own, height)
+                    }
+                    SplitDirection::Vertical => {
+                        let (offset, own) = share(height);
+                        (left, top + offset, width, own)
+                    }
+                };
+                place(child, area, placed);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handle(id: i32) -> WindowHandle {
+        WindowHandle::MockHandle(id)
+    }
+
+    fn handles(ids: &[i32]) -> Vec<WindowHandle> {
+        ids.iter().map(|id| handle(*id)).collect()
+    }
+
+    #[test]
+    fn new_windows_should_open_in_the_split_of_the_focused_one() {
+        let mut tree = SplitTree::default();
+        tree.sync(&handles(&[1, 2]));
+        assert_eq!(
+            tree.geometry(0, 0, 100, 50),
+            [(handle(1), 0, 0, 50, 50), (handle(2), 50, 0, 50, 50)]
+        );
+
+        assert!(tree.split(SplitDirection::Vertical));
+        tree.sync(&handles(&[1, 2, 3]));
+        assert_eq!(
+            tree.geometry(0, 0, 100, 50),
+            [
+                (handle(1), 0, 0, 50, 50),
+                (handle(2), 50, 0, 50, 25),
+                (handle(3), 50, 25, 50, 25)
+            ]
+        );
+
+        //the parent of 3 is the vertical split, opening beside that goes back to the top
+        assert!(tree.focus_parent());
+        tree.sync(&handles(&[1, 2, 3, 4]));
+        let widths: Vec<i32> = tree.geometry(0, 0, 99, 50).iter().map(|g| g.3).collect();
+        assert_eq!(widths, [33, 33, 33, 33]);
+        assert!(tree.focus_parent());
+        assert!(!tree.focus_parent());
+    }
+
+    #[test]
+    fn closed_windows_should_leave_no_empty_containers() {
+        let mut tree = SplitTree::default();
+        tree.sync(&handles(&[1, 2]));
+        tree.split(SplitDirection::Vertical);
+        tree.sync(&handles(&[1, 2, 3]));
+        tree.sync(&handles(&[1]));
+        assert_eq!(tree.geometry(0, 0, 100, 50), [(handle(1), 0, 0, 100, 50)]);
+
+        tree.sync(&[]);
+        assert_eq!(tree, SplitTree::default());
+    }
+
+    #[test]
+    fn splitting_a_lone_window_should_change_its_direction() {
+        let mut tree = SplitTree::default();
+        tree.sync(&handles(&[1, 2]));
+        tree.focus(&handle(1));
+        tree.split(SplitDirection::Vertical);
+        tree.split(SplitDirection::Horizontal);
+        tree.sync(&handles(&[1, 2, 3]));
+        assert_eq!(
+            tree.geometry(0, 0, 100, 50),
+            [
+                (handle(1), 0, 0, 25, 50),
+                (handle(3), 25, 0, 25, 50),
+                (handle(2), 50, 0, 50, 50)
+            ]
+        );
+    }
+}
@@ -1,15 +1,27 @@
 // We allow this so that we can be verbose and indicate that
 // this is `TagModel` and not `WindowModel` or anything else.
 #![allow(clippy::module_name_repetitions)]
+use super::{SplitTree, WindowHandle};
 use serde::{Deserialize, Serialize};
 
 #[derive(Default, Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Tag {
     pub id: String,
+    //shown by bars instead of the id, set in the config as `"ID:NAME"`
+    #[serde(default)]
+    pub name: Option<String>,
     pub hidden: bool,
     pub main_width_percentage: u8,
     pub flipped_horizontal: bool,
     pub flipped_vertical: bool,
+    //the windows that were tiled when tiling got paused, `None` while tiling
+    pub paused_tiling: Option<Vec<WindowHandle>>,
+    //the workspace owning the tag with independent tags, `None` for tags shared by all of them
+    #[serde(default)]
+    pub workspace: Option<i32>,
+    //the containers of the `Manual` layout
+    #[serde(default)]
+    pub split_tree: SplitTree,
 }
 
 impl Tag {
@@ -17,13 +29,35 @@ impl Tag {
     pub fn new(id: &str) -> Self {
         Self {
             id: id.to_owned(),
+            name: None,
             hidden: false,
             main_width_percentage: 50,
             flipped_horizontal: false,
             flipped_vertical: false,
+            paused_tiling: None,
+            workspace: None,
+            split_tree: SplitTree::default(),
         }
     }
 
+    /// A tag of the config: `"1:web"` is the tag `1` shown as `web`, `"web"` is just `web`.
+    #[must_use]
+    pub fn from_config(entry: &str) -> Self {
+        match entry.split_once(':') {
+            Some((id, name)) if !id.is_empty() && !name.is_empty() => Self {
+                name: Some(name.to_owned()),
+                ..Self::new(id)
+            },
+            _ => Self::new(entry),
+        }
+    }
+
+    /// The name bars show, the id if the tag has no name.
+    #[must_use]
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.id)
+    }
+
     pub fn change_main_width(&mut self, delta: i8) {
         //Check we are not gonna go negative
         let mwp = &mut self.main_width_percentage;
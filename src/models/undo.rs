@@ -0,0 +1,57 @@
+//! Journal of reversible window management operations, see `Command::UndoWindowOperation`.
+use super::{Manager, Tag, Window};
+use crate::display_action::DisplayAction;
+use crate::layouts::Layout;
+
+/// The placement of the windows and the layouts before an operation.
+#[derive(Debug, Clone)]
+pub struct UndoEntry {
+    windows: Vec<Window>,
+    layouts: Vec<(Option<i32>, Layout)>,
+    tags: Vec<Tag>,
+}
+
+impl UndoEntry {
+    #[must_use]
+    pub fn new(manager: &Manager) -> Self {
+        Self {
+            windows: manager.windows.clone(),
+            layouts: manager
+                .workspaces
+                .iter()
+                .map(|ws| (ws.id, ws.layout.clone()))
+                .collect(),
+            tags: manager.tags.clone(),
+        }
+    }
+
+    /// Puts the windows that still exist back on their tags, floating or tiled in their order,
+    /// and the layouts as they were.
+    pub fn restore(self, manager: &mut Manager) {
+        for window in &mut manager.windows {
+            if let Some(saved) = self.windows.iter().find(|w| w.handle == window.handle) {
+                if window.tags != saved.tags && !saved.tags.is_empty() {
+                    let act = DisplayAction::SetWindowTags(window.handle, saved.tags[0].clone());
+                    manager.actions.push_back(act);
+                }
+                window.restore_placement(saved);
+            }
+        }
+        //windows that showed up since stay on top
+        let order = &self.windows;
+        manager
+            .windows
+            .sort_by_key(|w| order.iter().position(|saved| saved.handle == w.handle));
+
+        for ws in &mut manager.workspaces {
+            if let Some((_, layout)) = self.layouts.iter().find(|(id, _)| *id == ws.id) {
+                ws.layout = layout.clone();
+            }
+        }
+        for tag in &mut manager.tags {
+            if let Some(saved) = self.tags.iter().find(|t| t.id == tag.id) {
+                *tag = saved.clone();
+            }
+        }
+    }
+}
@@ -14,7 +14,7 @@ use x11_dl::xlib;
 
 type MockHandle = i32;
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum WindowHandle {
     MockHandle(MockHandle),
     XlibHandle(xlib::Window),
@@ -32,8 +32,12 @@ pub struct Window {
     is_floating: bool,
     floating: Option<Xyhw>,
     pub never_focus: bool,
+    //neither takes the input focus nor asks for it by WM_TAKE_FOCUS, so it is never focused
+    #[serde(default)]
+    pub refuses_input: bool,
     pub debugging: bool,
     pub name: Option<String>,
+    pub res_class: Option<String>,
     pub pid: Option<u32>,
     pub type_: WindowType,
     pub tags: Vec<TagId>,
@@ -46,6 +50,28 @@ pub struct Window {
     pub start_loc: Option<Xyhw>,
     pub container_size: Option<Xyhw>,
     pub strut: Option<Xyhw>,
+    //windows sharing a tile, only the one not hidden is shown
+    pub group: Option<WindowHandle>,
+    #[serde(default)]
+    pub group_hidden: bool,
+    //where the window last floated, so floating it again can put it back
+    #[serde(default)]
+    pub saved_xyhw: Option<Xyhw>,
+    //how long before the user's latest input the window was launched, in ms
+    #[serde(default)]
+    pub launch_age: Option<u32>,
+    //the position and size the program asked for when it mapped, used when it floats
+    #[serde(default)]
+    pub requested_geometry: Option<XyhwChange>,
+    //the program is told it is fullscreen while the window keeps its tile
+    #[serde(default)]
+    pub fake_fullscreen: bool,
+    //the tags it was on under the previous worker, for windows adopted when the worker starts
+    #[serde(default)]
+    pub previous_tags: Vec<TagId>,
+    //the invisible shadow a window drawing its own title bar has around it, kept out of its tile
+    #[serde(default)]
+    pub frame_extents: Option<Margins>,
 }
 
 impl Window {
@@ -58,7 +84,9 @@ impl Window {
             is_floating: false,
             debugging: false,
             never_focus: false,
+            refuses_input: false,
             name,
+            res_class: None,
             pid,
             type_: WindowType::Normal,
             tags: Vec::new(),
@@ -71,6 +99,14 @@ impl Window {
             floating: None,
             start_loc: None,
             container_size: None,
+            group: None,
+            group_hidden: false,
+            saved_xyhw: None,
+            launch_age: None,
+            requested_geometry: None,
+            fake_fullscreen: false,
+            previous_tags: vec![],
+            frame_extents: None,
             strut: None,
         }
     }
@@ -85,6 +121,16 @@ impl Window {
         }
     }
 
+    /// Puts back the tags, floating state and position of a saved copy of the window.
+    pub fn restore_placement(&mut self, saved: &Self) {
+        self.tags = saved.tags.clone();
+        self.is_floating = saved.is_floating;
+        self.floating = saved.floating;
+        self.normal = saved.normal;
+        self.saved_xyhw = saved.saved_xyhw;
+        self.margin_multiplier = saved.margin_multiplier;
+    }
+
     pub fn set_visible(&mut self, value: bool) {
         self.visible = value;
     }
@@ -99,6 +145,9 @@ impl Window {
     }
 
     pub fn set_floating(&mut self, value: bool) {
+        if self.is_floating && !value {
+            self.saved_xyhw = self.floating.map(|offset| self.normal + offset);
+        }
         if !self.is_floating && value && self.floating.is_none() {
             //NOTE: We float relative to the normal position.
             self.reset_float_offset();
@@ -106,6 +155,19 @@ impl Window {
         self.is_floating = value;
     }
 
+    /// Floats the window again at the position and size it had when it was last tiled.
+    /// Returns false if it hasn't floated before.
+    pub fn restore_floating(&mut self) -> bool {
+        match self.saved_xyhw {
+            Some(saved) => {
+                self.set_floating_exact(saved);
+                self.set_floating(true);
+                true
+            }
+            None => false,
+        }
+    }
+
     #[must_use]
     pub fn floating(&self) -> bool {
         self.is_floating || self.must_float()
@@ -136,7 +198,7 @@ impl Window {
     }
 
     pub fn toggle_fullscreen(&mut self) -> Option<DisplayAction> {
-        let fullscreen = self.is_fullscreen();
+        let fullscreen = self.has_state(&WindowState::Fullscreen);
         if fullscreen {
             let index = self
                 .states
@@ -149,13 +211,25 @@ impl Window {
         Some(DisplayAction::SetFullScreen(self.clone(), !fullscreen))
     }
 
+    /// Switches fake fullscreen, where the program believes it is fullscreen but the window stays
+    /// in its tile.
+    pub fn toggle_fake_fullscreen(&mut self) -> DisplayAction {
+        self.fake_fullscreen = !self.fake_fullscreen;
+        self.states.retain(|s| *s != WindowState::Fullscreen);
+        if self.fake_fullscreen {
+            self.states.push(WindowState::Fullscreen);
+        }
+        DisplayAction::SetFullScreen(self.clone(), self.fake_fullscreen)
+    }
+
+    /// Returns whether the window covers its whole screen, which a fake fullscreen one doesn't.
     #[must_use]
     pub fn is_fullscreen(&self) -> bool {
-        self.states.contains(&WindowState::Fullscreen)
+        self.states.contains(&WindowState::Fullscreen) && !self.fake_fullscreen
     }
     #[must_use]
     pub fn must_float(&self) -> bool {
-        self.transient.is_some() || self.is_unmanaged() || self.type_ == WindowType::Splash
+        self.transient.is_some() || self.is_unmanaged()
     }
     #[must_use]
     pub fn can_move(&self) -> bool {
@@ -168,7 +242,7 @@ impl Window {
 
     #[must_use]
     pub fn can_focus(&self) -> bool {
-        !self.never_focus && !self.is_unmanaged() && self.visible()
+        !self.never_focus && !self.refuses_input && !self.is_unmanaged() && self.visible()
     }
 
     pub fn set_width(&mut self, width: i32) {
@@ -188,6 +262,21 @@ impl Window {
         self.states.contains(state)
     }
 
+    #[must_use]
+    pub fn is_urgent(&self) -> bool {
+        self.has_state(&WindowState::DemandsAttention)
+    }
+
+    pub fn set_urgent(&mut self) {
+        if !self.is_urgent() {
+            self.states.push(WindowState::DemandsAttention);
+        }
+    }
+
+    pub fn clear_urgent(&mut self) {
+        self.states.retain(|s| *s != WindowState::DemandsAttention);
+    }
+
     #[must_use]
     pub fn states(&self) -> Vec<WindowState> {
         self.states.clone()
@@ -223,12 +312,14 @@ impl Window {
             value = self.normal.w();
         } else if self.floating() && self.floating.is_some() {
             let relative = self.normal + self.floating.unwrap_or_default();
-            value = relative.w() - (self.border * 2);
+            value = relative.w() - (self.border() * 2);
         } else {
             value = self.normal.w()
                 - (((self.margin.clone().left() + self.margin.clone().right()) as f32)
                     * self.margin_multiplier) as i32
-                - (self.border * 2);
+                - (self.border() * 2)
+                + self.shadow().left()
+                + self.shadow().right();
         }
         if value < 100 && !self.is_unmanaged() {
             value = 100;
@@ -243,12 +334,14 @@ impl Window {
             value = self.normal.h();
         } else if self.floating() && self.floating.is_some() {
             let relative = self.normal + self.floating.unwrap_or_default();
-            value = relative.h() - (self.border * 2);
+            value = relative.h() - (self.border() * 2);
         } else {
             value = self.normal.h()
                 - (((self.margin.clone().top() + self.margin.clone().bottom()) as f32)
                     * self.margin_multiplier) as i32
-                - (self.border * 2);
+                - (self.border() * 2)
+                + self.shadow().top()
+                + self.shadow().bottom();
         }
         if value < 100 && !self.is_unmanaged() {
             value = 100;
@@ -265,13 +358,18 @@ impl Window {
 
     #[must_use]
     pub fn border(&self) -> i32 {
-        if self.is_fullscreen() {
+        //a tiled window drawing its own frame would get the border around its shadow
+        if self.is_fullscreen() || (self.frame_extents.is_some() && !self.floating()) {
             0
         } else {
             self.border
         }
     }
 
+    fn shadow(&self) -> Margins {
+        self.frame_extents.clone().unwrap_or(Margins::Int(0))
+    }
+
     #[must_use]
     pub fn x(&self) -> i32 {
         if self.is_fullscreen() {
@@ -282,6 +380,7 @@ impl Window {
             relative.x()
         } else {
             self.normal.x() + (self.margin.clone().left() as f32 * self.margin_multiplier) as i32
+                - self.shadow().left()
         }
     }
 
@@ -295,6 +394,7 @@ impl Window {
             relative.y()
         } else {
             self.normal.y() + (self.margin.clone().top() as f32 * self.margin_multiplier) as i32
+                - self.shadow().top()
         }
     }
 
@@ -357,6 +457,53 @@ impl Window {
 mod tests {
     use super::*;
 
+    #[test]
+    fn a_fake_fullscreen_window_should_keep_its_tile() {
+        let mut subject = Window::new(WindowHandle::MockHandle(1), None, None);
+        subject.normal = XyhwBuilder {
+            w: 300,
+            h: 200,
+            ..XyhwBuilder::default()
+        }
+        .into();
+        assert!(matches!(
+            subject.toggle_fake_fullscreen(),
+            DisplayAction::SetFullScreen(_, true)
+        ));
+        assert!(subject.has_state(&WindowState::Fullscreen));
+        assert!(!subject.is_fullscreen());
+        assert_eq!(subject.width(), 300 - subject.border * 2 - 20);
+
+        assert!(matches!(
+            subject.toggle_fake_fullscreen(),
+            DisplayAction::SetFullScreen(_, false)
+        ));
+        assert!(!subject.has_state(&WindowState::Fullscreen));
+    }
+
+    #[test]
+    fn the_shadow_of_a_tiled_window_should_be_left_out_of_its_tile() {
+        let mut subject = Window::new(WindowHandle::MockHandle(1), None, None);
+        subject.margin = Margins::Int(0);
+        subject.normal = XyhwBuilder {
+            x: 100,
+            y: 50,
+            w: 300,
+            h: 200,
+            ..XyhwBuilder::default()
+        }
+        .into();
+        //left, right, top and bottom of 10, 12, 4 and 20
+        subject.frame_extents = Some(Margins::Vec(vec![4, 12, 20, 10]));
+        assert_eq!(subject.border(), 0);
+        assert_eq!(
+            (subject.x(), subject.y(), subject.width(), subject.height()),
+            (90, 46, 322, 224)
+        );
+        subject.set_floating(true);
+        assert_eq!(subject.border(), subject.border);
+    }
+
     #[test]
     fn should_be_able_to_tag_a_window() {
         let mut subject = Window::new(WindowHandle::MockHandle(1), None, None);
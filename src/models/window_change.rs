@@ -4,19 +4,24 @@ use super::WindowHandle;
 use super::WindowState;
 use super::WindowType;
 use crate::models::{Margins, XyhwChange};
+use serde::{Deserialize, Serialize};
 
 type MaybeName = Option<String>;
 
-#[derive(Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct WindowChange {
     pub handle: WindowHandle,
     pub transient: Option<MaybeWindowHandle>,
     pub never_focus: Option<bool>,
+    pub refuses_input: Option<bool>,
     pub name: Option<MaybeName>,
     pub type_: Option<WindowType>,
     pub floating: Option<XyhwChange>,
     pub strut: Option<XyhwChange>,
     pub states: Option<Vec<WindowState>>,
+    pub frame_extents: Option<Option<Margins>>,
+    //the urgency hint of `WM_HINTS`
+    pub urgent: Option<bool>,
 }
 
 impl WindowChange {
@@ -26,11 +31,14 @@ impl WindowChange {
             handle: h,
             transient: None,
             never_focus: None,
+            refuses_input: None,
             name: None,
             type_: None,
             floating: None,
             strut: None,
             states: None,
+            frame_extents: None,
+            urgent: None,
         }
     }
 
@@ -44,12 +52,8 @@ impl WindowChange {
             changed = changed || changed_trans;
             window.transient = *trans;
         }
+        //the name is only shown by the state output and title bars, which don't need a re-render
         if let Some(name) = &self.name {
-            let changed_name = window.name.is_none() || &window.name != name;
-            //if changed_name {
-            //    warn!("CHANGED: name");
-            //}
-            changed = changed || changed_name;
             window.name = name.clone();
         }
         if let Some(nf) = self.never_focus {
@@ -60,6 +64,10 @@ impl WindowChange {
             changed = changed || changed_nf;
             window.never_focus = nf;
         }
+        if let Some(refuses_input) = self.refuses_input {
+            changed = changed || window.refuses_input != refuses_input;
+            window.refuses_input = refuses_input;
+        }
         if let Some(floating_change) = self.floating {
             let changed_floating = floating_change.update_window_floating(window);
             //if changed_floating {
@@ -86,11 +94,23 @@ impl WindowChange {
                 window.margin = Margins::Int(0);
             }
         }
+        if let Some(frame_extents) = self.frame_extents {
+            changed = changed || window.frame_extents != frame_extents;
+            window.frame_extents = frame_extents;
+        }
         if let Some(states) = self.states {
             //warn!("CHANGED: state");
             changed = true;
             window.set_states(states);
         }
+        if let Some(urgent) = self.urgent {
+            changed = changed || window.is_urgent() != urgent;
+            if urgent {
+                window.set_urgent();
+            } else {
+                window.clear_urgent();
+            }
+        }
         changed
     }
 }
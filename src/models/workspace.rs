@@ -1,5 +1,7 @@
 use super::Size;
 use super::{layouts::Layout, Margins};
+use crate::config::{FloatingPlacement, ThemeSetting};
+use crate::models::BBox;
 use crate::models::Gutter;
 use crate::models::Side;
 use crate::models::Tag;
@@ -7,7 +9,6 @@ use crate::models::TagId;
 use crate::models::Window;
 use crate::models::Xyhw;
 use crate::models::XyhwBuilder;
-use crate::{config::ThemeSetting, models::BBox};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -29,6 +30,18 @@ pub struct Workspace {
     all_tags: Vec<Tag>,
     layouts: Vec<Layout>,
     pub avoid: Vec<Xyhw>,
+    //space reserved by external programs with `ReserveSpace`, kept until released
+    #[serde(default)]
+    pub reserved: Vec<Gutter>,
+    //docks are hidden and their struts ignored, see `Command::HideDock`
+    #[serde(default)]
+    pub docks_hidden: bool,
+    //the output of the screen the workspace is on, for the theme's `reserve_space`
+    #[serde(default)]
+    pub output: Option<String>,
+    //the output is switched off, nothing gets focused here until it is back
+    #[serde(default)]
+    pub inactive: bool,
     pub xyhw: Xyhw,
     xyhw_avoided: Xyhw,
     pub max_window_width: Option<Size>,
@@ -71,6 +84,10 @@ impl Workspace {
             margin_multiplier: 1.0,
             gutters: vec![],
             avoid: vec![],
+            reserved: vec![],
+            docks_hidden: false,
+            output: None,
+            inactive: false,
             all_tags,
             layouts,
             xyhw: XyhwBuilder {
@@ -99,6 +116,22 @@ impl Workspace {
     }
 
     pub fn get_gutters_for_theme(&mut self, theme: &ThemeSetting) -> Vec<Gutter> {
+        let mut gutters = self.gutters_of_theme(theme);
+        //space the theme reserves for bars without struts takes the place of gutters
+        let reserved = theme
+            .reserve_space
+            .iter()
+            .filter(|r| r.output.is_none() || r.output == self.output);
+        for space in reserved {
+            for (side, value) in space.sides() {
+                gutters.retain(|g| g.side != side);
+                gutters.push(Gutter::new(side, value, self.id));
+            }
+        }
+        gutters
+    }
+
+    fn gutters_of_theme(&self, theme: &ThemeSetting) -> Vec<Gutter> {
         theme
             .get_list_of_gutters()
             .into_iter()
@@ -126,6 +159,12 @@ impl Workspace {
         self.xyhw.contains_point(x, y)
     }
 
+    /// Whether the point is on the workspace, outside the space kept free for docks.
+    #[must_use]
+    pub const fn contains_point_clear_of_docks(&self, x: i32, y: i32) -> bool {
+        self.xyhw_avoided.contains_point(x, y)
+    }
+
     #[must_use]
     pub fn has_tag(&self, tag: &str) -> bool {
         for t in &self.tags {
@@ -157,6 +196,9 @@ impl Workspace {
     /// Returns true if the workspace is displays a given window.
     #[must_use]
     pub fn is_displaying(&self, window: &Window) -> bool {
+        if window.group_hidden {
+            return false;
+        }
         for wd_t in &window.tags {
             if self.has_tag(wd_t) {
                 return true;
@@ -331,14 +373,90 @@ impl Workspace {
         self.xyhw_avoided.center_halfed()
     }
 
+    /// Where a new floating window half the size of the workspace goes. `cursor` is where the
+    /// mouse pointer is and `others` are the floating windows already on the workspace.
+    #[must_use]
+    pub fn floating_spot(
+        &self,
+        placement: FloatingPlacement,
+        cursor: (i32, i32),
+        others: &[Xyhw],
+    ) -> Xyhw {
+        let mut spot = self.center_halfed();
+        match placement {
+            FloatingPlacement::Center => {}
+            FloatingPlacement::UnderCursor => {
+                spot.set_x(cursor.0 - spot.w() / 2);
+                spot.set_y(cursor.1 - spot.h() / 2);
+                spot.clamp_into(self.xyhw_avoided);
+            }
+            FloatingPlacement::Smart => {
+                //try a grid of spots, nearest to the middle first, keeping the least covering one
+                const STEPS: i32 = 8;
+                let area = self.xyhw_avoided;
+                let free_w = area.w() - spot.w();
+                let free_h = area.h() - spot.h();
+                let mut candidates = vec![];
+                for i in 0..=STEPS {
+                    for j in 0..=STEPS {
+                        let mut candidate = spot;
+                        candidate.set_x(area.x() + free_w * i / STEPS);
+                        candidate.set_y(area.y() + free_h * j / STEPS);
+                        let covered: u64 =
+                            others.iter().map(|o| o.overlap_volume(&candidate)).sum();
+                        let distance = (i - STEPS / 2).abs() + (j - STEPS / 2).abs();
+                        candidates.push((covered, distance, candidate));
+                    }
+                }
+                if let Some((_, _, best)) = candidates.into_iter().min_by_key(|c| (c.0, c.1)) {
+                    spot = best;
+                }
+            }
+        }
+        spot
+    }
+
     pub fn update_avoided_areas(&mut self) {
         let mut xyhw = self.xyhw;
-        for a in &self.avoid {
-            xyhw = xyhw.without(a);
+        if !self.docks_hidden {
+            for a in &self.avoid {
+                xyhw = xyhw.without(a);
+            }
+        }
+        for reserved in &self.reserved {
+            let value = reserved.value;
+            match reserved.side {
+                Side::Top => xyhw.set_y(xyhw.y() + value),
+                Side::Left => xyhw.set_x(xyhw.x() + value),
+                Side::Bottom | Side::Right => {}
+            }
+            match reserved.side {
+                Side::Top | Side::Bottom => xyhw.set_h(xyhw.h() - value),
+                Side::Left | Side::Right => xyhw.set_w(xyhw.w() - value),
+            }
         }
         self.xyhw_avoided = xyhw;
     }
 
+    /// Reserves space on a side of the workspace, replacing what was reserved there before.
+    pub fn reserve(&mut self, side: Side, value: i32) {
+        self.reserved.retain(|r| r.side != side);
+        if value > 0 {
+            self.reserved.push(Gutter::new(side, value, self.id));
+        }
+        self.update_avoided_areas();
+    }
+
+    /// Releases the space reserved on a side, or on all of them. Returns false if nothing was
+    /// reserved.
+    pub fn release(&mut self, side: Option<&Side>) -> bool {
+        let before = self.reserved.len();
+        self.reserved
+            .retain(|r| matches!(side, Some(side) if &r.side != side));
+        self.update_avoided_areas();
+        self.reserved.len() != before
+    }
+
     /// Set the tag model's margin multiplier.
     pub fn set_margin_multiplier(&mut self, margin_multiplier: f32) {
         self.margin_multiplier = margin_multiplier;
@@ -398,4 +516,139 @@ mod tests {
         w.tag("test");
         assert!(subject.is_displaying(&w), "workspace should include window");
     }
+
+    #[test]
+    fn reserved_space_of_the_theme_should_replace_gutters_on_its_output() {
+        let theme: ThemeSetting = toml::from_str(
+            r#"
+border_width = 0
+margin = 0
+default_border_color = '#222222'
+floating_border_color = '#005500'
+focused_border_color = '#FFB53A'
+reserve_space = { top = 30, output = "eDP-1" }
+
+[[gutter]]
+side = "Top"
+value = 5
+"#,
+        )
+        .unwrap();
+        let workspace = |output: &str| {
+            let bbox = BBox {
+                width: 600,
+                height: 800,
+                x: 0,
+                y: 0,
+            };
+            let mut workspace = Workspace::new(Some(0), bbox, vec![], vec![], None);
+            workspace.output = Some(output.to_owned());
+            workspace.update_for_theme(&theme);
+            workspace.get_gutter(&Side::Top)
+        };
+        assert_eq!(workspace("eDP-1"), 30);
+        assert_eq!(workspace("HDMI-1"), 5);
+    }
+
+    #[test]
+    fn max_window_width_should_center_the_columns() {
+        let workspace = |max_window_width| {
+            Workspace::new(
+                None,
+                BBox {
+                    width: 3000,
+                    height: 800,
+                    x: 100,
+                    y: 0,
+                },
+                vec![],
+                vec![],
+                max_window_width,
+            )
+        };
+        // the default margin of 10 leaves 2980 pixels starting at 110
+        let unlimited = workspace(None);
+        assert_eq!(unlimited.width_limited(2), 2980);
+        assert_eq!(unlimited.x_limited(2), 110);
+
+        let pixels = workspace(Some(Size::Pixel(1000)));
+        assert_eq!(pixels.width_limited(2), 2000);
+        assert_eq!(pixels.x_limited(2), 600);
+        assert_eq!(pixels.width_limited(4), 2980);
+        assert_eq!(pixels.x_limited(4), 110);
+
+        let percentage = workspace(Some(Size::Percentage(0.25)));
+        assert_eq!(percentage.width_limited(1), 745);
+        assert_eq!(percentage.x_limited(1), 1227);
+    }
+
+    #[test]
+    fn floating_spot_should_follow_the_placement() {
+        let subject = Workspace::new(
+            None,
+            BBox {
+                width: 800,
+                height: 600,
+                x: 0,
+                y: 0,
+            },
+            vec![],
+            vec![],
+            None,
+        );
+        let spot = |placement, cursor, others: &[Xyhw]| {
+            let spot = subject.floating_spot(placement, cursor, others);
+            (spot.x(), spot.y(), spot.w(), spot.h())
+        };
+        let center = subject.center_halfed();
+        let center = (center.x(), center.y(), center.w(), center.h());
+        assert_eq!(spot(FloatingPlacement::Center, (0, 0), &[]), center);
+        assert_eq!(spot(FloatingPlacement::Smart, (0, 0), &[]), center);
+        assert_eq!(
+            spot(FloatingPlacement::UnderCursor, (400, 300), &[]),
+            center
+        );
+        assert_eq!(
+            spot(FloatingPlacement::UnderCursor, (790, 10), &[]),
+            (400, 0, 400, 300)
+        );
+
+        let left_half: Xyhw = XyhwBuilder {
+            x: 0,
+            y: 0,
+            w: 400,
+            h: 600,
+            ..XyhwBuilder::default()
+        }
+        .into();
+        let (x, _, _, _) = spot(FloatingPlacement::Smart, (0, 0), &[left_half]);
+        assert!(x >= 400);
+    }
+
+    #[test]
+    fn reserved_space_should_be_kept_free_until_released() {
+        let mut subject = Workspace::new(
+            None,
+            BBox {
+                width: 600,
+                height: 800,
+                x: 0,
+                y: 0,
+            },
+            vec![],
+            vec![],
+            None,
+        );
+        subject.reserve(Side::Top, 30);
+        subject.reserve(Side::Right, 100);
+        assert_eq!(
+            (subject.x(), subject.y(), subject.width(), subject.height()),
+            (10, 40, 480, 750)
+        );
+        assert!(subject.release(Some(&Side::Top)));
+        assert!(!subject.release(Some(&Side::Top)));
+        assert_eq!((subject.y(), subject.height()), (10, 780));
+        assert!(subject.release(None));
+        assert_eq!(subject.width(), 580);
+    }
 }
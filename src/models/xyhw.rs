@@ -216,6 +216,18 @@ impl Xyhw {
         self.h as u64 * self.w as u64
     }
 
+    /// The area the two boxes have in common.
+    #[must_use]
+    pub fn overlap_volume(&self, other: &Self) -> u64 {
+        let w = cmp::min(self.x + self.w, other.x + other.w) - cmp::max(self.x, other.x);
+        let h = cmp::min(self.y + self.h, other.y + other.h) - cmp::max(self.y, other.y);
+        if w > 0 && h > 0 {
+            h as u64 * w as u64
+        } else {
+            0
+        }
+    }
+
     /// Trim a Xyhw out of another Xyhw so that they don't overlap.
     #[must_use]
     pub const fn without(&self, other: &Self) -> Self {
@@ -281,6 +293,32 @@ impl Xyhw {
         }
     }
 
+    /// Shrinks and moves the box as little as needed to fit inside `outer`.
+    pub fn clamp_into(&mut self, outer: Self) {
+        self.w = self.w.min(outer.w);
+        self.h = self.h.min(outer.h);
+        self.x = self.x.max(outer.x).min(outer.x + outer.w - self.w);
+        self.y = self.y.max(outer.y).min(outer.y + outer.h - self.h);
+    }
+
+    /// Moves the box from one area to another, keeping where it is within them. The position is
+    /// scaled between areas of different sizes, the size is kept. A box sticking out of `to` is
+    /// pushed back in, and only shrunk where it is bigger than `to` itself.
+    #[must_use]
+    pub fn moved_between(&self, from: Self, to: Self) -> Self {
+        let scale = |offset: i32, to: i32, from: i32| {
+            if from == 0 {
+                return 0;
+            }
+            (i64::from(offset) * i64::from(to) / i64::from(from)) as i32
+        };
+        let mut moved = *self;
+        moved.x = to.x + scale(self.x - from.x, to.w, from.w);
+        moved.y = to.y + scale(self.y - from.y, to.h, from.h);
+        moved.clamp_into(to);
+        moved
+    }
+
     #[must_use]
     pub const fn center(&self) -> (i32, i32) {
         let x = self.x + (self.w / 2);
@@ -313,6 +351,70 @@ mod tests {
         assert_eq!(result, correct);
     }
 
+    #[test]
+    fn clamp_into_should_keep_the_box_inside() {
+        let outer = Xyhw {
+            x: 100,
+            y: 0,
+            w: 1000,
+            h: 500,
+            ..Xyhw::default()
+        };
+        let mut a = Xyhw {
+            x: 900,
+            y: -20,
+            w: 300,
+            h: 800,
+            ..Xyhw::default()
+        };
+        a.clamp_into(outer);
+        let correct = Xyhw {
+            x: 800,
+            y: 0,
+            w: 300,
+            h: 500,
+            ..Xyhw::default()
+        };
+        assert_eq!(a, correct);
+    }
+
+    #[test]
+    fn moved_between_should_scale_the_position() {
+        let from = Xyhw {
+            w: 1000,
+            h: 500,
+            ..Xyhw::default()
+        };
+        let to = Xyhw {
+            x: 1000,
+            w: 2000,
+            h: 1000,
+            ..Xyhw::default()
+        };
+        let a = Xyhw {
+            x: 500,
+            y: 100,
+            w: 300,
+            h: 200,
+            ..Xyhw::default()
+        };
+        let correct = Xyhw {
+            x: 2000,
+            y: 200,
+            w: 300,
+            h: 200,
+            ..Xyhw::default()
+        };
+        assert_eq!(a.moved_between(from, to), correct);
+        //the way back, where it only fits if pushed to the left
+        let b = Xyhw { x: 2900, ..a };
+        assert_eq!(b.moved_between(to, from).x(), 700);
+        //a box wider than the area it goes to is as wide as the area
+        let c = Xyhw { w: 1500, ..a };
+        let moved = c.moved_between(to, from);
+        assert_eq!((moved.x(), moved.w()), (0, 1000));
+    }
+
     #[test]
     fn without_should_trim_from_the_top() {
         let a = Xyhw {
@@ -0,0 +1,101 @@
+//! The channel display actions take from the handlers to the display server.
+//!
+//! The handlers queue their actions on `Manager::actions` as they run. The worker moves them into
+//! this bounded channel and runs them on the display server from a branch of its event loop of
+//! their own. No new events are read while actions wait in the channel, and once it is full the
+//! rest stay queued, so the worker handles no more events until the display server has caught up.
+use crate::display_action::DisplayAction;
+use std::collections::VecDeque;
+use tokio::sync::mpsc::{self, error::TrySendError, Receiver, Sender};
+
+/// How many actions wait for the display server at most.
+pub const ACTION_CHANNEL_CAPACITY: usize = 64;
+
+pub struct ActionChannel {
+    capacity: usize,
+    sender: Sender<DisplayAction>,
+    receiver: Receiver<DisplayAction>,
+}
+
+impl Default for ActionChannel {
+    fn default() -> Self {
+        Self::new(ACTION_CHANNEL_CAPACITY)
+    }
+}
+
+impl ActionChannel {
+    /// # Panics
+    ///
+    /// Panics if the capacity is 0.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        let (sender, receiver) = mpsc::channel(capacity);
+        Self {
+            capacity,
+            sender,
+            receiver,
+        }
+    }
+
+    /// Moves the queued actions into the channel, as many as fit. Returns true if all of them did.
+    pub fn forward(&self, queued: &mut VecDeque<DisplayAction>) -> bool {
+        while let Some(act) = queued.pop_front() {
+            if let Err(TrySendError::Full(act) | TrySendError::Closed(act)) =
+                self.sender.try_send(act)
+            {
+                queued.push_front(act);
+                return false;
+            }
+        }
+        true
+    }
+
+    /// How many actions wait for the display server.
+    #[must_use]
+    pub fn pending(&self) -> usize {
+        self.capacity - self.sender.capacity()
+    }
+
+    /// Takes every action waiting for the display server, waits for one if none is.
+    pub async fn recv_all(&mut self) -> Vec<DisplayAction> {
+        let mut actions = vec![];
+        actions.extend(self.receiver.recv().await);
+        //the rest is already in the channel, these don't wait
+        for _ in 0..self.pending() {
+            actions.extend(self.receiver.recv().await);
+        }
+        actions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn actions_past_the_capacity_should_stay_queued() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(actions_past_the_capacity_should_stay_queued_async());
+    }
+    async fn actions_past_the_capacity_should_stay_queued_async() {
+        let mut channel = ActionChannel::new(2);
+        let mut queued: VecDeque<DisplayAction> = vec![
+            DisplayAction::SetPresenting(true),
+            DisplayAction::NormalMode,
+            DisplayAction::Unfocus,
+        ]
+        .into();
+
+        assert!(!channel.forward(&mut queued));
+        assert_eq!(channel.pending(), 2);
+        assert_eq!(queued.len(), 1);
+
+        let received = channel.recv_all().await;
+        let names: Vec<String> = received.iter().map(|a| format!("{:?}", a)).collect();
+        assert_eq!(names, ["SetPresenting(true)", "NormalMode"]);
+        assert_eq!(channel.pending(), 0);
+        assert!(channel.forward(&mut queued));
+        assert!(queued.is_empty());
+        assert_eq!(channel.pending(), 1);
+    }
+}
@@ -1,16 +1,34 @@
 //! Starts programs in autostart, runs global 'up' script, and boots theme. Provides function to
 //! boot other desktop files also.
+use crate::config::ExecOptions;
 use crate::errors::Result;
 use crate::models::Manager;
+use nix::sys::signal::{kill, Signal};
+use nix::unistd::Pid;
+use signal_hook::consts::signal::{SIGINT, SIGTERM};
 use std::collections::HashMap;
+use std::env;
 use std::fs;
 use std::io;
 use std::iter::{Extend, FromIterator};
+use std::os::unix::net::UnixStream;
 use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
 use std::sync::{atomic::AtomicBool, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
 use xdg::BaseDirectories;
 
+/// Set by `leftwm` for the worker, so the worker knows which process to tell that the 'up'
+/// scripts are done.
+pub const LEFTWM_PID_ENV: &str = "LEFTWM_PID";
+
+/// Set by `leftwm` for a worker started after a reload, so the worker knows it is one.
+pub const LEFTWM_RELOAD_ENV: &str = "LEFTWM_RELOAD";
+
+/// The status a worker exits with when it ends for a reload, telling `leftwm` apart from a crash.
+pub const RELOAD_EXIT_CODE: i32 = 10;
+
 #[derive(Default)]
 pub struct Nanny {}
 
@@ -84,6 +102,33 @@ impl Nanny {
         path.push("up");
         Self::run_script(&path)
     }
+
+    /// Waits in the background for the 'up' scripts to exit, or for `timeout` to pass, then sends
+    /// `SIGUSR1` to the `leftwm` process that started this worker so it boots the autostart
+    /// programs.
+    pub fn notify_when_up(mut scripts: Vec<Child>, timeout: Duration) {
+        let leftwm = env::var(LEFTWM_PID_ENV)
+            .ok()
+            .and_then(|pid| pid.parse().ok())
+            .map(Pid::from_raw);
+        thread::spawn(move || {
+            let start = Instant::now();
+            while start.elapsed() < timeout
+                && scripts.iter_mut().any(|s| matches!(s.try_wait(), Ok(None)))
+            {
+                thread::sleep(Duration::from_millis(100));
+            }
+            if let Some(leftwm) = leftwm {
+                if let Err(err) = kill(leftwm, Signal::SIGUSR1) {
+                    log::error!("Cannot notify leftwm to autostart: {}", err);
+                }
+            }
+            // keep reaping scripts that outlived the timeout
+            for mut script in scripts {
+                let _ = script.wait();
+            }
+        });
+    }
 }
 
 fn boot_desktop_file(path: &Path) -> std::io::Result<Child> {
@@ -118,6 +163,8 @@ fn list_desktop_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
 #[derive(Debug, Default)]
 pub struct Children {
     inner: HashMap<u32, Child>,
+    //what the children were started with, if anything special, for debugging
+    exec_options: HashMap<u32, ExecOptions>,
 }
 
 impl Children {
@@ -140,16 +187,38 @@ impl Children {
         // Not possible to have duplication!
         self.inner.insert(child.id(), child).is_none()
     }
+    /// Insert a `Child` together with the environment and directory it was started with.
+    pub fn insert_with(&mut self, child: Child, options: ExecOptions) -> bool {
+        if !options.is_empty() {
+            self.exec_options.insert(child.id(), options);
+        }
+        self.insert(child)
+    }
+    /// Whether the child with this pid is still running, as of the last reap.
+    #[must_use]
+    pub fn contains(&self, pid: u32) -> bool {
+        self.inner.contains_key(&pid)
+    }
+    /// The environment and directory a child was started with, if it wasn't the default.
+    #[must_use]
+    pub fn exec_options(&self, pid: u32) -> Option<&ExecOptions> {
+        self.exec_options.get(&pid)
+    }
     /// Merge another `Children` into this `Children`.
     pub fn merge(&mut self, reaper: Self) {
         self.inner.extend(reaper.inner.into_iter());
+        self.exec_options.extend(reaper.exec_options);
     }
-    /// Try reaping all the children processes managed by this struct.
-    pub fn reap(&mut self) {
+    /// Try reaping all the children processes managed by this struct. Returns how many were.
+    pub fn reap(&mut self) -> usize {
+        let before = self.inner.len();
         // The `try_wait` needs `child` to be `mut`, but only `HashMap::retain`
         // allows modifying the value. Here `id` is not needed.
         self.inner
             .retain(|_, child| child.try_wait().map_or(true, |ret| ret.is_none()));
+        let inner = &self.inner;
+        self.exec_options.retain(|pid, _| inner.contains_key(pid));
+        before - self.inner.len()
     }
 }
 
@@ -160,6 +229,7 @@ impl FromIterator<Child> for Children {
                 .into_iter()
                 .map(|child| (child.id(), child))
                 .collect::<HashMap<_, _>>(),
+            exec_options: HashMap::new(),
         }
     }
 }
@@ -178,17 +248,135 @@ pub fn register_child_hook(flag: Arc<AtomicBool>) {
         .map_err(|err| log::error!("Cannot register SIGCHLD signal handler: {:?}", err));
 }
 
+/// Register the `SIGUSR1` signal handler, sent by the worker once its 'up' scripts are done.
+pub fn register_up_hook(flag: Arc<AtomicBool>) {
+    let _ = signal_hook::flag::register(signal_hook::consts::signal::SIGUSR1, flag)
+        .map_err(|err| log::error!("Cannot register SIGUSR1 signal handler: {:?}", err));
+}
+
+/// Register the `SIGTERM` and `SIGINT` signal handlers. Each signal writes to the returned
+/// socket, which the worker waits on to run the `wm_exiting` hooks before it exits.
+///
+/// # Errors
+///
+/// Will error if the socket can't be made or the handlers can't be registered.
+pub fn register_exit_hook() -> io::Result<UnixStream> {
+    let (read, write) = UnixStream::pair()?;
+    for signal in &[SIGTERM, SIGINT] {
+        signal_hook::low_level::pipe::register(*signal, write.try_clone()?)?;
+    }
+    read.set_nonblocking(true)?;
+    Ok(read)
+}
+
 /// Sends command to shell for execution
 /// Assumes STDIN/STDOUT unwanted.
 pub fn exec_shell(command: &str, manager: &mut Manager) -> Option<u32> {
-    let child = Command::new("sh")
+    exec_shell_with(command, &ExecOptions::default(), manager)
+}
+
+/// Sends command to shell for execution, with extra environment variables and in another
+/// directory if the options say so.
+/// Assumes STDIN/STDOUT unwanted.
+pub fn exec_shell_with(command: &str, options: &ExecOptions, manager: &mut Manager) -> Option<u32> {
+    let mut shell = Command::new("sh");
+    shell
         .arg("-c")
         .arg(&command)
+        .envs(&options.env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null());
+    if let Some(dir) = &options.working_dir {
+        shell.current_dir(expand_home(dir));
+    }
+    let child = shell
+        .spawn()
+        .map_err(|err| log::error!("Cannot start {}: {}", command, err))
+        .ok()?;
+    let pid = child.id();
+    if !options.is_empty() {
+        log::debug!("Started {} as {} with {:?}", command, pid, options);
+    }
+    manager.children.insert_with(child, options.clone());
+    Some(pid)
+}
+
+/// Sends a hook command to shell for execution, with `args` following it as `$1`, `$2`...
+/// Assumes STDIN/STDOUT unwanted.
+pub fn exec_hook(command: &str, args: &[&str], manager: &mut Manager) -> Option<u32> {
+    let child = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$@\"", command))
+        .arg("sh")
+        .args(args)
         .stdin(Stdio::null())
         .stdout(Stdio::null())
         .spawn()
+        .map_err(|err| log::error!("Cannot start {}: {}", command, err))
         .ok()?;
     let pid = child.id();
     manager.children.insert(child);
     Some(pid)
 }
+
+//only `~` and `~/...` are the home directory, `~user` is left as it is
+fn expand_home(path: &str) -> PathBuf {
+    let rest = match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => rest.trim_start_matches('/'),
+        _ => return PathBuf::from(path),
+    };
+    dirs_next::home_dir().map_or_else(|| PathBuf::from(path), |home| home.join(rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_a_leading_tilde_alone_or_with_a_slash_should_be_home() {
+        let home = dirs_next::home_dir().unwrap();
+        assert_eq!(expand_home("~"), home);
+        assert_eq!(expand_home("~/bin"), home.join("bin"));
+        assert_eq!(expand_home("~root/bin"), PathBuf::from("~root/bin"));
+        assert_eq!(expand_home("/tmp/~"), PathBuf::from("/tmp/~"));
+    }
+
+    #[test]
+    fn exec_options_should_apply_to_the_started_program() {
+        let dir = tempfile::tempdir_in("target").unwrap();
+        let mut options = ExecOptions {
+            working_dir: Some(dir.path().to_string_lossy().into_owned()),
+            ..ExecOptions::default()
+        };
+        options
+            .env
+            .insert("LEFTWM_TEST".to_owned(), "hello".to_owned());
+        let mut manager = Manager::new_test();
+
+        let pid = exec_shell_with("echo $LEFTWM_TEST > out", &options, &mut manager).unwrap();
+        assert_eq!(manager.children.exec_options(pid), Some(&options));
+        let out = dir.path().join("out");
+        let start = Instant::now();
+        while fs::read_to_string(&out).unwrap_or_default() != "hello\n" {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let pid = exec_shell("true", &mut manager).unwrap();
+        assert_eq!(manager.children.exec_options(pid), None);
+    }
+
+    #[test]
+    fn hooks_should_get_their_arguments() {
+        let dir = tempfile::tempdir_in("target").unwrap();
+        let out = dir.path().join("out");
+        let mut manager = Manager::new_test();
+        let command = format!("printf '%s|' > {}", out.display());
+        exec_hook(&command, &["web", "HDMI 1"], &mut manager).unwrap();
+        let start = Instant::now();
+        while fs::read_to_string(&out).unwrap_or_default() != "web|HDMI 1|" {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
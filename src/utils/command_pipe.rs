@@ -1,4 +1,5 @@
 //! Creates a pipe to listen for external commands.
+use crate::models::Side;
 use std::io::Result;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
@@ -35,16 +36,30 @@ impl CommandPipe {
     /// Will error if unable to `mkfifo`, likely a filesystem issue
     /// such as inadequate permissions.
     pub async fn new(pipe_file: PathBuf) -> Result<Self> {
+        Self::with_token(pipe_file, None).await
+    }
+
+    /// Create and listen to the named pipe. Only the user running `LeftWM` can write to the pipe,
+    /// and with a token set every command has to start with it, e.g. `<token> Reload`.
+    /// Commands without it are logged and dropped.
+    /// # Errors
+    ///
+    /// Will error if unable to `mkfifo`, likely a filesystem issue
+    /// such as inadequate permissions.
+    pub async fn with_token(pipe_file: PathBuf, token: Option<String>) -> Result<Self> {
+        use std::os::unix::fs::PermissionsExt;
         fs::remove_file(pipe_file.as_path()).await.ok();
         if let Err(e) = nix::unistd::mkfifo(&pipe_file, nix::sys::stat::Mode::S_IRWXU) {
             log::error!("Failed to create new fifo {:?}", e);
         }
+        //mkfifo honours the umask, set the mode explicitly so other users can't write to it
+        fs::set_permissions(&pipe_file, std::fs::Permissions::from_mode(0o600)).await?;
 
         let path = pipe_file.clone();
         let (tx, rx) = mpsc::unbounded_channel();
         tokio::spawn(async move {
             while !tx.is_closed() {
-                read_from_pipe(&path, &tx).await;
+                read_from_pipe(&path, &tx, token.as_deref()).await;
             }
             fs::remove_file(path).await.ok();
         });
@@ -60,47 +75,98 @@ impl CommandPipe {
 async fn read_from_pipe(
     pipe_file: &Path,
     tx: &mpsc::UnboundedSender<ExternalCommand>,
+    token: Option<&str>,
 ) -> Option<()> {
     let file = fs::File::open(pipe_file).await.ok()?;
     let mut lines = BufReader::new(file).lines();
 
     while let Some(line) = lines.next_line().await.ok()? {
-        let cmd = parse_command(&line).ok()?;
+        let line = match token.map(|token| strip_token(&line, token)) {
+            Some(Some(command)) => command,
+            Some(None) => {
+                log::warn!("Rejected a command without a valid token: {:?}", line);
+                continue;
+            }
+            None => &line,
+        };
+        let cmd = parse_command(line).ok()?;
         tx.send(cmd).ok()?;
     }
 
     Some(())
 }
 
+fn strip_token<'a>(line: &'a str, token: &str) -> Option<&'a str> {
+    let (given, command) = line.trim_start().split_once(char::is_whitespace)?;
+    if given == token {
+        Some(command.trim_start())
+    } else {
+        None
+    }
+}
+
 fn parse_command(s: &str) -> std::result::Result<ExternalCommand, ()> {
     let head = *s.split(' ').collect::<Vec<&str>>().get(0).unwrap_or(&"");
     match head {
         "UnloadTheme" => Ok(ExternalCommand::UnloadTheme),
         "Reload" => Ok(ExternalCommand::Reload),
         "ToggleFullScreen" => Ok(ExternalCommand::ToggleFullScreen),
+        "ToggleFakeFullscreen" => Ok(ExternalCommand::ToggleFakeFullscreen),
         "SwapScreens" => Ok(ExternalCommand::SwapScreens),
         "MoveWindowToLastWorkspace" => Ok(ExternalCommand::MoveWindowToLastWorkspace),
         "FloatingToTile" => Ok(ExternalCommand::FloatingToTile),
+        "GroupWindow" => Ok(ExternalCommand::GroupWindow),
+        "UngroupWindow" => Ok(ExternalCommand::UngroupWindow),
+        "CycleGroup" => Ok(ExternalCommand::CycleGroup),
         "MoveWindowUp" => Ok(ExternalCommand::MoveWindowUp),
         "MoveWindowDown" => Ok(ExternalCommand::MoveWindowDown),
         "FocusWindowUp" => Ok(ExternalCommand::FocusWindowUp),
         "MoveWindowTop" => Ok(ExternalCommand::MoveWindowTop),
+        "ZoomWindow" => Ok(ExternalCommand::ZoomWindow),
         "FocusWindowDown" => Ok(ExternalCommand::FocusWindowDown),
         "FocusNextTag" => Ok(ExternalCommand::FocusNextTag),
         "FocusPreviousTag" => Ok(ExternalCommand::FocusPreviousTag),
-        "FocusWorkspaceNext" => Ok(ExternalCommand::FocusWorkspaceNext),
-        "FocusWorkspacePrevious" => Ok(ExternalCommand::FocusWorkspacePrevious),
+        "FocusWorkspaceNext" | "FocusNextWorkspace" => {
+            Ok(ExternalCommand::FocusWorkspaceNext(optional_value(s)))
+        }
+        "FocusWorkspacePrevious" | "FocusPreviousWorkspace" => {
+            Ok(ExternalCommand::FocusWorkspacePrevious(optional_value(s)))
+        }
         "NextLayout" => Ok(ExternalCommand::NextLayout),
         "PreviousLayout" => Ok(ExternalCommand::PreviousLayout),
         "RotateTag" => Ok(ExternalCommand::RotateTag),
+        "ToggleTiling" => Ok(ExternalCommand::ToggleTiling),
+        "UndoWindowOperation" => Ok(ExternalCommand::UndoWindowOperation),
+        "LockScreen" => Ok(ExternalCommand::LockScreen),
+        "HideDock" => Ok(ExternalCommand::HideDock),
+        "ShowDock" => Ok(ExternalCommand::ShowDock),
+        "ToggleDock" => Ok(ExternalCommand::ToggleDock),
+        "ShowKeybindHelp" => Ok(ExternalCommand::ShowKeybindHelp),
+        "TogglePresentationMode" => Ok(ExternalCommand::TogglePresentationMode(optional_value(s))),
         "CloseWindow" => Ok(ExternalCommand::CloseWindow),
+        "DumpDiagnostics" => Ok(build_dump_diagnostics(s)),
         // These require arguments and might be more finicky
         "LoadTheme" => build_load_theme(s),
         "ToggleScratchPad" => build_toggle_scratchpad(s),
+        "AttachToScratchPad" => build_attach_to_scratchpad(s),
+        "ReleaseScratchPad" => Ok(ExternalCommand::ReleaseScratchPad),
         "SendWorkspaceToTag" => build_send_workspace_to_tag(s),
+        "SwapTags" => build_swap_tags(s),
         "SendWindowToTag" => build_send_window_to_tag(s),
+        "ToggleWindowTag" => build_toggle_window_tag(s),
+        "SetWindowTag" => build_set_window_tag(s),
+        "SaveSnapshot" => build_save_snapshot(s),
+        "LoadSnapshot" => build_load_snapshot(s),
+        "ToggleTagView" => build_toggle_tag_view(s),
         "SetLayout" => build_set_layout(s),
         "SetMarginMultiplier" => build_set_margin_multiplier(s),
+        "SetMainWidth" => build_set_main_width(s),
+        "SetModKey" => build_set_modkey(s),
+        "SetMouseKey" => build_set_mousekey(s),
+        "ReserveSpace" => build_reserve_space(s),
+        "ReleaseSpace" => build_release_space(s),
+        "SetLogLevel" => build_set_log_level(s),
+        "FocusWindowByHandle" => build_focus_window_by_handle(s),
         _ => Err(()),
     }
 }
@@ -122,6 +188,13 @@ fn build_toggle_scratchpad(raw: &str) -> std::result::Result<ExternalCommand, ()
     Ok(ExternalCommand::ToggleScratchPad(name.to_string()))
 }
 
+fn build_attach_to_scratchpad(raw: &str) -> std::result::Result<ExternalCommand, ()> {
+    let headless = without_head(raw, "AttachToScratchPad ");
+    let parts: Vec<&str> = headless.split(' ').collect();
+    let name = *parts.get(0).ok_or(())?;
+    Ok(ExternalCommand::AttachToScratchPad(name.to_string()))
+}
+
 fn build_send_window_to_tag(raw: &str) -> std::result::Result<ExternalCommand, ()> {
     let headless = without_head(raw, "SendWindowToTag ");
     let parts: Vec<&str> = headless.split(' ').collect();
@@ -129,6 +202,49 @@ fn build_send_window_to_tag(raw: &str) -> std::result::Result<ExternalCommand, (
     Ok(ExternalCommand::SendWindowToTag(tag_index))
 }
 
+fn build_set_window_tag(raw: &str) -> std::result::Result<ExternalCommand, ()> {
+    let headless = without_head(raw, "SetWindowTag ");
+    let (tag, pattern) = headless.trim().split_once(' ').ok_or(())?;
+    let pattern = pattern.trim();
+    if pattern.is_empty() {
+        return Err(());
+    }
+    Ok(ExternalCommand::SetWindowTag(
+        tag.to_owned(),
+        pattern.to_owned(),
+    ))
+}
+
+fn build_save_snapshot(raw: &str) -> std::result::Result<ExternalCommand, ()> {
+    let name = without_head(raw, "SaveSnapshot ").trim();
+    if name.is_empty() {
+        return Err(());
+    }
+    Ok(ExternalCommand::SaveSnapshot(name.to_owned()))
+}
+
+fn build_load_snapshot(raw: &str) -> std::result::Result<ExternalCommand, ()> {
+    let name = without_head(raw, "LoadSnapshot ").trim();
+    if name.is_empty() {
+        return Err(());
+    }
+    Ok(ExternalCommand::LoadSnapshot(name.to_owned()))
+}
+
+fn build_toggle_window_tag(raw: &str) -> std::result::Result<ExternalCommand, ()> {
+    let headless = without_head(raw, "ToggleWindowTag ");
+    let parts: Vec<&str> = headless.split(' ').collect();
+    let tag_index: usize = parts.get(0).ok_or(())?.parse().map_err(|_| ())?;
+    Ok(ExternalCommand::ToggleWindowTag(tag_index))
+}
+
+fn build_toggle_tag_view(raw: &str) -> std::result::Result<ExternalCommand, ()> {
+    let headless = without_head(raw, "ToggleTagView ");
+    let parts: Vec<&str> = headless.split(' ').collect();
+    let tag_index: usize = parts.get(0).ok_or(())?.parse().map_err(|_| ())?;
+    Ok(ExternalCommand::ToggleTagView(tag_index))
+}
+
 fn build_send_workspace_to_tag(raw: &str) -> std::result::Result<ExternalCommand, ()> {
     let headless = without_head(raw, "SendWorkspaceToTag ");
     let parts: Vec<&str> = headless.split(' ').collect();
@@ -137,6 +253,77 @@ fn build_send_workspace_to_tag(raw: &str) -> std::result::Result<ExternalCommand
     Ok(ExternalCommand::SendWorkspaceToTag(ws_index, tag_index))
 }
 
+fn build_swap_tags(raw: &str) -> std::result::Result<ExternalCommand, ()> {
+    let headless = without_head(raw, "SwapTags ");
+    let parts: Vec<&str> = headless.split(' ').collect();
+    let ws_index_a: usize = parts.get(0).ok_or(())?.parse().map_err(|_| ())?;
+    let ws_index_b: usize = parts.get(1).ok_or(())?.parse().map_err(|_| ())?;
+    Ok(ExternalCommand::SwapTags(ws_index_a, ws_index_b))
+}
+
+fn build_reserve_space(raw: &str) -> std::result::Result<ExternalCommand, ()> {
+    let headless = without_head(raw, "ReserveSpace ");
+    let parts: Vec<&str> = headless.split(' ').collect();
+    let ws_index: usize = parts.get(0).ok_or(())?.parse().map_err(|_| ())?;
+    let side = parse_side(parts.get(1).ok_or(())?)?;
+    let value: i32 = parts.get(2).ok_or(())?.parse().map_err(|_| ())?;
+    Ok(ExternalCommand::ReserveSpace(ws_index, side, value))
+}
+
+// The word after the command, if there is one.
+fn optional_value(raw: &str) -> Option<String> {
+    raw.split_whitespace().nth(1).map(str::to_owned)
+}
+
+fn build_release_space(raw: &str) -> std::result::Result<ExternalCommand, ()> {
+    let headless = without_head(raw, "ReleaseSpace ");
+    let parts: Vec<&str> = headless.split(' ').collect();
+    let ws_index: usize = parts.get(0).ok_or(())?.parse().map_err(|_| ())?;
+    let side = match parts.get(1) {
+        Some(side) => Some(parse_side(side)?),
+        None => None,
+    };
+    Ok(ExternalCommand::ReleaseSpace(ws_index, side))
+}
+
+fn parse_side(raw: &str) -> std::result::Result<Side, ()> {
+    match raw {
+        "Top" | "top" => Ok(Side::Top),
+        "Bottom" | "bottom" => Ok(Side::Bottom),
+        "Left" | "left" => Ok(Side::Left),
+        "Right" | "right" => Ok(Side::Right),
+        _ => Err(()),
+    }
+}
+
+fn build_set_log_level(raw: &str) -> std::result::Result<ExternalCommand, ()> {
+    let headless = without_head(raw, "SetLogLevel ");
+    let parts: Vec<&str> = headless.split(' ').collect();
+    let level = *parts.get(0).ok_or(())?;
+    Ok(ExternalCommand::SetLogLevel(level.to_string()))
+}
+
+fn build_focus_window_by_handle(raw: &str) -> std::result::Result<ExternalCommand, ()> {
+    let headless = without_head(raw, "FocusWindowByHandle ");
+    let parts: Vec<&str> = headless.split(' ').collect();
+    let id = parts.get(0).ok_or(())?;
+    //ids as `xprop` and `xdotool` print them, in hex or decimal
+    let id = match id.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => id.parse(),
+    };
+    Ok(ExternalCommand::FocusWindowByHandle(id.map_err(|_| ())?))
+}
+
+fn build_dump_diagnostics(raw: &str) -> ExternalCommand {
+    let headless = without_head(raw, "DumpDiagnostics").trim();
+    if headless.is_empty() {
+        ExternalCommand::DumpDiagnostics(None)
+    } else {
+        ExternalCommand::DumpDiagnostics(Some(headless.into()))
+    }
+}
+
 fn build_set_layout(raw: &str) -> std::result::Result<ExternalCommand, ()> {
     let headless = without_head(raw, "SetLayout ");
     let parts: Vec<&str> = headless.split(' ').collect();
@@ -155,6 +342,33 @@ fn build_set_margin_multiplier(raw: &str) -> std::result::Result<ExternalCommand
     Ok(ExternalCommand::SetMarginMultiplier(margin_multiplier))
 }
 
+fn build_set_main_width(raw: &str) -> std::result::Result<ExternalCommand, ()> {
+    let headless = without_head(raw, "SetMainWidth ");
+    let parts: Vec<&str> = headless.split(' ').collect();
+    if parts.len() != 1 {
+        return Err(());
+    }
+    Ok(ExternalCommand::SetMainWidth(parts[0].to_string()))
+}
+
+fn build_set_modkey(raw: &str) -> std::result::Result<ExternalCommand, ()> {
+    let headless = without_head(raw, "SetModKey ");
+    let parts: Vec<&str> = headless.split(' ').collect();
+    if parts.len() != 1 {
+        return Err(());
+    }
+    Ok(ExternalCommand::SetModKey(parts[0].to_string()))
+}
+
+fn build_set_mousekey(raw: &str) -> std::result::Result<ExternalCommand, ()> {
+    let headless = without_head(raw, "SetMouseKey ");
+    let parts: Vec<&str> = headless.split(' ').collect();
+    if parts.len() != 1 {
+        return Err(());
+    }
+    Ok(ExternalCommand::SetMouseKey(parts[0].to_string()))
+}
+
 fn without_head<'a, 'b>(s: &'a str, head: &'b str) -> &'a str {
     if !s.starts_with(head) {
         return s;
@@ -168,27 +382,56 @@ pub enum ExternalCommand {
     UnloadTheme,
     Reload,
     ToggleScratchPad(String),
+    AttachToScratchPad(String),
+    ReleaseScratchPad,
     ToggleFullScreen,
+    ToggleFakeFullscreen,
     SendWorkspaceToTag(usize, usize),
     SendWindowToTag(usize),
+    ToggleWindowTag(usize),
+    SetWindowTag(String, String),
+    SaveSnapshot(String),
+    LoadSnapshot(String),
+    ToggleTagView(usize),
     SwapScreens,
+    SwapTags(usize, usize),
     MoveWindowToLastWorkspace,
     FloatingToTile,
+    GroupWindow,
+    UngroupWindow,
+    CycleGroup,
     MoveWindowUp,
     MoveWindowDown,
     MoveWindowTop,
+    ZoomWindow,
     FocusWindowUp,
     FocusWindowDown,
     FocusNextTag,
     FocusPreviousTag,
-    FocusWorkspaceNext,
-    FocusWorkspacePrevious,
+    FocusWorkspaceNext(Option<String>),
+    FocusWorkspacePrevious(Option<String>),
     CloseWindow,
     NextLayout,
     PreviousLayout,
     RotateTag,
+    ToggleTiling,
+    UndoWindowOperation,
+    LockScreen,
+    HideDock,
+    ShowDock,
+    ToggleDock,
+    ShowKeybindHelp,
+    TogglePresentationMode(Option<String>),
+    FocusWindowByHandle(u64),
+    SetLogLevel(String),
+    DumpDiagnostics(Option<PathBuf>),
     SetLayout(String),
     SetMarginMultiplier(String),
+    SetMainWidth(String),
+    SetModKey(String),
+    SetMouseKey(String),
+    ReserveSpace(usize, Side, i32),
+    ReleaseSpace(usize, Option<Side>),
 }
 
 #[cfg(test)]
@@ -238,6 +481,38 @@ mod test {
         }
     }
 
+    #[test]
+    fn read_command_with_token() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(read_command_with_token_async());
+    }
+    async fn read_command_with_token_async() {
+        use std::os::unix::fs::PermissionsExt;
+        let pipe_file = temp_path().await.unwrap();
+        let mut command_pipe = CommandPipe::with_token(pipe_file.clone(), Some("secret".into()))
+            .await
+            .unwrap();
+        let mode = fs::metadata(&pipe_file).await.unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        {
+            let mut pipe = fs::OpenOptions::new()
+                .write(true)
+                .open(pipe_file.clone())
+                .await
+                .unwrap();
+            pipe.write_all(b"Reload\nwrong UnloadTheme\nsecret SendWorkspaceToTag 0 1\n")
+                .await
+                .unwrap();
+            pipe.flush().await.unwrap();
+
+            assert_eq!(
+                ExternalCommand::SendWorkspaceToTag(0, 1),
+                command_pipe.read_command().await.unwrap()
+            );
+        }
+    }
+
     #[test]
     fn pipe_cleanup() {
         let rt = tokio::runtime::Runtime::new().unwrap();
@@ -0,0 +1,104 @@
+//! Keeps the latest display events and actions around so they can be dumped for bug reports.
+use crate::display_action::DisplayAction;
+use crate::errors::Result;
+use crate::DisplayEvent;
+use log::LevelFilter;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "snake_case")]
+enum Entry {
+    Event(DisplayEvent),
+    Action(DisplayAction),
+}
+
+/// The latest `capacity` display events and actions. They are only turned into JSON when dumped.
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    capacity: usize,
+    entries: VecDeque<Entry>,
+}
+
+impl Diagnostics {
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn record_event(&mut self, event: &DisplayEvent) {
+        self.record(Entry::Event(event.clone()));
+    }
+
+    pub fn record_action(&mut self, action: &DisplayAction) {
+        self.record(Entry::Action(action.clone()));
+    }
+
+    fn record(&mut self, entry: Entry) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    /// Writes the entries to the file, oldest first, one per line.
+    /// # Errors
+    ///
+    /// Will error if the file cannot be written or an entry cannot be serialized.
+    pub fn dump(&self, path: &Path) -> Result<()> {
+        let mut file = File::create(path)?;
+        for entry in &self.entries {
+            writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        }
+        Ok(())
+    }
+}
+
+/// Sets the most verbose level that is logged from now on. Returns false if the level is unknown.
+pub fn set_log_level(level: &str) -> bool {
+    match LevelFilter::from_str(level) {
+        Ok(level) => {
+            log::set_max_level(level);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::WindowHandle;
+    use std::fs;
+
+    #[test]
+    fn only_the_latest_entries_should_be_dumped() {
+        let mut diagnostics = Diagnostics::new(2);
+        diagnostics.record_event(&DisplayEvent::ChangeToNormalMode(0, 0));
+        diagnostics.record_event(&DisplayEvent::WindowDestroy(WindowHandle::MockHandle(1)));
+        diagnostics.record_action(&DisplayAction::Unfocus);
+
+        let dir = tempfile::tempdir_in("target").unwrap();
+        let path = dir.path().join("diagnostics.jsonl");
+        diagnostics.dump(&path).unwrap();
+        let dumped = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = dumped.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                r#"{"event":{"WindowDestroy":{"MockHandle":1}}}"#,
+                r#"{"action":"Unfocus"}"#
+            ]
+        );
+    }
+}
@@ -0,0 +1,158 @@
+//! Tells which `[[hook]]` events happened, by comparing the manager with how it was before.
+use crate::config::{ExecOptions, HookEvent};
+use crate::layouts::Layout;
+use crate::models::{Manager, TagId, Window, WindowHandle};
+
+#[derive(Debug, Default)]
+pub struct HookTracker {
+    //the managed windows, with the class and title their hooks get
+    windows: Vec<(WindowHandle, Option<String>, Option<String>)>,
+    //the tag and layout of each workspace
+    workspaces: Vec<(Option<TagId>, Layout)>,
+}
+
+impl HookTracker {
+    /// The events since the last call, with the environment to run their hooks with.
+    /// On the first call every window and workspace is new. Nothing is copied while nothing
+    /// changed.
+    pub fn changes(&mut self, manager: &Manager) -> Vec<(HookEvent, ExecOptions)> {
+        let mut events = vec![];
+        let managed_windows = || manager.windows.iter().filter(|w| !w.is_unmanaged());
+        let windows_changed = !managed_windows().map(snapshot_of).eq(self
+            .windows
+            .iter()
+            .map(|(h, class, name)| (*h, class.as_ref(), name.as_ref())));
+        if windows_changed {
+            for window in managed_windows() {
+                if !self.windows.iter().any(|(h, _, _)| *h == window.handle) {
+                    events.push((HookEvent::WindowCreated, window_env(window)));
+                }
+            }
+            for (handle, class, name) in &self.windows {
+                if !managed_windows().any(|w| w.handle == *handle) {
+                    let env = env_of(*handle, class.as_deref(), name.as_deref());
+                    events.push((HookEvent::WindowDestroyed, env));
+                }
+            }
+            self.windows = managed_windows()
+                .map(|w| (w.handle, w.res_class.clone(), w.name.clone()))
+                .collect();
+        }
+
+        if manager
+            .workspaces
+            .iter()
+            .map(|ws| (ws.tags.first(), &ws.layout))
+            .eq(self.workspaces.iter().map(|(t, l)| (t.as_ref(), l)))
+        {
+            return events;
+        }
+        let workspaces: Vec<(Option<TagId>, Layout)> = manager
+            .workspaces
+            .iter()
+            .map(|ws| (ws.tags.first().cloned(), ws.layout.clone()))
+            .collect();
+        for (index, (tag, layout)) in workspaces.iter().enumerate() {
+            let before = self.workspaces.get(index);
+            let mut options = ExecOptions::default();
+            let output = manager.workspaces[index].output.clone();
+            options.env.extend(vec![
+                ("LEFTWM_WORKSPACE".to_owned(), index.to_string()),
+                ("LEFTWM_TAG".to_owned(), tag.clone().unwrap_or_default()),
+                ("LEFTWM_OUTPUT".to_owned(), output.unwrap_or_default()),
+                ("LEFTWM_LAYOUT".to_owned(), layout.to_string()),
+            ]);
+            if tag.is_some() && before.map(|(t, _)| t) != Some(tag) {
+                events.push((HookEvent::TagChanged, options.clone()));
+            }
+            if before.map(|(_, l)| l) != Some(layout) {
+                events.push((HookEvent::LayoutChanged, options));
+            }
+        }
+        self.workspaces = workspaces;
+        events
+    }
+}
+
+fn snapshot_of(window: &Window) -> (WindowHandle, Option<&String>, Option<&String>) {
+    (
+        window.handle,
+        window.res_class.as_ref(),
+        window.name.as_ref(),
+    )
+}
+
+fn window_env(window: &Window) -> ExecOptions {
+    env_of(
+        window.handle,
+        window.res_class.as_deref(),
+        window.name.as_deref(),
+    )
+}
+
+fn env_of(handle: WindowHandle, class: Option<&str>, name: Option<&str>) -> ExecOptions {
+    let id = match handle {
+        WindowHandle::XlibHandle(h) => h,
+        WindowHandle::MockHandle(h) => h as u64,
+    };
+    let mut options = ExecOptions::default();
+    options.env.extend(vec![
+        ("LEFTWM_WINDOW".to_owned(), id.to_string()),
+        (
+            "LEFTWM_CLASS".to_owned(),
+            class.unwrap_or_default().to_owned(),
+        ),
+        (
+            "LEFTWM_TITLE".to_owned(),
+            name.unwrap_or_default().to_owned(),
+        ),
+    ]);
+    options
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::{screen_create_handler, window_handler};
+    use crate::models::Screen;
+
+    fn events(tracker: &mut HookTracker, manager: &Manager) -> Vec<HookEvent> {
+        tracker
+            .changes(manager)
+            .into_iter()
+            .map(|(e, _)| e)
+            .collect()
+    }
+
+    #[test]
+    fn hook_events_should_follow_the_changes_of_the_manager() {
+        let mut manager = Manager::new_test();
+        let mut tracker = HookTracker::default();
+        screen_create_handler::process(&mut manager, Screen::default());
+        assert_eq!(
+            events(&mut tracker, &manager),
+            [HookEvent::TagChanged, HookEvent::LayoutChanged]
+        );
+        assert!(events(&mut tracker, &manager).is_empty());
+
+        let mut window = Window::new(WindowHandle::MockHandle(1), Some("term".into()), None);
+        window.res_class = Some("Alacritty".into());
+        window_handler::created(&mut manager, window, -1, -1);
+        let changes = tracker.changes(&manager);
+        assert_eq!(changes.len(), 1);
+        let (event, options) = &changes[0];
+        assert_eq!(event, &HookEvent::WindowCreated);
+        assert_eq!(options.env["LEFTWM_WINDOW"], "1");
+        assert_eq!(options.env["LEFTWM_CLASS"], "Alacritty");
+
+        manager.workspaces[0].tags = vec!["2".to_owned()];
+        manager.workspaces[0].layout = Layout::Monocle;
+        window_handler::destroyed(&mut manager, &WindowHandle::MockHandle(1));
+        let changes = tracker.changes(&manager);
+        let names: Vec<&str> = changes.iter().map(|(e, _)| e.name()).collect();
+        assert_eq!(names, ["window_destroyed", "tag_changed", "layout_changed"]);
+        assert_eq!(changes[0].1.env["LEFTWM_TITLE"], "term");
+        assert_eq!(changes[1].1.env["LEFTWM_TAG"], "2");
+        assert_eq!(changes[2].1.env["LEFTWM_LAYOUT"], "Monocle");
+    }
+}
@@ -0,0 +1,57 @@
+//! Counts what the window manager does, for profiling it and tracking down event storms.
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+/// Counters kept since the worker started, and the number of windows it manages.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Metrics {
+    pub events_processed: u64,
+    pub commands_processed: u64,
+    pub focus_changes: u64,
+    pub windows_created: u64,
+    pub layout_changes: u64,
+    pub children_reaped: u64,
+    pub windows_managed: u64,
+}
+
+impl Metrics {
+    /// The metrics in the Prometheus text format.
+    #[must_use]
+    pub fn to_prometheus(&self) -> String {
+        let counters = [
+            ("events_processed_total", self.events_processed),
+            ("commands_processed_total", self.commands_processed),
+            ("focus_changes_total", self.focus_changes),
+            ("windows_created_total", self.windows_created),
+            ("layout_changes_total", self.layout_changes),
+            ("children_reaped_total", self.children_reaped),
+        ];
+        let mut text = String::new();
+        for (name, value) in &counters {
+            let _ = writeln!(text, "# TYPE leftwm_{} counter", name);
+            let _ = writeln!(text, "leftwm_{} {}", name, value);
+        }
+        let _ = writeln!(text, "# TYPE leftwm_windows_managed gauge");
+        let _ = writeln!(text, "leftwm_windows_managed {}", self.windows_managed);
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prometheus_text_should_have_a_line_per_metric() {
+        let metrics = Metrics {
+            events_processed: 12,
+            windows_managed: 3,
+            ..Metrics::default()
+        };
+        let text = metrics.to_prometheus();
+        assert!(text.contains("# TYPE leftwm_events_processed_total counter\n"));
+        assert!(text.contains("leftwm_events_processed_total 12\n"));
+        assert!(text.contains("leftwm_windows_managed 3\n"));
+        assert_eq!(text.lines().filter(|l| !l.starts_with('#')).count(), 7);
+    }
+}
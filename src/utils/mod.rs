@@ -1,7 +1,16 @@
 //! Various shared functions that `LeftWM` uses.
+pub mod action_channel;
 pub mod child_process;
 pub mod command_pipe;
+pub mod diagnostics;
 pub mod helpers;
+pub mod hooks;
+pub mod metrics;
+pub mod profiler;
 pub mod state_socket;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod window_updater;
 pub mod xkeysym_lookup;
+#[cfg(feature = "xvfb")]
+pub mod xvfb;
@@ -0,0 +1,129 @@
+//! Times what the worker spends its time on, for `leftwm-worker --profile`.
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+/// How often and how long a branch of the event loop ran.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Timing {
+    pub count: u64,
+    pub total: Duration,
+    pub max: Duration,
+}
+
+impl Timing {
+    fn add(&mut self, elapsed: Duration) {
+        self.count += 1;
+        self.total += elapsed;
+        self.max = self.max.max(elapsed);
+    }
+
+    /// The mean time the branch took, zero if it never ran.
+    #[must_use]
+    pub fn mean(&self) -> Duration {
+        if self.count == 0 {
+            return Duration::default();
+        }
+        self.total / self.count as u32
+    }
+}
+
+/// The timings of every branch since the last summary.
+#[derive(Debug, Clone)]
+pub struct Profiler {
+    interval: Duration,
+    since: Instant,
+    timings: BTreeMap<&'static str, Timing>,
+}
+
+impl Profiler {
+    /// A profiler whose summary is due every `interval`.
+    #[must_use]
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            since: Instant::now(),
+            timings: BTreeMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, branch: &'static str, elapsed: Duration) {
+        self.timings.entry(branch).or_default().add(elapsed);
+    }
+
+    #[must_use]
+    pub fn timing(&self, branch: &str) -> Option<&Timing> {
+        self.timings.get(branch)
+    }
+
+    /// Whether a summary is due, nothing is if nothing was timed.
+    #[must_use]
+    pub fn is_due(&self) -> bool {
+        !self.timings.is_empty() && self.since.elapsed() >= self.interval
+    }
+
+    /// The timings so far, slowest branch in total first, one line each. They start over after.
+    pub fn summary(&mut self, windows: usize) -> String {
+        let mut timings: Vec<(&str, Timing)> = self.timings.iter().map(|(b, t)| (*b, *t)).collect();
+        timings.sort_by_key(|(_, timing)| Reverse(timing.total));
+        let mut text = format!(
+            "profile of the last {:.1}s with {} windows:",
+            self.since.elapsed().as_secs_f64(),
+            windows
+        );
+        for (branch, timing) in timings {
+            let _ = write!(
+                text,
+                "\n  {:<20} {:>8} calls  total {:>10.3}ms  mean {:>8.3}ms  max {:>8.3}ms",
+                branch,
+                timing.count,
+                millis(timing.total),
+                millis(timing.mean()),
+                millis(timing.max)
+            );
+        }
+        self.timings.clear();
+        self.since = Instant::now();
+        text
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_should_put_the_slowest_branch_first_and_start_over() {
+        let mut profiler = Profiler::new(Duration::from_secs(0));
+        assert!(!profiler.is_due());
+        profiler.record("KeyCombo", Duration::from_millis(1));
+        profiler.record("WindowCreate", Duration::from_millis(4));
+        profiler.record("WindowCreate", Duration::from_millis(2));
+        assert_eq!(
+            profiler.timing("WindowCreate"),
+            Some(&Timing {
+                count: 2,
+                total: Duration::from_millis(6),
+                max: Duration::from_millis(4),
+            })
+        );
+        assert_eq!(
+            profiler.timing("WindowCreate").map(Timing::mean),
+            Some(Duration::from_millis(3))
+        );
+        assert!(profiler.is_due());
+
+        let summary = profiler.summary(200);
+        let lines: Vec<&str> = summary.lines().collect();
+        assert!(lines[0].ends_with("with 200 windows:"));
+        assert!(lines[1].trim_start().starts_with("WindowCreate"));
+        assert!(lines[2].trim_start().starts_with("KeyCombo"));
+        assert_eq!(lines.len(), 3);
+        assert!(!profiler.is_due());
+    }
+}
@@ -1,17 +1,24 @@
+use crate::config::Keybind;
 use crate::errors::{stream_error, Result};
-use crate::models::dto::ManagerState;
+use crate::models::dto::{ManagerState, StateMessage, StateQuery, WindowInfo};
 use crate::models::Manager;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::fs;
-use tokio::io::AsyncWriteExt;
-use tokio::net::{UnixListener, UnixStream};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::UnixListener;
 use tokio::sync::Mutex;
 
+type Peer = Arc<Mutex<OwnedWriteHalf>>;
+
 #[derive(Debug, Default)]
 struct State {
-    peers: Vec<Option<UnixStream>>,
+    peers: Vec<Option<Peer>>,
     last_state: String, //last_state: String
+    keybinds: String,
+    windows: String,
+    metrics: String,
 }
 
 #[derive(Debug, Default)]
@@ -57,7 +64,12 @@ impl StateSocket {
     /// Will return error if state cannot be serialized
     pub async fn write_manager_state(&mut self, manager: &Manager) -> Result<()> {
         if self.listener.is_some() {
-            let state: ManagerState = manager.into();
+            //nothing about the windows is shared while the screen is locked
+            let state: ManagerState = if manager.screen_lock.is_some() {
+                ManagerState::default()
+            } else {
+                manager.into()
+            };
             let mut json = serde_json::to_string(&state)?;
             json.push('\n');
             let mut state = self.state.lock().await;
@@ -65,8 +77,10 @@ impl StateSocket {
                 state.peers.retain(std::option::Option::is_some);
                 for peer in &mut state.peers {
                     if peer
-                        .as_mut()
+                        .as_ref()
                         .ok_or_else(stream_error)?
+                        .lock()
+                        .await
                         .write_all(json.as_bytes())
                         .await
                         .is_err()
@@ -80,6 +94,39 @@ impl StateSocket {
         Ok(())
     }
 
+    /// Updates the window list and metrics `StateQuery::ListWindows` and `StateQuery::Metrics` are
+    /// answered with. These follow every change, not only those the state is written for.
+    /// # Errors
+    /// Will return error if the windows or metrics cannot be serialized
+    pub async fn refresh_queries(&mut self, manager: &Manager) -> Result<()> {
+        let windows = if manager.screen_lock.is_some() {
+            vec![]
+        } else {
+            WindowInfo::list(manager)
+        };
+        let mut windows = serde_json::to_string(&StateMessage::Windows(windows))?;
+        windows.push('\n');
+        let mut metrics = manager.metrics.clone();
+        metrics.windows_managed =
+            manager.windows.iter().filter(|w| !w.is_unmanaged()).count() as u64;
+        let mut metrics = serde_json::to_string(&StateMessage::Metrics(metrics))?;
+        metrics.push('\n');
+        let mut state = self.state.lock().await;
+        state.windows = windows;
+        state.metrics = metrics;
+        Ok(())
+    }
+
+    /// Sets the keybinds to answer `StateQuery::Keybinds` with.
+    /// # Errors
+    /// Will return error if the keybinds cannot be serialized
+    pub async fn set_keybinds(&mut self, keybinds: Vec<Keybind>) -> Result<()> {
+        let mut json = serde_json::to_string(&StateMessage::Keybinds(keybinds))?;
+        json.push('\n');
+        self.state.lock().await.keybinds = json;
+        Ok(())
+    }
+
     async fn build_listener(&self) -> Result<tokio::task::JoinHandle<()>> {
         let state = self.state.clone();
         let listener = if let Ok(m) = UnixListener::bind(&self.socket_file) {
@@ -88,13 +135,20 @@ impl StateSocket {
             fs::remove_file(&self.socket_file).await?;
             UnixListener::bind(&self.socket_file)?
         };
+        let mut hello = serde_json::to_string(&StateMessage::hello())?;
+        hello.push('\n');
         Ok(tokio::spawn(async move {
             loop {
                 match listener.accept().await {
-                    Ok((mut peer, _)) => {
-                        let mut state = state.lock().await;
-                        if peer.write_all(state.last_state.as_bytes()).await.is_ok() {
-                            state.peers.push(Some(peer));
+                    Ok((peer, _)) => {
+                        let (reader, mut writer) = peer.into_split();
+                        let mut peers = state.lock().await;
+                        if writer.write_all(hello.as_bytes()).await.is_ok()
+                            && writer.write_all(peers.last_state.as_bytes()).await.is_ok()
+                        {
+                            let writer = Arc::new(Mutex::new(writer));
+                            peers.peers.push(Some(writer.clone()));
+                            tokio::spawn(answer_queries(state.clone(), reader, writer));
                         }
                     }
                     Err(e) => log::error!("accept failed = {:?}", e),
@@ -104,11 +158,52 @@ impl StateSocket {
     }
 }
 
+/// Answers the queries a peer sends until it hangs up.
+async fn answer_queries(state: Arc<Mutex<State>>, reader: OwnedReadHalf, writer: Peer) {
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let answer = match serde_json::from_str(&line) {
+            Ok(StateQuery::Keybinds) => state.lock().await.keybinds.clone(),
+            Ok(StateQuery::ListWindows) => state.lock().await.windows.clone(),
+            Ok(StateQuery::Metrics) => state.lock().await.metrics.clone(),
+            Err(err) => {
+                log::warn!("Ignoring state socket query {:?}: {}", line, err);
+                continue;
+            }
+        };
+        if writer
+            .lock()
+            .await
+            .write_all(answer.as_bytes())
+            .await
+            .is_err()
+        {
+            break;
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::config::Keybind;
+    use crate::models::{Window, WindowHandle};
     use crate::utils::helpers::test::temp_path;
-    use tokio::io::{AsyncBufReadExt, BufReader};
+    use crate::Command;
+    use std::path::Path;
+    use tokio::io::Lines;
+    use tokio::net::UnixStream;
+
+    /// Connects to the socket and reads past the greeting.
+    async fn connect(socket_file: &Path) -> Lines<BufReader<UnixStream>> {
+        let mut lines = BufReader::new(UnixStream::connect(socket_file).await.unwrap()).lines();
+        let hello = lines.next_line().await.expect("Read hello").unwrap();
+        assert_eq!(
+            serde_json::from_str::<StateMessage>(&hello).unwrap(),
+            StateMessage::hello()
+        );
+        lines
+    }
 
     #[test]
     fn multiple_peers() {
@@ -125,8 +220,8 @@ mod test {
 
         assert_eq!(
             serde_json::to_string(&Into::<ManagerState>::into(&manager)).unwrap(),
-            BufReader::new(UnixStream::connect(socket_file.clone()).await.unwrap())
-                .lines()
+            connect(&socket_file)
+                .await
                 .next_line()
                 .await
                 .expect("Read next line")
@@ -135,8 +230,8 @@ mod test {
 
         assert_eq!(
             serde_json::to_string(&Into::<ManagerState>::into(&manager)).unwrap(),
-            BufReader::new(UnixStream::connect(socket_file.clone()).await.unwrap())
-                .lines()
+            connect(&socket_file)
+                .await
                 .next_line()
                 .await
                 .expect("Read next line")
@@ -145,8 +240,8 @@ mod test {
 
         assert_eq!(
             serde_json::to_string(&Into::<ManagerState>::into(&manager)).unwrap(),
-            BufReader::new(UnixStream::connect(socket_file).await.unwrap())
-                .lines()
+            connect(&socket_file)
+                .await
                 .next_line()
                 .await
                 .expect("Read next line")
@@ -169,7 +264,7 @@ mod test {
         state_socket.listen(socket_file.clone()).await.unwrap();
         state_socket.write_manager_state(&manager).await.unwrap();
 
-        let mut lines = BufReader::new(UnixStream::connect(socket_file).await.unwrap()).lines();
+        let mut lines = connect(&socket_file).await;
 
         assert_eq!(
             serde_json::to_string(&Into::<ManagerState>::into(&manager)).unwrap(),
@@ -188,6 +283,85 @@ mod test {
         state_socket.shutdown().await;
     }
 
+    #[test]
+    fn keybinds_query() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(keybinds_query_async());
+    }
+    async fn keybinds_query_async() {
+        let socket_file = temp_path().await.unwrap();
+        let mut state_socket = StateSocket::default();
+        state_socket.listen(socket_file.clone()).await.unwrap();
+        let keybind = Keybind {
+            command: Command::GotoTag,
+            value: Some("1".to_owned()),
+            modifier: vec!["Mod4".to_owned()],
+            key: "1".to_owned(),
+            repeat: false,
+            essential: false,
+            exec: Default::default(),
+        };
+        state_socket
+            .set_keybinds(vec![keybind.clone()])
+            .await
+            .unwrap();
+
+        let mut lines = connect(&socket_file).await;
+        let query = format!(
+            "{}\n",
+            serde_json::to_string(&StateQuery::Keybinds).unwrap()
+        );
+        let stream = lines.get_mut().get_mut();
+        stream.write_all(b"nonsense\n").await.unwrap();
+        stream.write_all(query.as_bytes()).await.unwrap();
+        let answer = lines.next_line().await.expect("Read answer").unwrap();
+        assert_eq!(
+            serde_json::from_str::<StateMessage>(&answer).unwrap(),
+            StateMessage::Keybinds(vec![keybind])
+        );
+
+        state_socket.shutdown().await;
+    }
+
+    #[test]
+    fn list_windows_query() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(list_windows_query_async());
+    }
+    async fn list_windows_query_async() {
+        let mut manager = Manager::new_test();
+        let mut window = Window::new(WindowHandle::MockHandle(1), Some("term".to_owned()), None);
+        window.res_class = Some("Alacritty".to_owned());
+        window.tags = vec!["1".to_owned()];
+        manager.windows.push(window);
+
+        let socket_file = temp_path().await.unwrap();
+        let mut state_socket = StateSocket::default();
+        state_socket.listen(socket_file.clone()).await.unwrap();
+        state_socket.write_manager_state(&manager).await.unwrap();
+        state_socket.refresh_queries(&manager).await.unwrap();
+
+        let mut lines = connect(&socket_file).await;
+        lines.next_line().await.expect("Read state").unwrap();
+        let query = format!(
+            "{}\n",
+            serde_json::to_string(&StateQuery::ListWindows).unwrap()
+        );
+        let stream = lines.get_mut().get_mut();
+        stream.write_all(query.as_bytes()).await.unwrap();
+        let answer = lines.next_line().await.expect("Read answer").unwrap();
+        let windows = match serde_json::from_str::<StateMessage>(&answer).unwrap() {
+            StateMessage::Windows(windows) => windows,
+            message => panic!("unexpected answer {:?}", message),
+        };
+        assert_eq!(windows.len(), 1);
+        assert_eq!(windows[0].id, 1);
+        assert_eq!(windows[0].class, Some("Alacritty".to_owned()));
+        assert_eq!(windows[0].tags, vec!["1".to_owned()]);
+
+        state_socket.shutdown().await;
+    }
+
     #[test]
     fn socket_cleanup() {
         let rt = tokio::runtime::Runtime::new().unwrap();
@@ -0,0 +1,130 @@
+//! A manager in a realistic state for unit tests, such as those of layouts and tools outside of
+//! leftwm, without a display server.
+//!
+//! ```
+//! use leftwm::utils::testing::TestManager;
+//!
+//! let manager = TestManager::with_screens(2).with_windows(3).focus_tag("2").build();
+//! assert_eq!(manager.workspaces.len(), 2);
+//! assert_eq!(manager.windows.len(), 3);
+//! ```
+use crate::handlers::{focus_handler, goto_tag_handler, screen_create_handler, window_handler};
+use crate::models::{Manager, Screen, Tag, Window, WindowHandle};
+
+/// The width of each screen of a `TestManager`.
+pub const SCREEN_WIDTH: i32 = 800;
+/// The height of each screen of a `TestManager`.
+pub const SCREEN_HEIGHT: i32 = 600;
+
+/// Builds a `Manager` like the one the worker has once screens and windows show up.
+pub struct TestManager {
+    manager: Manager,
+}
+
+impl TestManager {
+    /// A manager with tags 1 to 9 and `count` screens side by side, each showing the next tag.
+    /// The first workspace is focused.
+    #[must_use]
+    pub fn with_screens(count: i32) -> Self {
+        let mut manager = Manager::new_test();
+        manager.tags = (1..=9).map(|i| Tag::new(&i.to_string())).collect();
+        for i in 0..count {
+            let mut screen = Screen::default();
+            screen.bbox.x = i * SCREEN_WIDTH;
+            screen.bbox.width = SCREEN_WIDTH;
+            screen.bbox.height = SCREEN_HEIGHT;
+            screen_create_handler::process(&mut manager, screen);
+        }
+        Self { manager }.focus_workspace(0)
+    }
+
+    /// Opens `count` windows on the focused workspace. Their handles are `MockHandle`s counting
+    /// on from the windows already open, starting at 1. The last one opened is focused.
+    #[must_use]
+    pub fn with_windows(mut self, count: i32) -> Self {
+        let first = self
+            .manager
+            .windows
+            .iter()
+            .filter_map(|w| match w.handle {
+                WindowHandle::MockHandle(id) => Some(id),
+                WindowHandle::XlibHandle(_) => None,
+            })
+            .max()
+            .unwrap_or(0)
+            + 1;
+        for id in first..first + count {
+            let handle = WindowHandle::MockHandle(id);
+            let window = Window::new(handle, Some(format!("Window {}", id)), Some(id as u32));
+            window_handler::created(&mut self.manager, window, -1, -1);
+            focus_handler::focus_window(&mut self.manager, &handle);
+        }
+        self
+    }
+
+    /// Shows the tag on the focused workspace, like `GotoTag` would.
+    #[must_use]
+    pub fn focus_tag(mut self, tag: &str) -> Self {
+        if let Some(number) = self.manager.tag_number(tag) {
+            goto_tag_handler::process(&mut self.manager, number);
+        }
+        self
+    }
+
+    /// Focuses the workspace at that index, in the order of their ids.
+    #[must_use]
+    pub fn focus_workspace(mut self, index: usize) -> Self {
+        if let Some(workspace) = self.manager.workspaces.get(index).cloned() {
+            focus_handler::focus_workspace(&mut self.manager, &workspace);
+        }
+        self
+    }
+
+    /// Focuses the window with the `MockHandle` of that id.
+    #[must_use]
+    pub fn focus_window(mut self, id: i32) -> Self {
+        focus_handler::focus_window(&mut self.manager, &WindowHandle::MockHandle(id));
+        self
+    }
+
+    /// The manager, with the display actions queued while building it dropped.
+    #[must_use]
+    pub fn build(mut self) -> Manager {
+        self.manager.actions.clear();
+        self.manager
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_should_open_on_the_focused_tag() {
+        let manager = TestManager::with_screens(2)
+            .with_windows(2)
+            .focus_tag("3")
+            .with_windows(1)
+            .build();
+        assert_eq!(manager.workspaces[0].tags, ["3".to_owned()]);
+        assert_eq!(manager.workspaces[1].tags, ["2".to_owned()]);
+        let tags: Vec<&str> = manager.windows.iter().map(|w| w.tags[0].as_str()).collect();
+        assert_eq!(tags, ["1", "1", "3"]);
+        assert_eq!(
+            manager.focused_window().map(|w| w.handle),
+            Some(WindowHandle::MockHandle(3))
+        );
+        assert!(manager.actions.is_empty());
+    }
+
+    #[test]
+    fn focusing_should_follow_the_window_to_its_workspace() {
+        let manager = TestManager::with_screens(2)
+            .with_windows(1)
+            .focus_workspace(1)
+            .with_windows(1)
+            .focus_window(1)
+            .build();
+        assert_eq!(manager.focused_workspace().map(|ws| ws.xyhw.x()), Some(0));
+    }
+}
@@ -1,4 +1,5 @@
-use crate::models::Manager;
+use crate::config::ThemeSetting;
+use crate::models::{Manager, Margins, Window, WindowType, Workspace};
 
 /*
  * step over all the windows for each workspace and updates all the things
@@ -12,6 +13,15 @@ pub fn update_windows(manager: &mut Manager) {
 
     for ws in &mut manager.workspaces {
         ws.update_windows(&mut manager.windows, &mut manager.tags);
+        apply_smart_borders_and_gaps(ws, &mut manager.windows, &manager.theme_setting);
+
+        if ws.docks_hidden {
+            manager
+                .windows
+                .iter_mut()
+                .filter(|w| w.type_ == WindowType::Dock && ws.is_displaying(w))
+                .for_each(|w| w.set_visible(false));
+        }
 
         manager
             .windows
@@ -27,3 +37,88 @@ pub fn update_windows(manager: &mut Manager) {
             println!("{:?}", w);
         });
 }
+
+/// With `smart_borders` or `smart_gaps` set in the theme, a tiled window that is the only one
+/// shown on its workspace loses its border, or its margins and those of the workspace.
+fn apply_smart_borders_and_gaps(ws: &Workspace, windows: &mut [Window], theme: &ThemeSetting) {
+    if !theme.smart_borders && !theme.smart_gaps {
+        return;
+    }
+    let mut tiled: Vec<&mut Window> = windows
+        .iter_mut()
+        .filter(|w| ws.is_managed(w) && !w.floating())
+        .collect();
+    let alone = tiled.iter().filter(|w| w.visible()).count() == 1;
+    for w in &mut tiled {
+        w.update_for_theme(theme);
+        if !alone {
+            continue;
+        }
+        if theme.smart_borders {
+            w.border = 0;
+        }
+        if theme.smart_gaps {
+            w.margin = Margins::Int(0);
+            let scale = |margin: i32| (margin as f32 * ws.margin_multiplier()) as i32;
+            let (top, right) = (
+                scale(ws.margin.clone().top()),
+                scale(ws.margin.clone().right()),
+            );
+            let (bottom, left) = (
+                scale(ws.margin.clone().bottom()),
+                scale(ws.margin.clone().left()),
+            );
+            w.normal.set_x(w.normal.x() - left);
+            w.normal.set_y(w.normal.y() - top);
+            w.normal.set_w(w.normal.w() + left + right);
+            w.normal.set_h(w.normal.h() + top + bottom);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::{screen_create_handler, window_handler};
+    use crate::models::{BBox, Screen, WindowHandle};
+    use std::sync::Arc;
+
+    fn smart_manager() -> Manager {
+        let mut manager = Manager::new_test();
+        let mut theme = (*manager.theme_setting).clone();
+        theme.border_width = 2;
+        theme.margin = Margins::Int(5);
+        theme.smart_borders = true;
+        theme.smart_gaps = true;
+        manager.theme_setting = Arc::new(theme);
+        let bbox = BBox {
+            x: 0,
+            y: 0,
+            width: 1000,
+            height: 800,
+        };
+        screen_create_handler::process(&mut manager, Screen::new(bbox));
+        manager
+    }
+
+    #[test]
+    fn a_lone_tiled_window_should_lose_its_border_and_gaps() {
+        let mut manager = smart_manager();
+        let window = Window::new(WindowHandle::MockHandle(1), None, None);
+        window_handler::created(&mut manager, window, 0, 0);
+        update_windows(&mut manager);
+        let window = &manager.windows[0];
+        assert_eq!(window.border, 0);
+        assert_eq!(window.margin, Margins::Int(0));
+        assert_eq!(
+            (window.x(), window.y(), window.width(), window.height()),
+            (0, 0, 1000, 800)
+        );
+
+        let window = Window::new(WindowHandle::MockHandle(2), None, None);
+        window_handler::created(&mut manager, window, 0, 0);
+        update_windows(&mut manager);
+        assert!(manager.windows.iter().all(|w| w.border == 2));
+        assert!(manager.windows.iter().all(|w| w.margin == Margins::Int(5)));
+    }
+}
@@ -39,6 +39,18 @@ pub fn into_mod(key: &str) -> ModMask {
     }
 }
 
+#[must_use]
+pub fn into_button(button: &str) -> Option<Button> {
+    match button {
+        "Button1" | "Left" => Some(xlib::Button1),
+        "Button2" | "Middle" => Some(xlib::Button2),
+        "Button3" | "Right" => Some(xlib::Button3),
+        "Button4" | "ScrollUp" => Some(xlib::Button4),
+        "Button5" | "ScrollDown" => Some(xlib::Button5),
+        _ => None,
+    }
+}
+
 // We allow this because this function is simply a mapping wrapper.
 #[allow(clippy::too_many_lines)]
 #[must_use]
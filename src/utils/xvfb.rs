@@ -0,0 +1,336 @@
+//! Runs leftwm on a virtual X server, for tests that check how it behaves towards real clients.
+//!
+//! Needs `Xvfb`, `xprop` and `xdotool` on the `PATH`.
+use crate::errors::Result;
+use std::ffi::CString;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+use x11_dl::xlib;
+
+/// How long `wait_until` waits for leftwm to catch up.
+const WAIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// `Xvfb` and a `leftwm-worker` managing it, both killed when dropped.
+pub struct XvfbRunner {
+    display: String,
+    //dropped in this order, the clients have to disconnect before the server goes away
+    clients: Vec<Client>,
+    _worker: KillOnDrop,
+    _xvfb: KillOnDrop,
+    //config and runtime files of the worker, away from the user's
+    _home: TempDir,
+}
+
+impl XvfbRunner {
+    /// Starts `Xvfb` on display `:number` and the worker at `worker` on it. Every runner needs a
+    /// number of its own, so tests can run side by side.
+    /// # Errors
+    ///
+    /// Will error if `Xvfb` or the worker cannot be started.
+    pub fn start(number: u32, worker: &Path) -> Result<Self> {
+        let display = format!(":{}", number);
+        let xvfb = Command::new("Xvfb")
+            .args(&[
+                display.as_str(),
+                "-screen",
+                "0",
+                "1280x800x24",
+                "-nolisten",
+                "tcp",
+            ])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let xvfb = KillOnDrop(xvfb);
+        if !wait_until(|| run(&display, "xprop", &["-root"]).is_ok()) {
+            return Err(failed("Xvfb did not come up"));
+        }
+        let home = tempfile::tempdir()?;
+        let worker = Command::new(worker)
+            .env("DISPLAY", &display)
+            .env("XDG_CONFIG_HOME", home.path().join("config"))
+            .env("XDG_DATA_HOME", home.path().join("data"))
+            .env("XDG_RUNTIME_DIR", home.path())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let runner = Self {
+            display,
+            clients: vec![],
+            _worker: KillOnDrop(worker),
+            _xvfb: xvfb,
+            _home: home,
+        };
+        if !runner.wait_until(|| runner.root_property("_NET_SUPPORTED").is_ok()) {
+            return Err(failed("leftwm-worker did not take over the display"));
+        }
+        Ok(runner)
+    }
+
+    /// Whether the programs the runner needs are installed.
+    #[must_use]
+    pub fn available() -> bool {
+        ["Xvfb", "xprop", "xdotool"].iter().all(|program| {
+            Command::new("which")
+                .arg(program)
+                .stdout(Stdio::null())
+                .status()
+                .map_or(false, |status| status.success())
+        })
+    }
+
+    #[must_use]
+    pub fn display(&self) -> &str {
+        &self.display
+    }
+
+    /// Runs a program against the virtual display and returns what it printed.
+    /// # Errors
+    ///
+    /// Will error if the program cannot be run or exits with a failure.
+    pub fn run(&self, program: &str, args: &[&str]) -> Result<String> {
+        run(&self.display, program, args)
+    }
+
+    /// A property of the root window, as `xprop` prints it.
+    /// # Errors
+    ///
+    /// Will error if `xprop` fails or the property is not set.
+    pub fn root_property(&self, property: &str) -> Result<String> {
+        let output = self.run("xprop", &["-root", property])?;
+        not_set(output)
+    }
+
+    /// A property of a window, as `xprop` prints it.
+    /// # Errors
+    ///
+    /// Will error if `xprop` fails or the property is not set.
+    pub fn property(&self, window: u64, property: &str) -> Result<String> {
+        let output = self.run("xprop", &["-id", &window.to_string(), property])?;
+        not_set(output)
+    }
+
+    /// Runs `xdotool` with the arguments.
+    /// # Errors
+    ///
+    /// Will error if `xdotool` fails.
+    pub fn xdotool(&self, args: &[&str]) -> Result<String> {
+        self.run("xdotool", args)
+    }
+
+    /// The position and size of a window, as (x, y, width, height).
+    /// # Errors
+    ///
+    /// Will error if `xdotool` fails or prints something unexpected.
+    pub fn geometry(&self, window: u64) -> Result<(i32, i32, i32, i32)> {
+        let output = self.xdotool(&["getwindowgeometry", "--shell", &window.to_string()])?;
+        let value = |key: &str| -> Result<i32> {
+            output
+                .lines()
+                .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+                .and_then(|value| value.parse().ok())
+                .ok_or_else(|| failed(&format!("no {} in {}", key, output)))
+        };
+        Ok((value("X")?, value("Y")?, value("WIDTH")?, value("HEIGHT")?))
+    }
+
+    /// Maps a window of the class, and returns its id.
+    /// # Errors
+    ///
+    /// Will error if the display cannot be opened.
+    pub fn open_window(&mut self, class: &str) -> Result<u64> {
+        self.open(class, &[])
+    }
+
+    /// Maps a dock of the class, reserving `top` pixels along the top of the screen.
+    /// # Errors
+    ///
+    /// Will error if the display cannot be opened.
+    pub fn open_dock(&mut self, class: &str, top: u64) -> Result<u64> {
+        self.open(class, &[("_NET_WM_STRUT", vec![0, 0, top, 0])])
+    }
+
+    /// Asks the window manager to add a `_NET_WM_STATE` to a window, the way clients do.
+    /// # Errors
+    ///
+    /// Will error if the window was not opened by this runner.
+    pub fn request_state(&self, window: u64, state: &str) -> Result<()> {
+        let client = self
+            .clients
+            .iter()
+            .find(|client| client.window == window)
+            .ok_or_else(|| failed("the window was not opened by this runner"))?;
+        client.request_state(state);
+        Ok(())
+    }
+
+    /// Polls the condition until it holds or leftwm had plenty of time to get there.
+    pub fn wait_until(&self, condition: impl FnMut() -> bool) -> bool {
+        wait_until(condition)
+    }
+
+    fn open(&mut self, class: &str, cardinals: &[(&str, Vec<u64>)]) -> Result<u64> {
+        let client = Client::open(&self.display)?;
+        client.set_class(class);
+        //leftwm reads the type and struts when the window maps
+        if !cardinals.is_empty() {
+            let dock = client.atom("_NET_WM_WINDOW_TYPE_DOCK");
+            client.change_property("_NET_WM_WINDOW_TYPE", xlib::XA_ATOM, &[dock]);
+        }
+        for (property, values) in cardinals {
+            client.change_property(property, xlib::XA_CARDINAL, values);
+        }
+        client.map();
+        let window = client.window;
+        self.clients.push(client);
+        Ok(window)
+    }
+}
+
+// A connection to the virtual display owning one window, which goes away with it.
+struct Client {
+    xlib: xlib::Xlib,
+    display: *mut xlib::Display,
+    window: u64,
+}
+
+impl Client {
+    fn open(display_name: &str) -> Result<Self> {
+        let xlib = xlib::Xlib::open().map_err(|err| failed(&err.to_string()))?;
+        let name = CString::new(display_name).map_err(|err| failed(&err.to_string()))?;
+        let display = unsafe { (xlib.XOpenDisplay)(name.as_ptr()) };
+        if display.is_null() {
+            return Err(failed("cannot open the display"));
+        }
+        let window = unsafe {
+            let root = (xlib.XDefaultRootWindow)(display);
+            (xlib.XCreateSimpleWindow)(display, root, 0, 0, 200, 100, 0, 0, 0)
+        };
+        Ok(Self {
+            xlib,
+            display,
+            window,
+        })
+    }
+
+    fn atom(&self, name: &str) -> xlib::Atom {
+        let name = CString::new(name).unwrap_or_default();
+        unsafe { (self.xlib.XInternAtom)(self.display, name.as_ptr(), xlib::False) }
+    }
+
+    fn set_class(&self, class: &str) {
+        let class = CString::new(class).unwrap_or_default();
+        let mut hint = xlib::XClassHint {
+            res_name: class.as_ptr() as *mut _,
+            res_class: class.as_ptr() as *mut _,
+        };
+        unsafe {
+            (self.xlib.XSetClassHint)(self.display, self.window, &mut hint);
+        }
+    }
+
+    fn map(&self) {
+        unsafe {
+            (self.xlib.XMapWindow)(self.display, self.window);
+            (self.xlib.XSync)(self.display, xlib::False);
+        }
+    }
+
+    // Format 32 properties are passed to Xlib as longs.
+    fn change_property(&self, property: &str, type_: xlib::Atom, values: &[u64]) {
+        let property = self.atom(property);
+        unsafe {
+            (self.xlib.XChangeProperty)(
+                self.display,
+                self.window,
+                property,
+                type_,
+                32,
+                xlib::PropModeReplace,
+                values.as_ptr().cast::<u8>(),
+                values.len() as i32,
+            );
+        }
+    }
+
+    fn request_state(&self, state: &str) {
+        let mut data = xlib::ClientMessageData::new();
+        data.set_long(0, 1);
+        data.set_long(1, self.atom(state) as i64);
+        let message = xlib::XClientMessageEvent {
+            type_: xlib::ClientMessage,
+            serial: 0,
+            send_event: xlib::True,
+            display: self.display,
+            window: self.window,
+            message_type: self.atom("_NET_WM_STATE"),
+            format: 32,
+            data,
+        };
+        let mut event = xlib::XEvent {
+            client_message: message,
+        };
+        unsafe {
+            let root = (self.xlib.XDefaultRootWindow)(self.display);
+            let mask = xlib::SubstructureRedirectMask | xlib::SubstructureNotifyMask;
+            (self.xlib.XSendEvent)(self.display, root, xlib::False, mask, &mut event);
+            (self.xlib.XSync)(self.display, xlib::False);
+        }
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        unsafe {
+            (self.xlib.XCloseDisplay)(self.display);
+        }
+    }
+}
+
+struct KillOnDrop(Child);
+
+impl Drop for KillOnDrop {
+    fn drop(&mut self) {
+        self.0.kill().ok();
+        self.0.wait().ok();
+    }
+}
+
+fn run(display: &str, program: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(program)
+        .args(args)
+        .env("DISPLAY", display)
+        .output()?;
+    if !output.status.success() {
+        let err = String::from_utf8_lossy(&output.stderr);
+        return Err(failed(&format!("{} failed: {}", program, err.trim())));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn wait_until(mut condition: impl FnMut() -> bool) -> bool {
+    let started = Instant::now();
+    while started.elapsed() < WAIT_TIMEOUT {
+        if condition() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    false
+}
+
+// `xprop` succeeds for properties that are not set, saying so instead.
+fn not_set(output: String) -> Result<String> {
+    if output.contains("not found") || output.contains("no such atom") {
+        return Err(failed(output.trim()));
+    }
+    Ok(output)
+}
+
+fn failed(message: &str) -> crate::errors::LeftError {
+    Error::new(ErrorKind::Other, message.to_owned()).into()
+}
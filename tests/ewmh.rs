@@ -0,0 +1,103 @@
+//! Runs leftwm on Xvfb and checks what clients see of it. Needs the `xvfb` feature, and skips
+//! when Xvfb, xprop or xdotool are missing.
+#![cfg(feature = "xvfb")]
+
+use leftwm::utils::xvfb::XvfbRunner;
+use std::path::Path;
+
+// Each test gets a display of its own.
+fn start(display: u32) -> Option<XvfbRunner> {
+    if !XvfbRunner::available() {
+        eprintln!("skipped, Xvfb, xprop and xdotool are needed");
+        return None;
+    }
+    let worker = Path::new(env!("CARGO_BIN_EXE_leftwm-worker"));
+    Some(XvfbRunner::start(display, worker).expect("leftwm should start on Xvfb"))
+}
+
+fn hex(window: u64) -> String {
+    format!("{:#x}", window)
+}
+
+#[test]
+fn managed_windows_should_be_in_the_client_list() {
+    let mut runner = match start(91) {
+        Some(runner) => runner,
+        None => return,
+    };
+    let first = runner.open_window("first").unwrap();
+    let second = runner.open_window("second").unwrap();
+    assert!(runner.wait_until(|| {
+        let list = runner.root_property("_NET_CLIENT_LIST").unwrap_or_default();
+        list.contains(&hex(first)) && list.contains(&hex(second))
+    }));
+}
+
+#[test]
+fn new_windows_should_take_focus() {
+    let mut runner = match start(92) {
+        Some(runner) => runner,
+        None => return,
+    };
+    runner.open_window("first").unwrap();
+    let second = runner.open_window("second").unwrap();
+    assert!(runner.wait_until(|| {
+        let active = runner
+            .root_property("_NET_ACTIVE_WINDOW")
+            .unwrap_or_default();
+        active.contains(&hex(second))
+    }));
+}
+
+#[test]
+fn fullscreen_requests_should_cover_the_screen() {
+    let mut runner = match start(93) {
+        Some(runner) => runner,
+        None => return,
+    };
+    let window = runner.open_window("video").unwrap();
+    assert!(runner.wait_until(|| runner.geometry(window).is_ok()));
+    runner
+        .request_state(window, "_NET_WM_STATE_FULLSCREEN")
+        .unwrap();
+    assert!(runner.wait_until(|| {
+        let state = runner.property(window, "_NET_WM_STATE").unwrap_or_default();
+        state.contains("_NET_WM_STATE_FULLSCREEN")
+    }));
+    assert!(runner.wait_until(|| runner.geometry(window).ok() == Some((0, 0, 1280, 800))));
+}
+
+#[test]
+fn tiled_windows_should_keep_clear_of_struts() {
+    let mut runner = match start(94) {
+        Some(runner) => runner,
+        None => return,
+    };
+    runner.open_dock("bar", 30).unwrap();
+    let window = runner.open_window("editor").unwrap();
+    assert!(runner.wait_until(|| {
+        let geometry = runner.geometry(window);
+        geometry.map_or(false, |(_, y, _, height)| y >= 30 && y + height <= 800)
+    }));
+}
+
+#[test]
+fn windows_on_hidden_tags_should_be_unmapped() {
+    let mut runner = match start(95) {
+        Some(runner) => runner,
+        None => return,
+    };
+    let window = runner.open_window("editor").unwrap();
+    assert!(runner.wait_until(|| runner.geometry(window).is_ok()));
+    runner.xdotool(&["key", "super+2"]).unwrap();
+    assert!(runner.wait_until(|| {
+        let state = runner.property(window, "_NET_WM_STATE").unwrap_or_default();
+        let wm_state = runner.property(window, "WM_STATE").unwrap_or_default();
+        state.contains("_NET_WM_STATE_HIDDEN") && wm_state.contains("Iconic")
+    }));
+    runner.xdotool(&["key", "super+1"]).unwrap();
+    assert!(runner.wait_until(|| {
+        let state = runner.property(window, "_NET_WM_STATE").unwrap_or_default();
+        !state.contains("_NET_WM_STATE_HIDDEN")
+    }));
+}